@@ -25,6 +25,7 @@ pub static SECRET_MEASUREMENT_IMPL_INSTANCE: SpdmSecretMeasurement = SpdmSecretM
 pub static SECRET_PSK_IMPL_INSTANCE: SpdmSecretPsk = SpdmSecretPsk {
     handshake_secret_hkdf_expand_cb: handshake_secret_hkdf_expand_impl,
     master_secret_hkdf_expand_cb: master_secret_hkdf_expand_impl,
+    is_psk_provisioned_cb: is_psk_provisioned_impl,
 };
 
 #[allow(clippy::field_reassign_with_default)]
@@ -213,6 +214,11 @@ const SALT_0: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
 const ZERO_FILLED: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
 const BIN_STR0_LABEL: &[u8] = b"derived";
 
+// the same PSK is used for every hint.
+fn is_psk_provisioned_impl(psk_hint: &SpdmPskHintStruct) -> bool {
+    true
+}
+
 fn handshake_secret_hkdf_expand_impl(
     spdm_version: SpdmVersion,
     base_hash_algo: SpdmBaseHashAlgo,
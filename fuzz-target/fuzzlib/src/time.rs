@@ -2,10 +2,18 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use spdmlib::time::SpdmTime;
+use spdmlib::time::{SpdmCertValidityTime, SpdmTime};
 pub static SPDM_TIME_IMPL: SpdmTime = SpdmTime {
     sleep_cb: |time: usize| {
         use std::{thread, time::Duration};
         thread::sleep(Duration::from_millis(time as u64));
     },
+    get_cert_validity_time_cb: || SpdmCertValidityTime::Ignore,
+    get_monotonic_time_cb: || {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .ok()
+    },
 };
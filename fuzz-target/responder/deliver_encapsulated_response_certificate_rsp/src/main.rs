@@ -89,7 +89,7 @@ fn fuzz_handle_encap_response_certificate(data: &[u8]) {
             fake_root.data[0..fake_root.data_size as usize]
                 .copy_from_slice(&data[start_index..end_index]);
         }
-        context.common.provision_info.peer_root_cert_data = Some(fake_root);
+        context.common.provision_info.peer_root_cert_data[0] = Some(fake_root);
 
         let _ = context.handle_encap_response_certificate(data).is_err();
     }
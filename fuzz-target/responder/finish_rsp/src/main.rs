@@ -69,10 +69,6 @@ fn fuzz_handle_spdm_finish(data: &[u8]) {
         }
 
         context.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
-        context
-            .common
-            .runtime_info
-            .set_last_session_id(Some(4294836221));
         context.handle_spdm_finish(4294836221, data);
     }
     // TCD:
@@ -131,10 +127,6 @@ fn fuzz_handle_spdm_finish(data: &[u8]) {
         }
 
         context.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
-        context
-            .common
-            .runtime_info
-            .set_last_session_id(Some(4294836221));
         context.handle_spdm_finish(4294836221, data);
     }
     // TCD:
@@ -192,10 +184,6 @@ fn fuzz_handle_spdm_finish(data: &[u8]) {
                 spdmlib::crypto::hash::hash_ctx_init(SpdmBaseHashAlgo::TPM_ALG_SHA_384);
         }
         context.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
-        context
-            .common
-            .runtime_info
-            .set_last_session_id(Some(4294836221));
         context.handle_spdm_finish(4294836221, data);
     }
     // TCD:
@@ -259,10 +247,6 @@ fn fuzz_handle_spdm_finish(data: &[u8]) {
             .runtime_info
             .message_a
             .append_message(&[1u8; config::MAX_SPDM_MSG_SIZE - 103]);
-        context
-            .common
-            .runtime_info
-            .set_last_session_id(Some(4294836221));
         context.handle_spdm_finish(4294836221, data);
     }
 }
@@ -110,17 +110,26 @@ impl SpdmTransportEncap for MctpTransportEncap {
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
         let payload_len = spdm_buffer.len();
         let mut writer = Writer::init(&mut *app_buffer);
-        let mctp_header = if is_app_message {
-            MctpMessageHeader {
-                r#type: MctpMessageType::MctpMessageTypePldm,
-            }
-        } else {
-            MctpMessageHeader {
+        let mctp_header = match app_message_type {
+            None => MctpMessageHeader {
                 r#type: MctpMessageType::MctpMessageTypeSpdm,
+            },
+            Some(app_message_type) => {
+                let mctp_header = MctpMessageHeader::read_bytes(&[app_message_type])
+                    .ok_or(SPDM_STATUS_ENCAP_APP_FAIL)?;
+                // an application message must not be mistaken for SPDM on decap,
+                // and MCTP control messages stay outside of a session.
+                if mctp_header.r#type == MctpMessageType::MctpMessageTypeSpdm
+                    || mctp_header.r#type == MctpMessageType::MctpMessageTypeSecuredMctp
+                    || mctp_header.r#type == MctpMessageType::MctpMessageTypeMctpControl
+                {
+                    return Err(SPDM_STATUS_ENCAP_APP_FAIL);
+                }
+                mctp_header
             }
         };
         mctp_header
@@ -138,19 +147,19 @@ impl SpdmTransportEncap for MctpTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         let mut reader = Reader::init(app_buffer);
-        let mut is_app_mesaage = false;
-        match MctpMessageHeader::read(&mut reader) {
+        let app_message_type = match MctpMessageHeader::read(&mut reader) {
             Some(mctp_header) => match mctp_header.r#type {
-                MctpMessageType::MctpMessageTypeSpdm => {}
-                MctpMessageType::MctpMessageTypePldm => {
-                    is_app_mesaage = true;
+                MctpMessageType::MctpMessageTypeSpdm => None,
+                MctpMessageType::MctpMessageTypeSecuredMctp
+                | MctpMessageType::MctpMessageTypeMctpControl => {
+                    return Err(SPDM_STATUS_DECAP_APP_FAIL)
                 }
-                _ => return Err(SPDM_STATUS_DECAP_APP_FAIL),
+                r#type => Some(r#type.get_u8()),
             },
             None => return Err(SPDM_STATUS_DECAP_APP_FAIL),
-        }
+        };
         let header_size = reader.used();
         let payload_size = app_buffer.len() - header_size;
         if spdm_buffer.len() < payload_size {
@@ -158,7 +167,11 @@ impl SpdmTransportEncap for MctpTransportEncap {
         }
         let payload = &app_buffer[header_size..];
         spdm_buffer[..payload_size].copy_from_slice(payload);
-        Ok((payload_size, is_app_mesaage))
+        Ok((payload_size, app_message_type))
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        MctpMessageType::MctpMessageTypePldm.get_u8()
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
@@ -171,7 +184,9 @@ impl SpdmTransportEncap for MctpTransportEncap {
 
 #[cfg(all(test,))]
 mod tests {
+    use spdmlib::common::session::SpdmSession;
     use spdmlib::config;
+    use spdmlib_test::common::util::setup_established_session;
 
     use super::*;
 
@@ -258,14 +273,14 @@ mod tests {
         let spdm_buffer = [0u8; 10];
 
         let status = mctp_transport_encap
-            .encap_app(&spdm_buffer, &mut app_buffer, false)
+            .encap_app(&spdm_buffer, &mut app_buffer, None)
             .is_ok();
         assert!(status);
 
         let spdm_buffer = [100u8; config::MAX_SPDM_MSG_SIZE];
 
         let status = mctp_transport_encap
-            .encap_app(&spdm_buffer, &mut app_buffer, false)
+            .encap_app(&spdm_buffer, &mut app_buffer, None)
             .is_err();
         assert!(status);
     }
@@ -294,6 +309,56 @@ mod tests {
         assert!(status);
     }
     #[test]
+    fn test_case1_encap_decap_app() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let spdm_buffer = [0x11u8, 0x22];
+        let mut app_buffer = [0u8; 100];
+        let mut decoded_buffer = [0u8; 100];
+
+        let used = mctp_transport_encap
+            .encap_app(&spdm_buffer, &mut app_buffer, None)
+            .unwrap();
+        assert_eq!(app_buffer[..used], [0x05, 0x11, 0x22]);
+        let (used, app_message_type) = mctp_transport_encap
+            .decap_app(&app_buffer[..used], &mut decoded_buffer)
+            .unwrap();
+        assert_eq!(app_message_type, None);
+        assert_eq!(decoded_buffer[..used], spdm_buffer);
+
+        for r#type in [
+            MctpMessageType::MctpMessageTypePldm,
+            MctpMessageType::MctpMessageTypeNvme,
+            MctpMessageType::MctpMessageTypeVendorDefinedPci,
+        ] {
+            let used = mctp_transport_encap
+                .encap_app(&spdm_buffer, &mut app_buffer, Some(r#type.get_u8()))
+                .unwrap();
+            assert_eq!(app_buffer[0], r#type.get_u8());
+            let (used, app_message_type) = mctp_transport_encap
+                .decap_app(&app_buffer[..used], &mut decoded_buffer)
+                .unwrap();
+            assert_eq!(app_message_type, Some(r#type.get_u8()));
+            assert_eq!(decoded_buffer[..used], spdm_buffer);
+        }
+
+        for r#type in [
+            MctpMessageType::MctpMessageTypeSpdm,
+            MctpMessageType::MctpMessageTypeSecuredMctp,
+            MctpMessageType::MctpMessageTypeMctpControl,
+        ] {
+            assert!(mctp_transport_encap
+                .encap_app(&spdm_buffer, &mut app_buffer, Some(r#type.get_u8()))
+                .is_err());
+        }
+        assert!(mctp_transport_encap
+            .decap_app(&[0x06, 0x11, 0x22], &mut decoded_buffer)
+            .is_err());
+        assert_eq!(
+            mctp_transport_encap.get_default_app_message_type(),
+            MctpMessageType::MctpMessageTypePldm.get_u8()
+        );
+    }
+    #[test]
     fn test_case0_get_sequence_number_count() {
         let mut mctp_transport_encap = MctpTransportEncap {};
         assert_eq!(mctp_transport_encap.get_sequence_number_count(), 2);
@@ -303,4 +368,189 @@ mod tests {
         let mut mctp_transport_encap = MctpTransportEncap {};
         assert_eq!(mctp_transport_encap.get_max_random_count(), 32);
     }
+
+    fn create_mctp_session(mctp_transport_encap: &mut MctpTransportEncap) -> SpdmSession {
+        let mut session = SpdmSession::new();
+        setup_established_session(&mut session, 4294901758u32);
+        session.set_transport_param(
+            mctp_transport_encap.get_sequence_number_count(),
+            mctp_transport_encap.get_max_random_count(),
+        );
+        session
+    }
+
+    fn encode_mctp_secured_message(
+        mctp_transport_encap: &mut MctpTransportEncap,
+        session: &mut SpdmSession,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+    ) -> usize {
+        let mut app_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+        let used = mctp_transport_encap
+            .encap_app(spdm_buffer, &mut app_buffer, None)
+            .unwrap();
+        let mut secured_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+        let used = session
+            .encode_spdm_secured_message(&app_buffer[..used], &mut secured_buffer, true)
+            .unwrap();
+        mctp_transport_encap
+            .encap(&secured_buffer[..used], transport_buffer, true)
+            .unwrap()
+    }
+
+    fn decode_mctp_secured_message(
+        mctp_transport_encap: &mut MctpTransportEncap,
+        session: &mut SpdmSession,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut secured_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        let (used, secured_message) =
+            mctp_transport_encap.decap(transport_buffer, &mut secured_buffer)?;
+        assert!(secured_message);
+        let mut app_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        let used =
+            session.decode_spdm_secured_message(&secured_buffer[..used], &mut app_buffer, true)?;
+        let (used, app_message_type) =
+            mctp_transport_encap.decap_app(&app_buffer[..used], spdm_buffer)?;
+        assert!(app_message_type.is_none());
+        Ok(used)
+    }
+
+    #[test]
+    fn test_case0_secured_message_sequence_number() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let mut sender = create_mctp_session(&mut mctp_transport_encap);
+        let mut receiver = create_mctp_session(&mut mctp_transport_encap);
+        sender.set_request_direction_sequence_number(0x1FFFE);
+        receiver.set_request_direction_sequence_number(0x1FFFE);
+
+        let spdm_buffer = [0x12u8, 0xE8, 0, 0];
+        for sequence_number in [0x1FFFEu64, 0x1FFFF, 0x20000] {
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = encode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut sender,
+                &spdm_buffer,
+                &mut transport_buffer,
+            );
+            // MCTP message type, session ID, 2 low order bytes of the sequence number, length
+            assert_eq!(transport_buffer[0], 0x06);
+            assert_eq!(transport_buffer[1..5], 4294901758u32.to_le_bytes());
+            assert_eq!(
+                transport_buffer[5..7],
+                (sequence_number as u16).to_le_bytes()
+            );
+            let length = u16::from_le_bytes([transport_buffer[7], transport_buffer[8]]) as usize;
+            assert_eq!(used, 9 + length);
+
+            let mut decoded_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let used = decode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut receiver,
+                &transport_buffer[..used],
+                &mut decoded_buffer,
+            )
+            .unwrap();
+            assert_eq!(decoded_buffer[..used], spdm_buffer);
+        }
+        assert_eq!(receiver.get_request_direction_sequence_number(), 0x20001);
+    }
+
+    #[test]
+    fn test_case1_secured_message_sequence_number() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let mut sender = create_mctp_session(&mut mctp_transport_encap);
+        let mut receiver = create_mctp_session(&mut mctp_transport_encap);
+
+        let spdm_buffer = [0x12u8, 0xE8, 0, 0];
+        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+        let used = encode_mctp_secured_message(
+            &mut mctp_transport_encap,
+            &mut sender,
+            &spdm_buffer,
+            &mut transport_buffer,
+        );
+        transport_buffer[5] ^= 1;
+
+        let mut decoded_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        assert!(decode_mctp_secured_message(
+            &mut mctp_transport_encap,
+            &mut receiver,
+            &transport_buffer[..used],
+            &mut decoded_buffer,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_case0_secured_message_random_padding() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let mut sender = create_mctp_session(&mut mctp_transport_encap);
+        let mut receiver = create_mctp_session(&mut mctp_transport_encap);
+        let max_random_count = mctp_transport_encap.get_max_random_count() as usize;
+
+        // MCTP header, application length, SPDM message and AES-GCM tag
+        let spdm_buffer = [0x12u8, 0xE8, 0, 0];
+        let min_length = 1 + 2 + spdm_buffer.len() + 16;
+        let mut padded = [false; 33];
+        for _ in 0..128 {
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = encode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut sender,
+                &spdm_buffer,
+                &mut transport_buffer,
+            );
+            let length = u16::from_le_bytes([transport_buffer[7], transport_buffer[8]]) as usize;
+            assert!(length >= min_length && length <= min_length + max_random_count);
+            padded[length - min_length] = true;
+
+            let mut decoded_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let used = decode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut receiver,
+                &transport_buffer[..used],
+                &mut decoded_buffer,
+            )
+            .unwrap();
+            assert_eq!(decoded_buffer[..used], spdm_buffer);
+        }
+        assert!(padded.iter().filter(|p| **p).count() > 1);
+    }
+
+    #[test]
+    fn test_case1_secured_message_random_padding() {
+        let mut mctp_transport_encap = MctpTransportEncap {};
+        let mut sender = create_mctp_session(&mut mctp_transport_encap);
+        let mut receiver = create_mctp_session(&mut mctp_transport_encap);
+        // the peer chooses its padding, a receiver that does not pad accepts it.
+        receiver.set_transport_param(2, 0);
+
+        let spdm_buffer = [0x12u8, 0xE8, 0, 0];
+        let min_length = 1 + 2 + spdm_buffer.len() + 16;
+        loop {
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = encode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut sender,
+                &spdm_buffer,
+                &mut transport_buffer,
+            );
+            let length = u16::from_le_bytes([transport_buffer[7], transport_buffer[8]]) as usize;
+
+            let mut decoded_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let used = decode_mctp_secured_message(
+                &mut mctp_transport_encap,
+                &mut receiver,
+                &transport_buffer[..used],
+                &mut decoded_buffer,
+            )
+            .unwrap();
+            assert_eq!(decoded_buffer[..used], spdm_buffer);
+            if length > min_length {
+                break;
+            }
+        }
+    }
 }
@@ -5,7 +5,19 @@
 #![forbid(unsafe_code)]
 #![no_std]
 
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 mod header;
 pub use header::*;
 
+mod packet;
+pub use packet::*;
+
+mod smbus;
+pub use smbus::*;
+
+mod serial;
+pub use serial::*;
+
 extern crate codec;
@@ -0,0 +1,426 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_SEND_FAIL};
+
+pub const MCTP_PACKET_HEADER_SIZE: usize = 4;
+pub const MCTP_HEADER_VERSION: u8 = 0x01;
+// baseline transmission unit, every MCTP endpoint supports it.
+pub const MCTP_BASELINE_TRANSMISSION_UNIT: usize = 64;
+pub const MCTP_MAX_TRANSMISSION_UNIT: usize = 1024;
+pub const MCTP_NULL_EID: u8 = 0x00;
+pub const MCTP_MAX_PACKET_SEQUENCE: u8 = 0x03;
+pub const MCTP_MAX_MSG_TAG: u8 = 0x07;
+
+const MCTP_SOM: u8 = 0x80;
+const MCTP_EOM: u8 = 0x40;
+const MCTP_PACKET_SEQUENCE_SHIFT: u8 = 4;
+const MCTP_TAG_OWNER: u8 = 0x08;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MctpPacketHeader {
+    pub version: u8,
+    pub dest_eid: u8,
+    pub src_eid: u8,
+    pub som: bool,
+    pub eom: bool,
+    pub pkt_seq: u8,
+    pub tag_owner: bool,
+    pub msg_tag: u8,
+}
+
+impl Codec for MctpPacketHeader {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += (self.version & 0x0F).encode(bytes)?;
+        cnt += self.dest_eid.encode(bytes)?;
+        cnt += self.src_eid.encode(bytes)?;
+        let mut flags = ((self.pkt_seq & MCTP_MAX_PACKET_SEQUENCE) << MCTP_PACKET_SEQUENCE_SHIFT)
+            | (self.msg_tag & MCTP_MAX_MSG_TAG);
+        if self.som {
+            flags |= MCTP_SOM;
+        }
+        if self.eom {
+            flags |= MCTP_EOM;
+        }
+        if self.tag_owner {
+            flags |= MCTP_TAG_OWNER;
+        }
+        cnt += flags.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<MctpPacketHeader> {
+        let version = u8::read(r)? & 0x0F;
+        let dest_eid = u8::read(r)?;
+        let src_eid = u8::read(r)?;
+        let flags = u8::read(r)?;
+        Some(MctpPacketHeader {
+            version,
+            dest_eid,
+            src_eid,
+            som: flags & MCTP_SOM != 0,
+            eom: flags & MCTP_EOM != 0,
+            pkt_seq: (flags >> MCTP_PACKET_SEQUENCE_SHIFT) & MCTP_MAX_PACKET_SEQUENCE,
+            tag_owner: flags & MCTP_TAG_OWNER != 0,
+            msg_tag: flags & MCTP_MAX_MSG_TAG,
+        })
+    }
+}
+
+// splits the MCTP messages built by MctpTransportEncap into MCTP packets and
+// reassembles the received ones. the wrapped device io carries exactly one
+// packet per send / receive.
+pub struct MctpPacketDeviceIo<'a> {
+    device_io: &'a mut dyn SpdmDeviceIo,
+    local_eid: u8,
+    remote_eid: u8,
+    transmission_unit: usize,
+    next_msg_tag: u8,
+    // tag of our outstanding request, the response must carry it.
+    request_tag: Option<u8>,
+    // tag of the peer request we have not answered yet.
+    response_tag: Option<u8>,
+}
+
+impl<'a> MctpPacketDeviceIo<'a> {
+    pub fn new(device_io: &'a mut dyn SpdmDeviceIo, local_eid: u8, remote_eid: u8) -> Self {
+        MctpPacketDeviceIo {
+            device_io,
+            local_eid,
+            remote_eid,
+            transmission_unit: MCTP_BASELINE_TRANSMISSION_UNIT,
+            next_msg_tag: 0,
+            request_tag: None,
+            response_tag: None,
+        }
+    }
+
+    pub fn set_local_eid(&mut self, local_eid: u8) {
+        self.local_eid = local_eid;
+    }
+
+    pub fn get_local_eid(&self) -> u8 {
+        self.local_eid
+    }
+
+    pub fn set_remote_eid(&mut self, remote_eid: u8) {
+        self.remote_eid = remote_eid;
+    }
+
+    pub fn get_remote_eid(&self) -> u8 {
+        self.remote_eid
+    }
+
+    // the transmission unit is the payload size of all but the last packet.
+    pub fn set_transmission_unit(&mut self, transmission_unit: usize) -> SpdmResult {
+        if !(MCTP_BASELINE_TRANSMISSION_UNIT..=MCTP_MAX_TRANSMISSION_UNIT)
+            .contains(&transmission_unit)
+        {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        self.transmission_unit = transmission_unit;
+        Ok(())
+    }
+
+    pub fn get_transmission_unit(&self) -> usize {
+        self.transmission_unit
+    }
+
+    fn accept_packet(&self, header: &MctpPacketHeader) -> bool {
+        header.version == MCTP_HEADER_VERSION
+            && (header.dest_eid == self.local_eid || header.dest_eid == MCTP_NULL_EID)
+            && header.src_eid == self.remote_eid
+    }
+}
+
+impl SpdmDeviceIo for MctpPacketDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        if buffer.is_empty() {
+            return Err(SPDM_STATUS_SEND_FAIL);
+        }
+
+        // answer the pending peer request with its tag, or own a new tag.
+        let (tag_owner, msg_tag) = if let Some(msg_tag) = self.response_tag.take() {
+            (false, msg_tag)
+        } else {
+            let msg_tag = self.next_msg_tag;
+            self.next_msg_tag = (self.next_msg_tag + 1) & MCTP_MAX_MSG_TAG;
+            self.request_tag = Some(msg_tag);
+            (true, msg_tag)
+        };
+
+        let mut packet = [0u8; MCTP_PACKET_HEADER_SIZE + MCTP_MAX_TRANSMISSION_UNIT];
+        for (index, chunk) in buffer.chunks(self.transmission_unit).enumerate() {
+            let offset = index * self.transmission_unit;
+            let header = MctpPacketHeader {
+                version: MCTP_HEADER_VERSION,
+                dest_eid: self.remote_eid,
+                src_eid: self.local_eid,
+                som: index == 0,
+                eom: offset + chunk.len() == buffer.len(),
+                pkt_seq: index as u8 & MCTP_MAX_PACKET_SEQUENCE,
+                tag_owner,
+                msg_tag,
+            };
+            let mut writer = Writer::init(&mut packet);
+            header
+                .encode(&mut writer)
+                .map_err(|_| SPDM_STATUS_SEND_FAIL)?;
+            let used = MCTP_PACKET_HEADER_SIZE + chunk.len();
+            packet[MCTP_PACKET_HEADER_SIZE..used].copy_from_slice(chunk);
+            self.device_io.send(&packet[..used])?;
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize> {
+        let mut packet = [0u8; MCTP_PACKET_HEADER_SIZE + MCTP_MAX_TRANSMISSION_UNIT];
+        // header of the first packet and the last packet of the message being assembled.
+        let mut assembling: Option<(MctpPacketHeader, MctpPacketHeader)> = None;
+        let mut used = 0usize;
+
+        loop {
+            let packet_size = self.device_io.receive(&mut packet, timeout)?;
+            let mut reader = Reader::init(&packet[..packet_size]);
+            let header = match MctpPacketHeader::read(&mut reader) {
+                Some(header) => header,
+                None => continue,
+            };
+            if !self.accept_packet(&header) {
+                continue;
+            }
+            let payload = &packet[MCTP_PACKET_HEADER_SIZE..packet_size];
+            if payload.len() > self.transmission_unit {
+                return Err(0);
+            }
+
+            if header.som {
+                // a new message drops the partially assembled one.
+                used = 0;
+                if !header.tag_owner && self.request_tag != Some(header.msg_tag) {
+                    // a response to a request we did not send.
+                    assembling = None;
+                    continue;
+                }
+                assembling = Some((header, header));
+            } else if let Some((first, last)) = assembling {
+                if header.tag_owner != first.tag_owner || header.msg_tag != first.msg_tag {
+                    return Err(0);
+                }
+                if header.pkt_seq != (last.pkt_seq + 1) & MCTP_MAX_PACKET_SEQUENCE {
+                    return Err(0);
+                }
+                assembling = Some((first, header));
+            } else {
+                // middle of a message we never saw the start of.
+                continue;
+            }
+
+            // all but the last packet carry a full transmission unit.
+            if !header.eom && payload.len() != self.transmission_unit {
+                return Err(0);
+            }
+            if used + payload.len() > buffer.len() {
+                return Err(0);
+            }
+            buffer[used..(used + payload.len())].copy_from_slice(payload);
+            used += payload.len();
+
+            if header.eom {
+                if header.tag_owner {
+                    self.response_tag = Some(header.msg_tag);
+                } else {
+                    self.request_tag = None;
+                }
+                return Ok(used);
+            }
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.device_io.flush_all()
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    extern crate alloc;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    use super::*;
+
+    const REQUESTER_EID: u8 = 0x08;
+    const RESPONDER_EID: u8 = 0x10;
+
+    // lower layer that moves packets between two queues.
+    struct PacketQueueDeviceIo<'a> {
+        tx: &'a RefCell<VecDeque<Vec<u8>>>,
+        rx: &'a RefCell<VecDeque<Vec<u8>>>,
+    }
+
+    impl SpdmDeviceIo for PacketQueueDeviceIo<'_> {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            self.tx.borrow_mut().push_back(buffer.to_vec());
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+            let packet = self.rx.borrow_mut().pop_front().ok_or(0usize)?;
+            buffer[..packet.len()].copy_from_slice(&packet);
+            Ok(packet.len())
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+
+    fn message(size: usize) -> Vec<u8> {
+        (0..size).map(|i| i as u8).collect()
+    }
+
+    fn packet_header(packet: &[u8]) -> MctpPacketHeader {
+        MctpPacketHeader::read(&mut Reader::init(packet)).unwrap()
+    }
+
+    #[test]
+    fn test_case0_mctp_packet_header() {
+        let u8_slice = &mut [0u8; MCTP_PACKET_HEADER_SIZE];
+        let mut writer = Writer::init(u8_slice);
+        let value = MctpPacketHeader {
+            version: MCTP_HEADER_VERSION,
+            dest_eid: RESPONDER_EID,
+            src_eid: REQUESTER_EID,
+            som: true,
+            eom: false,
+            pkt_seq: 2,
+            tag_owner: true,
+            msg_tag: 5,
+        };
+        assert_eq!(value.encode(&mut writer), Ok(MCTP_PACKET_HEADER_SIZE));
+        assert_eq!(*u8_slice, [0x01, RESPONDER_EID, REQUESTER_EID, 0xAD]);
+        let mut reader = Reader::init(u8_slice);
+        assert_eq!(MctpPacketHeader::read(&mut reader), Some(value));
+        assert_eq!(0, reader.left());
+    }
+
+    #[test]
+    fn test_case0_send_receive() {
+        let to_responder = RefCell::new(VecDeque::new());
+        let to_requester = RefCell::new(VecDeque::new());
+        let requester_io = &mut PacketQueueDeviceIo {
+            tx: &to_responder,
+            rx: &to_requester,
+        };
+        let responder_io = &mut PacketQueueDeviceIo {
+            tx: &to_requester,
+            rx: &to_responder,
+        };
+        let mut requester = MctpPacketDeviceIo::new(requester_io, REQUESTER_EID, RESPONDER_EID);
+        let mut responder = MctpPacketDeviceIo::new(responder_io, RESPONDER_EID, REQUESTER_EID);
+
+        // 200 bytes go out as 64 + 64 + 64 + 8.
+        let request = message(200);
+        assert!(requester.send(&request).is_ok());
+        {
+            let packets = to_responder.borrow();
+            assert_eq!(packets.len(), 4);
+            for (index, packet) in packets.iter().enumerate() {
+                let header = packet_header(packet);
+                assert_eq!(header.dest_eid, RESPONDER_EID);
+                assert_eq!(header.src_eid, REQUESTER_EID);
+                assert_eq!(header.som, index == 0);
+                assert_eq!(header.eom, index == 3);
+                assert_eq!(header.pkt_seq, index as u8);
+                assert!(header.tag_owner);
+                assert_eq!(header.msg_tag, 0);
+            }
+            assert_eq!(packets[3].len(), MCTP_PACKET_HEADER_SIZE + 8);
+        }
+
+        let buffer = &mut [0u8; 1024];
+        assert_eq!(responder.receive(buffer, 0), Ok(200));
+        assert_eq!(&buffer[..200], &request[..]);
+
+        // the response echoes the tag without owning it.
+        let response = message(64);
+        assert!(responder.send(&response).is_ok());
+        let header = packet_header(&to_requester.borrow()[0]);
+        assert!(header.som && header.eom);
+        assert!(!header.tag_owner);
+        assert_eq!(header.msg_tag, 0);
+        assert_eq!(requester.receive(buffer, 0), Ok(64));
+        assert_eq!(&buffer[..64], &response[..]);
+
+        // the next request owns the next tag, the sequence wraps after 3.
+        assert!(requester.set_transmission_unit(32).is_err());
+        assert!(requester.set_transmission_unit(128).is_ok());
+        assert!(responder.set_transmission_unit(128).is_ok());
+        let request = message(128 * 5);
+        assert!(requester.send(&request).is_ok());
+        let sequence: Vec<u8> = to_responder
+            .borrow()
+            .iter()
+            .map(|packet| packet_header(packet).pkt_seq)
+            .collect();
+        assert_eq!(sequence, [0, 1, 2, 3, 0]);
+        assert_eq!(packet_header(&to_responder.borrow()[0]).msg_tag, 1);
+        assert_eq!(responder.receive(buffer, 0), Ok(128 * 5));
+        assert_eq!(&buffer[..128 * 5], &request[..]);
+    }
+
+    #[test]
+    fn test_case1_send_receive() {
+        let to_responder = RefCell::new(VecDeque::new());
+        let to_requester = RefCell::new(VecDeque::new());
+        let requester_io = &mut PacketQueueDeviceIo {
+            tx: &to_responder,
+            rx: &to_requester,
+        };
+        let responder_io = &mut PacketQueueDeviceIo {
+            tx: &to_requester,
+            rx: &to_responder,
+        };
+        let mut requester = MctpPacketDeviceIo::new(requester_io, REQUESTER_EID, RESPONDER_EID);
+        let mut responder = MctpPacketDeviceIo::new(responder_io, RESPONDER_EID, REQUESTER_EID);
+        let buffer = &mut [0u8; 1024];
+        let request = message(150);
+
+        // a lost packet breaks the sequence.
+        assert!(requester.send(&request).is_ok());
+        to_responder.borrow_mut().remove(1);
+        assert_eq!(responder.receive(buffer, 0), Err(0));
+        to_responder.borrow_mut().clear();
+
+        // a packet of another message in the middle is rejected.
+        assert!(requester.send(&request).is_ok());
+        to_responder.borrow_mut()[1][3] ^= 0x01;
+        assert_eq!(responder.receive(buffer, 0), Err(0));
+        to_responder.borrow_mut().clear();
+
+        // packets for other endpoints are ignored.
+        assert!(requester.send(&request).is_ok());
+        let mut stray = to_responder.borrow()[0].clone();
+        stray[1] = 0x20;
+        to_responder.borrow_mut().push_front(stray);
+        assert_eq!(responder.receive(buffer, 0), Ok(150));
+
+        // a restarted message drops the partial one.
+        assert!(requester.send(&request).is_ok());
+        let first = to_responder.borrow()[0].clone();
+        to_responder.borrow_mut().push_front(first);
+        assert_eq!(responder.receive(buffer, 0), Ok(150));
+        assert_eq!(&buffer[..150], &request[..]);
+
+        // a response with a tag we did not hand out is dropped.
+        assert!(responder.send(&message(16)).is_ok());
+        to_requester.borrow_mut()[0][3] ^= 0x01;
+        assert_eq!(requester.receive(buffer, 0), Err(0));
+    }
+}
@@ -0,0 +1,444 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+pub const MCTP_SERIAL_FRAME_FLAG: u8 = 0x7E;
+pub const MCTP_SERIAL_ESCAPE: u8 = 0x7D;
+pub const MCTP_SERIAL_ESCAPE_XOR: u8 = 0x20;
+pub const MCTP_SERIAL_REVISION: u8 = 0x01;
+pub const MCTP_SERIAL_MAX_PACKET_SIZE: usize = 0xFF;
+// start flag, revision, byte count, FCS and end flag, with every packet and
+// FCS byte escaped at worst.
+pub const MCTP_SERIAL_MAX_FRAME_SIZE: usize = 5 + 2 * (MCTP_SERIAL_MAX_PACKET_SIZE + 2);
+
+const FCS16_INIT: u16 = 0xFFFF;
+
+// FCS-16 of RFC 1662, start with 0xFFFF. DSP0253 sends the final value
+// uncomplemented, most significant byte first.
+pub fn serial_fcs16(fcs: u16, data: &[u8]) -> u16 {
+    let mut fcs = fcs;
+    for byte in data {
+        let mut byte = *byte;
+        for _ in 0..8 {
+            fcs = if (fcs ^ byte as u16) & 0x01 != 0 {
+                (fcs >> 1) ^ 0x8408
+            } else {
+                fcs >> 1
+            };
+            byte >>= 1;
+        }
+    }
+    fcs
+}
+
+fn frame_fcs(byte_count: u8, packet: &[u8]) -> u16 {
+    let fcs = serial_fcs16(FCS16_INIT, &[MCTP_SERIAL_REVISION, byte_count]);
+    serial_fcs16(fcs, packet)
+}
+
+// frames one MCTP packet, the packet and the FCS are byte stuffed.
+pub fn encode_serial_frame(packet: &[u8], frame: &mut [u8]) -> Option<usize> {
+    if packet.is_empty() || packet.len() > MCTP_SERIAL_MAX_PACKET_SIZE {
+        return None;
+    }
+    let fcs = frame_fcs(packet.len() as u8, packet);
+
+    let mut used = 0usize;
+    let mut push = |byte: u8| -> Option<()> {
+        *frame.get_mut(used)? = byte;
+        used += 1;
+        Some(())
+    };
+    push(MCTP_SERIAL_FRAME_FLAG)?;
+    push(MCTP_SERIAL_REVISION)?;
+    push(packet.len() as u8)?;
+    for byte in packet.iter().chain(fcs.to_be_bytes().iter()) {
+        if *byte == MCTP_SERIAL_FRAME_FLAG || *byte == MCTP_SERIAL_ESCAPE {
+            push(MCTP_SERIAL_ESCAPE)?;
+            push(*byte ^ MCTP_SERIAL_ESCAPE_XOR)?;
+        } else {
+            push(*byte)?;
+        }
+    }
+    push(MCTP_SERIAL_FRAME_FLAG)?;
+    Some(used)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum MctpSerialDecodeState {
+    Idle,
+    Revision,
+    ByteCount,
+    Body,
+    Fcs,
+    End,
+}
+
+// reassembles frames from the byte stream. a frame flag in the middle of a
+// frame or a bad FCS drops the frame, the decoder then waits for the next one.
+pub struct MctpSerialDecoder {
+    state: MctpSerialDecodeState,
+    escape: bool,
+    byte_count: usize,
+    used: usize,
+    packet: [u8; MCTP_SERIAL_MAX_PACKET_SIZE],
+    fcs: [u8; 2],
+    fcs_used: usize,
+}
+
+impl Default for MctpSerialDecoder {
+    fn default() -> Self {
+        MctpSerialDecoder {
+            state: MctpSerialDecodeState::Idle,
+            escape: false,
+            byte_count: 0,
+            used: 0,
+            packet: [0u8; MCTP_SERIAL_MAX_PACKET_SIZE],
+            fcs: [0u8; 2],
+            fcs_used: 0,
+        }
+    }
+}
+
+impl MctpSerialDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.state = MctpSerialDecodeState::Idle;
+        self.escape = false;
+    }
+
+    // returns the size of the packet once the byte completes a valid frame,
+    // the packet is then in packet().
+    pub fn decode(&mut self, byte: u8) -> Option<usize> {
+        match self.state {
+            MctpSerialDecodeState::Idle => {
+                if byte == MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialDecodeState::Revision;
+                }
+            }
+            MctpSerialDecodeState::Revision => {
+                // back to back flags are allowed between frames.
+                if byte == MCTP_SERIAL_REVISION {
+                    self.state = MctpSerialDecodeState::ByteCount;
+                } else if byte != MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialDecodeState::Idle;
+                }
+            }
+            MctpSerialDecodeState::ByteCount => {
+                if byte == 0 || byte == MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialDecodeState::Revision;
+                } else {
+                    self.byte_count = byte as usize;
+                    self.used = 0;
+                    self.fcs_used = 0;
+                    self.escape = false;
+                    self.state = MctpSerialDecodeState::Body;
+                }
+            }
+            MctpSerialDecodeState::Body | MctpSerialDecodeState::Fcs => {
+                let byte = self.unescape(byte)?;
+                if self.state == MctpSerialDecodeState::Body {
+                    self.packet[self.used] = byte;
+                    self.used += 1;
+                    if self.used == self.byte_count {
+                        self.state = MctpSerialDecodeState::Fcs;
+                    }
+                } else {
+                    self.fcs[self.fcs_used] = byte;
+                    self.fcs_used += 1;
+                    if self.fcs_used == self.fcs.len() {
+                        self.state = MctpSerialDecodeState::End;
+                    }
+                }
+            }
+            MctpSerialDecodeState::End => {
+                if byte != MCTP_SERIAL_FRAME_FLAG {
+                    self.state = MctpSerialDecodeState::Idle;
+                    return None;
+                }
+                // the end flag may also start the next frame.
+                self.state = MctpSerialDecodeState::Revision;
+                let fcs = frame_fcs(self.byte_count as u8, &self.packet[..self.used]);
+                if fcs.to_be_bytes() == self.fcs {
+                    return Some(self.used);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn packet(&self) -> &[u8] {
+        &self.packet[..self.used]
+    }
+
+    fn unescape(&mut self, byte: u8) -> Option<u8> {
+        if byte == MCTP_SERIAL_FRAME_FLAG {
+            // an unexpected flag aborts the frame and starts a new one.
+            self.escape = false;
+            self.state = MctpSerialDecodeState::Revision;
+            return None;
+        }
+        if self.escape {
+            self.escape = false;
+            return Some(byte ^ MCTP_SERIAL_ESCAPE_XOR);
+        }
+        if byte == MCTP_SERIAL_ESCAPE {
+            self.escape = true;
+            return None;
+        }
+        Some(byte)
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+pub use self::device_io::MctpSerialDeviceIo;
+
+#[cfg(any(feature = "std", test))]
+mod device_io {
+    use super::*;
+    use spdmlib::common::SpdmDeviceIo;
+    use spdmlib::error::{SpdmResult, SPDM_STATUS_SEND_FAIL};
+    use std::io::{ErrorKind, Read, Write};
+
+    const MCTP_SERIAL_READ_SIZE: usize = 64;
+
+    // carries one MCTP packet per frame over a UART like stream, MctpPacketDeviceIo
+    // goes on top. a read timeout, if any, is the one configured on the stream.
+    pub struct MctpSerialDeviceIo<S: Read + Write> {
+        stream: S,
+        decoder: MctpSerialDecoder,
+        // bytes read from the stream but not decoded yet.
+        pending: [u8; MCTP_SERIAL_READ_SIZE],
+        pending_start: usize,
+        pending_end: usize,
+    }
+
+    impl<S: Read + Write> MctpSerialDeviceIo<S> {
+        pub fn new(stream: S) -> Self {
+            MctpSerialDeviceIo {
+                stream,
+                decoder: MctpSerialDecoder::new(),
+                pending: [0u8; MCTP_SERIAL_READ_SIZE],
+                pending_start: 0,
+                pending_end: 0,
+            }
+        }
+
+        pub fn get_mut(&mut self) -> &mut S {
+            &mut self.stream
+        }
+
+        fn fill_pending(&mut self) -> Result<(), usize> {
+            loop {
+                match self.stream.read(&mut self.pending) {
+                    Ok(0) => return Err(0),
+                    Ok(size) => {
+                        self.pending_start = 0;
+                        self.pending_end = size;
+                        return Ok(());
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => return Err(0),
+                }
+            }
+        }
+    }
+
+    impl<S: Read + Write> SpdmDeviceIo for MctpSerialDeviceIo<S> {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            let mut frame = [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+            let used = encode_serial_frame(buffer, &mut frame).ok_or(SPDM_STATUS_SEND_FAIL)?;
+            self.stream
+                .write_all(&frame[..used])
+                .map_err(|_| SPDM_STATUS_SEND_FAIL)?;
+            self.stream.flush().map_err(|_| SPDM_STATUS_SEND_FAIL)
+        }
+
+        fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+            loop {
+                if self.pending_start == self.pending_end {
+                    self.fill_pending()?;
+                }
+                let byte = self.pending[self.pending_start];
+                self.pending_start += 1;
+                if let Some(size) = self.decoder.decode(byte) {
+                    if buffer.len() < size {
+                        return Err(0);
+                    }
+                    buffer[..size].copy_from_slice(self.decoder.packet());
+                    return Ok(size);
+                }
+            }
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            self.pending_start = 0;
+            self.pending_end = 0;
+            self.decoder.reset();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    extern crate alloc;
+    use alloc::collections::VecDeque;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use spdmlib::common::SpdmDeviceIo;
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::MctpPacketDeviceIo;
+
+    fn decode_all(decoder: &mut MctpSerialDecoder, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut packets = Vec::new();
+        for byte in bytes {
+            if decoder.decode(*byte).is_some() {
+                packets.push(decoder.packet().to_vec());
+            }
+        }
+        packets
+    }
+
+    #[test]
+    fn test_case0_serial_fcs16() {
+        // CRC-16/MCRF4XX check value
+        assert_eq!(serial_fcs16(FCS16_INIT, b"123456789"), 0x6F91);
+    }
+
+    #[test]
+    fn test_case0_serial_frame() {
+        let packet = [0x01, 0x7E, 0x08, 0x7D, 0x05, 0x10];
+        let frame = &mut [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+        let used = encode_serial_frame(&packet, frame).unwrap();
+
+        assert_eq!(
+            &frame[..9],
+            &[0x7E, 0x01, 0x06, 0x01, 0x7D, 0x5E, 0x08, 0x7D, 0x5D]
+        );
+        assert_eq!(frame[used - 1], MCTP_SERIAL_FRAME_FLAG);
+        // only the flags are unescaped 0x7E.
+        assert_eq!(
+            frame[1..(used - 1)]
+                .iter()
+                .filter(|b| **b == MCTP_SERIAL_FRAME_FLAG)
+                .count(),
+            0
+        );
+
+        let mut decoder = MctpSerialDecoder::new();
+        assert_eq!(decode_all(&mut decoder, &frame[..used]), [packet.to_vec()]);
+
+        assert!(encode_serial_frame(&[], frame).is_none());
+        assert!(encode_serial_frame(&[0u8; MCTP_SERIAL_MAX_PACKET_SIZE + 1], frame).is_none());
+        assert!(encode_serial_frame(&packet, &mut [0u8; 8]).is_none());
+    }
+
+    #[test]
+    fn test_case1_serial_frame() {
+        let frame = &mut [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+        let mut stream = Vec::new();
+        let first = [0x01u8, 0x10, 0x08, 0xC8, 0x05, 0x11];
+        let second = [0x01u8, 0x10, 0x08, 0xC9, 0x05, 0x12, 0x7E];
+
+        // line noise, a frame with a bad FCS, a truncated frame, then two good frames.
+        stream.extend_from_slice(&[0x00, 0x55, 0x7D]);
+        let used = encode_serial_frame(&first, frame).unwrap();
+        frame[4] ^= 0x01;
+        stream.extend_from_slice(&frame[..used]);
+        let used = encode_serial_frame(&first, frame).unwrap();
+        stream.extend_from_slice(&frame[..(used - 4)]);
+        stream.extend_from_slice(&frame[..used]);
+        let used = encode_serial_frame(&second, frame).unwrap();
+        stream.extend_from_slice(&frame[..used]);
+
+        let mut decoder = MctpSerialDecoder::new();
+        assert_eq!(
+            decode_all(&mut decoder, &stream),
+            [first.to_vec(), second.to_vec()]
+        );
+    }
+
+    // one direction of a null modem cable.
+    #[derive(Clone, Default)]
+    struct SerialLine(Rc<RefCell<VecDeque<u8>>>);
+
+    struct SerialPort {
+        tx: SerialLine,
+        rx: SerialLine,
+    }
+
+    impl Read for SerialPort {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let mut rx = self.rx.0.borrow_mut();
+            let size = buf.len().min(rx.len());
+            for (d, s) in buf.iter_mut().zip(rx.drain(..size)) {
+                *d = s;
+            }
+            Ok(size)
+        }
+    }
+
+    impl Write for SerialPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.tx.0.borrow_mut().extend(buf.iter());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_case0_serial_send_receive() {
+        let to_responder = SerialLine::default();
+        let to_requester = SerialLine::default();
+        let requester_serial = &mut MctpSerialDeviceIo::new(SerialPort {
+            tx: to_responder.clone(),
+            rx: to_requester.clone(),
+        });
+        let responder_serial = &mut MctpSerialDeviceIo::new(SerialPort {
+            tx: to_requester.clone(),
+            rx: to_responder.clone(),
+        });
+        let mut requester = MctpPacketDeviceIo::new(requester_serial, 0x08, 0x10);
+        let mut responder = MctpPacketDeviceIo::new(responder_serial, 0x10, 0x08);
+
+        let request: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        assert!(requester.send(&request).is_ok());
+        let buffer = &mut [0u8; 1024];
+        assert_eq!(responder.receive(buffer, 0), Ok(300));
+        assert_eq!(&buffer[..300], &request[..]);
+
+        let response = [0x7Eu8; 80];
+        assert!(responder.send(&response).is_ok());
+        assert_eq!(requester.receive(buffer, 0), Ok(80));
+        assert_eq!(&buffer[..80], &response);
+
+        // the line is drained, end of stream is an error.
+        assert_eq!(requester.receive(buffer, 0), Err(0));
+    }
+
+    #[test]
+    fn test_case2_serial_frame() {
+        // an SPDM GET_VERSION to EID 0x08, FCS-16 over revision, byte count and packet.
+        let packet = [0x01, 0x00, 0x08, 0xC8, 0x05, 0x10, 0x84, 0x00, 0x00];
+        let expected = [
+            0x7E, 0x01, 0x09, 0x01, 0x00, 0x08, 0xC8, 0x05, 0x10, 0x84, 0x00, 0x00, 0xDF, 0xF6,
+            0x7E,
+        ];
+        let frame = &mut [0u8; MCTP_SERIAL_MAX_FRAME_SIZE];
+        let used = encode_serial_frame(&packet, frame).unwrap();
+        assert_eq!(&frame[..used], &expected);
+
+        let mut decoder = MctpSerialDecoder::new();
+        assert_eq!(decode_all(&mut decoder, &expected), [packet.to_vec()]);
+    }
+}
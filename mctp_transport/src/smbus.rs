@@ -0,0 +1,308 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use core::cell::RefCell;
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::{SpdmResult, SPDM_STATUS_SEND_FAIL};
+
+use crate::MCTP_PACKET_HEADER_SIZE;
+
+pub const MCTP_SMBUS_COMMAND_CODE: u8 = 0x0F;
+// destination slave address, command code, byte count and PEC.
+pub const MCTP_SMBUS_FRAME_OVERHEAD: usize = 4;
+// byte count covers the source slave address and the MCTP packet.
+pub const MCTP_SMBUS_MAX_BYTE_COUNT: usize = 0xFF;
+pub const MCTP_SMBUS_MAX_PACKET_SIZE: usize = MCTP_SMBUS_MAX_BYTE_COUNT - 1;
+pub const MCTP_SMBUS_MAX_FRAME_SIZE: usize = MCTP_SMBUS_FRAME_OVERHEAD + MCTP_SMBUS_MAX_BYTE_COUNT;
+
+// CRC-8 with polynomial x^8 + x^2 + x + 1 over the whole frame, as SMBus
+// packet error checking.
+pub fn smbus_pec(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// one SMBus block write per call, the frame starts at the destination slave
+// address and ends with the PEC.
+pub trait MctpSmbusBus {
+    fn write_block(&mut self, frame: &[u8]) -> SpdmResult;
+
+    fn read_block(&mut self, frame: &mut [u8], timeout: usize) -> Result<usize, usize>;
+}
+
+// carries one MCTP packet per SMBus frame, MctpPacketDeviceIo goes on top.
+// slave addresses are 7 bit.
+pub struct MctpSmbusDeviceIo<'a> {
+    bus: &'a mut dyn MctpSmbusBus,
+    local_address: u8,
+    remote_address: u8,
+}
+
+impl<'a> MctpSmbusDeviceIo<'a> {
+    pub fn new(bus: &'a mut dyn MctpSmbusBus, local_address: u8, remote_address: u8) -> Self {
+        MctpSmbusDeviceIo {
+            bus,
+            local_address,
+            remote_address,
+        }
+    }
+
+    fn accept_frame(&self, frame: &[u8]) -> bool {
+        if frame.len() < MCTP_SMBUS_FRAME_OVERHEAD + 1 + MCTP_PACKET_HEADER_SIZE {
+            return false;
+        }
+        let pec_offset = frame.len() - 1;
+        frame[0] == self.local_address << 1
+            && frame[1] == MCTP_SMBUS_COMMAND_CODE
+            && frame[2] as usize == frame.len() - MCTP_SMBUS_FRAME_OVERHEAD
+            && frame[3] == (self.remote_address << 1) | 0x01
+            && frame[pec_offset] == smbus_pec(&frame[..pec_offset])
+    }
+}
+
+impl SpdmDeviceIo for MctpSmbusDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        if buffer.len() > MCTP_SMBUS_MAX_PACKET_SIZE {
+            return Err(SPDM_STATUS_SEND_FAIL);
+        }
+        let mut frame = [0u8; MCTP_SMBUS_MAX_FRAME_SIZE];
+        let pec_offset = 4 + buffer.len();
+        frame[0] = self.remote_address << 1;
+        frame[1] = MCTP_SMBUS_COMMAND_CODE;
+        frame[2] = (buffer.len() + 1) as u8;
+        frame[3] = (self.local_address << 1) | 0x01;
+        frame[4..pec_offset].copy_from_slice(buffer);
+        frame[pec_offset] = smbus_pec(&frame[..pec_offset]);
+        self.bus.write_block(&frame[..(pec_offset + 1)])
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize> {
+        let mut frame = [0u8; MCTP_SMBUS_MAX_FRAME_SIZE];
+        loop {
+            let used = self.bus.read_block(&mut frame, timeout)?;
+            // frames for other slaves or failing the PEC are dropped.
+            if !self.accept_frame(&frame[..used]) {
+                continue;
+            }
+            let packet = &frame[4..(used - 1)];
+            if buffer.len() < packet.len() {
+                return Err(0);
+            }
+            buffer[..packet.len()].copy_from_slice(packet);
+            return Ok(packet.len());
+        }
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+pub const MCTP_SMBUS_LOOPBACK_FRAME_COUNT: usize = 16;
+
+// software SMBus segment, frames wait until the addressed slave reads them.
+pub struct MctpSmbusLoopbackBus {
+    frames: [[u8; MCTP_SMBUS_MAX_FRAME_SIZE]; MCTP_SMBUS_LOOPBACK_FRAME_COUNT],
+    frame_size: [usize; MCTP_SMBUS_LOOPBACK_FRAME_COUNT],
+    frame_count: usize,
+}
+
+impl Default for MctpSmbusLoopbackBus {
+    fn default() -> Self {
+        MctpSmbusLoopbackBus {
+            frames: [[0u8; MCTP_SMBUS_MAX_FRAME_SIZE]; MCTP_SMBUS_LOOPBACK_FRAME_COUNT],
+            frame_size: [0usize; MCTP_SMBUS_LOOPBACK_FRAME_COUNT],
+            frame_count: 0,
+        }
+    }
+}
+
+impl MctpSmbusLoopbackBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pending_frames(&self) -> usize {
+        self.frame_count
+    }
+
+    // the first pending frame, to corrupt or drop it in tests.
+    pub fn peek_frame_mut(&mut self) -> Option<&mut [u8]> {
+        if self.frame_count == 0 {
+            return None;
+        }
+        Some(&mut self.frames[0][..self.frame_size[0]])
+    }
+
+    pub fn drop_frame(&mut self, index: usize) {
+        if index >= self.frame_count {
+            return;
+        }
+        for i in index..(self.frame_count - 1) {
+            self.frames[i] = self.frames[i + 1];
+            self.frame_size[i] = self.frame_size[i + 1];
+        }
+        self.frame_count -= 1;
+    }
+
+    fn push(&mut self, frame: &[u8]) -> SpdmResult {
+        if self.frame_count == MCTP_SMBUS_LOOPBACK_FRAME_COUNT
+            || frame.len() > MCTP_SMBUS_MAX_FRAME_SIZE
+            || frame.is_empty()
+        {
+            return Err(SPDM_STATUS_SEND_FAIL);
+        }
+        self.frames[self.frame_count][..frame.len()].copy_from_slice(frame);
+        self.frame_size[self.frame_count] = frame.len();
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self, address: u8, frame: &mut [u8]) -> Option<usize> {
+        let index = (0..self.frame_count).find(|i| self.frames[*i][0] >> 1 == address)?;
+        let size = self.frame_size[index];
+        if frame.len() < size {
+            return None;
+        }
+        frame[..size].copy_from_slice(&self.frames[index][..size]);
+        self.drop_frame(index);
+        Some(size)
+    }
+}
+
+// a slave on a MctpSmbusLoopbackBus, it reads the frames sent to its address.
+pub struct MctpSmbusLoopbackPort<'a> {
+    bus: &'a RefCell<MctpSmbusLoopbackBus>,
+    address: u8,
+}
+
+impl<'a> MctpSmbusLoopbackPort<'a> {
+    pub fn new(bus: &'a RefCell<MctpSmbusLoopbackBus>, address: u8) -> Self {
+        MctpSmbusLoopbackPort { bus, address }
+    }
+}
+
+impl MctpSmbusBus for MctpSmbusLoopbackPort<'_> {
+    fn write_block(&mut self, frame: &[u8]) -> SpdmResult {
+        self.bus.borrow_mut().push(frame)
+    }
+
+    fn read_block(&mut self, frame: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        self.bus.borrow_mut().pop(self.address, frame).ok_or(0)
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::MctpPacketDeviceIo;
+
+    const BMC_ADDRESS: u8 = 0x10;
+    const CARD_ADDRESS: u8 = 0x52;
+    const BMC_EID: u8 = 0x08;
+    const CARD_EID: u8 = 0x20;
+
+    #[test]
+    fn test_case0_smbus_pec() {
+        assert_eq!(smbus_pec(b"123456789"), 0xF4);
+        assert_eq!(smbus_pec(&[]), 0x00);
+    }
+
+    #[test]
+    fn test_case0_smbus_frame() {
+        let bus = RefCell::new(MctpSmbusLoopbackBus::new());
+        let mut port = MctpSmbusLoopbackPort::new(&bus, BMC_ADDRESS);
+        let mut device_io = MctpSmbusDeviceIo::new(&mut port, BMC_ADDRESS, CARD_ADDRESS);
+
+        let packet = [0x01, CARD_EID, BMC_EID, 0xC8, 0x05, 0x11, 0xE1];
+        assert!(device_io.send(&packet).is_ok());
+
+        let mut bus = bus.borrow_mut();
+        let frame = bus.peek_frame_mut().unwrap();
+        assert_eq!(frame.len(), MCTP_SMBUS_FRAME_OVERHEAD + 1 + packet.len());
+        assert_eq!(frame[0], CARD_ADDRESS << 1);
+        assert_eq!(frame[1], MCTP_SMBUS_COMMAND_CODE);
+        assert_eq!(frame[2] as usize, 1 + packet.len());
+        assert_eq!(frame[3], (BMC_ADDRESS << 1) | 0x01);
+        assert_eq!(&frame[4..(4 + packet.len())], &packet);
+        let pec_offset = frame.len() - 1;
+        assert_eq!(frame[pec_offset], smbus_pec(&frame[..pec_offset]));
+
+        // a packet larger than the byte count allows does not fit in a frame.
+        drop(bus);
+        assert!(device_io
+            .send(&[0u8; MCTP_SMBUS_MAX_PACKET_SIZE + 1])
+            .is_err());
+    }
+
+    #[test]
+    fn test_case0_smbus_send_receive() {
+        let bus = RefCell::new(MctpSmbusLoopbackBus::new());
+        let bmc_port = &mut MctpSmbusLoopbackPort::new(&bus, BMC_ADDRESS);
+        let card_port = &mut MctpSmbusLoopbackPort::new(&bus, CARD_ADDRESS);
+        let bmc_smbus = &mut MctpSmbusDeviceIo::new(bmc_port, BMC_ADDRESS, CARD_ADDRESS);
+        let card_smbus = &mut MctpSmbusDeviceIo::new(card_port, CARD_ADDRESS, BMC_ADDRESS);
+        let mut bmc = MctpPacketDeviceIo::new(bmc_smbus, BMC_EID, CARD_EID);
+        let mut card = MctpPacketDeviceIo::new(card_smbus, CARD_EID, BMC_EID);
+
+        let request = [0x5Au8; 150];
+        assert!(bmc.send(&request).is_ok());
+        assert_eq!(bus.borrow().pending_frames(), 3);
+
+        let buffer = &mut [0u8; 1024];
+        assert_eq!(card.receive(buffer, 0), Ok(150));
+        assert_eq!(&buffer[..150], &request);
+
+        let response = [0xA5u8; 20];
+        assert!(card.send(&response).is_ok());
+        assert_eq!(bmc.receive(buffer, 0), Ok(20));
+        assert_eq!(&buffer[..20], &response);
+        assert_eq!(bus.borrow().pending_frames(), 0);
+    }
+
+    #[test]
+    fn test_case1_smbus_send_receive() {
+        let bus = RefCell::new(MctpSmbusLoopbackBus::new());
+        let bmc_port = &mut MctpSmbusLoopbackPort::new(&bus, BMC_ADDRESS);
+        let card_port = &mut MctpSmbusLoopbackPort::new(&bus, CARD_ADDRESS);
+        let mut bmc = MctpSmbusDeviceIo::new(bmc_port, BMC_ADDRESS, CARD_ADDRESS);
+        let mut card = MctpSmbusDeviceIo::new(card_port, CARD_ADDRESS, BMC_ADDRESS);
+        let buffer = &mut [0u8; MCTP_SMBUS_MAX_PACKET_SIZE];
+        let packet = [0x01, CARD_EID, BMC_EID, 0xC8, 0x05];
+
+        // a frame failing the PEC is dropped.
+        assert!(bmc.send(&packet).is_ok());
+        bus.borrow_mut().peek_frame_mut().unwrap()[5] ^= 0x01;
+        assert_eq!(card.receive(buffer, 0), Err(0));
+        assert_eq!(bus.borrow().pending_frames(), 0);
+
+        // so is a frame with another command code.
+        assert!(bmc.send(&packet).is_ok());
+        {
+            let mut bus = bus.borrow_mut();
+            let frame = bus.peek_frame_mut().unwrap();
+            frame[1] = 0x0E;
+            let pec_offset = frame.len() - 1;
+            frame[pec_offset] = smbus_pec(&frame[..pec_offset]);
+        }
+        assert_eq!(card.receive(buffer, 0), Err(0));
+
+        // the valid frame behind them is still delivered.
+        assert!(bmc.send(&packet).is_ok());
+        assert!(bmc.send(&[0x01, CARD_EID, BMC_EID, 0xC9, 0x05]).is_ok());
+        bus.borrow_mut().peek_frame_mut().unwrap()[2] = 0x01;
+        assert_eq!(card.receive(buffer, 0), Ok(packet.len()));
+        assert_eq!(buffer[3], 0xC9);
+    }
+}
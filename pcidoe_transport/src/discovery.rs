@@ -0,0 +1,323 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+// DOE Discovery, PCIe 6.0 section 6.30.1.1
+
+use crate::header::{PciDoeDataObjectType, PciDoeMessageHeader, PciDoeVendorId};
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::{SpdmDeviceIo, ST1};
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_FAIL,
+    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_RECEIVE_FAIL,
+};
+
+pub const PCI_DOE_DISCOVERY_PAYLOAD_SIZE: usize = 4;
+pub const PCI_DOE_DISCOVERY_OBJECT_SIZE: usize = 8 + PCI_DOE_DISCOVERY_PAYLOAD_SIZE;
+
+// a vendor ID and data object type pair supported by a DOE mailbox.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PciDoeProtocol {
+    pub vendor_id: PciDoeVendorId,
+    pub data_object_type: PciDoeDataObjectType,
+}
+
+impl Codec for PciDoeProtocol {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.vendor_id.encode(bytes)?;
+        cnt += self.data_object_type.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<PciDoeProtocol> {
+        let vendor_id = PciDoeVendorId::read(r)?;
+        let data_object_type = PciDoeDataObjectType::read(r)?;
+        Some(PciDoeProtocol {
+            vendor_id,
+            data_object_type,
+        })
+    }
+}
+
+// the protocols of a mailbox that carries SPDM, discovery itself is always at index 0.
+pub const PCI_DOE_SPDM_PROTOCOLS: [PciDoeProtocol; 3] = [
+    PciDoeProtocol {
+        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
+    },
+    PciDoeProtocol {
+        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeSpdm,
+    },
+    PciDoeProtocol {
+        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeSecuredSpdm,
+    },
+];
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PciDoeDiscoveryRequest {
+    pub index: u8,
+}
+
+impl Codec for PciDoeDiscoveryRequest {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.index.encode(bytes)?;
+        cnt += 0u8.encode(bytes)?; // version
+        cnt += 0u16.encode(bytes)?; // reserved
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<PciDoeDiscoveryRequest> {
+        let index = u8::read(r)?;
+        u8::read(r)?;
+        u16::read(r)?;
+        Some(PciDoeDiscoveryRequest { index })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PciDoeDiscoveryResponse {
+    pub protocol: PciDoeProtocol,
+    pub next_index: u8, // 0 for the last entry
+}
+
+impl Codec for PciDoeDiscoveryResponse {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.protocol.encode(bytes)?;
+        cnt += self.next_index.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<PciDoeDiscoveryResponse> {
+        let protocol = PciDoeProtocol::read(r)?;
+        let next_index = u8::read(r)?;
+        Some(PciDoeDiscoveryResponse {
+            protocol,
+            next_index,
+        })
+    }
+}
+
+fn encode_discovery_object<T: Codec>(payload: &T, buffer: &mut [u8]) -> SpdmResult<usize> {
+    let mut writer = Writer::init(buffer);
+    let pcidoe_header = PciDoeMessageHeader {
+        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
+        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
+        payload_length: PCI_DOE_DISCOVERY_PAYLOAD_SIZE as u32,
+    };
+    pcidoe_header
+        .encode(&mut writer)
+        .map_err(|_| SPDM_STATUS_ENCAP_FAIL)?;
+    payload
+        .encode(&mut writer)
+        .map_err(|_| SPDM_STATUS_ENCAP_FAIL)?;
+    Ok(writer.used())
+}
+
+// return the payload of a DOE discovery data object.
+fn decode_discovery_object(buffer: &[u8]) -> SpdmResult<&[u8]> {
+    let mut reader = Reader::init(buffer);
+    let pcidoe_header = PciDoeMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_DECAP_FAIL)?;
+    if pcidoe_header.vendor_id != PciDoeVendorId::PciDoeVendorIdPciSig
+        || pcidoe_header.data_object_type != PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery
+        || pcidoe_header.payload_length as usize != PCI_DOE_DISCOVERY_PAYLOAD_SIZE
+    {
+        return Err(SPDM_STATUS_DECAP_FAIL);
+    }
+    let header_size = reader.used();
+    buffer
+        .get(header_size..(header_size + PCI_DOE_DISCOVERY_PAYLOAD_SIZE))
+        .ok_or(SPDM_STATUS_DECAP_FAIL)
+}
+
+// enumerate the protocols of a DOE mailbox into protocols, and return how many there are.
+pub fn pci_doe_discovery(
+    device_io: &mut dyn SpdmDeviceIo,
+    protocols: &mut [PciDoeProtocol],
+) -> SpdmResult<usize> {
+    let mut count = 0;
+    let mut index = 0u8;
+    loop {
+        let mut send_buffer = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let used = encode_discovery_object(&PciDoeDiscoveryRequest { index }, &mut send_buffer)?;
+        device_io.send(&send_buffer[..used])?;
+
+        let mut receive_buffer = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let used = device_io
+            .receive(&mut receive_buffer, ST1)
+            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+        let payload = decode_discovery_object(&receive_buffer[..used])?;
+        let response =
+            PciDoeDiscoveryResponse::read_bytes(payload).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+
+        if count == protocols.len() {
+            return Err(SPDM_STATUS_BUFFER_FULL);
+        }
+        protocols[count] = response.protocol;
+        count += 1;
+
+        if response.next_index == 0 {
+            return Ok(count);
+        }
+        // the index only moves forward, so a broken mailbox cannot loop forever.
+        if response.next_index <= index {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        index = response.next_index;
+    }
+}
+
+// answer a DOE discovery request in request with the protocols of the mailbox.
+pub fn pci_doe_discovery_response(
+    request: &[u8],
+    protocols: &[PciDoeProtocol],
+    response: &mut [u8],
+) -> SpdmResult<usize> {
+    let payload = decode_discovery_object(request)?;
+    let discovery_request =
+        PciDoeDiscoveryRequest::read_bytes(payload).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    let index = discovery_request.index as usize;
+    let protocol = protocols.get(index).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    let next_index = if index + 1 < protocols.len() {
+        (index + 1) as u8
+    } else {
+        0
+    };
+    encode_discovery_object(
+        &PciDoeDiscoveryResponse {
+            protocol: *protocol,
+            next_index,
+        },
+        response,
+    )
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+
+    // a DOE mailbox answering discovery from a fixed table.
+    struct FakeDoeMailbox<'a> {
+        protocols: &'a [PciDoeProtocol],
+        response: [u8; PCI_DOE_DISCOVERY_OBJECT_SIZE],
+        response_size: usize,
+        requests: usize,
+    }
+
+    impl<'a> FakeDoeMailbox<'a> {
+        fn new(protocols: &'a [PciDoeProtocol]) -> Self {
+            FakeDoeMailbox {
+                protocols,
+                response: [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE],
+                response_size: 0,
+                requests: 0,
+            }
+        }
+    }
+
+    impl SpdmDeviceIo for FakeDoeMailbox<'_> {
+        fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+            self.requests += 1;
+            self.response_size =
+                pci_doe_discovery_response(buffer, self.protocols, &mut self.response)?;
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+            buffer[..self.response_size].copy_from_slice(&self.response[..self.response_size]);
+            Ok(self.response_size)
+        }
+
+        fn flush_all(&mut self) -> SpdmResult {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_case0_discovery_request() {
+        let mut buffer = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let used =
+            encode_discovery_object(&PciDoeDiscoveryRequest { index: 2 }, &mut buffer).unwrap();
+        assert_eq!(used, PCI_DOE_DISCOVERY_OBJECT_SIZE);
+        assert_eq!(
+            buffer,
+            [0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0, 0, 0]
+        );
+
+        let mut response = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let used =
+            pci_doe_discovery_response(&buffer, &PCI_DOE_SPDM_PROTOCOLS, &mut response).unwrap();
+        // secured SPDM is the last entry.
+        assert_eq!(
+            response[..used],
+            [0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_case1_discovery_request() {
+        let mut response = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        let mut request = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+        encode_discovery_object(&PciDoeDiscoveryRequest { index: 3 }, &mut request).unwrap();
+        assert!(
+            pci_doe_discovery_response(&request, &PCI_DOE_SPDM_PROTOCOLS, &mut response).is_err()
+        );
+
+        // an SPDM data object is not a discovery request.
+        request[4] = 0x01;
+        request[2] = 0x01;
+        assert!(
+            pci_doe_discovery_response(&request, &PCI_DOE_SPDM_PROTOCOLS, &mut response).is_err()
+        );
+    }
+
+    #[test]
+    fn test_case0_pci_doe_discovery() {
+        let mut mailbox = FakeDoeMailbox::new(&PCI_DOE_SPDM_PROTOCOLS);
+        let mut protocols = [PciDoeProtocol::default(); 8];
+        let count = pci_doe_discovery(&mut mailbox, &mut protocols).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(protocols[..count], PCI_DOE_SPDM_PROTOCOLS);
+        assert_eq!(mailbox.requests, 3);
+
+        let mut protocols = [PciDoeProtocol::default(); 2];
+        assert_eq!(
+            pci_doe_discovery(&mut mailbox, &mut protocols),
+            Err(SPDM_STATUS_BUFFER_FULL)
+        );
+    }
+
+    #[test]
+    fn test_case1_pci_doe_discovery() {
+        // a mailbox that keeps pointing back to index 0.
+        struct LoopingDoeMailbox;
+        impl SpdmDeviceIo for LoopingDoeMailbox {
+            fn send(&mut self, _buffer: &[u8]) -> SpdmResult {
+                Ok(())
+            }
+            fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+                encode_discovery_object(
+                    &PciDoeDiscoveryResponse {
+                        protocol: PCI_DOE_SPDM_PROTOCOLS[0],
+                        next_index: 1,
+                    },
+                    buffer,
+                )
+                .map_err(|_| 0usize)
+            }
+            fn flush_all(&mut self) -> SpdmResult {
+                Ok(())
+            }
+        }
+
+        let mut protocols = [PciDoeProtocol::default(); 8];
+        assert_eq!(
+            pci_doe_discovery(&mut LoopingDoeMailbox, &mut protocols),
+            Err(SPDM_STATUS_INVALID_MSG_FIELD)
+        );
+    }
+}
@@ -5,7 +5,9 @@
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
 use spdmlib::common::SpdmTransportEncap;
-use spdmlib::error::{SpdmResult, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_FAIL};
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_APP_FAIL, SPDM_STATUS_ENCAP_FAIL,
+};
 
 enum_builder! {
     @U16
@@ -143,12 +145,17 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         Ok((payload_size, secured_message))
     }
 
+    // DOE carries SPDM only, a secured message has no application message
+    // type in front.
     fn encap_app(
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        _is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
+        if app_message_type.is_some() {
+            return Err(SPDM_STATUS_ENCAP_APP_FAIL);
+        }
         app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
         Ok(spdm_buffer.len())
     }
@@ -157,9 +164,13 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
-        Ok((app_buffer.len(), false))
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        0
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
@@ -270,4 +281,17 @@ mod tests_header {
         };
         assert!(value.encode(&mut writer).is_ok());
     }
+    #[test]
+    fn test_case0_encap_app() {
+        let mut pcidoe_transport_encap = PciDoeTransportEncap {};
+        let app_buffer = &mut [0u8; 8];
+        assert_eq!(
+            pcidoe_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, None),
+            Ok(2)
+        );
+        assert_eq!(
+            pcidoe_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, Some(0x01)),
+            Err(SPDM_STATUS_ENCAP_APP_FAIL)
+        );
+    }
 }
@@ -8,4 +8,10 @@
 mod header;
 pub use header::*;
 
+mod discovery;
+pub use discovery::*;
+
+mod mailbox;
+pub use mailbox::*;
+
 extern crate codec;
@@ -0,0 +1,560 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+// DOE mailbox register protocol, PCIe 6.0 section 6.30.2 and 7.9.24
+
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::config;
+use spdmlib::error::{
+    SpdmResult, SpdmStatus, SPDM_STATUS_BUSY_PEER, SPDM_STATUS_ERROR_PEER,
+    SPDM_STATUS_INVALID_MSG_SIZE, SPDM_STATUS_INVALID_PARAMETER,
+};
+
+// register offsets from the start of the DOE extended capability.
+pub const PCI_DOE_CONTROL_OFFSET: usize = 0x08;
+pub const PCI_DOE_STATUS_OFFSET: usize = 0x0C;
+pub const PCI_DOE_WRITE_DATA_MAILBOX_OFFSET: usize = 0x10;
+pub const PCI_DOE_READ_DATA_MAILBOX_OFFSET: usize = 0x14;
+
+pub const PCI_DOE_CONTROL_ABORT: u32 = 1 << 0;
+pub const PCI_DOE_CONTROL_GO: u32 = 1 << 31;
+
+pub const PCI_DOE_STATUS_BUSY: u32 = 1 << 0;
+pub const PCI_DOE_STATUS_ERROR: u32 = 1 << 2;
+pub const PCI_DOE_STATUS_DATA_OBJECT_READY: u32 = 1 << 31;
+
+// the header length field counts dwords in 18 bits, 0 stands for 2^18.
+pub const PCI_DOE_HEADER_DWORDS: usize = 2;
+pub const PCI_DOE_MAX_DATA_OBJECT_DWORDS: usize = 0x40000;
+pub const PCI_DOE_MAX_DATA_OBJECT_SIZE: usize = PCI_DOE_MAX_DATA_OBJECT_DWORDS * 4;
+
+pub const PCI_DOE_DEFAULT_POLL_LIMIT: usize = 0x10000;
+
+// dword access to the registers of one DOE capability.
+pub trait PciDoeMailboxRegisters {
+    fn read_u32(&mut self, offset: usize) -> u32;
+    fn write_u32(&mut self, offset: usize, value: u32);
+}
+
+fn data_object_dwords(length_dword: u32) -> usize {
+    match length_dword & 0x3ffff {
+        0 => PCI_DOE_MAX_DATA_OBJECT_DWORDS,
+        length => length as usize,
+    }
+}
+
+// drive a DOE mailbox with the data objects built by PciDoeTransportEncap.
+// the registers carry no clock, so waiting is bounded by a number of status polls.
+pub struct PciDoeMailboxDeviceIo<'a> {
+    registers: &'a mut dyn PciDoeMailboxRegisters,
+    max_data_object_size: usize,
+    poll_limit: usize,
+}
+
+impl<'a> PciDoeMailboxDeviceIo<'a> {
+    pub fn new(registers: &'a mut dyn PciDoeMailboxRegisters) -> Self {
+        PciDoeMailboxDeviceIo {
+            registers,
+            max_data_object_size: PCI_DOE_MAX_DATA_OBJECT_SIZE,
+            poll_limit: PCI_DOE_DEFAULT_POLL_LIMIT,
+        }
+    }
+
+    // the largest data object the mailbox accepts, in bytes.
+    pub fn set_max_data_object_size(&mut self, max_data_object_size: usize) {
+        self.max_data_object_size = max_data_object_size.min(PCI_DOE_MAX_DATA_OBJECT_SIZE);
+    }
+
+    pub fn set_poll_limit(&mut self, poll_limit: usize) {
+        self.poll_limit = poll_limit;
+    }
+
+    fn status(&mut self) -> u32 {
+        self.registers.read_u32(PCI_DOE_STATUS_OFFSET)
+    }
+
+    fn wait_not_busy(&mut self) -> SpdmResult {
+        for _ in 0..self.poll_limit {
+            if self.status() & PCI_DOE_STATUS_BUSY == 0 {
+                return Ok(());
+            }
+        }
+        Err(SPDM_STATUS_BUSY_PEER)
+    }
+
+    // discard any data object in flight and clear the error bit.
+    pub fn abort(&mut self) -> SpdmResult {
+        self.registers
+            .write_u32(PCI_DOE_CONTROL_OFFSET, PCI_DOE_CONTROL_ABORT);
+        self.wait_not_busy()?;
+        if self.status() & PCI_DOE_STATUS_ERROR != 0 {
+            return Err(SPDM_STATUS_ERROR_PEER);
+        }
+        Ok(())
+    }
+
+    // abort after a failure, and report the failure rather than the abort.
+    fn abort_with(&mut self, status: SpdmStatus) -> SpdmResult {
+        let _ = self.abort();
+        Err(status)
+    }
+
+    fn next_read_dword(&mut self) -> u32 {
+        let dword = self.registers.read_u32(PCI_DOE_READ_DATA_MAILBOX_OFFSET);
+        // any write pops the dword just read.
+        self.registers
+            .write_u32(PCI_DOE_READ_DATA_MAILBOX_OFFSET, 0);
+        dword
+    }
+}
+
+impl SpdmDeviceIo for PciDoeMailboxDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        // data objects are whole dwords, pad the tail with zeros.
+        let aligned_size = (buffer.len() + 3) & !3;
+        if aligned_size < PCI_DOE_HEADER_DWORDS * 4 || aligned_size > self.max_data_object_size {
+            return Err(SPDM_STATUS_INVALID_MSG_SIZE);
+        }
+        if self.status() & PCI_DOE_STATUS_ERROR != 0 {
+            self.abort()?;
+        }
+        self.wait_not_busy()?;
+
+        for chunk in buffer.chunks(4) {
+            let mut dword = [0u8; 4];
+            dword[..chunk.len()].copy_from_slice(chunk);
+            self.registers
+                .write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, u32::from_le_bytes(dword));
+        }
+        self.registers
+            .write_u32(PCI_DOE_CONTROL_OFFSET, PCI_DOE_CONTROL_GO);
+
+        if self.status() & PCI_DOE_STATUS_ERROR != 0 {
+            return self.abort_with(SPDM_STATUS_ERROR_PEER);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        let mut ready = false;
+        for _ in 0..self.poll_limit {
+            let status = self.status();
+            if status & PCI_DOE_STATUS_ERROR != 0 {
+                let _ = self.abort();
+                return Err(0);
+            }
+            if status & PCI_DOE_STATUS_DATA_OBJECT_READY != 0 {
+                ready = true;
+                break;
+            }
+        }
+        if !ready {
+            return Err(0);
+        }
+
+        let header = self.next_read_dword();
+        let length_dword = self.next_read_dword();
+        let dwords = data_object_dwords(length_dword);
+        if dwords < PCI_DOE_HEADER_DWORDS
+            || dwords * 4 > self.max_data_object_size
+            || dwords * 4 > buffer.len()
+        {
+            let _ = self.abort();
+            return Err(0);
+        }
+
+        buffer[..4].copy_from_slice(&header.to_le_bytes());
+        buffer[4..8].copy_from_slice(&length_dword.to_le_bytes());
+        for index in PCI_DOE_HEADER_DWORDS..dwords {
+            let dword = self.next_read_dword();
+            buffer[index * 4..(index + 1) * 4].copy_from_slice(&dword.to_le_bytes());
+        }
+        Ok(dwords * 4)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.abort()
+    }
+}
+
+pub const PCI_DOE_EMULATED_MAILBOX_DWORDS: usize =
+    if config::SENDER_BUFFER_SIZE > config::RECEIVER_BUFFER_SIZE {
+        config::SENDER_BUFFER_SIZE / 4
+    } else {
+        config::RECEIVER_BUFFER_SIZE / 4
+    };
+
+// answers a request data object with a response data object, and returns its size.
+// None leaves the mailbox without a response.
+pub type PciDoeMailboxHandler<'a> = &'a mut dyn FnMut(&[u8], &mut [u8]) -> Option<usize>;
+
+// an in-memory DOE mailbox, the device side of the register protocol.
+pub struct PciDoeEmulatedMailbox<'a> {
+    handler: PciDoeMailboxHandler<'a>,
+    max_data_object_dwords: usize,
+    request: [u32; PCI_DOE_EMULATED_MAILBOX_DWORDS],
+    request_dwords: usize,
+    response: [u32; PCI_DOE_EMULATED_MAILBOX_DWORDS],
+    response_dwords: usize,
+    response_read: usize,
+    busy_polls: usize,
+    busy_left: usize,
+    error: bool,
+}
+
+impl<'a> PciDoeEmulatedMailbox<'a> {
+    pub fn new(handler: PciDoeMailboxHandler<'a>) -> Self {
+        PciDoeEmulatedMailbox {
+            handler,
+            max_data_object_dwords: PCI_DOE_EMULATED_MAILBOX_DWORDS,
+            request: [0u32; PCI_DOE_EMULATED_MAILBOX_DWORDS],
+            request_dwords: 0,
+            response: [0u32; PCI_DOE_EMULATED_MAILBOX_DWORDS],
+            response_dwords: 0,
+            response_read: 0,
+            busy_polls: 0,
+            busy_left: 0,
+            error: false,
+        }
+    }
+
+    pub fn set_max_data_object_size(&mut self, max_data_object_size: usize) -> SpdmResult {
+        let dwords = max_data_object_size / 4;
+        if !(PCI_DOE_HEADER_DWORDS..=PCI_DOE_EMULATED_MAILBOX_DWORDS).contains(&dwords) {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        self.max_data_object_dwords = dwords;
+        Ok(())
+    }
+
+    // report Busy for this many status reads after each Go.
+    pub fn set_busy_polls(&mut self, busy_polls: usize) {
+        self.busy_polls = busy_polls;
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.error
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.busy_left != 0
+    }
+
+    fn reset(&mut self) {
+        self.request_dwords = 0;
+        self.response_dwords = 0;
+        self.response_read = 0;
+        self.busy_left = 0;
+        self.error = false;
+    }
+
+    fn go(&mut self) {
+        if self.error || self.is_busy() {
+            return;
+        }
+        // the request must be exactly the data object its header describes.
+        if self.request_dwords < PCI_DOE_HEADER_DWORDS
+            || data_object_dwords(self.request[1]) != self.request_dwords
+        {
+            self.error = true;
+            return;
+        }
+        // a new request drops a response that was not read.
+        self.response_dwords = 0;
+        self.response_read = 0;
+        if self.busy_polls == 0 {
+            self.process();
+        } else {
+            self.busy_left = self.busy_polls;
+        }
+    }
+
+    fn process(&mut self) {
+        let mut request = [0u8; PCI_DOE_EMULATED_MAILBOX_DWORDS * 4];
+        for (index, dword) in self.request[..self.request_dwords].iter().enumerate() {
+            request[index * 4..(index + 1) * 4].copy_from_slice(&dword.to_le_bytes());
+        }
+        let request_size = self.request_dwords * 4;
+        self.request_dwords = 0;
+
+        let mut response = [0u8; PCI_DOE_EMULATED_MAILBOX_DWORDS * 4];
+        let max_response_size = self.max_data_object_dwords * 4;
+        let response_size =
+            match (self.handler)(&request[..request_size], &mut response[..max_response_size]) {
+                Some(response_size) => response_size,
+                None => return,
+            };
+        // the mailbox cannot hold a larger data object, as for a request.
+        if response_size > max_response_size {
+            self.error = true;
+            return;
+        }
+        let aligned_size = (response_size + 3) & !3;
+        for (index, chunk) in response[..aligned_size].chunks(4).enumerate() {
+            let mut dword = [0u8; 4];
+            dword.copy_from_slice(chunk);
+            self.response[index] = u32::from_le_bytes(dword);
+        }
+        self.response_dwords = aligned_size / 4;
+    }
+
+    fn status(&mut self) -> u32 {
+        if self.busy_left != 0 {
+            self.busy_left -= 1;
+            if self.busy_left == 0 {
+                self.process();
+            }
+            return PCI_DOE_STATUS_BUSY;
+        }
+        let mut status = 0;
+        if self.error {
+            status |= PCI_DOE_STATUS_ERROR;
+        }
+        if self.response_read < self.response_dwords {
+            status |= PCI_DOE_STATUS_DATA_OBJECT_READY;
+        }
+        status
+    }
+}
+
+impl PciDoeMailboxRegisters for PciDoeEmulatedMailbox<'_> {
+    fn read_u32(&mut self, offset: usize) -> u32 {
+        match offset {
+            PCI_DOE_STATUS_OFFSET => self.status(),
+            PCI_DOE_READ_DATA_MAILBOX_OFFSET if self.response_read < self.response_dwords => {
+                self.response[self.response_read]
+            }
+            _ => 0,
+        }
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        match offset {
+            PCI_DOE_CONTROL_OFFSET if value & PCI_DOE_CONTROL_ABORT != 0 => self.reset(),
+            PCI_DOE_CONTROL_OFFSET if value & PCI_DOE_CONTROL_GO != 0 => self.go(),
+            PCI_DOE_WRITE_DATA_MAILBOX_OFFSET => {
+                if self.is_busy() || self.request_dwords == self.max_data_object_dwords {
+                    self.error = true;
+                } else if !self.error {
+                    self.request[self.request_dwords] = value;
+                    self.request_dwords += 1;
+                }
+            }
+            PCI_DOE_READ_DATA_MAILBOX_OFFSET if self.response_read < self.response_dwords => {
+                self.response_read += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::discovery::*;
+    use crate::header::PciDoeTransportEncap;
+    use spdmlib::common::SpdmTransportEncap;
+
+    // echo the request back with the first payload byte incremented.
+    fn echo(request: &[u8], response: &mut [u8]) -> Option<usize> {
+        response[..request.len()].copy_from_slice(request);
+        response[8] = response[8].wrapping_add(1);
+        Some(request.len())
+    }
+
+    fn encap(spdm_buffer: &[u8], transport_buffer: &mut [u8]) -> usize {
+        PciDoeTransportEncap {}
+            .encap(spdm_buffer, transport_buffer, false)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_case0_send_receive() {
+        let handler = &mut echo;
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+
+        // a 5 byte SPDM message is padded to 2 dwords of payload.
+        let mut transport_buffer = [0u8; 64];
+        let used = encap(&[0x10, 0x84, 0, 0, 0x55], &mut transport_buffer);
+        assert_eq!(used, 16);
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        let mut receive_buffer = [0u8; 64];
+        let received = device_io.receive(&mut receive_buffer, 0).unwrap();
+        assert_eq!(received, 16);
+        assert_eq!(receive_buffer[8..13], [0x11, 0x84, 0, 0, 0x55]);
+        assert_eq!(receive_buffer[13..16], [0, 0, 0]);
+
+        // nothing is left in the mailbox.
+        device_io.set_poll_limit(4);
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+    }
+
+    #[test]
+    fn test_case1_send_receive_unaligned() {
+        let handler = &mut echo;
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+
+        // the adapter pads a data object that is not dword aligned.
+        let request = [0x01u8, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x10, 0x84];
+        device_io.send(&request).unwrap();
+        let mut receive_buffer = [0u8; 64];
+        let received = device_io.receive(&mut receive_buffer, 0).unwrap();
+        assert_eq!(received, 12);
+        assert_eq!(receive_buffer[8..12], [0x11, 0x84, 0, 0]);
+    }
+
+    #[test]
+    fn test_case0_busy() {
+        let handler = &mut echo;
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        mailbox.set_busy_polls(10);
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+
+        let mut transport_buffer = [0u8; 64];
+        let used = encap(&[0x10, 0x84, 0, 0], &mut transport_buffer);
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        // the mailbox stays busy for longer than the adapter polls.
+        device_io.set_poll_limit(5);
+        let mut receive_buffer = [0u8; 64];
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+        device_io.set_poll_limit(3);
+        assert_eq!(
+            device_io.send(&transport_buffer[..used]),
+            Err(SPDM_STATUS_BUSY_PEER)
+        );
+
+        device_io.set_poll_limit(PCI_DOE_DEFAULT_POLL_LIMIT);
+        let received = device_io.receive(&mut receive_buffer, 0).unwrap();
+        assert_eq!(receive_buffer[8..received], [0x11, 0x84, 0, 0]);
+    }
+
+    #[test]
+    fn test_case0_abort() {
+        let handler = &mut echo;
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+
+        // a request whose length does not match its header sets the error bit.
+        mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 0x0001_0001);
+        mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 4);
+        mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 0x0000_8410);
+        mailbox.write_u32(PCI_DOE_CONTROL_OFFSET, PCI_DOE_CONTROL_GO);
+        assert!(mailbox.is_error());
+
+        {
+            // the adapter aborts before it sends.
+            let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+            let mut transport_buffer = [0u8; 64];
+            let used = encap(&[0x10, 0x84, 0, 0], &mut transport_buffer);
+            device_io.send(&transport_buffer[..used]).unwrap();
+
+            // an abort drops the pending response.
+            device_io.abort().unwrap();
+            device_io.set_poll_limit(4);
+            let mut receive_buffer = [0u8; 64];
+            assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+        }
+        assert!(!mailbox.is_error());
+    }
+
+    #[test]
+    fn test_case0_oversize() {
+        let handler = &mut echo;
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        mailbox.set_max_data_object_size(16).unwrap();
+        assert!(mailbox.set_max_data_object_size(4).is_err());
+
+        // the mailbox rejects a data object larger than it holds.
+        mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 0x0001_0001);
+        mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 5);
+        for _ in 0..3 {
+            mailbox.write_u32(PCI_DOE_WRITE_DATA_MAILBOX_OFFSET, 0);
+        }
+        assert!(mailbox.is_error());
+
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+        device_io.set_max_data_object_size(16);
+        let mut transport_buffer = [0u8; 64];
+        let used = encap(&[0x10, 0x84, 0, 0, 0, 0, 0, 0, 0], &mut transport_buffer);
+        assert_eq!(
+            device_io.send(&transport_buffer[..used]),
+            Err(SPDM_STATUS_INVALID_MSG_SIZE)
+        );
+        let used = encap(&[0x10, 0x84, 0, 0], &mut transport_buffer);
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        // a response larger than the receive buffer is aborted.
+        let mut receive_buffer = [0u8; 8];
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+        device_io.set_poll_limit(4);
+        let mut receive_buffer = [0u8; 64];
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+    }
+
+    #[test]
+    fn test_case1_oversize() {
+        // a response one dword larger than the mailbox holds.
+        let handler = &mut |request: &[u8], response: &mut [u8]| -> Option<usize> {
+            response[..request.len()].copy_from_slice(request);
+            Some(response.len() + 4)
+        };
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        mailbox.set_max_data_object_size(16).unwrap();
+        let mut transport_buffer = [0u8; 64];
+        let used = encap(&[0x10, 0x84, 0, 0], &mut transport_buffer);
+        let mut receive_buffer = [0u8; 64];
+        {
+            let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+            device_io.set_poll_limit(4);
+            // the requester sees a mailbox error instead of a truncated response.
+            assert_eq!(
+                device_io.send(&transport_buffer[..used]),
+                Err(SPDM_STATUS_ERROR_PEER)
+            );
+            assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+        }
+
+        // the same once the mailbox is done being busy.
+        mailbox.set_busy_polls(2);
+        {
+            let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+            device_io.set_poll_limit(4);
+            device_io.send(&transport_buffer[..used]).unwrap();
+            assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+        }
+        // the requester aborted the failed exchange.
+        assert!(!mailbox.is_error());
+    }
+
+    #[test]
+    fn test_case0_discovery() {
+        let handler = &mut |request: &[u8], response: &mut [u8]| -> Option<usize> {
+            pci_doe_discovery_response(request, &PCI_DOE_SPDM_PROTOCOLS, response).ok()
+        };
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        mailbox.set_busy_polls(3);
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+
+        let mut protocols = [PciDoeProtocol::default(); 4];
+        let count = pci_doe_discovery(&mut device_io, &mut protocols).unwrap();
+        assert_eq!(protocols[..count], PCI_DOE_SPDM_PROTOCOLS);
+    }
+
+    #[test]
+    fn test_case0_no_response() {
+        let handler = &mut |_: &[u8], _: &mut [u8]| -> Option<usize> { None };
+        let mut mailbox = PciDoeEmulatedMailbox::new(handler);
+        let mut device_io = PciDoeMailboxDeviceIo::new(&mut mailbox);
+        device_io.set_poll_limit(16);
+
+        let mut transport_buffer = [0u8; 64];
+        let used = encap(&[0x10, 0x84, 0, 0], &mut transport_buffer);
+        device_io.send(&transport_buffer[..used]).unwrap();
+        let mut receive_buffer = [0u8; 64];
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+    }
+}
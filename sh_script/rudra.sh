@@ -20,6 +20,8 @@ paths=(
     "spdmlib"
     "mctp_transport"
     "pcidoe_transport"
+    "tcp_transport"
+    "storage_transport"
     
 )
 
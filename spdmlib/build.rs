@@ -35,6 +35,11 @@ impl SpdmConfig {
 
         assert!(self.cert_config.max_cert_chain_data_size <= 0xFFFF);
         // no need to check max_cert_chain_data_size against max_spdm_msg_size
+        assert!(self.cert_config.max_root_cert_support >= 1);
+        // SpdmProvisionInfo derives Default, which is only implemented for arrays up to 32.
+        assert!(self.cert_config.max_root_cert_support <= 32);
+        // same as max_root_cert_support, 0 means no CRL can be provisioned.
+        assert!(self.cert_config.max_crl_support <= 32);
 
         assert!(self.measurement_config.max_measurement_record_size <= 0xFFFFFF);
         assert!(self.measurement_config.max_measurement_val_len <= 0xFFFF - 7);
@@ -45,7 +50,8 @@ impl SpdmConfig {
         assert!(self.measurement_config.max_measurement_val_len >= 32);
         assert!(self.measurement_config.max_measurement_record_size < self.max_spdm_msg_size);
 
-        assert!(self.psk_config.max_psk_context_size >= 32);
+        // the PSK context holds at least one digest of the largest hash (SHA-512).
+        assert!(self.psk_config.max_psk_context_size >= 64);
         assert!(self.psk_config.max_psk_context_size <= 0xFFFF);
         assert!(self.psk_config.max_psk_hint_size <= 0xFFFF);
         assert!(
@@ -60,6 +66,8 @@ impl SpdmConfig {
 #[derive(Debug, PartialEq, Deserialize)]
 struct SpdmCertConfig {
     max_cert_chain_data_size: usize,
+    max_root_cert_support: usize,
+    max_crl_support: usize,
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -93,6 +101,12 @@ macro_rules! TEMPLATE {
 /// This is used in SpdmCertChainData without SpdmCertChainHeader.
 pub const MAX_SPDM_CERT_CHAIN_DATA_SIZE: usize = {cert_chain_data_sz}; // 0x1000;
 
+/// This is used in SpdmProvisionInfo as the number of trusted peer root certificates.
+pub const MAX_ROOT_CERT_SUPPORT: usize = {root_cert_cnt};
+
+/// This is used in SpdmProvisionInfo as the number of peer certificate revocation lists.
+pub const MAX_CRL_SUPPORT: usize = {crl_cnt};
+
 /// This is used in SpdmMeasurementsResponsePayload
 pub const MAX_SPDM_MEASUREMENT_RECORD_SIZE: usize = {meas_rec_sz}; // 0x1000
 
@@ -174,6 +188,8 @@ fn main() {
         &mut to_generate,
         TEMPLATE!(),
         cert_chain_data_sz = spdm_config.cert_config.max_cert_chain_data_size,
+        root_cert_cnt = spdm_config.cert_config.max_root_cert_support,
+        crl_cnt = spdm_config.cert_config.max_crl_support,
         meas_rec_sz = spdm_config.measurement_config.max_measurement_record_size,
         meas_val_len = spdm_config.measurement_config.max_measurement_val_len,
         psk_ctx_sz = spdm_config.psk_config.max_psk_context_size,
@@ -0,0 +1,474 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+// the async counterparts of SpdmDeviceIo and SpdmTransportEncap. the futures
+// are boxed, so the traits stay object safe, and no executor is assumed: a
+// future is woken by whatever drives the device io.
+// the sync API runs the same futures in block_on, which polls them once. a
+// sync device io, or an async one that completes without returning Pending,
+// serves both APIs, an async one that has to wait is driven by the async API.
+
+extern crate alloc;
+use alloc::boxed::Box;
+use core::fmt::Debug;
+use core::future::{ready, Future};
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+#[cfg(feature = "downcast")]
+use core::any::Any;
+
+use super::{SpdmDeviceIo, SpdmTransportEncap};
+use crate::error::{SpdmResult, SPDM_STATUS_INVALID_STATE_LOCAL};
+
+pub type SpdmFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+pub trait AsyncSpdmDeviceIo {
+    fn send<'a>(&'a mut self, buffer: &'a [u8]) -> SpdmFuture<'a, SpdmResult>;
+
+    // timeout is in microseconds, as for SpdmDeviceIo.
+    fn receive<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+        timeout: usize,
+    ) -> SpdmFuture<'a, Result<usize, usize>>;
+
+    fn flush_all(&mut self) -> SpdmFuture<'_, SpdmResult>;
+
+    #[cfg(feature = "downcast")]
+    fn as_any(&mut self) -> &mut dyn Any;
+}
+
+impl Debug for dyn AsyncSpdmDeviceIo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Dyn AsyncSpdmDeviceIo")
+    }
+}
+
+pub trait AsyncSpdmTransportEncap {
+    fn encap<'a>(
+        &'a mut self,
+        spdm_buffer: &'a [u8],
+        transport_buffer: &'a mut [u8],
+        secured_message: bool,
+    ) -> SpdmFuture<'a, SpdmResult<usize>>;
+
+    fn decap<'a>(
+        &'a mut self,
+        transport_buffer: &'a [u8],
+        spdm_buffer: &'a mut [u8],
+    ) -> SpdmFuture<'a, SpdmResult<(usize, bool)>>;
+
+    fn encap_app<'a>(
+        &'a mut self,
+        spdm_buffer: &'a [u8],
+        app_buffer: &'a mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmFuture<'a, SpdmResult<usize>>;
+
+    fn decap_app<'a>(
+        &'a mut self,
+        app_buffer: &'a [u8],
+        spdm_buffer: &'a mut [u8],
+    ) -> SpdmFuture<'a, SpdmResult<(usize, Option<u8>)>>;
+
+    fn get_default_app_message_type(&mut self) -> u8;
+
+    // for session
+    fn get_sequence_number_count(&mut self) -> u8;
+    fn get_max_random_count(&mut self) -> u16;
+}
+
+impl Debug for dyn AsyncSpdmTransportEncap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Dyn AsyncSpdmTransportEncap")
+    }
+}
+
+// a waker that does nothing, block_on never waits for a wake.
+static SPDM_NOOP_WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(spdm_noop_waker_clone, spdm_noop, spdm_noop, spdm_noop);
+
+fn spdm_noop_raw_waker() -> RawWaker {
+    RawWaker::new(core::ptr::null(), &SPDM_NOOP_WAKER_VTABLE)
+}
+
+fn spdm_noop_waker_clone(_data: *const ()) -> RawWaker {
+    spdm_noop_raw_waker()
+}
+
+fn spdm_noop(_data: *const ()) {}
+
+// poll a future once, None if it is pending.
+#[allow(unsafe_code)]
+fn poll_once<F: Future>(future: F) -> Option<F::Output> {
+    // SAFETY: the vtable functions do nothing and never touch the data pointer.
+    let waker = unsafe { Waker::from_raw(spdm_noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    match Box::pin(future).as_mut().poll(&mut context) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+// run a future of the sync API on the current thread. the future must complete
+// without returning Pending, as it does on a sync device io, block_on does not
+// wait for it and fails with SPDM_STATUS_INVALID_STATE_LOCAL instead.
+pub fn block_on<T>(future: impl Future<Output = SpdmResult<T>>) -> SpdmResult<T> {
+    poll_once(future).unwrap_or(Err(SPDM_STATUS_INVALID_STATE_LOCAL))
+}
+
+// the device io of an SpdmContext, either kind serves both the sync and the
+// async API of the context. the sync functions call a sync device io directly,
+// on an async one they run the future in block_on.
+pub enum SpdmDeviceIoHandle<'a> {
+    Sync(&'a mut dyn SpdmDeviceIo),
+    Async(&'a mut dyn AsyncSpdmDeviceIo),
+}
+
+impl SpdmDeviceIoHandle<'_> {
+    pub fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => device_io.send(buffer),
+            SpdmDeviceIoHandle::Async(device_io) => block_on(device_io.send(buffer)),
+        }
+    }
+
+    pub fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize> {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => device_io.receive(buffer, timeout),
+            SpdmDeviceIoHandle::Async(device_io) => {
+                poll_once(device_io.receive(buffer, timeout)).unwrap_or(Err(0))
+            }
+        }
+    }
+
+    pub fn flush_all(&mut self) -> SpdmResult {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => device_io.flush_all(),
+            SpdmDeviceIoHandle::Async(device_io) => block_on(device_io.flush_all()),
+        }
+    }
+
+    #[cfg(feature = "downcast")]
+    pub fn as_any(&mut self) -> &mut dyn Any {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => device_io.as_any(),
+            SpdmDeviceIoHandle::Async(device_io) => device_io.as_any(),
+        }
+    }
+
+    pub fn send_async<'b>(&'b mut self, buffer: &'b [u8]) -> SpdmFuture<'b, SpdmResult> {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => Box::pin(ready(device_io.send(buffer))),
+            SpdmDeviceIoHandle::Async(device_io) => device_io.send(buffer),
+        }
+    }
+
+    pub fn receive_async<'b>(
+        &'b mut self,
+        buffer: &'b mut [u8],
+        timeout: usize,
+    ) -> SpdmFuture<'b, Result<usize, usize>> {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => {
+                Box::pin(ready(device_io.receive(buffer, timeout)))
+            }
+            SpdmDeviceIoHandle::Async(device_io) => device_io.receive(buffer, timeout),
+        }
+    }
+
+    pub fn flush_all_async(&mut self) -> SpdmFuture<'_, SpdmResult> {
+        match self {
+            SpdmDeviceIoHandle::Sync(device_io) => Box::pin(ready(device_io.flush_all())),
+            SpdmDeviceIoHandle::Async(device_io) => device_io.flush_all(),
+        }
+    }
+}
+
+// the transport encap of an SpdmContext, see SpdmDeviceIoHandle.
+pub enum SpdmTransportEncapHandle<'a> {
+    Sync(&'a mut dyn SpdmTransportEncap),
+    Async(&'a mut dyn AsyncSpdmTransportEncap),
+}
+
+impl SpdmTransportEncapHandle<'_> {
+    pub fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.encap(spdm_buffer, transport_buffer, secured_message)
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                block_on(transport_encap.encap(spdm_buffer, transport_buffer, secured_message))
+            }
+        }
+    }
+
+    pub fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.decap(transport_buffer, spdm_buffer)
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                block_on(transport_encap.decap(transport_buffer, spdm_buffer))
+            }
+        }
+    }
+
+    pub fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult<usize> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.encap_app(spdm_buffer, app_buffer, app_message_type)
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                block_on(transport_encap.encap_app(spdm_buffer, app_buffer, app_message_type))
+            }
+        }
+    }
+
+    pub fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.decap_app(app_buffer, spdm_buffer)
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                block_on(transport_encap.decap_app(app_buffer, spdm_buffer))
+            }
+        }
+    }
+
+    pub fn get_default_app_message_type(&mut self) -> u8 {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.get_default_app_message_type()
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.get_default_app_message_type()
+            }
+        }
+    }
+
+    pub fn get_sequence_number_count(&mut self) -> u8 {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.get_sequence_number_count()
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.get_sequence_number_count()
+            }
+        }
+    }
+
+    pub fn get_max_random_count(&mut self) -> u16 {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                transport_encap.get_max_random_count()
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.get_max_random_count()
+            }
+        }
+    }
+
+    pub fn encap_async<'b>(
+        &'b mut self,
+        spdm_buffer: &'b [u8],
+        transport_buffer: &'b mut [u8],
+        secured_message: bool,
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => Box::pin(ready(
+                transport_encap.encap(spdm_buffer, transport_buffer, secured_message),
+            )),
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.encap(spdm_buffer, transport_buffer, secured_message)
+            }
+        }
+    }
+
+    pub fn decap_async<'b>(
+        &'b mut self,
+        transport_buffer: &'b [u8],
+        spdm_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<(usize, bool)>> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                Box::pin(ready(transport_encap.decap(transport_buffer, spdm_buffer)))
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.decap(transport_buffer, spdm_buffer)
+            }
+        }
+    }
+
+    pub fn encap_app_async<'b>(
+        &'b mut self,
+        spdm_buffer: &'b [u8],
+        app_buffer: &'b mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => Box::pin(ready(
+                transport_encap.encap_app(spdm_buffer, app_buffer, app_message_type),
+            )),
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.encap_app(spdm_buffer, app_buffer, app_message_type)
+            }
+        }
+    }
+
+    pub fn decap_app_async<'b>(
+        &'b mut self,
+        app_buffer: &'b [u8],
+        spdm_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<(usize, Option<u8>)>> {
+        match self {
+            SpdmTransportEncapHandle::Sync(transport_encap) => {
+                Box::pin(ready(transport_encap.decap_app(app_buffer, spdm_buffer)))
+            }
+            SpdmTransportEncapHandle::Async(transport_encap) => {
+                transport_encap.decap_app(app_buffer, spdm_buffer)
+            }
+        }
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::error::SPDM_STATUS_RECEIVE_FAIL;
+
+    // a future that is pending for the given number of polls.
+    struct SpdmPendingFuture(usize);
+
+    impl Future for SpdmPendingFuture {
+        type Output = usize;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+            if self.0 == 0 {
+                return Poll::Ready(7);
+            }
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn test_case0_block_on() {
+        assert_eq!(block_on(async { Ok(1 + 2) }), Ok(3));
+        assert_eq!(block_on(async { Ok(SpdmPendingFuture(0).await) }), Ok(7));
+        // a pending future is not polled again.
+        assert_eq!(
+            block_on(async { Ok(SpdmPendingFuture(1).await) }),
+            Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+        );
+    }
+
+    // echoes the message sent, each transfer is pending for the given number of polls.
+    struct SpdmEchoDeviceIo {
+        buffer: [u8; 8],
+        size: usize,
+        pending: usize,
+    }
+
+    impl AsyncSpdmDeviceIo for SpdmEchoDeviceIo {
+        fn send<'a>(&'a mut self, buffer: &'a [u8]) -> SpdmFuture<'a, SpdmResult> {
+            Box::pin(async move {
+                SpdmPendingFuture(self.pending).await;
+                self.buffer[..buffer.len()].copy_from_slice(buffer);
+                self.size = buffer.len();
+                Ok(())
+            })
+        }
+
+        fn receive<'a>(
+            &'a mut self,
+            buffer: &'a mut [u8],
+            _timeout: usize,
+        ) -> SpdmFuture<'a, Result<usize, usize>> {
+            Box::pin(async move {
+                SpdmPendingFuture(self.pending).await;
+                let size = core::mem::take(&mut self.size);
+                buffer[..size].copy_from_slice(&self.buffer[..size]);
+                Ok(size)
+            })
+        }
+
+        fn flush_all(&mut self) -> SpdmFuture<'_, SpdmResult> {
+            self.size = 0;
+            Box::pin(ready(Ok(())))
+        }
+
+        #[cfg(feature = "downcast")]
+        fn as_any(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_case0_device_io_handle() {
+        let device_io = &mut SpdmEchoDeviceIo {
+            buffer: [0u8; 8],
+            size: 0,
+            pending: 0,
+        };
+        let mut handle = SpdmDeviceIoHandle::Async(device_io);
+        let buffer = &mut [0u8; 8];
+
+        assert!(handle.send(&[1, 2, 3]).is_ok());
+        assert_eq!(handle.receive(buffer, 0), Ok(3));
+        assert_eq!(&buffer[..3], &[1, 2, 3]);
+
+        let received = block_on(async {
+            handle.send_async(&[4, 5]).await?;
+            handle
+                .receive_async(buffer, 0)
+                .await
+                .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)
+        });
+        assert_eq!(received, Ok(2));
+        assert_eq!(&buffer[..2], &[4, 5]);
+
+        assert!(handle.send(&[6]).is_ok());
+        assert!(handle.flush_all().is_ok());
+        assert_eq!(handle.receive(buffer, 0), Ok(0));
+    }
+
+    #[test]
+    fn test_case1_device_io_handle() {
+        // the sync API fails on a device io that has to wait.
+        let device_io = &mut SpdmEchoDeviceIo {
+            buffer: [0u8; 8],
+            size: 0,
+            pending: 1,
+        };
+        let mut handle = SpdmDeviceIoHandle::Async(device_io);
+        let buffer = &mut [0u8; 8];
+
+        assert_eq!(
+            handle.send(&[1, 2, 3]),
+            Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+        );
+        assert_eq!(handle.receive(buffer, 0), Err(0));
+    }
+}
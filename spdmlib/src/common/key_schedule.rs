@@ -10,6 +10,9 @@ use crate::secret;
 use alloc::boxed::Box;
 
 const MAX_BIN_CONCAT_BUF_SIZE: usize = 2 + 8 + 12 + SPDM_MAX_HASH_SIZE;
+// label and context of an exported secret are chosen by the downstream protocol.
+pub const SPDM_MAX_EXPORT_LABEL_CONTEXT_SIZE: usize = 64 + SPDM_MAX_HASH_SIZE;
+const MAX_EXPORT_BIN_CONCAT_BUF_SIZE: usize = 2 + 8 + SPDM_MAX_EXPORT_LABEL_CONTEXT_SIZE;
 const SALT_0: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
 const BIN_STR0_LABEL: &[u8] = b"derived";
 const BIN_STR1_LABEL: &[u8] = b"req hs data";
@@ -389,6 +392,32 @@ impl SpdmKeySchedule {
         SpdmExportMasterSecretStruct::from_spdm_hkdf_okm(okm)
     }
 
+    // HKDF-Expand(export_master_secret, bin_concat(length, version, label, context), length),
+    // e.g. for PCIe IDE or CXL IDE key derivation.
+    pub fn derive_export_secret(
+        &self,
+        spdm_version: SpdmVersion,
+        hash_algo: SpdmBaseHashAlgo,
+        key: &SpdmExportMasterSecretStruct,
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> Option<SpdmHkdfOutputKeyingMaterial> {
+        if length == 0 || length as usize > SPDM_MAX_HKDF_OKM_SIZE {
+            return None;
+        }
+        let buffer = &mut [0; MAX_EXPORT_BIN_CONCAT_BUF_SIZE];
+        let bin_str = self.binconcat(length, spdm_version, label, Some(context), buffer)?;
+        crypto::hkdf::hkdf_expand(
+            hash_algo,
+            &SpdmHkdfPseudoRandomKey::from_input_keying_material(
+                &SpdmHkdfInputKeyingMaterial::SpdmExportMasterSecret(key),
+            )?,
+            bin_str,
+            length,
+        )
+    }
+
     pub fn derive_update_secret(
         &self,
         spdm_version: SpdmVersion,
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+pub mod async_io;
 pub mod key_schedule;
 pub mod opaque;
 pub mod session;
@@ -10,19 +11,24 @@ pub mod spdm_codec;
 use crate::message::SpdmRequestResponseCode;
 use crate::{crypto, protocol::*};
 
+pub use async_io::*;
 pub use opaque::*;
 pub use spdm_codec::SpdmCodec;
 
-use crate::config::{self, MAX_SPDM_SESSION_COUNT};
+use crate::config;
 use crate::error::{
     SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_DECAP_FAIL,
-    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL,
-    SPDM_STATUS_SESSION_NUMBER_EXCEED,
+    SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_SESSION_NUMBER_EXCEED,
 };
 
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
 use session::*;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+extern crate alloc;
+use alloc::boxed::Box;
 
 enum_builder! {
     @U8
@@ -105,15 +111,23 @@ pub trait SpdmTransportEncap {
         spdm_buffer: &mut [u8],
     ) -> SpdmResult<(usize, bool)>;
 
+    // app_message_type is None for an SPDM message, otherwise the transport
+    // specific type of the application message, e.g. the MCTP message type.
     fn encap_app(
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize>;
 
-    fn decap_app(&mut self, app_buffer: &[u8], spdm_buffer: &mut [u8])
-        -> SpdmResult<(usize, bool)>;
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)>;
+
+    // type of the application messages in a session that does not set one.
+    fn get_default_app_message_type(&mut self) -> u8;
 
     // for session
     fn get_sequence_number_count(&mut self) -> u8;
@@ -127,8 +141,8 @@ impl Debug for dyn SpdmTransportEncap {
 }
 
 pub struct SpdmContext<'a> {
-    pub device_io: &'a mut dyn SpdmDeviceIo,
-    pub transport_encap: &'a mut dyn SpdmTransportEncap,
+    pub device_io: SpdmDeviceIoHandle<'a>,
+    pub transport_encap: SpdmTransportEncapHandle<'a>,
 
     pub config_info: SpdmConfigInfo,
     pub negotiate_info: SpdmNegotiateInfo,
@@ -149,6 +163,20 @@ impl<'a> SpdmContext<'a> {
         transport_encap: &'a mut dyn SpdmTransportEncap,
         config_info: SpdmConfigInfo,
         provision_info: SpdmProvisionInfo,
+    ) -> Self {
+        SpdmContext::new_with_handle(
+            SpdmDeviceIoHandle::Sync(device_io),
+            SpdmTransportEncapHandle::Sync(transport_encap),
+            config_info,
+            provision_info,
+        )
+    }
+
+    pub fn new_with_handle(
+        device_io: SpdmDeviceIoHandle<'a>,
+        transport_encap: SpdmTransportEncapHandle<'a>,
+        config_info: SpdmConfigInfo,
+        provision_info: SpdmProvisionInfo,
     ) -> Self {
         SpdmContext {
             device_io,
@@ -202,6 +230,19 @@ impl<'a> SpdmContext<'a> {
             .find(|&session| session.get_session_id() == session_id)
     }
 
+    pub fn export_secret(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> SpdmResult<SpdmHkdfOutputKeyingMaterial> {
+        let session = self
+            .get_immutable_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        session.export_secret(self.negotiate_info.spdm_version_sel, label, context, length)
+    }
+
     pub fn get_session_via_id(&mut self, session_id: u32) -> Option<&mut SpdmSession> {
         self.session
             .iter_mut()
@@ -226,19 +267,52 @@ impl<'a> SpdmContext<'a> {
         status
     }
 
+    // a half session ID starting from a random value, so the session IDs cannot
+    // be predicted. it is never INVALID_HALF_SESSION_ID and differs from our half
+    // of every other session, so the full session ID cannot collide whatever the
+    // peer picks. on a clash the next lower value is tried.
     pub fn get_next_half_session_id(&self, is_requester: bool) -> SpdmResult<u16> {
         let shift = if is_requester { 0 } else { 16 };
 
-        for (index, s) in self.session.iter().enumerate().take(MAX_SPDM_SESSION_COUNT) {
-            if ((s.get_session_id() & (0xFFFF << shift)) >> shift) as u16 == INVALID_HALF_SESSION_ID
+        if self
+            .get_immutable_session_via_id(INVALID_SESSION_ID)
+            .is_none()
+        {
+            return Err(SPDM_STATUS_SESSION_NUMBER_EXCEED);
+        }
+
+        let mut random = [0u8; 2];
+        crypto::rand::get_random(&mut random)?;
+        let mut half_session_id = u16::from_le_bytes(random);
+        // at most MAX_SPDM_SESSION_COUNT values are taken, counting INVALID_HALF_SESSION_ID.
+        for _ in 0..=config::MAX_SPDM_SESSION_COUNT {
+            if half_session_id != INVALID_HALF_SESSION_ID
+                && self
+                    .session
+                    .iter()
+                    .all(|s| ((s.get_session_id() >> shift) & 0xFFFF) as u16 != half_session_id)
             {
-                return Ok(INITIAL_SESSION_ID - index as u16);
+                return Ok(half_session_id);
             }
+            half_session_id = half_session_id.wrapping_sub(1);
         }
 
         Err(SPDM_STATUS_SESSION_NUMBER_EXCEED)
     }
 
+    // the session ID of a new session, neither half may be INVALID_HALF_SESSION_ID
+    // and no other session may use it.
+    pub fn get_new_session_id(&self, req_session_id: u16, rsp_session_id: u16) -> SpdmResult<u32> {
+        if req_session_id == INVALID_HALF_SESSION_ID || rsp_session_id == INVALID_HALF_SESSION_ID {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        let session_id = ((rsp_session_id as u32) << 16) + req_session_id as u32;
+        if self.get_immutable_session_via_id(session_id).is_some() {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        Ok(session_id)
+    }
+
     pub fn construct_my_cert_chain(&mut self) -> SpdmResult {
         for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
             if self.provision_info.my_cert_chain[slot_id].is_none()
@@ -278,6 +352,130 @@ impl<'a> SpdmContext<'a> {
         Ok(())
     }
 
+    /// Verify the peer cert chain (without SpdmCertChainHeader) against the
+    /// provisioned peer root certs. Returns the index of the root cert in
+    /// `provision_info.peer_root_cert_data` the chain is anchored on, or None
+    /// if no root cert is provisioned and the chain is only checked against its own root.
+    /// The chain is then checked against the provisioned peer CRLs, if any.
+    pub fn verify_peer_cert_chain(
+        &self,
+        cert_chain: &[u8],
+        is_requester_cert: bool,
+    ) -> SpdmResult<Option<usize>> {
+        self.check_peer_cert_chain_profile(cert_chain, is_requester_cert)?;
+        let root_cert_index = self.verify_peer_cert_chain_trust(cert_chain, is_requester_cert)?;
+        self.check_peer_cert_chain_revocation(cert_chain, root_cert_index)?;
+        Ok(root_cert_index)
+    }
+
+    // the peer leaf cert must carry a key of the negotiated algorithm,
+    // and follow the DSP0274 certificate requirements of its role.
+    fn check_peer_cert_chain_profile(
+        &self,
+        cert_chain: &[u8],
+        is_requester_cert: bool,
+    ) -> SpdmResult {
+        let (asym_algo, is_device_cert_model) = if is_requester_cert {
+            (
+                SpdmBaseAsymAlgo::from_bits_truncate(self.negotiate_info.req_asym_sel.bits() as u32),
+                true,
+            )
+        } else {
+            (
+                self.negotiate_info.base_asym_sel,
+                !self
+                    .negotiate_info
+                    .rsp_capabilities_sel
+                    .contains(SpdmResponseCapabilityFlags::ALIAS_CERT_CAP),
+            )
+        };
+
+        crypto::check_cert_chain_format(cert_chain, asym_algo)?;
+        let (leaf_cert_begin, leaf_cert_end) =
+            crypto::cert_operation::get_cert_from_cert_chain(cert_chain, -1)?;
+        crypto::check_leaf_certificate(
+            &cert_chain[leaf_cert_begin..leaf_cert_end],
+            is_requester_cert,
+            is_device_cert_model,
+        )
+    }
+
+    fn verify_peer_cert_chain_trust(
+        &self,
+        cert_chain: &[u8],
+        is_requester_cert: bool,
+    ) -> SpdmResult<Option<usize>> {
+        let mut trust_anchors: [&[u8]; config::MAX_ROOT_CERT_SUPPORT] =
+            [&[]; config::MAX_ROOT_CERT_SUPPORT];
+        let mut trust_anchor_index = [0usize; config::MAX_ROOT_CERT_SUPPORT];
+        let mut trust_anchor_count = 0usize;
+        let peer_root_cert_data_list = &self.provision_info.peer_root_cert_data;
+        for (index, peer_root_cert_data) in peer_root_cert_data_list.iter().enumerate() {
+            if let Some(peer_root_cert_data) = peer_root_cert_data {
+                trust_anchors[trust_anchor_count] = peer_root_cert_data.as_ref();
+                trust_anchor_index[trust_anchor_count] = index;
+                trust_anchor_count += 1;
+            }
+        }
+
+        if trust_anchor_count == 0 {
+            let (root_cert_begin, root_cert_end) =
+                crypto::cert_operation::get_cert_from_cert_chain(cert_chain, 0)?;
+            crypto::cert_operation::verify_cert_chain(
+                cert_chain,
+                &[&cert_chain[root_cert_begin..root_cert_end]],
+                is_requester_cert,
+            )?;
+            return Ok(None);
+        }
+
+        let matched = crypto::cert_operation::verify_cert_chain(
+            cert_chain,
+            &trust_anchors[..trust_anchor_count],
+            is_requester_cert,
+        )?;
+        if matched >= trust_anchor_count {
+            return Err(SPDM_STATUS_INVALID_CERT);
+        }
+        Ok(Some(trust_anchor_index[matched]))
+    }
+
+    fn check_peer_cert_chain_revocation(
+        &self,
+        cert_chain: &[u8],
+        root_cert_index: Option<usize>,
+    ) -> SpdmResult {
+        let mut crls: [&[u8]; config::MAX_CRL_SUPPORT] = [&[]; config::MAX_CRL_SUPPORT];
+        let mut crl_count = 0usize;
+        for peer_crl_data in self.provision_info.peer_crl_data.iter().flatten() {
+            crls[crl_count] = peer_crl_data.as_ref();
+            crl_count += 1;
+        }
+
+        if crl_count == 0 {
+            return Ok(());
+        }
+
+        // the trust anchor the chain was verified against, see verify_peer_cert_chain_trust.
+        let trust_anchor = match root_cert_index {
+            Some(index) => self.provision_info.peer_root_cert_data[index]
+                .as_ref()
+                .ok_or(SPDM_STATUS_INVALID_CERT)?
+                .as_ref(),
+            None => {
+                let (root_cert_begin, root_cert_end) =
+                    crypto::cert_operation::get_cert_from_cert_chain(cert_chain, 0)?;
+                &cert_chain[root_cert_begin..root_cert_end]
+            }
+        };
+
+        crypto::cert_operation::check_cert_chain_revocation(
+            cert_chain,
+            trust_anchor,
+            &crls[..crl_count],
+        )
+    }
+
     pub fn append_message_a(&mut self, new_message: &[u8]) -> SpdmResult {
         self.runtime_info
             .message_a
@@ -956,18 +1154,30 @@ impl<'a> SpdmContext<'a> {
             .encap(send_buffer, transport_buffer, false)
     }
 
+    pub fn encap_async<'b>(
+        &'b mut self,
+        send_buffer: &'b [u8],
+        transport_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            self.transport_encap
+                .encap_async(send_buffer, transport_buffer, false)
+                .await
+        })
+    }
+
     pub fn encode_secured_message(
         &mut self,
         session_id: u32,
         send_buffer: &[u8],
         transport_buffer: &mut [u8],
         is_requester: bool,
-        is_app_message: bool,
+        app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
-        let mut app_buffer = [0u8; config::SENDER_BUFFER_SIZE];
-        let used = self
-            .transport_encap
-            .encap_app(send_buffer, &mut app_buffer, is_app_message)?;
+        let mut app_buffer = Zeroizing::new([0u8; config::SENDER_BUFFER_SIZE]);
+        let used =
+            self.transport_encap
+                .encap_app(send_buffer, &mut app_buffer[..], app_message_type)?;
 
         let spdm_session = self
             .get_session_via_id(session_id)
@@ -984,6 +1194,38 @@ impl<'a> SpdmContext<'a> {
             .encap(&encoded_send_buffer[..encode_size], transport_buffer, true)
     }
 
+    pub fn encode_secured_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        send_buffer: &'b [u8],
+        transport_buffer: &'b mut [u8],
+        is_requester: bool,
+        app_message_type: Option<u8>,
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            let mut app_buffer = Zeroizing::new([0u8; config::SENDER_BUFFER_SIZE]);
+            let used = self
+                .transport_encap
+                .encap_app_async(send_buffer, &mut app_buffer[..], app_message_type)
+                .await?;
+
+            let spdm_session = self
+                .get_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+
+            let mut encoded_send_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let encode_size = spdm_session.encode_spdm_secured_message(
+                &app_buffer[0..used],
+                &mut encoded_send_buffer,
+                is_requester,
+            )?;
+
+            self.transport_encap
+                .encap_async(&encoded_send_buffer[..encode_size], transport_buffer, true)
+                .await
+        })
+    }
+
     pub fn decap(
         &mut self,
         transport_buffer: &[u8],
@@ -1000,12 +1242,64 @@ impl<'a> SpdmContext<'a> {
         Ok(used)
     }
 
+    pub fn decap_async<'b>(
+        &'b mut self,
+        transport_buffer: &'b [u8],
+        receive_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            let (used, secured_message) = self
+                .transport_encap
+                .decap_async(transport_buffer, receive_buffer)
+                .await?;
+
+            if secured_message {
+                return Err(SPDM_STATUS_DECAP_FAIL); //need check
+            }
+
+            Ok(used)
+        })
+    }
+
+    pub fn get_app_message_type(&mut self, session_id: u32) -> SpdmResult<u8> {
+        let app_message_type = self
+            .get_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+            .get_app_message_type();
+        Ok(app_message_type.unwrap_or_else(|| self.transport_encap.get_default_app_message_type()))
+    }
+
     pub fn decode_secured_message(
         &mut self,
         session_id: u32,
         transport_buffer: &[u8],
         receive_buffer: &mut [u8],
     ) -> SpdmResult<usize> {
+        self.decode_secured_app_message(session_id, transport_buffer, receive_buffer)
+            .map(|(used, _)| used)
+    }
+
+    pub fn decode_secured_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        transport_buffer: &'b [u8],
+        receive_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            self.decode_secured_app_message_async(session_id, transport_buffer, receive_buffer)
+                .await
+                .map(|(used, _)| used)
+        })
+    }
+
+    // decode a secured message that may carry an application message,
+    // along with its application message type.
+    pub fn decode_secured_app_message(
+        &mut self,
+        session_id: u32,
+        transport_buffer: &[u8],
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
         let mut encoded_receive_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
         let (used, secured_message) = self
             .transport_encap
@@ -1019,18 +1313,49 @@ impl<'a> SpdmContext<'a> {
             .get_session_via_id(session_id)
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
 
-        let mut app_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        let mut app_buffer = Zeroizing::new([0u8; config::RECEIVER_BUFFER_SIZE]);
         let decode_size = spdm_session.decode_spdm_secured_message(
             &encoded_receive_buffer[..used],
-            &mut app_buffer,
+            &mut app_buffer[..],
             false,
         )?;
 
-        let used = self
-            .transport_encap
-            .decap_app(&app_buffer[0..decode_size], receive_buffer)?;
+        self.transport_encap
+            .decap_app(&app_buffer[0..decode_size], receive_buffer)
+    }
 
-        Ok(used.0)
+    pub fn decode_secured_app_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        transport_buffer: &'b [u8],
+        receive_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<(usize, Option<u8>)>> {
+        Box::pin(async move {
+            let mut encoded_receive_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let (used, secured_message) = self
+                .transport_encap
+                .decap_async(transport_buffer, &mut encoded_receive_buffer)
+                .await?;
+
+            if !secured_message {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+
+            let spdm_session = self
+                .get_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+
+            let mut app_buffer = Zeroizing::new([0u8; config::RECEIVER_BUFFER_SIZE]);
+            let decode_size = spdm_session.decode_spdm_secured_message(
+                &encoded_receive_buffer[..used],
+                &mut app_buffer[..],
+                false,
+            )?;
+
+            self.transport_encap
+                .decap_app_async(&app_buffer[0..decode_size], receive_buffer)
+                .await
+        })
     }
 }
 
@@ -1056,6 +1381,8 @@ pub struct SpdmConfigInfo {
     pub max_spdm_msg_size: u32,
     pub heartbeat_period: u8, // used by responder only
     pub secure_spdm_version: [u8; MAX_SECURE_SPDM_VERSION_COUNT], // used by responder only
+    pub key_update_policy: SpdmKeyUpdatePolicy, // used by requester only
+    pub sequence_number_mode: SpdmSequenceNumberMode,
 }
 
 #[derive(Debug, Default)]
@@ -1224,7 +1551,7 @@ impl Default for ManagedBufferM {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct ManagedBufferK(usize, [u8; MAX_MANAGED_BUFFER_K_SIZE]);
 
 impl ManagedBufferK {
@@ -1252,7 +1579,7 @@ impl Default for ManagedBufferK {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct ManagedBufferF(usize, [u8; MAX_MANAGED_BUFFER_F_SIZE]);
 
 impl ManagedBufferF {
@@ -1405,7 +1732,6 @@ bitflags! {
 #[cfg(not(feature = "hashed-transcript-data"))]
 pub struct SpdmRuntimeInfo {
     connection_state: SpdmConnectionState,
-    last_session_id: Option<u32>,
     local_used_cert_chain_slot_id: u8,
     peer_used_cert_chain_slot_id: u8,
     pub need_measurement_summary_hash: bool,
@@ -1422,7 +1748,6 @@ pub struct SpdmRuntimeInfo {
 #[cfg(feature = "hashed-transcript-data")]
 pub struct SpdmRuntimeInfo {
     connection_state: SpdmConnectionState,
-    last_session_id: Option<u32>,
     local_used_cert_chain_slot_id: u8,
     peer_used_cert_chain_slot_id: u8,
     pub need_measurement_summary_hash: bool,
@@ -1443,14 +1768,6 @@ impl SpdmRuntimeInfo {
         self.connection_state
     }
 
-    pub fn set_last_session_id(&mut self, last_session_id: Option<u32>) {
-        self.last_session_id = last_session_id;
-    }
-
-    pub fn get_last_session_id(&self) -> Option<u32> {
-        self.last_session_id
-    }
-
     pub fn set_peer_used_cert_chain_slot_id(&mut self, slot_id: u8) {
         self.peer_used_cert_chain_slot_id = slot_id;
     }
@@ -1472,13 +1789,18 @@ impl SpdmRuntimeInfo {
 pub struct SpdmProvisionInfo {
     pub my_cert_chain_data: [Option<SpdmCertChainData>; SPDM_MAX_SLOT_NUMBER],
     pub my_cert_chain: [Option<SpdmCertChainBuffer>; SPDM_MAX_SLOT_NUMBER],
-    pub peer_root_cert_data: Option<SpdmCertChainData>,
+    // trust anchor store, the peer cert chain must be anchored on one of them if any is provisioned.
+    pub peer_root_cert_data: [Option<SpdmCertChainData>; config::MAX_ROOT_CERT_SUPPORT],
+    // revocation source, DER encoded CRLs issued by the CAs of the peer cert chain.
+    pub peer_crl_data: [Option<SpdmCertChainData>; config::MAX_CRL_SUPPORT],
 }
 
 #[derive(Default)]
 pub struct SpdmPeerInfo {
     pub peer_cert_chain: [Option<SpdmCertChainBuffer>; SPDM_MAX_SLOT_NUMBER],
     pub peer_cert_chain_temp: Option<SpdmCertChainBuffer>,
+    // index in provision_info.peer_root_cert_data the peer cert chain is anchored on.
+    pub peer_root_cert_index: [Option<usize>; SPDM_MAX_SLOT_NUMBER],
 }
 
 #[cfg(feature = "mut-auth")]
@@ -2,24 +2,28 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use super::key_schedule::SpdmKeySchedule;
+use super::key_schedule::{SpdmKeySchedule, SPDM_MAX_EXPORT_LABEL_CONTEXT_SIZE};
 use crate::config;
 use crate::crypto;
 use crate::error::SpdmResult;
 use crate::error::SPDM_STATUS_BUFFER_TOO_SMALL;
 use crate::error::SPDM_STATUS_CRYPTO_ERROR;
 use crate::error::SPDM_STATUS_DECODE_AEAD_FAIL;
+use crate::error::SPDM_STATUS_INVALID_PARAMETER;
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
 use crate::error::SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW;
 use crate::message::SpdmKeyExchangeMutAuthAttributes;
 
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
 
 use super::*;
 
+// a session refuses to protect more messages once the sequence number gets here.
+pub const SPDM_SEQUENCE_NUMBER_LIMIT: u64 = 0xFFFFFFFFFFFFFFFFu64;
+
 enum_builder! {
     @U8
     EnumName: SpdmSessionState;
@@ -47,21 +51,23 @@ pub struct SpdmSessionCryptoParam {
     pub key_schedule_algo: SpdmKeyScheduleAlgo,
 }
 
-#[derive(Debug, Clone, Default, Zeroize, ZeroizeOnDrop)]
+#[derive(Debug, Default, Zeroize, ZeroizeOnDrop)]
 pub struct SpdmSessionDheSecretRoot {
     pub dhe_secret: SpdmDheFinalKeyStruct,
     pub handshake_secret: SpdmHandshakeSecretStruct,
     pub master_secret: SpdmMasterSecretStruct,
 }
 
-#[derive(Debug, Clone, Default, Zeroize, ZeroizeOnDrop)]
+#[derive(Debug, Default, Zeroize, ZeroizeOnDrop)]
 pub struct SpdmSessionSecretParam {
     pub encryption_key: SpdmAeadKeyStruct,
     pub salt: SpdmAeadIvStruct,
     pub sequence_number: u64,
+    // bit i set: sequence_number - 1 - i was received, used by SpdmSequenceNumberMode::Window.
+    pub replay_window: u64,
 }
 
-#[derive(Debug, Clone, Default, Zeroize, ZeroizeOnDrop)]
+#[derive(Debug, Default, Zeroize, ZeroizeOnDrop)]
 pub struct SpdmSessionHandshakeSecret {
     pub request_handshake_secret: SpdmDirectionHandshakeSecretStruct,
     pub response_handshake_secret: SpdmDirectionHandshakeSecretStruct,
@@ -71,7 +77,7 @@ pub struct SpdmSessionHandshakeSecret {
     pub response_direction: SpdmSessionSecretParam,
 }
 
-#[derive(Debug, Clone, Default, Zeroize, ZeroizeOnDrop)]
+#[derive(Debug, Default, Zeroize, ZeroizeOnDrop)]
 pub struct SpdmSessionAppliationSecret {
     pub request_data_secret: SpdmDirectionDataSecretStruct,
     pub response_data_secret: SpdmDirectionDataSecretStruct,
@@ -84,6 +90,35 @@ pub struct SpdmSessionAppliationSecret {
 pub struct SpdmSessionTransportParam {
     pub sequence_number_count: u8,
     pub max_random_count: u16,
+    pub sequence_number_mode: SpdmSequenceNumberMode,
+}
+
+// How the receiver picks the sequence number (AEAD nonce) of a secured message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpdmSequenceNumberMode {
+    // the next expected sequence number, advanced even if the message fails to decrypt.
+    #[default]
+    Implicit,
+    // the next expected sequence number, only advanced once the message decrypts.
+    Strict,
+    // the sequence number carried in the secured message header, accepted if it
+    // is ahead of or within SPDM_REPLAY_WINDOW_SIZE behind the highest one seen
+    // and not received before. Strict if the transport carries no sequence number.
+    Window,
+}
+
+pub const SPDM_REPLAY_WINDOW_SIZE: u64 = 64;
+
+// When a requester updates the data keys of a session on its own.
+// Every threshold is optional, the default policy never updates the keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpdmKeyUpdatePolicy {
+    // messages protected by the current keys in either direction.
+    pub max_messages: Option<u64>,
+    // seconds the current keys have been in use.
+    pub max_key_lifetime: Option<u64>,
+    // messages left before the sequence number reaches its limit.
+    pub sequence_number_margin: Option<u64>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -110,7 +145,6 @@ pub struct SpdmSessionRuntimeInfo {
     pub digest_context_l1l2: Option<SpdmHashCtx>,
 }
 
-#[derive(Clone)]
 pub struct SpdmSession {
     session_id: u32,
     use_psk: bool,
@@ -127,6 +161,10 @@ pub struct SpdmSession {
     slot_id: u8,
     pub heartbeat_period: u8, // valid only when HEARTBEAT cap set
     pub secure_spdm_version_sel: u8,
+    key_update_policy: SpdmKeyUpdatePolicy,
+    data_key_start_time: Option<u64>, // monotonic time in ms the data keys were first checked
+    heartbeat_timer: u64,             // monotonic time in ms of the last secured message
+    app_message_type: Option<u8>,     // transport specific, None selects the transport default
 }
 
 impl Default for SpdmSession {
@@ -153,6 +191,10 @@ impl SpdmSession {
             heartbeat_period: 0,
             secure_spdm_version_sel: DMTF_SECURE_SPDM_VERSION_11,
             mut_auth_requested: SpdmKeyExchangeMutAuthAttributes::default(),
+            key_update_policy: SpdmKeyUpdatePolicy::default(),
+            data_key_start_time: None,
+            heartbeat_timer: 0,
+            app_message_type: None,
         }
     }
 
@@ -177,16 +219,21 @@ impl SpdmSession {
         self.use_psk = false;
         self.session_state = SpdmSessionState::default();
         self.crypto_param = SpdmSessionCryptoParam::default();
-        self.dhe_secret_root = SpdmSessionDheSecretRoot::default();
-        self.handshake_secret = SpdmSessionHandshakeSecret::default();
-        self.application_secret = SpdmSessionAppliationSecret::default();
-        self.application_secret_backup = SpdmSessionAppliationSecret::default();
+        // scrub the secrets in place, a zeroized secret is also its default.
+        self.dhe_secret_root.zeroize();
+        self.handshake_secret.zeroize();
+        self.application_secret.zeroize();
+        self.application_secret_backup.zeroize();
         self.transport_param = SpdmSessionTransportParam::default();
         self.runtime_info = SpdmSessionRuntimeInfo::default();
         self.key_schedule = SpdmKeySchedule::default();
         self.heartbeat_period = 0;
         self.secure_spdm_version_sel = DMTF_SECURE_SPDM_VERSION_11;
         self.mut_auth_requested = SpdmKeyExchangeMutAuthAttributes::empty();
+        self.key_update_policy = SpdmKeyUpdatePolicy::default();
+        self.data_key_start_time = None;
+        self.heartbeat_timer = 0;
+        self.app_message_type = None;
     }
 
     pub fn get_session_id(&self) -> u32 {
@@ -228,6 +275,84 @@ impl SpdmSession {
         self.slot_id
     }
 
+    pub fn reset_heartbeat_timer(&mut self, now: u64) {
+        self.heartbeat_timer = now;
+    }
+
+    pub fn get_heartbeat_timer(&self) -> u64 {
+        self.heartbeat_timer
+    }
+
+    // the requester is expected to send HEARTBEAT once the session was idle for
+    // a heartbeat period.
+    pub fn is_heartbeat_idle(&self, now: u64) -> bool {
+        self.heartbeat_elapsed(now, 1)
+    }
+
+    // the responder gives up on a session that was idle for twice the heartbeat period.
+    pub fn is_heartbeat_expired(&self, now: u64) -> bool {
+        self.heartbeat_elapsed(now, 2)
+    }
+
+    fn heartbeat_elapsed(&self, now: u64, periods: u64) -> bool {
+        self.heartbeat_period != 0
+            && self.session_state == SpdmSessionState::SpdmSessionEstablished
+            && now.saturating_sub(self.heartbeat_timer)
+                >= self.heartbeat_period as u64 * 1000 * periods
+    }
+
+    pub fn set_key_update_policy(&mut self, policy: SpdmKeyUpdatePolicy) {
+        self.key_update_policy = policy;
+        self.data_key_start_time = None;
+    }
+
+    pub fn get_key_update_policy(&self) -> SpdmKeyUpdatePolicy {
+        self.key_update_policy
+    }
+
+    // type of the application messages sent in this session, e.g. the MCTP message type.
+    pub fn set_app_message_type(&mut self, app_message_type: Option<u8>) {
+        self.app_message_type = app_message_type;
+    }
+
+    pub fn get_app_message_type(&self) -> Option<u8> {
+        self.app_message_type
+    }
+
+    // check the data keys of an established session against the key update policy.
+    // the clock is only read when the policy limits the key lifetime, and the
+    // lifetime is counted from the first check after the keys were installed.
+    pub fn need_key_update(&mut self) -> bool {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return false;
+        }
+        let policy = self.key_update_policy;
+        let sequence_number = core::cmp::max(
+            self.application_secret.request_direction.sequence_number,
+            self.application_secret.response_direction.sequence_number,
+        );
+
+        if let Some(max_messages) = policy.max_messages {
+            if sequence_number >= max_messages {
+                return true;
+            }
+        }
+        if let Some(margin) = policy.sequence_number_margin {
+            if SPDM_SEQUENCE_NUMBER_LIMIT - sequence_number <= margin {
+                return true;
+            }
+        }
+        if let (Some(max_key_lifetime), Some(now)) =
+            (policy.max_key_lifetime, crate::time::get_monotonic_time())
+        {
+            let start = *self.data_key_start_time.get_or_insert(now);
+            if now.saturating_sub(start) / 1000 >= max_key_lifetime {
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn set_dhe_secret(
         &mut self,
         spdm_version: SpdmVersion,
@@ -293,6 +418,14 @@ impl SpdmSession {
         self.transport_param.max_random_count = max_random_count;
     }
 
+    pub fn set_sequence_number_mode(&mut self, sequence_number_mode: SpdmSequenceNumberMode) {
+        self.transport_param.sequence_number_mode = sequence_number_mode;
+    }
+
+    pub fn get_sequence_number_mode(&self) -> SpdmSequenceNumberMode {
+        self.transport_param.sequence_number_mode
+    }
+
     pub fn set_session_state(&mut self, session_state: SpdmSessionState) {
         self.session_state = session_state;
     }
@@ -583,101 +716,96 @@ impl SpdmSession {
         let aead_algo = self.crypto_param.aead_algo;
 
         if update_requester {
-            self.application_secret_backup.request_data_secret =
-                self.application_secret.request_data_secret.clone();
-            self.application_secret_backup.request_direction =
-                self.application_secret.request_direction.clone();
-
-            self.application_secret.request_data_secret = if let Some(us) =
-                self.key_schedule.derive_update_secret(
-                    spdm_version,
-                    hash_algo,
-                    &self.application_secret.request_data_secret,
-                ) {
+            let request_data_secret = if let Some(us) = self.key_schedule.derive_update_secret(
+                spdm_version,
+                hash_algo,
+                &self.application_secret.request_data_secret,
+            ) {
                 us
             } else {
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             };
             debug!(
                 "!!! request_data_secret !!!: {:02x?}\n",
-                self.application_secret.request_data_secret.as_ref()
+                request_data_secret.as_ref()
             );
 
             let res = if let Some(aki) = self.key_schedule.derive_aead_key_iv(
                 spdm_version,
                 hash_algo,
                 aead_algo,
-                &SpdmMajorSecret::SpdmDirectionDataSecret(
-                    &self.application_secret.request_data_secret,
-                ),
+                &SpdmMajorSecret::SpdmDirectionDataSecret(&request_data_secret),
             ) {
                 aki
             } else {
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             };
-            self.application_secret.request_direction.encryption_key = res.0;
-            self.application_secret.request_direction.salt = res.1;
             debug!(
                 "!!! request_direction.encryption_key !!!: {:02x?}\n",
-                self.application_secret
-                    .request_direction
-                    .encryption_key
-                    .as_ref()
+                res.0.as_ref()
             );
-            debug!(
-                "!!! request_direction.salt !!!: {:02x?}\n",
-                self.application_secret.request_direction.salt.as_ref()
+            debug!("!!! request_direction.salt !!!: {:02x?}\n", res.1.as_ref());
+
+            // move the old secrets into the backup instead of copying them.
+            self.application_secret_backup.request_data_secret = core::mem::replace(
+                &mut self.application_secret.request_data_secret,
+                request_data_secret,
+            );
+            self.application_secret_backup.request_direction = core::mem::replace(
+                &mut self.application_secret.request_direction,
+                SpdmSessionSecretParam {
+                    encryption_key: res.0,
+                    salt: res.1,
+                    sequence_number: 0,
+                    replay_window: 0,
+                },
             );
-            self.application_secret.request_direction.sequence_number = 0;
         }
 
         if update_responder {
-            self.application_secret_backup.response_data_secret =
-                self.application_secret.response_data_secret.clone();
-            self.application_secret_backup.response_direction =
-                self.application_secret.response_direction.clone();
-
-            self.application_secret.response_data_secret = if let Some(us) =
-                self.key_schedule.derive_update_secret(
-                    spdm_version,
-                    hash_algo,
-                    &self.application_secret.response_data_secret,
-                ) {
+            let response_data_secret = if let Some(us) = self.key_schedule.derive_update_secret(
+                spdm_version,
+                hash_algo,
+                &self.application_secret.response_data_secret,
+            ) {
                 us
             } else {
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             };
             debug!(
                 "!!! response_data_secret !!!: {:02x?}\n",
-                self.application_secret.response_data_secret.as_ref()
+                response_data_secret.as_ref()
             );
 
             let res = if let Some(aki) = self.key_schedule.derive_aead_key_iv(
                 spdm_version,
                 hash_algo,
                 aead_algo,
-                &SpdmMajorSecret::SpdmDirectionDataSecret(
-                    &self.application_secret.response_data_secret,
-                ),
+                &SpdmMajorSecret::SpdmDirectionDataSecret(&response_data_secret),
             ) {
                 aki
             } else {
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             };
-            self.application_secret.response_direction.encryption_key = res.0;
-            self.application_secret.response_direction.salt = res.1;
             debug!(
                 "!!! response_direction.encryption_key !!!: {:02x?}\n",
-                self.application_secret
-                    .response_direction
-                    .encryption_key
-                    .as_ref()
+                res.0.as_ref()
             );
-            debug!(
-                "!!! response_direction.salt !!!: {:02x?}\n",
-                self.application_secret.response_direction.salt.as_ref()
+            debug!("!!! response_direction.salt !!!: {:02x?}\n", res.1.as_ref());
+
+            self.application_secret_backup.response_data_secret = core::mem::replace(
+                &mut self.application_secret.response_data_secret,
+                response_data_secret,
+            );
+            self.application_secret_backup.response_direction = core::mem::replace(
+                &mut self.application_secret.response_direction,
+                SpdmSessionSecretParam {
+                    encryption_key: res.0,
+                    salt: res.1,
+                    sequence_number: 0,
+                    replay_window: 0,
+                },
             );
-            self.application_secret.response_direction.sequence_number = 0;
         }
         Ok(())
     }
@@ -692,17 +820,18 @@ impl SpdmSession {
         if !use_new_key {
             if update_requester {
                 self.application_secret.request_data_secret =
-                    self.application_secret_backup.request_data_secret.clone();
+                    core::mem::take(&mut self.application_secret_backup.request_data_secret);
                 self.application_secret.request_direction =
-                    self.application_secret_backup.request_direction.clone();
+                    core::mem::take(&mut self.application_secret_backup.request_direction);
             }
             if update_responder {
                 self.application_secret.response_data_secret =
-                    self.application_secret_backup.response_data_secret.clone();
+                    core::mem::take(&mut self.application_secret_backup.response_data_secret);
                 self.application_secret.response_direction =
-                    self.application_secret_backup.response_direction.clone();
+                    core::mem::take(&mut self.application_secret_backup.response_direction);
             }
         } else {
+            self.data_key_start_time = None;
             if update_requester {
                 self.application_secret_backup.request_data_secret =
                     SpdmDirectionDataSecretStruct::default();
@@ -769,6 +898,36 @@ impl SpdmSession {
         )
     }
 
+    // derive a secret for a downstream protocol, such as PCIe IDE or CXL IDE, from the
+    // export master secret. the result is zeroized when dropped.
+    pub fn export_secret(
+        &self,
+        spdm_version: SpdmVersion,
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> SpdmResult<SpdmHkdfOutputKeyingMaterial> {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+        if length == 0
+            || length as usize > SPDM_MAX_HKDF_OKM_SIZE
+            || label.len() + context.len() > SPDM_MAX_EXPORT_LABEL_CONTEXT_SIZE
+        {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        self.key_schedule
+            .derive_export_secret(
+                spdm_version,
+                self.crypto_param.base_hash_algo,
+                &self.application_secret.export_master_secret,
+                label,
+                context,
+                length,
+            )
+            .ok_or(SPDM_STATUS_CRYPTO_ERROR)
+    }
+
     pub fn export_keys(&mut self) -> (SpdmSessionSecretParam, SpdmSessionSecretParam) {
         (
             SpdmSessionSecretParam {
@@ -779,6 +938,7 @@ impl SpdmSession {
                     .clone(),
                 salt: self.application_secret.request_direction.salt.clone(),
                 sequence_number: self.application_secret.request_direction.sequence_number,
+                replay_window: self.application_secret.request_direction.replay_window,
             },
             SpdmSessionSecretParam {
                 encryption_key: self
@@ -788,6 +948,7 @@ impl SpdmSession {
                     .clone(),
                 salt: self.application_secret.response_direction.salt.clone(),
                 sequence_number: self.application_secret.response_direction.sequence_number,
+                replay_window: self.application_secret.response_direction.replay_window,
             },
         )
     }
@@ -798,7 +959,7 @@ impl SpdmSession {
         secured_buffer: &mut [u8],
         is_requester: bool,
     ) -> SpdmResult<usize> {
-        match self.session_state {
+        let r = match self.session_state {
             SpdmSessionState::SpdmSessionNotStarted => Err(SPDM_STATUS_INVALID_STATE_LOCAL),
             SpdmSessionState::SpdmSessionHandshaking => {
                 if is_requester {
@@ -806,17 +967,17 @@ impl SpdmSession {
                         app_buffer,
                         secured_buffer,
                         &self.handshake_secret.request_direction,
-                    );
+                    )?;
                     self.handshake_secret.request_direction.sequence_number += 1;
-                    r
+                    Ok(r)
                 } else {
                     let r = self.encode_msg(
                         app_buffer,
                         secured_buffer,
                         &self.handshake_secret.response_direction,
-                    );
+                    )?;
                     self.handshake_secret.response_direction.sequence_number += 1;
-                    r
+                    Ok(r)
                 }
             }
             SpdmSessionState::SpdmSessionEstablished => {
@@ -825,21 +986,25 @@ impl SpdmSession {
                         app_buffer,
                         secured_buffer,
                         &self.application_secret.request_direction,
-                    );
+                    )?;
                     self.application_secret.request_direction.sequence_number += 1;
-                    r
+                    Ok(r)
                 } else {
                     let r = self.encode_msg(
                         app_buffer,
                         secured_buffer,
                         &self.application_secret.response_direction,
-                    );
+                    )?;
                     self.application_secret.response_direction.sequence_number += 1;
-                    r
+                    Ok(r)
                 }
             }
             _ => panic!("unknown session state"),
+        };
+        if r.is_ok() {
+            self.refresh_heartbeat_timer();
         }
+        r
     }
 
     pub fn decode_spdm_secured_message(
@@ -848,47 +1013,176 @@ impl SpdmSession {
         app_buffer: &mut [u8],
         is_requester: bool,
     ) -> SpdmResult<usize> {
-        match self.session_state {
-            SpdmSessionState::SpdmSessionNotStarted => Err(SPDM_STATUS_INVALID_STATE_LOCAL),
-            SpdmSessionState::SpdmSessionHandshaking => {
-                if is_requester {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.handshake_secret.request_direction,
-                    );
-                    self.handshake_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.handshake_secret.response_direction,
-                    );
-                    self.handshake_secret.response_direction.sequence_number += 1;
-                    r
+        let r = match self.session_state {
+            SpdmSessionState::SpdmSessionHandshaking | SpdmSessionState::SpdmSessionEstablished => {
+                self.decode_direction(secured_buffer, app_buffer, is_requester)
+            }
+            _ => Err(SPDM_STATUS_INVALID_STATE_LOCAL),
+        };
+        if r.is_ok() {
+            self.refresh_heartbeat_timer();
+        }
+        r
+    }
+
+    fn decode_direction(
+        &mut self,
+        secured_buffer: &[u8],
+        app_buffer: &mut [u8],
+        is_requester: bool,
+    ) -> SpdmResult<usize> {
+        let mode = if self.transport_param.sequence_number_count == 0 {
+            match self.transport_param.sequence_number_mode {
+                SpdmSequenceNumberMode::Window => SpdmSequenceNumberMode::Strict,
+                mode => mode,
+            }
+        } else {
+            self.transport_param.sequence_number_mode
+        };
+
+        let secret_param = self.receive_secret_param(is_requester);
+        let (r, sequence_number) = match mode {
+            SpdmSequenceNumberMode::Window => {
+                match self.window_sequence_number(secured_buffer, secret_param) {
+                    Ok(sequence_number) => (
+                        self.decode_msg(secured_buffer, app_buffer, secret_param, sequence_number),
+                        sequence_number,
+                    ),
+                    Err(e) => (Err(e), 0),
                 }
             }
-            SpdmSessionState::SpdmSessionEstablished => {
-                if is_requester {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.application_secret.request_direction,
-                    );
-                    self.application_secret.request_direction.sequence_number += 1;
-                    r
-                } else {
-                    let r = self.decode_msg(
-                        secured_buffer,
-                        app_buffer,
-                        &self.application_secret.response_direction,
-                    );
-                    self.application_secret.response_direction.sequence_number += 1;
-                    r
+            _ => {
+                let sequence_number = secret_param.sequence_number;
+                (
+                    self.decode_msg(secured_buffer, app_buffer, secret_param, sequence_number),
+                    sequence_number,
+                )
+            }
+        };
+
+        let secret_param = self.receive_secret_param_mut(is_requester);
+        match mode {
+            SpdmSequenceNumberMode::Implicit => {
+                secret_param.sequence_number = secret_param.sequence_number.saturating_add(1);
+            }
+            SpdmSequenceNumberMode::Strict => {
+                if r.is_ok() {
+                    secret_param.sequence_number = secret_param.sequence_number.saturating_add(1);
                 }
             }
-            _ => Err(SPDM_STATUS_INVALID_STATE_LOCAL),
+            SpdmSequenceNumberMode::Window => {
+                if r.is_ok() {
+                    Self::accept_window_sequence_number(secret_param, sequence_number);
+                }
+            }
+        }
+        r
+    }
+
+    // is_requester selects the direction the requester receives on, following
+    // the existing convention of the callers.
+    fn receive_secret_param(&self, is_requester: bool) -> &SpdmSessionSecretParam {
+        match (self.session_state, is_requester) {
+            (SpdmSessionState::SpdmSessionHandshaking, true) => {
+                &self.handshake_secret.request_direction
+            }
+            (SpdmSessionState::SpdmSessionHandshaking, false) => {
+                &self.handshake_secret.response_direction
+            }
+            (_, true) => &self.application_secret.request_direction,
+            (_, false) => &self.application_secret.response_direction,
+        }
+    }
+
+    fn receive_secret_param_mut(&mut self, is_requester: bool) -> &mut SpdmSessionSecretParam {
+        match (self.session_state, is_requester) {
+            (SpdmSessionState::SpdmSessionHandshaking, true) => {
+                &mut self.handshake_secret.request_direction
+            }
+            (SpdmSessionState::SpdmSessionHandshaking, false) => {
+                &mut self.handshake_secret.response_direction
+            }
+            (_, true) => &mut self.application_secret.request_direction,
+            (_, false) => &mut self.application_secret.response_direction,
+        }
+    }
+
+    // rebuild the full sequence number from the low order bytes in the header,
+    // picking the candidate closest to the next expected one, and reject it if
+    // it falls behind the replay window or was already received.
+    fn window_sequence_number(
+        &self,
+        secured_buffer: &[u8],
+        secret_param: &SpdmSessionSecretParam,
+    ) -> SpdmResult<u64> {
+        let sequence_number_count = self.transport_param.sequence_number_count as usize;
+        if sequence_number_count > 8 || secured_buffer.len() < 4 + sequence_number_count {
+            return Err(SPDM_STATUS_DECODE_AEAD_FAIL);
+        }
+        let mut received = 0u64;
+        for (i, s) in secured_buffer[4..4 + sequence_number_count]
+            .iter()
+            .enumerate()
+        {
+            received |= (*s as u64) << (8 * i);
+        }
+
+        let expected = secret_param.sequence_number;
+        let sequence_number = if sequence_number_count == 8 {
+            received
+        } else {
+            let range = 1u64 << (8 * sequence_number_count);
+            let candidate = (expected & !(range - 1)) | received;
+            if candidate > expected.saturating_add(range / 2) && candidate >= range {
+                candidate - range
+            } else if candidate.saturating_add(range / 2) < expected {
+                candidate
+                    .checked_add(range)
+                    .ok_or(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW)?
+            } else {
+                candidate
+            }
+        };
+
+        if sequence_number < expected {
+            let offset = expected - 1 - sequence_number;
+            if offset >= SPDM_REPLAY_WINDOW_SIZE {
+                info!("sequence_num too old!\n");
+                return Err(SPDM_STATUS_DECODE_AEAD_FAIL);
+            }
+            if secret_param.replay_window & (1u64 << offset) != 0 {
+                info!("sequence_num replayed!\n");
+                return Err(SPDM_STATUS_DECODE_AEAD_FAIL);
+            }
+        }
+        Ok(sequence_number)
+    }
+
+    fn accept_window_sequence_number(
+        secret_param: &mut SpdmSessionSecretParam,
+        sequence_number: u64,
+    ) {
+        let expected = secret_param.sequence_number;
+        if sequence_number < expected {
+            secret_param.replay_window |= 1u64 << (expected - 1 - sequence_number);
+        } else {
+            let shift = sequence_number - expected + 1;
+            secret_param.replay_window = if shift >= SPDM_REPLAY_WINDOW_SIZE {
+                0
+            } else {
+                secret_param.replay_window << shift
+            } | 1;
+            secret_param.sequence_number = sequence_number.saturating_add(1);
+        }
+    }
+
+    // any secured message restarts the heartbeat timer, the clock is only read
+    // when a heartbeat period is negotiated.
+    fn refresh_heartbeat_timer(&mut self) {
+        if self.heartbeat_period != 0 {
+            if let Some(now) = crate::time::get_monotonic_time() {
+                self.heartbeat_timer = now;
+            }
         }
     }
 
@@ -901,19 +1195,39 @@ impl SpdmSession {
         let session_id = self.session_id;
         let aead_algo = self.crypto_param.aead_algo;
         let transport_param = &self.transport_param;
-
-        let cipher_text_size = app_buffer.len() + 2;
         let tag_size = aead_algo.get_tag_size() as usize;
 
+        if secret_param.sequence_number == SPDM_SEQUENCE_NUMBER_LIMIT {
+            return Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW);
+        }
+
+        let max_cipher_text_size = core::cmp::min(
+            config::SENDER_BUFFER_SIZE,
+            secured_buffer
+                .len()
+                .saturating_sub(6 + transport_param.sequence_number_count as usize + tag_size),
+        );
+        if app_buffer.len() + 2 > max_cipher_text_size {
+            return Err(SPDM_STATUS_BUFFER_TOO_SMALL);
+        }
+        // random padding of 0 to max_random_count bytes, as much as still fits.
+        let random_count = if transport_param.max_random_count != 0 {
+            let mut random = [0u8; 2];
+            crypto::rand::get_random(&mut random)?;
+            let random_count = u16::from_le_bytes(random) as usize
+                % (transport_param.max_random_count as usize + 1);
+            core::cmp::min(random_count, max_cipher_text_size - app_buffer.len() - 2)
+        } else {
+            0
+        };
+
+        let cipher_text_size = app_buffer.len() + 2 + random_count;
+
         let mut aad_buffer = [0u8; 6 + 8];
         let mut writer = Writer::init(&mut aad_buffer);
         let app_length = app_buffer.len() as u16;
         let length = cipher_text_size as u16 + tag_size as u16;
 
-        if secret_param.sequence_number == 0xFFFFFFFFFFFFFFFFu64 {
-            return Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW);
-        }
-
         session_id
             .encode(&mut writer)
             .map_err(|_| SPDM_STATUS_BUFFER_TOO_SMALL)?;
@@ -931,14 +1245,19 @@ impl SpdmSession {
         let aad_size = writer.used();
         assert_eq!(aad_size, 6 + transport_param.sequence_number_count as usize);
 
-        let mut plain_text_buf = [0; config::SENDER_BUFFER_SIZE];
-        let mut writer = Writer::init(&mut plain_text_buf);
+        let mut plain_text_buf = Zeroizing::new([0u8; config::SENDER_BUFFER_SIZE]);
+        let mut writer = Writer::init(&mut plain_text_buf[..]);
         app_length
             .encode(&mut writer)
             .map_err(|_| SPDM_STATUS_BUFFER_TOO_SMALL)?;
         let head_size = writer.used();
         assert_eq!(head_size, 2);
         plain_text_buf[head_size..(head_size + app_buffer.len())].copy_from_slice(app_buffer);
+        if random_count != 0 {
+            crypto::rand::get_random(
+                &mut plain_text_buf[(head_size + app_buffer.len())..cipher_text_size],
+            )?;
+        }
 
         let mut tag_buffer = [0u8; 16];
 
@@ -977,13 +1296,14 @@ impl SpdmSession {
         secured_buffer: &[u8],
         app_buffer: &mut [u8],
         secret_param: &SpdmSessionSecretParam,
+        sequence_number: u64,
     ) -> SpdmResult<usize> {
         let session_id = self.session_id;
         let aead_algo = self.crypto_param.aead_algo;
         let transport_param = &self.transport_param;
         let tag_size = aead_algo.get_tag_size() as usize;
 
-        if secret_param.sequence_number == 0xFFFFFFFFFFFFFFFFu64 {
+        if sequence_number == SPDM_SEQUENCE_NUMBER_LIMIT {
             return Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW);
         }
 
@@ -994,7 +1314,6 @@ impl SpdmSession {
             return Err(SPDM_STATUS_DECODE_AEAD_FAIL);
         }
         if transport_param.sequence_number_count != 0 {
-            let sequence_number = secret_param.sequence_number;
             for i in 0..transport_param.sequence_number_count {
                 let s = u8::read(&mut reader).ok_or(SPDM_STATUS_DECODE_AEAD_FAIL)?;
                 if s != ((sequence_number >> (8 * i)) & 0xFF) as u8 {
@@ -1018,10 +1337,9 @@ impl SpdmSession {
 
         let cipher_text_size = length as usize - tag_size;
 
-        let mut plain_text_buf = [0; config::RECEIVER_BUFFER_SIZE];
+        let mut plain_text_buf = Zeroizing::new([0u8; config::RECEIVER_BUFFER_SIZE]);
 
         let mut salt = secret_param.salt.clone();
-        let sequence_number = secret_param.sequence_number;
         salt.data[0] ^= (sequence_number & 0xFF) as u8;
         salt.data[1] ^= ((sequence_number >> 8) & 0xFF) as u8;
         salt.data[2] ^= ((sequence_number >> 16) & 0xFF) as u8;
@@ -1042,11 +1360,12 @@ impl SpdmSession {
             &mut plain_text_buf[..cipher_text_size],
         )?;
 
-        let mut reader = Reader::init(&plain_text_buf);
+        let mut reader = Reader::init(&plain_text_buf[..]);
         let app_length = u16::read(&mut reader).ok_or(SPDM_STATUS_DECODE_AEAD_FAIL)? as usize;
         if ret_plain_text_size < app_length + 2 {
             return Err(SPDM_STATUS_DECODE_AEAD_FAIL);
         }
+        // the rest is random padding of the length the peer chose, ignored here.
 
         app_buffer[..app_length].copy_from_slice(&plain_text_buf[2..(app_length + 2)]);
         Ok(app_length)
@@ -1101,6 +1420,7 @@ mod tests_session {
                 data: Box::new([10u8; SPDM_MAX_AEAD_IV_SIZE]),
             },
             sequence_number: 100u64,
+            replay_window: 0,
         };
         session.transport_param.sequence_number_count = 1;
 
@@ -1109,6 +1429,7 @@ mod tests_session {
                 &receive_buffer,
                 &mut decoded_receive_buffer,
                 &session.handshake_secret.request_direction,
+                100u64,
             )
             .is_ok();
         assert!(!status);
@@ -1120,6 +1441,7 @@ mod tests_session {
                 &receive_buffer[0..100],
                 &mut decoded_receive_buffer,
                 &session.handshake_secret.request_direction,
+                100u64,
             )
             .is_ok();
         assert!(!status);
@@ -1170,6 +1492,296 @@ mod tests_session {
         assert!(status);
     }
     #[test]
+    fn test_case0_encode_spdm_secured_message_sequence_number_limit() {
+        let mut session = SpdmSession::default();
+        let send_buffer = [100u8; 16];
+        let mut encoded_send_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+
+        session.setup(4294901758u32).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.set_request_direction_sequence_number(SPDM_SEQUENCE_NUMBER_LIMIT);
+
+        for _ in 0..2 {
+            assert_eq!(
+                session.encode_spdm_secured_message(&send_buffer, &mut encoded_send_buffer, true),
+                Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW)
+            );
+            assert_eq!(
+                session.get_request_direction_sequence_number(),
+                SPDM_SEQUENCE_NUMBER_LIMIT
+            );
+        }
+    }
+    #[test]
+    fn test_case0_need_key_update() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_key_update_policy(SpdmKeyUpdatePolicy {
+            max_messages: Some(10),
+            ..Default::default()
+        });
+        session.set_request_direction_sequence_number(10);
+        assert!(!session.need_key_update());
+
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.set_request_direction_sequence_number(9);
+        assert!(!session.need_key_update());
+        session.set_response_direction_sequence_number(10);
+        assert!(session.need_key_update());
+
+        session.set_key_update_policy(SpdmKeyUpdatePolicy::default());
+        assert!(!session.need_key_update());
+
+        session.set_key_update_policy(SpdmKeyUpdatePolicy {
+            sequence_number_margin: Some(16),
+            ..Default::default()
+        });
+        session.set_request_direction_sequence_number(SPDM_SEQUENCE_NUMBER_LIMIT - 17);
+        assert!(!session.need_key_update());
+        session.set_request_direction_sequence_number(SPDM_SEQUENCE_NUMBER_LIMIT - 16);
+        assert!(session.need_key_update());
+
+        session.set_request_direction_sequence_number(0);
+        session.set_response_direction_sequence_number(0);
+        session.set_key_update_policy(SpdmKeyUpdatePolicy {
+            max_key_lifetime: Some(3600),
+            ..Default::default()
+        });
+        assert!(!session.need_key_update());
+        session.set_key_update_policy(SpdmKeyUpdatePolicy {
+            max_key_lifetime: Some(0),
+            ..Default::default()
+        });
+        assert!(session.need_key_update());
+    }
+    #[test]
+    fn test_case0_heartbeat_timer() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.reset_heartbeat_timer(5000);
+        assert!(!session.is_heartbeat_idle(u64::MAX));
+        assert!(!session.is_heartbeat_expired(u64::MAX));
+
+        session.heartbeat_period = 2;
+        assert!(!session.is_heartbeat_idle(6999));
+        assert!(session.is_heartbeat_idle(7000));
+        assert!(!session.is_heartbeat_expired(8999));
+        assert!(session.is_heartbeat_expired(9000));
+        assert!(!session.is_heartbeat_idle(0));
+
+        session.set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+        assert!(!session.is_heartbeat_expired(9000));
+    }
+    #[test]
+    fn test_case0_export_secret() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        let export_master_secret: Vec<u8> = (0u8..48).collect();
+        session.application_secret.export_master_secret =
+            SpdmExportMasterSecretStruct::from(&export_master_secret[..]);
+
+        assert_eq!(
+            session
+                .export_secret(SpdmVersion::SpdmVersion12, b"ide key", &[0, 1, 2, 3], 32)
+                .err(),
+            Some(SPDM_STATUS_INVALID_STATE_LOCAL)
+        );
+
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        let secret = session
+            .export_secret(SpdmVersion::SpdmVersion12, b"ide key", &[0, 1, 2, 3], 32)
+            .unwrap();
+        let expected = [
+            0xd9, 0xe7, 0x91, 0xde, 0x11, 0x15, 0x18, 0x28, 0x62, 0x0b, 0xfd, 0x20, 0x80, 0x2f,
+            0xef, 0x36, 0x90, 0xe1, 0x16, 0xf2, 0x16, 0x28, 0x9d, 0xfc, 0xe9, 0x4d, 0x30, 0xad,
+            0x18, 0x42, 0xea, 0xd8,
+        ];
+        assert_eq!(secret.as_ref(), &expected[..]);
+
+        assert_eq!(
+            session
+                .export_secret(SpdmVersion::SpdmVersion12, b"ide key", &[], 0)
+                .err(),
+            Some(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert_eq!(
+            session
+                .export_secret(
+                    SpdmVersion::SpdmVersion12,
+                    b"ide key",
+                    &[],
+                    SPDM_MAX_HKDF_OKM_SIZE as u16 + 1
+                )
+                .err(),
+            Some(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert_eq!(
+            session
+                .export_secret(
+                    SpdmVersion::SpdmVersion12,
+                    b"ide key",
+                    &[0u8; SPDM_MAX_EXPORT_LABEL_CONTEXT_SIZE],
+                    32
+                )
+                .err(),
+            Some(SPDM_STATUS_INVALID_PARAMETER)
+        );
+    }
+    #[test]
+    fn test_case1_export_secret() {
+        let mut session = SpdmSession::default();
+        session.setup(4294901758u32).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            SpdmDheAlgo::SECP_256_R1,
+            SpdmAeadAlgo::AES_128_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        session.application_secret.export_master_secret =
+            SpdmExportMasterSecretStruct::from(&[0x5au8; 32][..]);
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+        // longer than the hash size, so it takes two HKDF-Expand blocks.
+        let secret = session
+            .export_secret(SpdmVersion::SpdmVersion11, b"cxl ide", &[], 48)
+            .unwrap();
+        let expected = [
+            0x4c, 0x93, 0x1c, 0xb3, 0x5b, 0xe2, 0xe4, 0xa7, 0x33, 0x26, 0x89, 0xb9, 0xb6, 0x5a,
+            0xb5, 0xfe, 0x44, 0xf8, 0x10, 0x82, 0xaf, 0x97, 0x25, 0x8b, 0x61, 0x24, 0x23, 0x45,
+            0x7b, 0xf8, 0x28, 0xd4, 0xfb, 0xf3, 0xcd, 0x4c, 0xa4, 0xdc, 0x4b, 0x57, 0x4e, 0xba,
+            0x2e, 0xa8, 0xa6, 0x45, 0x5c, 0x88,
+        ];
+        assert_eq!(secret.as_ref(), &expected[..]);
+    }
+    #[test]
+    fn test_case0_teardown_scrubs_secrets() {
+        fn is_zero(data: &[u8]) -> bool {
+            data.iter().all(|b| *b == 0)
+        }
+
+        let mut session = SpdmSession::default();
+        let session_id = 4294901758u32;
+        session.setup(session_id).unwrap();
+        session.set_crypto_param(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmDheAlgo::SECP_384_R1,
+            SpdmAeadAlgo::AES_256_GCM,
+            SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+        );
+        session
+            .set_dhe_secret(
+                SpdmVersion::SpdmVersion12,
+                SpdmDheFinalKeyStruct {
+                    data_size: 48,
+                    data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE]),
+                },
+            )
+            .unwrap();
+        let th = SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([100u8; SPDM_MAX_HASH_SIZE]),
+        };
+        session
+            .generate_handshake_secret(SpdmVersion::SpdmVersion12, &th)
+            .unwrap();
+        session
+            .generate_data_secret(SpdmVersion::SpdmVersion12, &th)
+            .unwrap();
+        session
+            .create_data_secret_update(SpdmVersion::SpdmVersion12, true, true)
+            .unwrap();
+
+        let key = session
+            .application_secret
+            .request_direction
+            .encryption_key
+            .data
+            .as_ptr();
+        let backup_key = session
+            .application_secret_backup
+            .request_direction
+            .encryption_key
+            .data
+            .as_ptr();
+        assert!(!is_zero(session.dhe_secret_root.master_secret.as_ref()));
+        assert!(!is_zero(
+            session
+                .application_secret
+                .request_direction
+                .encryption_key
+                .as_ref()
+        ));
+        assert!(!is_zero(
+            session
+                .application_secret_backup
+                .request_direction
+                .encryption_key
+                .as_ref()
+        ));
+
+        session.teardown(session_id).unwrap();
+
+        // the memory that held the keys is scrubbed in place.
+        assert_eq!(
+            session
+                .application_secret
+                .request_direction
+                .encryption_key
+                .data
+                .as_ptr(),
+            key
+        );
+        assert_eq!(
+            session
+                .application_secret_backup
+                .request_direction
+                .encryption_key
+                .data
+                .as_ptr(),
+            backup_key
+        );
+        let dhe_secret_root = &session.dhe_secret_root;
+        assert!(is_zero(&dhe_secret_root.dhe_secret.data[..]));
+        assert!(is_zero(&dhe_secret_root.handshake_secret.data[..]));
+        assert!(is_zero(&dhe_secret_root.master_secret.data[..]));
+        let handshake_secret = &session.handshake_secret;
+        assert!(is_zero(&handshake_secret.request_handshake_secret.data[..]));
+        assert!(is_zero(
+            &handshake_secret.response_handshake_secret.data[..]
+        ));
+        assert!(is_zero(&handshake_secret.request_finished_key.data[..]));
+        assert!(is_zero(&handshake_secret.response_finished_key.data[..]));
+        for application_secret in [
+            &session.application_secret,
+            &session.application_secret_backup,
+        ] {
+            assert!(is_zero(&application_secret.request_data_secret.data[..]));
+            assert!(is_zero(&application_secret.response_data_secret.data[..]));
+            assert!(is_zero(&application_secret.export_master_secret.data[..]));
+            for direction in [
+                &application_secret.request_direction,
+                &application_secret.response_direction,
+            ] {
+                assert!(is_zero(&direction.encryption_key.data[..]));
+                assert!(is_zero(&direction.salt.data[..]));
+                assert_eq!(direction.sequence_number, 0);
+            }
+        }
+    }
+    #[test]
     #[should_panic]
     fn test_case0_setup() {
         let mut session = SpdmSession::default();
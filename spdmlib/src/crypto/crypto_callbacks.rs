@@ -2,7 +2,9 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use crate::crypto::X509SubjectPublicKeyInfo;
 use crate::error::SpdmResult;
+use crate::time::SpdmCertValidityTime;
 
 extern crate alloc;
 use alloc::boxed::Box;
@@ -70,12 +72,13 @@ pub struct SpdmAead {
     pub decrypt_cb: DecryptCb,
 }
 
+// public_key is the leaf public key of a cert chain accepted by check_cert_chain_format.
 #[derive(Clone)]
 pub struct SpdmAsymVerify {
     pub verify_cb: fn(
         base_hash_algo: SpdmBaseHashAlgo,
         base_asym_algo: SpdmBaseAsymAlgo,
-        public_cert_der: &[u8],
+        public_key: &X509SubjectPublicKeyInfo,
         data: &[u8],
         signature: &SpdmSignatureStruct,
     ) -> SpdmResult,
@@ -98,11 +101,35 @@ pub struct SpdmHkdf {
 
 type GetCertFromCertChainCb = fn(cert_chain: &[u8], index: isize) -> SpdmResult<(usize, usize)>;
 
+// verify the chain against each trust anchor (DER root cert) in order,
+// and return the index of the first anchor the chain terminates at.
+// is_requester_cert selects the requester-auth instead of the responder-auth key purpose.
+type VerifyCertChainCb = fn(
+    cert_chain: &[u8],
+    trust_anchors: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+    is_requester_cert: bool,
+) -> SpdmResult<usize>;
+
+// check each cert of a verified chain against the CRLs (DER) signed by its issuer in the chain,
+// and return SPDM_STATUS_CERT_REVOKED if any of them is revoked.
+// trust_anchor is the cert the chain was verified against, it is the issuer of
+// the first cert unless the chain starts with the trust anchor or a root cert.
+// a CRL outside its validity period at validity_time is an error.
+type CheckCertChainRevocationCb = fn(
+    cert_chain: &[u8],
+    trust_anchor: &[u8],
+    crls: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+) -> SpdmResult;
+
 #[derive(Clone)]
 pub struct SpdmCertOperation {
     pub get_cert_from_cert_chain_cb: GetCertFromCertChainCb,
 
-    pub verify_cert_chain_cb: fn(cert_chain: &[u8]) -> SpdmResult,
+    pub verify_cert_chain_cb: VerifyCertChainCb,
+
+    pub check_cert_chain_revocation_cb: CheckCertChainRevocationCb,
 }
 
 type GenerateKeyPairCb =
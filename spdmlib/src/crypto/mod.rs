@@ -205,7 +205,7 @@ pub mod asym_verify {
     static DEFAULT: SpdmAsymVerify = SpdmAsymVerify {
         verify_cb: |_base_hash_algo: SpdmBaseHashAlgo,
                     _base_asym_algo: SpdmBaseAsymAlgo,
-                    _public_cert_der: &[u8],
+                    _public_key: &crate::crypto::X509SubjectPublicKeyInfo,
                     _data: &[u8],
                     _signature: &SpdmSignatureStruct|
          -> SpdmResult { unimplemented!() },
@@ -225,16 +225,13 @@ pub mod asym_verify {
         data: &[u8],
         signature: &SpdmSignatureStruct,
     ) -> SpdmResult {
+        crate::crypto::check_cert_chain_format(public_cert_der, base_asym_algo)?;
+        let public_key = crate::crypto::get_leaf_public_key_info(public_cert_der)?;
+
         (CRYPTO_ASYM_VERIFY
             .try_get_or_init(|| DEFAULT.clone())
             .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
-            .verify_cb)(
-            base_hash_algo,
-            base_asym_algo,
-            public_cert_der,
-            data,
-            signature,
-        )
+            .verify_cb)(base_hash_algo, base_asym_algo, &public_key, data, signature)
     }
 }
 
@@ -281,7 +278,16 @@ pub mod cert_operation {
         get_cert_from_cert_chain_cb: |_cert_chain: &[u8],
                                       _index: isize|
          -> SpdmResult<(usize, usize)> { unimplemented!() },
-        verify_cert_chain_cb: |_cert_chain: &[u8]| -> SpdmResult { unimplemented!() },
+        verify_cert_chain_cb: |_cert_chain: &[u8],
+                               _trust_anchors: &[&[u8]],
+                               _validity_time: crate::time::SpdmCertValidityTime,
+                               _is_requester_cert: bool|
+         -> SpdmResult<usize> { unimplemented!() },
+        check_cert_chain_revocation_cb: |_cert_chain: &[u8],
+                                         _trust_anchor: &[u8],
+                                         _crls: &[&[u8]],
+                                         _validity_time: crate::time::SpdmCertValidityTime|
+         -> SpdmResult { unimplemented!() },
     };
 
     #[cfg(feature = "spdm-ring")]
@@ -298,11 +304,36 @@ pub mod cert_operation {
             .get_cert_from_cert_chain_cb)(cert_chain, index)
     }
 
-    pub fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
+    pub fn verify_cert_chain(
+        cert_chain: &[u8],
+        trust_anchors: &[&[u8]],
+        is_requester_cert: bool,
+    ) -> SpdmResult<usize> {
+        (CRYPTO_CERT_OPERATION
+            .try_get_or_init(|| DEFAULT.clone())
+            .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
+            .verify_cert_chain_cb)(
+            cert_chain,
+            trust_anchors,
+            crate::time::get_cert_validity_time(),
+            is_requester_cert,
+        )
+    }
+
+    pub fn check_cert_chain_revocation(
+        cert_chain: &[u8],
+        trust_anchor: &[u8],
+        crls: &[&[u8]],
+    ) -> SpdmResult {
         (CRYPTO_CERT_OPERATION
             .try_get_or_init(|| DEFAULT.clone())
             .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
-            .verify_cert_chain_cb)(cert_chain)
+            .check_cert_chain_revocation_cb)(
+            cert_chain,
+            trust_anchor,
+            crls,
+            crate::time::get_cert_validity_time(),
+        )
     }
 }
 
@@ -425,7 +456,7 @@ pub mod aead {
 pub mod rand {
     use super::CRYPTO_RAND;
     use crate::crypto::SpdmCryptoRandom;
-    use crate::error::{SpdmResult, SPDM_STATUS_INVALID_STATE_LOCAL};
+    use crate::error::SpdmResult;
 
     #[cfg(not(any(feature = "spdm-ring")))]
     static DEFAULT: SpdmCryptoRandom = SpdmCryptoRandom {
@@ -439,11 +470,9 @@ pub mod rand {
         CRYPTO_RAND.try_init_once(|| context).is_ok()
     }
 
+    // reading does not lock in DEFAULT, a later register() still takes effect.
     pub fn get_random(data: &mut [u8]) -> SpdmResult<usize> {
-        (CRYPTO_RAND
-            .try_get_or_init(|| DEFAULT.clone())
-            .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
-            .get_random_cb)(data)
+        (CRYPTO_RAND.get().unwrap_or(&DEFAULT).get_random_cb)(data)
     }
 }
 
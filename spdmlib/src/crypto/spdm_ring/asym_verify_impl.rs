@@ -2,10 +2,9 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use crate::crypto::{x509v3, SpdmAsymVerify};
-use crate::error::{SpdmResult, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_VERIF_FAIL};
+use crate::crypto::{SpdmAsymVerify, X509SubjectPublicKeyInfo};
+use crate::error::{SpdmResult, SPDM_STATUS_VERIF_FAIL};
 use crate::protocol::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmSignatureStruct};
-use core::convert::TryFrom;
 
 pub static DEFAULT: SpdmAsymVerify = SpdmAsymVerify {
     verify_cb: asym_verify,
@@ -14,7 +13,7 @@ pub static DEFAULT: SpdmAsymVerify = SpdmAsymVerify {
 fn asym_verify(
     base_hash_algo: SpdmBaseHashAlgo,
     base_asym_algo: SpdmBaseAsymAlgo,
-    public_cert_der: &[u8],
+    public_key: &X509SubjectPublicKeyInfo,
     data: &[u8],
     signature: &SpdmSignatureStruct,
 ) -> SpdmResult {
@@ -22,98 +21,87 @@ fn asym_verify(
         return Err(SPDM_STATUS_VERIF_FAIL);
     }
 
-    let algorithm = match (base_hash_algo, base_asym_algo) {
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) => {
-            &webpki::ECDSA_P256_SHA256
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
-            &webpki::ECDSA_P384_SHA256
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) => {
-            &webpki::ECDSA_P256_SHA384
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
-            &webpki::ECDSA_P384_SHA384
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
-            &webpki::RSA_PKCS1_2048_8192_SHA256
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
-            &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
-            &webpki::RSA_PKCS1_2048_8192_SHA384
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
-            &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
-            &webpki::RSA_PKCS1_2048_8192_SHA512
-        }
-        (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
-        | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
-            &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY
-        }
-        _ => {
-            panic!();
-        }
-    };
-
-    x509v3::check_cert_chain_format(public_cert_der, base_asym_algo)?;
-
-    let (leaf_begin, leaf_end) =
-        (super::cert_operation_impl::DEFAULT.get_cert_from_cert_chain_cb)(public_cert_der, -1)?;
-    let leaf_cert_der = &public_cert_der[leaf_begin..leaf_end];
-
-    let res = webpki::EndEntityCert::try_from(leaf_cert_der);
-    match res {
-        Ok(cert) => {
-            //
-            // Need translate from ECDSA_P384_SHA384_FIXED_SIGNING to ECDSA_P384_SHA384_ASN1
-            // webpki only support ASN1 format ECDSA signature
-            //
-            match base_asym_algo {
-                SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256
-                | SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384 => {
-                    // DER has this format: 0x30 size 0x02 r_size 0x00 [r_size] 0x02 s_size 0x00 [s_size]
-                    let mut der_signature =
-                        [0u8; crate::protocol::ECDSA_ECC_NIST_P384_KEY_SIZE + 8];
-                    let der_sign_size =
-                        ecc_signature_bin_to_der(signature.as_ref(), &mut der_signature)?;
-
-                    match cert.verify_signature(algorithm, data, &der_signature[..(der_sign_size)])
-                    {
-                        Ok(()) => Ok(()),
-                        Err(_) => Err(SPDM_STATUS_VERIF_FAIL),
-                    }
-                }
-                SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048
-                | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072
-                | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096
-                | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048
-                | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
-                | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096 => {
-                    // RSASSA or RSAPSS
-                    match cert.verify_signature(algorithm, data, signature.as_ref()) {
-                        Ok(()) => Ok(()),
-                        Err(_) => Err(SPDM_STATUS_VERIF_FAIL),
-                    }
-                }
-                _ => Err(SPDM_STATUS_VERIF_FAIL),
+    let algorithm: &dyn ring::signature::VerificationAlgorithm =
+        match (base_hash_algo, base_asym_algo) {
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) => {
+                &ring::signature::ECDSA_P256_SHA256_ASN1
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
+                &ring::signature::ECDSA_P384_SHA256_ASN1
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256) => {
+                &ring::signature::ECDSA_P256_SHA384_ASN1
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
+                &ring::signature::ECDSA_P384_SHA384_ASN1
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
+                &ring::signature::RSA_PKCS1_2048_8192_SHA256
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_256, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
+                &ring::signature::RSA_PSS_2048_8192_SHA256
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
+                &ring::signature::RSA_PKCS1_2048_8192_SHA384
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
+                &ring::signature::RSA_PSS_2048_8192_SHA384
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096) => {
+                &ring::signature::RSA_PKCS1_2048_8192_SHA512
+            }
+            (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072)
+            | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
+                &ring::signature::RSA_PSS_2048_8192_SHA512
+            }
+            _ => {
+                panic!();
             }
+        };
+
+    // ring takes the RSAPublicKey for RSA and the uncompressed point for ECDSA,
+    // that is the subjectPublicKey of the certificate.
+    let public_key =
+        ring::signature::UnparsedPublicKey::new(algorithm, public_key.subject_public_key);
+
+    //
+    // Need translate from ECDSA_P384_SHA384_FIXED_SIGNING to ECDSA_P384_SHA384_ASN1
+    // ring *_ASN1 only support ASN1 format ECDSA signature
+    //
+    match base_asym_algo {
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256
+        | SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384 => {
+            // DER has this format: 0x30 size 0x02 r_size 0x00 [r_size] 0x02 s_size 0x00 [s_size]
+            let mut der_signature = [0u8; crate::protocol::ECDSA_ECC_NIST_P384_KEY_SIZE + 8];
+            let der_sign_size = ecc_signature_bin_to_der(signature.as_ref(), &mut der_signature)?;
+
+            public_key
+                .verify(data, &der_signature[..(der_sign_size)])
+                .map_err(|_| SPDM_STATUS_VERIF_FAIL)
+        }
+        SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048
+        | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072
+        | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096
+        | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048
+        | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
+        | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096 => {
+            // RSASSA or RSAPSS
+            public_key
+                .verify(data, signature.as_ref())
+                .map_err(|_| SPDM_STATUS_VERIF_FAIL)
         }
-        Err(_e) => Err(SPDM_STATUS_INVALID_CERT),
+        _ => Err(SPDM_STATUS_VERIF_FAIL),
     }
 }
 
@@ -189,6 +177,7 @@ fn ecc_signature_bin_to_der(signature: &[u8], der_signature: &mut [u8]) -> SpdmR
 #[cfg(all(test,))]
 mod tests {
     use super::*;
+    use crate::crypto::get_leaf_public_key_info;
 
     #[test]
     fn test_case0_ecc_signature_bin_to_der() {
@@ -240,12 +229,13 @@ mod tests {
         signature.data[510] = 0x10;
 
         let public_cert_der = &include_bytes!("public_cert.der")[..];
+        let public_key = get_leaf_public_key_info(public_cert_der).unwrap();
         let data = &mut [0x10u8; 4096];
 
         let asym_verify = asym_verify(
             base_hash_algo,
             base_asym_algo,
-            public_cert_der,
+            &public_key,
             data,
             &signature,
         );
@@ -263,12 +253,13 @@ mod tests {
         signature.data[510] = 0x10;
 
         let public_cert_der = &include_bytes!("public_cert.der")[..];
+        let public_key = get_leaf_public_key_info(public_cert_der).unwrap();
         let data = &mut [0x10u8; 4096];
 
         let asym_verify = asym_verify(
             base_hash_algo,
             base_asym_algo,
-            public_cert_der,
+            &public_key,
             data,
             &signature,
         );
@@ -297,6 +288,7 @@ mod tests {
         signature.data[510] = 0x10;
 
         let public_cert_der = &include_bytes!("public_cert.der")[..];
+        let public_key = get_leaf_public_key_info(public_cert_der).unwrap();
         let data = &mut [0x10u8; 4096];
 
         for base_hash_algo in base_hash_algo.iter() {
@@ -304,7 +296,7 @@ mod tests {
                 let asym_verify = asym_verify(
                     *base_hash_algo,
                     *base_asym_algo,
-                    public_cert_der,
+                    &public_key,
                     data,
                     &signature,
                 );
@@ -324,15 +316,59 @@ mod tests {
         signature.data[510] = 0x10;
 
         let public_cert_der = &include_bytes!("public_cert.der")[..];
+        let public_key = get_leaf_public_key_info(public_cert_der).unwrap();
         let data = &mut [0x10u8; 4096];
 
         let asym_verify = asym_verify(
             base_hash_algo,
             base_asym_algo,
-            public_cert_der,
+            &public_key,
             data,
             &signature,
         );
         assert!(asym_verify.is_err());
     }
+    #[test]
+    fn test_case4_asym_verify() {
+        let base_hash_algo = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        let base_asym_algo = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+        let cert_chain = std::fs::read("../test_key/ecp384/bundle_responder.certchain.der")
+            .expect("unable to read cert chain!");
+        let key_bytes =
+            std::fs::read("../test_key/ecp384/end_responder.key.p8").expect("unable to read key!");
+        let public_key = get_leaf_public_key_info(&cert_chain).unwrap();
+        let data = &mut [0x10u8; 4096];
+
+        let key_pair = ring::signature::EcdsaKeyPair::from_pkcs8(
+            &ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            &key_bytes,
+        )
+        .unwrap();
+        let rng = ring::rand::SystemRandom::new();
+        let fixed_signature = key_pair.sign(&rng, data).unwrap();
+        let mut signature = SpdmSignatureStruct {
+            data_size: fixed_signature.as_ref().len() as u16,
+            data: [0x00u8; crate::protocol::SPDM_MAX_ASYM_KEY_SIZE],
+        };
+        signature.data[..fixed_signature.as_ref().len()].copy_from_slice(fixed_signature.as_ref());
+
+        assert!(asym_verify(
+            base_hash_algo,
+            base_asym_algo,
+            &public_key,
+            data,
+            &signature
+        )
+        .is_ok());
+
+        data[0] = 0x00;
+        assert!(asym_verify(
+            base_hash_algo,
+            base_asym_algo,
+            &public_key,
+            data,
+            &signature
+        )
+        .is_err());
+    }
 }
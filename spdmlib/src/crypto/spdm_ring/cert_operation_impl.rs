@@ -3,19 +3,26 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 extern crate alloc;
-use alloc::vec;
 use alloc::vec::Vec;
 use core::convert::TryFrom;
 
-use crate::crypto::SpdmCertOperation;
-use crate::error::{SpdmResult, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_STATE_LOCAL};
+use crate::crypto::{SpdmCertOperation, X509Certificate, X509CertificateList, X509Time};
+use crate::error::{SpdmResult, SPDM_STATUS_CERT_REVOKED, SPDM_STATUS_INVALID_CERT};
+use crate::time::SpdmCertValidityTime;
 use ring::io::der;
 
 pub static DEFAULT: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: get_cert_from_cert_chain,
     verify_cert_chain_cb: verify_cert_chain,
+    check_cert_chain_revocation_cb: check_cert_chain_revocation,
 };
 
+const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+const OID_RSA_SHA256RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+const OID_RSA_SHA384RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c];
+const OID_RSA_SHA512RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d];
+
 fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usize, usize)> {
     let mut offset = 0usize;
     let mut this_index = 0isize;
@@ -45,8 +52,17 @@ fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usiz
     }
 }
 
-fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
-    static EKU_SPDM_RESPONDER_AUTH: &[u8] = &[40 + 3, 6, 1, 5, 5, 7, 3, 1];
+fn verify_cert_chain(
+    cert_chain: &[u8],
+    trust_anchors: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+    is_requester_cert: bool,
+) -> SpdmResult<usize> {
+    // webpki requires the EKU in every cert of the path which has the extKeyUsage extension.
+    // id-DMTF-eku-responder-auth
+    static EKU_SPDM_RESPONDER_AUTH: &[u8] = &[40 + 3, 6, 1, 4, 1, 131, 28, 130, 18, 3];
+    // id-DMTF-eku-requester-auth
+    static EKU_SPDM_REQUESTER_AUTH: &[u8] = &[40 + 3, 6, 1, 4, 1, 131, 28, 130, 18, 4];
 
     static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
         &webpki::RSA_PKCS1_2048_8192_SHA256,
@@ -58,6 +74,88 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
         &webpki::ECDSA_P384_SHA384,
     ];
 
+    let certs = split_cert_chain(cert_chain)?;
+    let certs_len = certs.len();
+
+    if certs_len == 0 {
+        return Err(SPDM_STATUS_INVALID_CERT);
+    }
+    // a root cert at the start of the chain is not needed for path building because
+    // the chain must terminate at one of the trust anchors. any other first cert is
+    // an intermediate signed by a trust anchor.
+    let first = if certs_len > 1 && is_root_cert(certs[0], trust_anchors)? {
+        1
+    } else {
+        0
+    };
+    let (inters, ee): (&[&[u8]], &[u8]) = (&certs[first..(certs_len - 1)], certs[certs_len - 1]);
+
+    let timestamp = match validity_time {
+        SpdmCertValidityTime::Now(timestamp) => timestamp,
+        // webpki always checks the validity period, so pick the latest notBefore
+        // of the path, which is inside the validity period of every cert if any is.
+        SpdmCertValidityTime::Ignore => {
+            let mut not_before = 0u64;
+            let mut not_after = u64::MAX;
+            for cert in inters.iter().chain(core::iter::once(&ee)) {
+                let validity = parse_certificate(cert)?.tbs_certificate.validity;
+                not_before = not_before.max(get_unix_time(validity.not_before)?);
+                not_after = not_after.min(get_unix_time(validity.not_after)?);
+            }
+            if not_before > not_after {
+                error!("Cert validity periods do not overlap\n");
+                return Err(SPDM_STATUS_INVALID_CERT);
+            }
+            not_before
+        }
+    };
+    let time = webpki::Time::from_seconds_since_unix_epoch(timestamp);
+
+    let cert = if let Ok(eec) = webpki::EndEntityCert::try_from(ee) {
+        eec
+    } else {
+        return Err(SPDM_STATUS_INVALID_CERT);
+    };
+
+    let eku = if is_requester_cert {
+        EKU_SPDM_REQUESTER_AUTH
+    } else {
+        EKU_SPDM_RESPONDER_AUTH
+    };
+
+    // verify against one anchor at a time, so that the matched one can be reported.
+    for (index, trust_anchor) in trust_anchors.iter().enumerate() {
+        let anchors = if let Ok(ta) = webpki::TrustAnchor::try_from_cert_der(trust_anchor) {
+            [ta]
+        } else {
+            error!("invalid trust anchor {:?}\n", index);
+            continue;
+        };
+
+        // we cannot call verify_is_valid_tls_server_cert because it will check verify_cert::EKU_SERVER_AUTH.
+        if cert
+            .verify_cert_chain_with_eku(eku, ALL_SIGALGS, &anchors, inters, time, 0)
+            .is_ok()
+        {
+            info!("Cert verification Pass - trust anchor {:?}\n", index);
+            return Ok(index);
+        }
+    }
+
+    error!("Cert verification Fail\n");
+    Err(SPDM_STATUS_INVALID_CERT)
+}
+
+// a self-signed cert or one of the trust anchors.
+fn is_root_cert(cert: &[u8], trust_anchors: &[&[u8]]) -> SpdmResult<bool> {
+    if trust_anchors.contains(&cert) {
+        return Ok(true);
+    }
+    let tbs = parse_certificate(cert)?.tbs_certificate;
+    Ok(tbs.issuer.as_der() == tbs.subject.as_der())
+}
+
+fn split_cert_chain(cert_chain: &[u8]) -> SpdmResult<Vec<&[u8]>> {
     let certs_der = untrusted::Input::from(cert_chain);
     let reader = &mut untrusted::Reader::new(certs_der);
 
@@ -75,59 +173,109 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
             Err(_) => break,
         }
     }
-    let certs_len = certs.len();
-
-    let (ca, inters, ee): (&[u8], &[&[u8]], &[u8]) = match certs_len {
-        0 => return Err(SPDM_STATUS_INVALID_CERT),
-        1 => (certs[0], &[], certs[0]),
-        2 => (certs[0], &[], certs[1]),
-        n => (certs[0], &certs[1..(n - 1)], certs[n - 1]),
-    };
+    Ok(certs)
+}
 
-    let anchors = if let Ok(ta) = webpki::TrustAnchor::try_from_cert_der(ca) {
-        vec![ta]
-    } else {
+fn check_cert_chain_revocation(
+    cert_chain: &[u8],
+    trust_anchor: &[u8],
+    crls: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+) -> SpdmResult {
+    let mut certs = split_cert_chain(cert_chain)?;
+    if certs.is_empty() {
         return Err(SPDM_STATUS_INVALID_CERT);
-    };
+    }
+    let crls = crls
+        .iter()
+        .map(|crl| X509CertificateList::from_der(crl).map_err(|_| SPDM_STATUS_INVALID_CERT))
+        .collect::<SpdmResult<Vec<X509CertificateList>>>()?;
 
-    #[cfg(any(target_os = "uefi", target_os = "none"))]
-    let timestamp = uefi_time::get_rtc_time() as u64;
-    #[cfg(not(any(target_os = "uefi", target_os = "none")))]
-    let timestamp = {
-        extern crate std;
-        if let Ok(ds) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-            ds.as_secs()
-        } else {
-            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+    // a stale CRL may miss recent revocations, one not yet issued cannot be trusted.
+    if let SpdmCertValidityTime::Now(timestamp) = validity_time {
+        for crl in crls.iter() {
+            let this_update = get_unix_time(crl.tbs_cert_list.this_update)?;
+            let next_update = crl
+                .tbs_cert_list
+                .next_update
+                .map(get_unix_time)
+                .transpose()?;
+            if timestamp < this_update
+                || matches!(next_update, Some(next_update) if timestamp > next_update)
+            {
+                error!("CRL is outside its validity period\n");
+                return Err(SPDM_STATUS_INVALID_CERT);
+            }
         }
-    };
-    let time = webpki::Time::from_seconds_since_unix_epoch(timestamp);
+    }
 
-    let cert = if let Ok(eec) = webpki::EndEntityCert::try_from(ee) {
-        eec
+    // a root cert can only be checked as an issuer, any other first cert is
+    // issued by the trust anchor, which then heads the path.
+    let first = if is_root_cert(certs[0], &[trust_anchor])? {
+        0
     } else {
-        return Err(SPDM_STATUS_INVALID_CERT);
+        certs.insert(0, trust_anchor);
+        1
     };
+    for (index, pair) in certs.windows(2).enumerate() {
+        let index = index + 1 - first;
+        let issuer = parse_certificate(pair[0])?.tbs_certificate;
+        let cert = parse_certificate(pair[1])?.tbs_certificate;
+        if cert.issuer.as_der() != issuer.subject.as_der() {
+            return Err(SPDM_STATUS_INVALID_CERT);
+        }
+        let issuer_cert =
+            webpki::EndEntityCert::try_from(pair[0]).map_err(|_| SPDM_STATUS_INVALID_CERT)?;
 
-    // we cannot call verify_is_valid_tls_server_cert because it will check verify_cert::EKU_SERVER_AUTH.
-    if cert
-        .verify_cert_chain_with_eku(
-            EKU_SPDM_RESPONDER_AUTH,
-            ALL_SIGALGS,
-            &anchors,
-            inters,
-            time,
-            0,
-        )
-        .is_ok()
-    {
-        info!("Cert verification Pass\n");
-        Ok(())
-    } else {
-        error!("Cert verification Fail\n");
-        Err(SPDM_STATUS_INVALID_CERT)
+        for crl in crls
+            .iter()
+            .filter(|crl| crl.tbs_cert_list.issuer.as_der() == cert.issuer.as_der())
+        {
+            // a CRL is only authoritative if it is signed by the issuer in the chain.
+            if !verify_crl_signature(crl, &issuer_cert) {
+                error!("CRL is not signed by the issuer of cert {:?}\n", index);
+                continue;
+            }
+            if crl.tbs_cert_list.is_revoked(cert.serial_number) {
+                error!("Cert {:?} in the chain is revoked\n", index);
+                return Err(SPDM_STATUS_CERT_REVOKED);
+            }
+        }
     }
+
+    Ok(())
+}
+
+fn parse_certificate(cert: &[u8]) -> SpdmResult<X509Certificate<'_>> {
+    X509Certificate::from_der(cert).map_err(|_| SPDM_STATUS_INVALID_CERT)
 }
+
+// seconds since the Unix epoch
+fn get_unix_time(time: X509Time) -> SpdmResult<u64> {
+    time.to_unix_time().map_err(|_| SPDM_STATUS_INVALID_CERT)
+}
+
+fn verify_crl_signature(crl: &X509CertificateList, issuer_cert: &webpki::EndEntityCert) -> bool {
+    let sig_algs: &[&webpki::SignatureAlgorithm] = match crl.signature_algorithm.algorithm {
+        OID_ECDSA_SHA256 => &[&webpki::ECDSA_P256_SHA256, &webpki::ECDSA_P384_SHA256],
+        OID_ECDSA_SHA384 => &[&webpki::ECDSA_P256_SHA384, &webpki::ECDSA_P384_SHA384],
+        OID_RSA_SHA256RSA => &[&webpki::RSA_PKCS1_2048_8192_SHA256],
+        OID_RSA_SHA384RSA => &[&webpki::RSA_PKCS1_2048_8192_SHA384],
+        OID_RSA_SHA512RSA => &[&webpki::RSA_PKCS1_2048_8192_SHA512],
+        _ => return false,
+    };
+    // the first byte of the BIT STRING is the number of unused bits, a signature has none.
+    let signature = match crl.signature_value.split_first() {
+        Some((0, signature)) => signature,
+        _ => return false,
+    };
+    sig_algs.iter().any(|sig_alg| {
+        issuer_cert
+            .verify_signature(sig_alg, crl.tbs_cert_list.as_der(), signature)
+            .is_ok()
+    })
+}
+
 #[cfg(all(test,))]
 mod tests {
     use super::*;
@@ -171,8 +319,23 @@ mod tests {
         let status = get_cert_from_cert_chain(cert_chain, -1).is_ok();
         assert!(status);
 
-        let status = verify_cert_chain(cert_chain).is_ok();
+        // the chain only has the TLS EKUs
+        let status = verify_cert_chain(cert_chain, &[root_cert(cert_chain)], now(), false).is_err();
         assert!(status);
+
+        let cert_chain =
+            &include_bytes!("../../../../test_key/ecp256/bundle_responder.certchain.der")[..];
+        let status = verify_cert_chain(cert_chain, &[root_cert(cert_chain)], now(), false).is_ok();
+        assert!(status);
+    }
+
+    fn now() -> SpdmCertValidityTime {
+        crate::time::get_cert_validity_time()
+    }
+
+    fn root_cert(cert_chain: &[u8]) -> &[u8] {
+        let (root_cert_begin, root_cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        &cert_chain[root_cert_begin..root_cert_end]
     }
 
     /// verfiy cert chain
@@ -180,30 +343,291 @@ mod tests {
     fn test_verify_cert_chain_case1() {
         let bundle_certs_der =
             &include_bytes!("../../../../test_key/crypto_chains/ca_selfsigned.crt.der")[..];
-        assert!(verify_cert_chain(bundle_certs_der).is_ok());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root_cert(bundle_certs_der)],
+            now(),
+            false
+        )
+        .is_ok());
 
         let bundle_certs_der =
             &include_bytes!("../../../../test_key/crypto_chains/bundle_two_level_cert.der")[..];
-        assert!(verify_cert_chain(bundle_certs_der).is_ok());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root_cert(bundle_certs_der)],
+            now(),
+            false
+        )
+        .is_ok());
 
         let bundle_certs_der =
             &include_bytes!("../../../../test_key/ecp384/bundle_requester.certchain.der")[..];
-        assert!(verify_cert_chain(bundle_certs_der).is_ok());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root_cert(bundle_certs_der)],
+            now(),
+            false
+        )
+        .is_ok());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root_cert(bundle_certs_der)],
+            now(),
+            true
+        )
+        .is_ok());
 
         let bundle_certs_der =
             &include_bytes!("../../../../test_key/crypto_chains/bundle_cert.der")[..];
-        assert!(verify_cert_chain(bundle_certs_der).is_ok());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root_cert(bundle_certs_der)],
+            now(),
+            false
+        )
+        .is_ok());
 
         // Flipping bits to test signature hash is invalid.
         let mut cert_chain = bundle_certs_der.to_vec();
         // offset 3140 is in signature range.
         cert_chain[3140] ^= 0xFE;
-        assert!(verify_cert_chain(&cert_chain).is_err());
+        assert!(verify_cert_chain(&cert_chain, &[root_cert(&cert_chain)], now(), false).is_err());
 
         // Invalid Intermediate cert
         let mut cert_chain = bundle_certs_der.to_vec();
         // Change intermediate cert data
         cert_chain[1380] = 0xFF;
-        assert!(verify_cert_chain(&cert_chain).is_err());
+        assert!(verify_cert_chain(&cert_chain, &[root_cert(&cert_chain)], now(), false).is_err());
+    }
+
+    /// verify cert chain against a trust anchor store
+    #[test]
+    fn test_verify_cert_chain_case2() {
+        let ecp256_root = &include_bytes!("../../../../test_key/ecp256/ca.cert.der")[..];
+        let ecp384_root = &include_bytes!("../../../../test_key/ecp384/ca.cert.der")[..];
+        let ecp384_root1 = &include_bytes!("../../../../test_key/ecp384/ca1.cert.der")[..];
+        let bundle_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der")[..];
+
+        // the matched trust anchor is reported
+        assert_eq!(
+            verify_cert_chain(bundle_certs_der, &[ecp384_root], now(), false),
+            Ok(0)
+        );
+        assert_eq!(
+            verify_cert_chain(
+                bundle_certs_der,
+                &[ecp256_root, ecp384_root1, ecp384_root],
+                now(),
+                false
+            ),
+            Ok(2)
+        );
+
+        // the root cert carried in the chain is not trusted by itself
+        assert!(
+            verify_cert_chain(bundle_certs_der, &[ecp256_root, ecp384_root1], now(), false)
+                .is_err()
+        );
+        assert!(verify_cert_chain(bundle_certs_der, &[], now(), false).is_err());
+
+        // an invalid trust anchor is skipped
+        assert_eq!(
+            verify_cert_chain(
+                bundle_certs_der,
+                &[&[0x30, 0x82, 0x00, 0x00], ecp384_root],
+                now(),
+                false
+            ),
+            Ok(1)
+        );
+    }
+
+    /// verify cert chain that starts with an intermediate cert
+    #[test]
+    fn test_verify_cert_chain_case4() {
+        let bundle_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der")[..];
+        let root = root_cert(bundle_certs_der);
+        let cert_chain = &bundle_certs_der[root.len()..];
+
+        assert_eq!(verify_cert_chain(cert_chain, &[root], now(), false), Ok(0));
+
+        // the leaf cert alone does not reach the trust anchor
+        let (leaf_begin, leaf_end) = get_cert_from_cert_chain(cert_chain, -1).unwrap();
+        assert!(
+            verify_cert_chain(&cert_chain[leaf_begin..leaf_end], &[root], now(), false).is_err()
+        );
+    }
+
+    /// verify cert chain validity period against the given time
+    #[test]
+    fn test_verify_cert_chain_case3() {
+        let bundle_certs_der = &include_bytes!(
+            "../../../../test_key/rsa3072_Expiration/bundle_responder.certchain.der"
+        )[..];
+        let root = root_cert(bundle_certs_der);
+
+        // expired on 2023-04-04
+        assert!(verify_cert_chain(bundle_certs_der, &[root], now(), false).is_err());
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root],
+            SpdmCertValidityTime::Ignore,
+            false
+        )
+        .is_ok());
+
+        // 2023-04-03T12:00:00Z
+        assert_eq!(
+            verify_cert_chain(
+                bundle_certs_der,
+                &[root],
+                SpdmCertValidityTime::Now(1680523200),
+                false
+            ),
+            Ok(0)
+        );
+        // 2023-04-03T07:38:23Z, one second before the end entity cert is valid
+        assert!(verify_cert_chain(
+            bundle_certs_der,
+            &[root],
+            SpdmCertValidityTime::Now(1680507503),
+            false
+        )
+        .is_err());
+    }
+
+    /// check cert chain against a CRL set
+    #[test]
+    fn test_check_cert_chain_revocation_case1() {
+        let ca_crl = &include_bytes!("../../../../test_key/ecp384/ca.crl.der")[..];
+        let inter_crl = &include_bytes!("../../../../test_key/ecp384/inter.crl.der")[..];
+        let ecp256_ca_crl = &include_bytes!("../../../../test_key/ecp256/ca.crl.der")[..];
+        let responder_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der")[..];
+        let requester_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_requester.certchain.der")[..];
+
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[],
+                now()
+            ),
+            Ok(())
+        );
+
+        // the intermediate cert is revoked by the root CA
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[ca_crl],
+                now()
+            ),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+
+        // only the requester cert is revoked by the intermediate CA
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[inter_crl],
+                now()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_cert_chain_revocation(
+                requester_certs_der,
+                root_cert(requester_certs_der),
+                &[inter_crl],
+                now()
+            ),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+
+        // a CRL with the same issuer name but another signer is ignored
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[ecp256_ca_crl],
+                now()
+            ),
+            Ok(())
+        );
+
+        // a malformed CRL is rejected
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[&ca_crl[..ca_crl.len() - 1]],
+                now()
+            ),
+            Err(SPDM_STATUS_INVALID_CERT)
+        );
+    }
+
+    /// check the CRL validity period against the given time
+    #[test]
+    fn test_check_cert_chain_revocation_case2() {
+        let inter_crl = &include_bytes!("../../../../test_key/ecp384/inter.crl.der")[..];
+        let responder_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der")[..];
+
+        // thisUpdate 2026-10-19T09:16:25Z, nextUpdate 2036-10-16T09:16:25Z
+        for (timestamp, result) in [
+            (1792401385, Ok(())),
+            (1792401384, Err(SPDM_STATUS_INVALID_CERT)),
+            (2107761385, Ok(())),
+            (2107761386, Err(SPDM_STATUS_INVALID_CERT)),
+        ] {
+            assert_eq!(
+                check_cert_chain_revocation(
+                    responder_certs_der,
+                    root_cert(responder_certs_der),
+                    &[inter_crl],
+                    SpdmCertValidityTime::Now(timestamp)
+                ),
+                result
+            );
+        }
+        assert_eq!(
+            check_cert_chain_revocation(
+                responder_certs_der,
+                root_cert(responder_certs_der),
+                &[inter_crl],
+                SpdmCertValidityTime::Ignore
+            ),
+            Ok(())
+        );
+    }
+
+    /// check a cert chain that starts with an intermediate cert, which is
+    /// issued by the trust anchor
+    #[test]
+    fn test_check_cert_chain_revocation_case3() {
+        let ca_crl = &include_bytes!("../../../../test_key/ecp384/ca.crl.der")[..];
+        let inter_crl = &include_bytes!("../../../../test_key/ecp384/inter.crl.der")[..];
+        let bundle_certs_der =
+            &include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der")[..];
+        let root = root_cert(bundle_certs_der);
+        let cert_chain = &bundle_certs_der[root.len()..];
+
+        // the first intermediate cert is revoked by the trust anchor
+        assert_eq!(
+            check_cert_chain_revocation(cert_chain, root, &[ca_crl], now()),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+        assert_eq!(
+            check_cert_chain_revocation(cert_chain, root, &[inter_crl], now()),
+            Ok(())
+        );
     }
 }
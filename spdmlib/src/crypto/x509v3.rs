@@ -12,30 +12,1149 @@ const ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK: u8 = 0x80;
 
 const ASN1_FORM_CONSTRUCTED_MASK: u8 = 0x20;
 
+const ASN1_TAG_NUMBER_BOOLEAN: u8 = 0x1;
 const ASN1_TAG_NUMBER_INTEGER: u8 = 0x2;
+const ASN1_TAG_NUMBER_BIT_STRING: u8 = 0x3;
+const ASN1_TAG_NUMBER_OCTET_STRING: u8 = 0x4;
 const ASN1_TAG_NUMBER_OBJECT_IDENTIFIER: u8 = 0x6;
 const ASN1_TAG_NUMBER_SEQUENCE: u8 = 0x10;
+const ASN1_TAG_NUMBER_SET: u8 = 0x11;
+const ASN1_TAG_NUMBER_UTC_TIME: u8 = 0x17;
+const ASN1_TAG_NUMBER_GENERALIZED_TIME: u8 = 0x18;
 
 const ASN1_TAG_SEQUENCE: u8 =
     ASN1_TAG_CLASS_UNIVERSAL_MASK | ASN1_FORM_CONSTRUCTED_MASK | ASN1_TAG_NUMBER_SEQUENCE;
+const ASN1_TAG_SET: u8 =
+    ASN1_TAG_CLASS_UNIVERSAL_MASK | ASN1_FORM_CONSTRUCTED_MASK | ASN1_TAG_NUMBER_SET;
+
+// issuerUniqueID [1] IMPLICIT, subjectUniqueID [2] IMPLICIT, extensions [3] EXPLICIT
+const ASN1_TAG_ISSUER_UNIQUE_ID: u8 = ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK | 0x1;
+const ASN1_TAG_SUBJECT_UNIQUE_ID: u8 = ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK | 0x2;
+const ASN1_TAG_EXTENSIONS: u8 =
+    ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK | ASN1_FORM_CONSTRUCTED_MASK | 0x3;
+// crlExtensions [0] EXPLICIT
+const ASN1_TAG_CRL_EXTENSIONS: u8 =
+    ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK | ASN1_FORM_CONSTRUCTED_MASK | 0x0;
 
 const ASN1_LENGTH_MULTI_OCTET_MASK: u8 = 0x80;
 
 const X509V3_VERSION: u8 = 2;
+const X509_CRL_V2_VERSION: u8 = 1;
 const OID_RSA_SHA256RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0bu8];
 const OID_RSA_SHA384RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0cu8];
 const OID_RSA_SHA512RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0du8];
 const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02u8];
 const OID_ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03u8];
 
+// subjectPublicKeyInfo algorithms
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01u8];
+const OID_RSASSA_PSS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0au8];
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01u8];
+const OID_EC_NIST_P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07u8];
+const OID_EC_NIST_P384: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22u8];
+
+// name attributes
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03u8];
+
+// certificate extensions
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13u8];
+const OID_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0fu8];
+const OID_EXTENDED_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25u8];
+
+// reference: DSP0274 1.2 - SPDM certificate requirements and recommendations
+// id-DMTF-spdm 1.3.6.1.4.1.412.274
+const OID_DMTF_SPDM: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12u8];
+// id-DMTF-spdm-extension 1.3.6.1.4.1.412.274.6
+const OID_DMTF_SPDM_EXTENSION: &[u8] =
+    &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x06u8];
+// id-DMTF-hardware-identity 1.3.6.1.4.1.412.274.2
+const OID_DMTF_HARDWARE_IDENTITY: &[u8] =
+    &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x02u8];
+// id-DMTF-eku-responder-auth 1.3.6.1.4.1.412.274.3
+const OID_DMTF_EKU_RESPONDER_AUTH: &[u8] =
+    &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x03u8];
+// id-DMTF-eku-requester-auth 1.3.6.1.4.1.412.274.4
+const OID_DMTF_EKU_REQUESTER_AUTH: &[u8] =
+    &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x04u8];
+
 // reference: https://www.rfc-editor.org/rfc/rfc5280.txt
+// The types below are a zero-copy view of a DER encoded certificate,
+// every field borrows from the certificate slice.
+// An OBJECT IDENTIFIER is represented by its content octets (without tag and length).
+
+// Certificate  ::=  SEQUENCE  {
+//      tbsCertificate       TBSCertificate,
+//      signatureAlgorithm   AlgorithmIdentifier,
+//      signatureValue       BIT STRING  }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509Certificate<'a> {
+    der: &'a [u8],
+    pub tbs_certificate: X509TbsCertificate<'a>,
+    pub signature_algorithm: X509AlgorithmIdentifier<'a>,
+    // the content of the BIT STRING, including the unused bits byte
+    pub signature_value: &'a [u8],
+}
+
+impl<'a> X509Certificate<'a> {
+    // IN DER encoded certificate
+    // OUT Error the data is not exactly one certificate
+    pub fn from_der(data: &'a [u8]) -> SpdmResult<Self> {
+        let (certificate, bytes_consumed) = Self::parse(data)?;
+        if bytes_consumed == data.len() {
+            Ok(certificate)
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    }
+
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (certificate, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let (tbs_certificate, mut c_walker) = X509TbsCertificate::parse(certificate)?;
+        let (signature_algorithm, algorithm_length) =
+            X509AlgorithmIdentifier::parse(&certificate[c_walker..])?;
+        c_walker += algorithm_length;
+        let (signature_value, signature_length) =
+            check_tag_and_get_value(&certificate[c_walker..], ASN1_TAG_NUMBER_BIT_STRING)?;
+        c_walker += signature_length;
+
+        if c_walker != certificate.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+        Ok((
+            Self {
+                der: &data[..bytes_consumed],
+                tbs_certificate,
+                signature_algorithm,
+                signature_value,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+// TBSCertificate  ::=  SEQUENCE  {
+//      version         [0]  EXPLICIT Version DEFAULT v1,
+//      serialNumber         CertificateSerialNumber,
+//      signature            AlgorithmIdentifier,
+//      issuer               Name,
+//      validity             Validity,
+//      subject              Name,
+//      subjectPublicKeyInfo SubjectPublicKeyInfo,
+//      issuerUniqueID  [1]  IMPLICIT UniqueIdentifier OPTIONAL,
+//      subjectUniqueID [2]  IMPLICIT UniqueIdentifier OPTIONAL,
+//      extensions      [3]  EXPLICIT Extensions OPTIONAL }
+// only v3 is accepted, so the version is not kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509TbsCertificate<'a> {
+    der: &'a [u8],
+    // the content of the INTEGER
+    pub serial_number: &'a [u8],
+    pub signature: X509AlgorithmIdentifier<'a>,
+    pub issuer: X509Name<'a>,
+    pub validity: X509Validity<'a>,
+    pub subject: X509Name<'a>,
+    pub subject_public_key_info: X509SubjectPublicKeyInfo<'a>,
+    // the content of the BIT STRING, including the unused bits byte
+    pub issuer_unique_id: Option<&'a [u8]>,
+    pub subject_unique_id: Option<&'a [u8]>,
+    pub extensions: Option<X509Extensions<'a>>,
+}
+
+impl<'a> X509TbsCertificate<'a> {
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (tbs, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let mut t_walker = check_version(tbs)?;
+
+        let (serial_number, serial_number_length) =
+            check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_NUMBER_INTEGER)?;
+        t_walker += serial_number_length;
+
+        let (signature, signature_length) = X509AlgorithmIdentifier::parse(&tbs[t_walker..])?;
+        t_walker += signature_length;
+
+        let (issuer, issuer_length) = X509Name::parse(&tbs[t_walker..])?;
+        t_walker += issuer_length;
+
+        let (validity, validity_length) = X509Validity::parse(&tbs[t_walker..])?;
+        t_walker += validity_length;
+
+        let (subject, subject_length) = X509Name::parse(&tbs[t_walker..])?;
+        t_walker += subject_length;
+
+        let (subject_public_key_info, public_key_info_length) =
+            X509SubjectPublicKeyInfo::parse(&tbs[t_walker..])?;
+        t_walker += public_key_info_length;
+
+        // the optional fields must be in order, each at most once.
+        let mut issuer_unique_id = None;
+        if tbs.get(t_walker) == Some(&ASN1_TAG_ISSUER_UNIQUE_ID) {
+            let (value, bytes_consumed) =
+                check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_ISSUER_UNIQUE_ID)?;
+            issuer_unique_id = Some(value);
+            t_walker += bytes_consumed;
+        }
+        let mut subject_unique_id = None;
+        if tbs.get(t_walker) == Some(&ASN1_TAG_SUBJECT_UNIQUE_ID) {
+            let (value, bytes_consumed) =
+                check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_SUBJECT_UNIQUE_ID)?;
+            subject_unique_id = Some(value);
+            t_walker += bytes_consumed;
+        }
+        let mut extensions = None;
+        if tbs.get(t_walker) == Some(&ASN1_TAG_EXTENSIONS) {
+            let (value, bytes_consumed) =
+                check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_EXTENSIONS)?;
+            extensions = Some(X509Extensions::parse(value)?);
+            t_walker += bytes_consumed;
+        }
+
+        if t_walker != tbs.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+        Ok((
+            Self {
+                der: &data[..bytes_consumed],
+                serial_number,
+                signature,
+                issuer,
+                validity,
+                subject,
+                subject_public_key_info,
+                issuer_unique_id,
+                subject_unique_id,
+                extensions,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+// AlgorithmIdentifier  ::=  SEQUENCE  {
+//      algorithm               OBJECT IDENTIFIER,
+//      parameters              ANY DEFINED BY algorithm OPTIONAL  }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509AlgorithmIdentifier<'a> {
+    pub algorithm: &'a [u8],
+    // the DER encoded parameters
+    pub parameters: Option<&'a [u8]>,
+}
+
+impl<'a> X509AlgorithmIdentifier<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (algorithm_identifier, bytes_consumed) =
+            check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+        let (algorithm, oid_length) =
+            check_tag_and_get_value(algorithm_identifier, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER)?;
+
+        let parameters = &algorithm_identifier[oid_length..];
+        let parameters = if parameters.is_empty() {
+            None
+        } else if check_and_skip_common_tag(parameters)? == parameters.len() {
+            Some(parameters)
+        } else {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        };
+
+        Ok((
+            Self {
+                algorithm,
+                parameters,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+// Name ::= SEQUENCE OF RelativeDistinguishedName
+// RelativeDistinguishedName ::= SET SIZE (1..MAX) OF AttributeTypeAndValue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509Name<'a> {
+    der: &'a [u8],
+    rdn_sequence: &'a [u8],
+}
+
+impl<'a> X509Name<'a> {
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    // all attributes of all RDNs, in encoding order
+    pub fn attributes(&self) -> X509NameAttributes<'a> {
+        X509NameAttributes {
+            rdn_sequence: self.rdn_sequence,
+            rdn: &[],
+        }
+    }
+
+    // the first attribute of the type
+    pub fn get_attribute(&self, attribute_type: &[u8]) -> Option<X509NameAttribute<'a>> {
+        self.attributes()
+            .find(|attribute| object_identifiers_are_same(attribute.attribute_type, attribute_type))
+    }
+
+    pub fn common_name(&self) -> Option<&'a [u8]> {
+        self.get_attribute(OID_COMMON_NAME)
+            .map(|attribute| attribute.value)
+    }
+
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (rdn_sequence, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let mut n_walker = 0usize;
+        while n_walker < rdn_sequence.len() {
+            let (rdn, rdn_length) =
+                check_tag_and_get_value(&rdn_sequence[n_walker..], ASN1_TAG_SET)?;
+            if rdn.is_empty() {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+
+            let mut r_walker = 0usize;
+            while r_walker < rdn.len() {
+                let (_, attribute_length) = X509NameAttribute::parse(&rdn[r_walker..])?;
+                r_walker += attribute_length;
+            }
+            n_walker += rdn_length;
+        }
+
+        Ok((
+            Self {
+                der: &data[..bytes_consumed],
+                rdn_sequence,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+// AttributeTypeAndValue ::= SEQUENCE { type OBJECT IDENTIFIER, value ANY }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509NameAttribute<'a> {
+    pub attribute_type: &'a [u8],
+    // the tag of the value, for example UTF8String or PrintableString
+    pub value_tag: u8,
+    pub value: &'a [u8],
+}
+
+impl<'a> X509NameAttribute<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (attribute, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+        let (attribute_type, oid_length) =
+            check_tag_and_get_value(attribute, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER)?;
+        let (value_tag, value, value_length) = get_tag_and_value(&attribute[oid_length..])?;
+
+        if oid_length + value_length == attribute.len() {
+            Ok((
+                Self {
+                    attribute_type,
+                    value_tag,
+                    value,
+                },
+                bytes_consumed,
+            ))
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct X509NameAttributes<'a> {
+    rdn_sequence: &'a [u8],
+    rdn: &'a [u8],
+}
+
+impl<'a> Iterator for X509NameAttributes<'a> {
+    type Item = X509NameAttribute<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rdn.is_empty() {
+            let (rdn, rdn_length) =
+                check_tag_and_get_value(self.rdn_sequence, ASN1_TAG_SET).ok()?;
+            self.rdn_sequence = &self.rdn_sequence[rdn_length..];
+            self.rdn = rdn;
+        }
+        let (attribute, attribute_length) = X509NameAttribute::parse(self.rdn).ok()?;
+        self.rdn = &self.rdn[attribute_length..];
+        Some(attribute)
+    }
+}
+
+// Validity ::= SEQUENCE { notBefore Time, notAfter Time }
+// the validity period itself is checked by the crypto backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509Validity<'a> {
+    pub not_before: X509Time<'a>,
+    pub not_after: X509Time<'a>,
+}
+
+impl<'a> X509Validity<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (validity, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let (not_before, not_before_length) = X509Time::parse(validity)?;
+        let (not_after, not_after_length) = X509Time::parse(&validity[not_before_length..])?;
+
+        if not_before_length + not_after_length == validity.len() {
+            Ok((
+                Self {
+                    not_before,
+                    not_after,
+                },
+                bytes_consumed,
+            ))
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    }
+}
+
+// Time ::= CHOICE { utcTime UTCTime, generalTime GeneralizedTime }
+// the value is the ASCII content, YYMMDDHHMMSSZ or YYYYMMDDHHMMSSZ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum X509Time<'a> {
+    UtcTime(&'a [u8]),
+    GeneralizedTime(&'a [u8]),
+}
+
+impl<'a> X509Time<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        match data.first() {
+            Some(&ASN1_TAG_NUMBER_UTC_TIME) => {
+                let (time, bytes_consumed) =
+                    check_tag_and_get_value(data, ASN1_TAG_NUMBER_UTC_TIME)?;
+                Ok((X509Time::UtcTime(time), bytes_consumed))
+            }
+            Some(&ASN1_TAG_NUMBER_GENERALIZED_TIME) => {
+                let (time, bytes_consumed) =
+                    check_tag_and_get_value(data, ASN1_TAG_NUMBER_GENERALIZED_TIME)?;
+                Ok((X509Time::GeneralizedTime(time), bytes_consumed))
+            }
+            _ => Err(SPDM_STATUS_VERIF_FAIL),
+        }
+    }
+
+    // OUT Ok seconds since the Unix epoch
+    // OUT Error the time is not in the RFC 5280 form (UTC with seconds) or before 1970
+    pub fn to_unix_time(&self) -> SpdmResult<u64> {
+        let (year, time) = match *self {
+            X509Time::UtcTime(time) if time.len() == 13 => {
+                let year = get_decimal_digits(&time[..2])?;
+                // RFC 5280 4.1.2.5.1, YY >= 50 means 19YY.
+                (
+                    if year >= 50 { 1900 + year } else { 2000 + year },
+                    &time[2..],
+                )
+            }
+            X509Time::GeneralizedTime(time) if time.len() == 15 => {
+                (get_decimal_digits(&time[..4])?, &time[4..])
+            }
+            _ => return Err(SPDM_STATUS_VERIF_FAIL),
+        };
+        if time[10] != b'Z' {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let month = get_decimal_digits(&time[0..2])?;
+        let day = get_decimal_digits(&time[2..4])?;
+        let hour = get_decimal_digits(&time[4..6])?;
+        let minute = get_decimal_digits(&time[6..8])?;
+        let second = get_decimal_digits(&time[8..10])?;
+        if year < 1970
+            || !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        // days since the Unix epoch of a proleptic Gregorian date.
+        let (y, m) = if month <= 2 {
+            (year - 1, month + 9)
+        } else {
+            (year, month - 3)
+        };
+        let era = y / 400;
+        let yoe = y - era * 400;
+        let doy = (153 * m + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+
+        Ok(days * 86400 + hour * 3600 + minute * 60 + second)
+    }
+}
+
+// SubjectPublicKeyInfo  ::=  SEQUENCE  {
+//      algorithm            AlgorithmIdentifier,
+//      subjectPublicKey     BIT STRING  }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509SubjectPublicKeyInfo<'a> {
+    der: &'a [u8],
+    pub algorithm: X509AlgorithmIdentifier<'a>,
+    // the key bytes, without the unused bits byte of the BIT STRING:
+    // RSAPublicKey for RSA, the uncompressed point for EC.
+    pub subject_public_key: &'a [u8],
+}
+
+impl<'a> X509SubjectPublicKeyInfo<'a> {
+    // the DER encoded SubjectPublicKeyInfo
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (public_key_info, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let (algorithm, algorithm_length) = X509AlgorithmIdentifier::parse(public_key_info)?;
+        let (public_key, public_key_length) = check_tag_and_get_value(
+            &public_key_info[algorithm_length..],
+            ASN1_TAG_NUMBER_BIT_STRING,
+        )?;
+        if algorithm_length + public_key_length != public_key_info.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        // the first byte of the BIT STRING is the number of unused bits, a key has none.
+        match public_key.split_first() {
+            Some((0, subject_public_key)) => Ok((
+                Self {
+                    der: &data[..bytes_consumed],
+                    algorithm,
+                    subject_public_key,
+                },
+                bytes_consumed,
+            )),
+            _ => Err(SPDM_STATUS_VERIF_FAIL),
+        }
+    }
+}
+
+// Extensions  ::=  SEQUENCE SIZE (1..MAX) OF Extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509Extensions<'a> {
+    extensions: &'a [u8],
+}
+
+impl<'a> X509Extensions<'a> {
+    pub fn iter(&self) -> X509ExtensionIter<'a> {
+        X509ExtensionIter {
+            extensions: self.extensions,
+        }
+    }
+
+    pub fn get(&self, extn_id: &[u8]) -> Option<X509Extension<'a>> {
+        self.iter()
+            .find(|extension| object_identifiers_are_same(extension.extn_id, extn_id))
+    }
+
+    // OUT Ok None the extension is absent
+    // OUT Error the extension is malformed
+    pub fn basic_constraints(&self) -> SpdmResult<Option<X509BasicConstraints>> {
+        self.get(OID_BASIC_CONSTRAINTS)
+            .map(|extension| X509BasicConstraints::from_extn_value(extension.extn_value))
+            .transpose()
+    }
+
+    pub fn key_usage(&self) -> SpdmResult<Option<X509KeyUsage>> {
+        self.get(OID_KEY_USAGE)
+            .map(|extension| X509KeyUsage::from_extn_value(extension.extn_value))
+            .transpose()
+    }
+
+    pub fn extended_key_usage(&self) -> SpdmResult<Option<X509ExtendedKeyUsage<'a>>> {
+        self.get(OID_EXTENDED_KEY_USAGE)
+            .map(|extension| X509ExtendedKeyUsage::from_extn_value(extension.extn_value))
+            .transpose()
+    }
+
+    pub fn spdm_extension(&self) -> SpdmResult<Option<X509SpdmExtension<'a>>> {
+        self.get(OID_DMTF_SPDM_EXTENSION)
+            .map(|extension| X509SpdmExtension::from_extn_value(extension.extn_value))
+            .transpose()
+    }
+
+    // IN the content of the extensions [3] EXPLICIT tag
+    fn parse(data: &'a [u8]) -> SpdmResult<Self> {
+        let (extensions, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+        if bytes_consumed != data.len() || extensions.is_empty() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let mut e_walker = 0usize;
+        while e_walker < extensions.len() {
+            let (_, bytes_consumed) = X509Extension::parse(&extensions[e_walker..])?;
+            e_walker += bytes_consumed;
+        }
+        Ok(Self { extensions })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct X509ExtensionIter<'a> {
+    extensions: &'a [u8],
+}
+
+impl<'a> Iterator for X509ExtensionIter<'a> {
+    type Item = X509Extension<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (extension, bytes_consumed) = X509Extension::parse(self.extensions).ok()?;
+        self.extensions = &self.extensions[bytes_consumed..];
+        Some(extension)
+    }
+}
+
+// Extension  ::=  SEQUENCE  {
+//      extnID      OBJECT IDENTIFIER,
+//      critical    BOOLEAN DEFAULT FALSE,
+//      extnValue   OCTET STRING }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509Extension<'a> {
+    pub extn_id: &'a [u8],
+    pub critical: bool,
+    // the content of the OCTET STRING
+    pub extn_value: &'a [u8],
+}
+
+impl<'a> X509Extension<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (extension, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let (extn_id, mut e_walker) =
+            check_tag_and_get_value(extension, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER)?;
+        let mut critical = false;
+        if extension.get(e_walker) == Some(&ASN1_TAG_NUMBER_BOOLEAN) {
+            let (value, boolean_length) =
+                check_tag_and_get_value(&extension[e_walker..], ASN1_TAG_NUMBER_BOOLEAN)?;
+            critical = get_boolean(value)?;
+            e_walker += boolean_length;
+        }
+        let (extn_value, extn_value_length) =
+            check_tag_and_get_value(&extension[e_walker..], ASN1_TAG_NUMBER_OCTET_STRING)?;
+
+        if e_walker + extn_value_length == extension.len() {
+            Ok((
+                Self {
+                    extn_id,
+                    critical,
+                    extn_value,
+                },
+                bytes_consumed,
+            ))
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    }
+}
+
+// BasicConstraints ::= SEQUENCE {
+//      cA                      BOOLEAN DEFAULT FALSE,
+//      pathLenConstraint       INTEGER (0..MAX) OPTIONAL }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct X509BasicConstraints {
+    pub ca: bool,
+    pub path_len_constraint: Option<u32>,
+}
+
+impl X509BasicConstraints {
+    pub fn from_extn_value(extn_value: &[u8]) -> SpdmResult<Self> {
+        let (basic_constraints, bytes_consumed) =
+            check_tag_and_get_value(extn_value, ASN1_TAG_SEQUENCE)?;
+        if bytes_consumed != extn_value.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let mut b_walker = 0usize;
+        let mut ca = false;
+        if basic_constraints.first() == Some(&ASN1_TAG_NUMBER_BOOLEAN) {
+            let (value, boolean_length) =
+                check_tag_and_get_value(basic_constraints, ASN1_TAG_NUMBER_BOOLEAN)?;
+            ca = get_boolean(value)?;
+            b_walker += boolean_length;
+        }
+        let mut path_len_constraint = None;
+        if b_walker < basic_constraints.len() {
+            let (value, integer_length) =
+                check_tag_and_get_value(&basic_constraints[b_walker..], ASN1_TAG_NUMBER_INTEGER)?;
+            path_len_constraint = Some(get_unsigned_integer(value)?);
+            b_walker += integer_length;
+        }
+
+        if b_walker == basic_constraints.len() {
+            Ok(Self {
+                ca,
+                path_len_constraint,
+            })
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    }
+}
+
+// KeyUsage ::= BIT STRING, bit n of the BIT STRING is bit n of the flags.
+bitflags! {
+    #[derive(Default)]
+    pub struct X509KeyUsage: u16 {
+        const DIGITAL_SIGNATURE = 0b0000_0000_0000_0001;
+        const NON_REPUDIATION = 0b0000_0000_0000_0010;
+        const KEY_ENCIPHERMENT = 0b0000_0000_0000_0100;
+        const DATA_ENCIPHERMENT = 0b0000_0000_0000_1000;
+        const KEY_AGREEMENT = 0b0000_0000_0001_0000;
+        const KEY_CERT_SIGN = 0b0000_0000_0010_0000;
+        const CRL_SIGN = 0b0000_0000_0100_0000;
+        const ENCIPHER_ONLY = 0b0000_0000_1000_0000;
+        const DECIPHER_ONLY = 0b0000_0001_0000_0000;
+    }
+}
+
+impl X509KeyUsage {
+    pub fn from_extn_value(extn_value: &[u8]) -> SpdmResult<Self> {
+        let (key_usage, bytes_consumed) =
+            check_tag_and_get_value(extn_value, ASN1_TAG_NUMBER_BIT_STRING)?;
+        if bytes_consumed != extn_value.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        // the first byte is the number of unused bits,
+        // bit 0 is the most significant bit of the next byte.
+        let (_, bits) = key_usage.split_first().ok_or(SPDM_STATUS_VERIF_FAIL)?;
+        let mut usage = 0u16;
+        for (i, byte) in bits.iter().take(2).enumerate() {
+            usage |= (byte.reverse_bits() as u16) << (i * 8);
+        }
+        Ok(X509KeyUsage::from_bits_truncate(usage))
+    }
+}
+
+// ExtKeyUsageSyntax ::= SEQUENCE SIZE (1..MAX) OF KeyPurposeId
+// KeyPurposeId ::= OBJECT IDENTIFIER
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509ExtendedKeyUsage<'a> {
+    key_purposes: &'a [u8],
+}
+
+impl<'a> X509ExtendedKeyUsage<'a> {
+    pub fn from_extn_value(extn_value: &'a [u8]) -> SpdmResult<Self> {
+        let (key_purposes, bytes_consumed) =
+            check_tag_and_get_value(extn_value, ASN1_TAG_SEQUENCE)?;
+        if bytes_consumed != extn_value.len() || key_purposes.is_empty() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let mut k_walker = 0usize;
+        while k_walker < key_purposes.len() {
+            let (_, bytes_consumed) = check_tag_and_get_value(
+                &key_purposes[k_walker..],
+                ASN1_TAG_NUMBER_OBJECT_IDENTIFIER,
+            )?;
+            k_walker += bytes_consumed;
+        }
+        Ok(Self { key_purposes })
+    }
+
+    pub fn key_purposes(&self) -> X509KeyPurposes<'a> {
+        X509KeyPurposes {
+            key_purposes: self.key_purposes,
+        }
+    }
+
+    pub fn contains(&self, key_purpose: &[u8]) -> bool {
+        self.key_purposes()
+            .any(|oid| object_identifiers_are_same(oid, key_purpose))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct X509KeyPurposes<'a> {
+    key_purposes: &'a [u8],
+}
+
+impl<'a> Iterator for X509KeyPurposes<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key_purpose, bytes_consumed) =
+            check_tag_and_get_value(self.key_purposes, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER).ok()?;
+        self.key_purposes = &self.key_purposes[bytes_consumed..];
+        Some(key_purpose)
+    }
+}
+
+// reference: DSP0274 1.2 - SPDM certificate requirements and recommendations
+// SPDMExtension ::= SEQUENCE SIZE (1..MAX) OF SPDMOID
+// SPDMOID ::= SEQUENCE {
+//      id      OBJECT IDENTIFIER,
+//      value   OCTET STRING OPTIONAL }
+// Some certificates, such as the DMTF libspdm test certificates, carry the
+// bare OBJECT IDENTIFIERs instead of the SEQUENCE, both forms are accepted.
+// A bare OBJECT IDENTIFIER must be one of the id-DMTF-spdm OIDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509SpdmExtension<'a> {
+    spdm_oids: &'a [u8],
+    is_bare: bool,
+}
+
+impl<'a> X509SpdmExtension<'a> {
+    pub fn from_extn_value(extn_value: &'a [u8]) -> SpdmResult<Self> {
+        let is_bare = extn_value.first() == Some(&ASN1_TAG_NUMBER_OBJECT_IDENTIFIER);
+        let spdm_oids = if is_bare {
+            extn_value
+        } else {
+            let (spdm_oids, bytes_consumed) =
+                check_tag_and_get_value(extn_value, ASN1_TAG_SEQUENCE)?;
+            if bytes_consumed != extn_value.len() {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+            spdm_oids
+        };
+        if spdm_oids.is_empty() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let mut s_walker = 0usize;
+        while s_walker < spdm_oids.len() {
+            let (_, bytes_consumed) = parse_spdm_oid(&spdm_oids[s_walker..], is_bare)?;
+            s_walker += bytes_consumed;
+        }
+        Ok(Self { spdm_oids, is_bare })
+    }
+
+    // the id of every SPDMOID
+    pub fn ids(&self) -> X509SpdmOids<'a> {
+        X509SpdmOids {
+            spdm_oids: self.spdm_oids,
+            is_bare: self.is_bare,
+        }
+    }
+
+    pub fn contains(&self, id: &[u8]) -> bool {
+        self.ids().any(|oid| object_identifiers_are_same(oid, id))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct X509SpdmOids<'a> {
+    spdm_oids: &'a [u8],
+    is_bare: bool,
+}
+
+impl<'a> Iterator for X509SpdmOids<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, bytes_consumed) = parse_spdm_oid(self.spdm_oids, self.is_bare).ok()?;
+        self.spdm_oids = &self.spdm_oids[bytes_consumed..];
+        Some(id)
+    }
+}
+
+// OUT Ok the id of the SPDMOID, or the bare OBJECT IDENTIFIER, and the bytes consumed
+fn parse_spdm_oid(data: &[u8], is_bare: bool) -> SpdmResult<(&[u8], usize)> {
+    if is_bare {
+        let (id, bytes_consumed) =
+            check_tag_and_get_value(data, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER)?;
+        return match id.strip_prefix(OID_DMTF_SPDM) {
+            Some([_]) => Ok((id, bytes_consumed)),
+            _ => Err(SPDM_STATUS_VERIF_FAIL),
+        };
+    }
+
+    let (spdm_oid, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+    let (id, mut s_walker) = check_tag_and_get_value(spdm_oid, ASN1_TAG_NUMBER_OBJECT_IDENTIFIER)?;
+    if s_walker < spdm_oid.len() {
+        let (_, value_length) =
+            check_tag_and_get_value(&spdm_oid[s_walker..], ASN1_TAG_NUMBER_OCTET_STRING)?;
+        s_walker += value_length;
+    }
+
+    if s_walker == spdm_oid.len() {
+        Ok((id, bytes_consumed))
+    } else {
+        Err(SPDM_STATUS_VERIF_FAIL)
+    }
+}
+
+// The certificates of a DER encoded certificate chain, from the root to the leaf.
+// A chain has no depth limit, the iteration stops after the first malformed certificate.
+#[derive(Debug, Clone)]
+pub struct X509CertChain<'a> {
+    cert_chain: &'a [u8],
+}
+
+impl<'a> X509CertChain<'a> {
+    pub fn new(cert_chain: &'a [u8]) -> Self {
+        Self { cert_chain }
+    }
+}
+
+impl<'a> Iterator for X509CertChain<'a> {
+    type Item = SpdmResult<X509Certificate<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cert_chain.is_empty() {
+            return None;
+        }
+        match X509Certificate::parse(self.cert_chain) {
+            Ok((certificate, bytes_consumed)) => {
+                self.cert_chain = &self.cert_chain[bytes_consumed..];
+                Some(Ok(certificate))
+            }
+            Err(e) => {
+                self.cert_chain = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// CertificateList  ::=  SEQUENCE  {
+//      tbsCertList          TBSCertList,
+//      signatureAlgorithm   AlgorithmIdentifier,
+//      signatureValue       BIT STRING  }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509CertificateList<'a> {
+    der: &'a [u8],
+    pub tbs_cert_list: X509TbsCertList<'a>,
+    pub signature_algorithm: X509AlgorithmIdentifier<'a>,
+    // the content of the BIT STRING, including the unused bits byte
+    pub signature_value: &'a [u8],
+}
+
+impl<'a> X509CertificateList<'a> {
+    // IN DER encoded CRL
+    // OUT Error the data is not exactly one CRL
+    pub fn from_der(data: &'a [u8]) -> SpdmResult<Self> {
+        let (crl, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+        if bytes_consumed != data.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+
+        let (tbs_cert_list, mut c_walker) = X509TbsCertList::parse(crl)?;
+        let (signature_algorithm, algorithm_length) =
+            X509AlgorithmIdentifier::parse(&crl[c_walker..])?;
+        c_walker += algorithm_length;
+        let (signature_value, signature_length) =
+            check_tag_and_get_value(&crl[c_walker..], ASN1_TAG_NUMBER_BIT_STRING)?;
+        c_walker += signature_length;
+
+        if c_walker != crl.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+        Ok(Self {
+            der: data,
+            tbs_cert_list,
+            signature_algorithm,
+            signature_value,
+        })
+    }
+
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+}
+
+// TBSCertList  ::=  SEQUENCE  {
+//      version                 Version OPTIONAL, -- if present, MUST be v2
+//      signature               AlgorithmIdentifier,
+//      issuer                  Name,
+//      thisUpdate              Time,
+//      nextUpdate              Time OPTIONAL,
+//      revokedCertificates     SEQUENCE OF SEQUENCE  { ... } OPTIONAL,
+//      crlExtensions           [0]  EXPLICIT Extensions OPTIONAL }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509TbsCertList<'a> {
+    der: &'a [u8],
+    pub signature: X509AlgorithmIdentifier<'a>,
+    pub issuer: X509Name<'a>,
+    pub this_update: X509Time<'a>,
+    pub next_update: Option<X509Time<'a>>,
+    revoked_certificates: &'a [u8],
+    pub crl_extensions: Option<X509Extensions<'a>>,
+}
+
+impl<'a> X509TbsCertList<'a> {
+    pub fn as_der(&self) -> &'a [u8] {
+        self.der
+    }
+
+    pub fn revoked_certificates(&self) -> X509RevokedCertificates<'a> {
+        X509RevokedCertificates {
+            revoked_certificates: self.revoked_certificates,
+        }
+    }
+
+    // IN the content of the serial number INTEGER of a certificate
+    pub fn is_revoked(&self, serial_number: &[u8]) -> bool {
+        self.revoked_certificates()
+            .any(|revoked_certificate| revoked_certificate.user_certificate == serial_number)
+    }
+
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (tbs, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let mut t_walker = 0usize;
+        if tbs.first() == Some(&ASN1_TAG_NUMBER_INTEGER) {
+            let (version, version_length) = check_tag_and_get_value(tbs, ASN1_TAG_NUMBER_INTEGER)?;
+            if version != [X509_CRL_V2_VERSION] {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+            t_walker += version_length;
+        }
+
+        let (signature, signature_length) = X509AlgorithmIdentifier::parse(&tbs[t_walker..])?;
+        t_walker += signature_length;
+
+        let (issuer, issuer_length) = X509Name::parse(&tbs[t_walker..])?;
+        t_walker += issuer_length;
+
+        let (this_update, this_update_length) = X509Time::parse(&tbs[t_walker..])?;
+        t_walker += this_update_length;
+
+        // the optional fields must be in order, each at most once.
+        let mut next_update = None;
+        if matches!(
+            tbs.get(t_walker),
+            Some(&ASN1_TAG_NUMBER_UTC_TIME) | Some(&ASN1_TAG_NUMBER_GENERALIZED_TIME)
+        ) {
+            let (time, bytes_consumed) = X509Time::parse(&tbs[t_walker..])?;
+            next_update = Some(time);
+            t_walker += bytes_consumed;
+        }
+        let mut revoked_certificates: &[u8] = &[];
+        if tbs.get(t_walker) == Some(&ASN1_TAG_SEQUENCE) {
+            let (value, bytes_consumed) =
+                check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_SEQUENCE)?;
+            let mut r_walker = 0usize;
+            while r_walker < value.len() {
+                let (_, bytes_consumed) = X509RevokedCertificate::parse(&value[r_walker..])?;
+                r_walker += bytes_consumed;
+            }
+            revoked_certificates = value;
+            t_walker += bytes_consumed;
+        }
+        let mut crl_extensions = None;
+        if tbs.get(t_walker) == Some(&ASN1_TAG_CRL_EXTENSIONS) {
+            let (value, bytes_consumed) =
+                check_tag_and_get_value(&tbs[t_walker..], ASN1_TAG_CRL_EXTENSIONS)?;
+            crl_extensions = Some(X509Extensions::parse(value)?);
+            t_walker += bytes_consumed;
+        }
+
+        if t_walker != tbs.len() {
+            return Err(SPDM_STATUS_VERIF_FAIL);
+        }
+        Ok((
+            Self {
+                der: &data[..bytes_consumed],
+                signature,
+                issuer,
+                this_update,
+                next_update,
+                revoked_certificates,
+                crl_extensions,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+// SEQUENCE  {
+//      userCertificate         CertificateSerialNumber,
+//      revocationDate          Time,
+//      crlEntryExtensions      Extensions OPTIONAL }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X509RevokedCertificate<'a> {
+    // the content of the INTEGER
+    pub user_certificate: &'a [u8],
+    pub revocation_date: X509Time<'a>,
+    pub crl_entry_extensions: Option<X509Extensions<'a>>,
+}
+
+impl<'a> X509RevokedCertificate<'a> {
+    fn parse(data: &'a [u8]) -> SpdmResult<(Self, usize)> {
+        let (entry, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_SEQUENCE)?;
+
+        let (user_certificate, mut e_walker) =
+            check_tag_and_get_value(entry, ASN1_TAG_NUMBER_INTEGER)?;
+        let (revocation_date, revocation_date_length) = X509Time::parse(&entry[e_walker..])?;
+        e_walker += revocation_date_length;
+        let crl_entry_extensions = if e_walker == entry.len() {
+            None
+        } else {
+            Some(X509Extensions::parse(&entry[e_walker..])?)
+        };
+
+        Ok((
+            Self {
+                user_certificate,
+                revocation_date,
+                crl_entry_extensions,
+            },
+            bytes_consumed,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct X509RevokedCertificates<'a> {
+    revoked_certificates: &'a [u8],
+}
+
+impl<'a> Iterator for X509RevokedCertificates<'a> {
+    type Item = X509RevokedCertificate<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (revoked_certificate, bytes_consumed) =
+            X509RevokedCertificate::parse(self.revoked_certificates).ok()?;
+        self.revoked_certificates = &self.revoked_certificates[bytes_consumed..];
+        Some(revoked_certificate)
+    }
+}
+
+// IN DER encoded certificate chain slice
+// OUT Ok the last certificate of the chain
+// OUT Error the chain is empty or the leaf certificate is malformed
+// the certificates before the leaf are only skipped, use check_cert_chain_format to check them.
+pub fn get_leaf_certificate(cert_chain: &[u8]) -> SpdmResult<X509Certificate<'_>> {
+    let mut cc_walker = 0usize;
+    loop {
+        let cert_length = check_and_skip_common_sequence(&cert_chain[cc_walker..])?;
+        if cc_walker + cert_length == cert_chain.len() {
+            return X509Certificate::from_der(&cert_chain[cc_walker..]);
+        }
+        cc_walker += cert_length;
+    }
+}
+
+pub fn get_leaf_public_key_info(cert_chain: &[u8]) -> SpdmResult<X509SubjectPublicKeyInfo<'_>> {
+    Ok(get_leaf_certificate(cert_chain)?
+        .tbs_certificate
+        .subject_public_key_info)
+}
+
+pub fn get_leaf_subject(cert_chain: &[u8]) -> SpdmResult<X509Name<'_>> {
+    Ok(get_leaf_certificate(cert_chain)?.tbs_certificate.subject)
+}
+
 // IN DER encoded certificate chain slice
 // OUT Ok certificate count
 // OUT Error Mulformed certificate found
 // checked:
 // 1. version should be x509v3.
 // 2. the algorithm is match for leaf certificate
-// 3. no more or less bytes found
+// 3. the public key of leaf certificate is match for the algorithm
+// 4. no more or less bytes found
 pub fn check_cert_chain_format(
     cert_chain: &[u8],
     base_asym_algo: SpdmBaseAsymAlgo,
@@ -56,6 +1175,91 @@ pub fn check_cert_chain_format(
     }
 }
 
+// reference: DSP0274 1.2 - Leaf certificate
+// IN DER encoded leaf certificate slice of a chain accepted by check_cert_chain_format
+// IN is_requester_cert the certificate is used for requester (mutual) authentication
+// IN is_device_cert_model the responder negotiated the device certificate model
+//    instead of the alias certificate model
+// OUT Ok the leaf certificate matches the SPDM certificate profile
+// OUT Error the leaf certificate violates the SPDM certificate profile
+// checked:
+// 1. basicConstraints, if present, is not a CA.
+// 2. keyUsage is present and has digitalSignature.
+// 3. extKeyUsage, if present, does not restrict the certificate to the other role.
+// 4. the hardware identity OID, which is optional, is absent from the leaf of the alias
+//    certificate model.
+pub fn check_leaf_certificate(
+    cert: &[u8],
+    is_requester_cert: bool,
+    is_device_cert_model: bool,
+) -> SpdmResult {
+    let certificate = X509Certificate::from_der(cert)?;
+    // keyUsage is required, so are the extensions
+    let extensions = certificate
+        .tbs_certificate
+        .extensions
+        .ok_or(SPDM_STATUS_VERIF_FAIL)?;
+
+    let mut has_key_usage = false;
+    let mut has_hardware_identity = false;
+    for extension in extensions.iter() {
+        if object_identifiers_are_same(extension.extn_id, OID_BASIC_CONSTRAINTS) {
+            check_basic_constraints(extension.extn_value)?;
+        } else if object_identifiers_are_same(extension.extn_id, OID_KEY_USAGE) {
+            check_key_usage(extension.extn_value)?;
+            has_key_usage = true;
+        } else if object_identifiers_are_same(extension.extn_id, OID_EXTENDED_KEY_USAGE) {
+            check_extended_key_usage(extension.extn_value, is_requester_cert)?;
+        } else if object_identifiers_are_same(extension.extn_id, OID_DMTF_SPDM_EXTENSION) {
+            has_hardware_identity = X509SpdmExtension::from_extn_value(extension.extn_value)?
+                .contains(OID_DMTF_HARDWARE_IDENTITY);
+        }
+    }
+
+    // the hardware identity identifies the device, in the alias certificate model
+    // it is carried by the device certificate, not by the leaf (alias) certificate.
+    if has_key_usage && (is_device_cert_model || !has_hardware_identity) {
+        Ok(())
+    } else {
+        Err(SPDM_STATUS_VERIF_FAIL)
+    }
+}
+
+fn check_basic_constraints(extn_value: &[u8]) -> SpdmResult {
+    if X509BasicConstraints::from_extn_value(extn_value)?.ca {
+        Err(SPDM_STATUS_VERIF_FAIL)
+    } else {
+        Ok(())
+    }
+}
+
+fn check_key_usage(extn_value: &[u8]) -> SpdmResult {
+    if X509KeyUsage::from_extn_value(extn_value)?.contains(X509KeyUsage::DIGITAL_SIGNATURE) {
+        Ok(())
+    } else {
+        Err(SPDM_STATUS_VERIF_FAIL)
+    }
+}
+
+// a responder certificate must not have id-DMTF-eku-requester-auth only,
+// a requester certificate must not have id-DMTF-eku-responder-auth only.
+fn check_extended_key_usage(extn_value: &[u8], is_requester_cert: bool) -> SpdmResult {
+    let extended_key_usage = X509ExtendedKeyUsage::from_extn_value(extn_value)?;
+    let has_responder_auth = extended_key_usage.contains(OID_DMTF_EKU_RESPONDER_AUTH);
+    let has_requester_auth = extended_key_usage.contains(OID_DMTF_EKU_REQUESTER_AUTH);
+
+    let is_other_role_only = if is_requester_cert {
+        has_responder_auth && !has_requester_auth
+    } else {
+        has_requester_auth && !has_responder_auth
+    };
+    if is_other_role_only {
+        Err(SPDM_STATUS_VERIF_FAIL)
+    } else {
+        Ok(())
+    }
+}
+
 // IN DER encoded certificate slice
 // OUT Ok cert size
 // OUT Error Mulformed certificate found
@@ -69,7 +1273,8 @@ fn check_cert_format(cert: &[u8], base_asym_algo: SpdmBaseAsymAlgo) -> SpdmResul
     let (body_size, bytes_consumed) = check_length(&cert[c_walker..])?;
     c_walker += bytes_consumed;
 
-    if len == c_walker + body_size {
+    let cert_size = get_tlv_size(bytes_consumed, body_size)?;
+    if len == cert_size {
         c_walker += check_tbs_certificate(&cert[c_walker..], base_asym_algo, true)?;
         c_walker += check_signature_algorithm(&cert[c_walker..], base_asym_algo, true)?;
     } else {
@@ -79,7 +1284,7 @@ fn check_cert_format(cert: &[u8], base_asym_algo: SpdmBaseAsymAlgo) -> SpdmResul
 
     c_walker += check_signature_value(&cert[c_walker..], base_asym_algo)?;
 
-    if c_walker == 1 + bytes_consumed + body_size {
+    if c_walker == cert_size {
         Ok(c_walker)
     } else {
         Err(SPDM_STATUS_VERIF_FAIL)
@@ -91,61 +1296,14 @@ fn check_tbs_certificate(
     base_asym_algo: SpdmBaseAsymAlgo,
     is_leaf_cert: bool,
 ) -> SpdmResult<usize> {
-    let mut t_walker = 0usize;
-    let len = data.len();
-
-    check_tag_is_sequence(data)?;
-    t_walker += 1;
-
-    let (tbs_length, bytes_consumed) = check_length(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    let length_before_tbs = t_walker;
-
-    if len < t_walker + tbs_length {
-        return Err(SPDM_STATUS_VERIF_FAIL);
-    }
-
-    // version         [0]  EXPLICIT Version DEFAULT v1,
-    let bytes_consumed = check_version(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    // serialNumber         CertificateSerialNumber,
-    let bytes_consumed = check_and_skip_common_tag(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    // signature            AlgorithmIdentifier,
-    check_tag_is_sequence(&data[t_walker..])?;
-    t_walker += 1;
-    let (signature_id_length, bytes_consumed) = check_length(&data[t_walker..])?;
-    t_walker += bytes_consumed;
+    let (tbs_certificate, bytes_consumed) = X509TbsCertificate::parse(data)?;
 
     if is_leaf_cert {
-        check_object_identifier(&data[t_walker..], get_oid_by_base_asym_algo(base_asym_algo))?;
-    } else {
-        check_object_identifier(&data[t_walker..], None)?;
-    }
-    t_walker += signature_id_length;
-    // issuer               Name,
-    let bytes_consumed = check_name(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    // validity             Validity,
-    let bytes_consumed = check_validity(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    // subject              Name,
-    let bytes_consumed = check_name(&data[t_walker..])?;
-    t_walker += bytes_consumed;
-
-    // subjectPublicKeyInfo SubjectPublicKeyInfo,
-    check_public_key_info(&data[t_walker..])?;
-
-    // issuerUniqueID  [1]  IMPLICIT UniqueIdentifier OPTIONAL,
-    // subjectUniqueID [2]  IMPLICIT UniqueIdentifier OPTIONAL,
-    // extensions      [3]  EXPLICIT Extensions OPTIONAL
+        check_algorithm_identifier(&tbs_certificate.signature, base_asym_algo)?;
+        check_public_key_algorithm(&tbs_certificate.subject_public_key_info, base_asym_algo)?;
+    }
 
-    Ok(length_before_tbs + tbs_length)
+    Ok(bytes_consumed)
 }
 
 fn check_signature_algorithm(
@@ -153,24 +1311,31 @@ fn check_signature_algorithm(
     base_asym_algo: SpdmBaseAsymAlgo,
     is_leaf_cert: bool,
 ) -> SpdmResult<usize> {
-    let mut s_walker = 0usize;
-    // signature            AlgorithmIdentifier,
-    check_tag_is_sequence(&data[s_walker..])?;
-    s_walker += 1;
-    let (signature_id_length, bytes_consumed) = check_length(&data[s_walker..])?;
-    s_walker += bytes_consumed;
+    let (signature_algorithm, bytes_consumed) = X509AlgorithmIdentifier::parse(data)?;
 
     if is_leaf_cert {
-        check_object_identifier(&data[s_walker..], get_oid_by_base_asym_algo(base_asym_algo))?;
-    } else {
-        check_object_identifier(&data[s_walker..], None)?;
+        check_algorithm_identifier(&signature_algorithm, base_asym_algo)?;
     }
 
-    Ok(s_walker + signature_id_length)
+    Ok(bytes_consumed)
+}
+
+// the signature algorithm must match the negotiated algorithm, if it is known.
+fn check_algorithm_identifier(
+    algorithm_identifier: &X509AlgorithmIdentifier,
+    base_asym_algo: SpdmBaseAsymAlgo,
+) -> SpdmResult {
+    match get_oid_by_base_asym_algo(base_asym_algo) {
+        Some(oid) if !object_identifiers_are_same(algorithm_identifier.algorithm, oid) => {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+        _ => Ok(()),
+    }
 }
 
 fn check_signature_value(data: &[u8], _base_asym_algo: SpdmBaseAsymAlgo) -> SpdmResult<usize> {
-    check_and_skip_common_tag(data)
+    let (_, bytes_consumed) = check_tag_and_get_value(data, ASN1_TAG_NUMBER_BIT_STRING)?;
+    Ok(bytes_consumed)
 }
 
 fn check_tag_is_sequence(data: &[u8]) -> SpdmResult {
@@ -193,19 +1358,32 @@ fn check_length(data: &[u8]) -> SpdmResult<(usize, usize)> {
     } else if data[0] & ASN1_LENGTH_MULTI_OCTET_MASK == 0 {
         Ok((data[0] as usize, 1))
     } else {
-        let length_count = data[0] - ASN1_LENGTH_MULTI_OCTET_MASK;
-        if len < (length_count as usize + 1) || length_count == 0 {
+        let length_count = (data[0] - ASN1_LENGTH_MULTI_OCTET_MASK) as usize;
+        // a length that does not fit in usize cannot describe any slice.
+        if len < (length_count + 1)
+            || length_count == 0
+            || length_count > core::mem::size_of::<usize>()
+        {
             Err(SPDM_STATUS_VERIF_FAIL)
         } else {
-            let mut length = [0u8; 8];
-            for (i, b) in data[1..length_count as usize + 1].iter().rev().enumerate() {
-                length[i] = *b;
-            }
-            Ok((usize::from_le_bytes(length), length_count as usize + 1))
+            let length = data[1..length_count + 1]
+                .iter()
+                .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+            Ok((length, length_count + 1))
         }
     }
 }
 
+// IN the tag size, the length octets size and the content length
+// OUT Ok total size of the TLV
+// OUT Error the size overflows
+fn get_tlv_size(bytes_consumed: usize, length: usize) -> SpdmResult<usize> {
+    1usize
+        .checked_add(bytes_consumed)
+        .and_then(|size| size.checked_add(length))
+        .ok_or(SPDM_STATUS_VERIF_FAIL)
+}
+
 fn check_version(data: &[u8]) -> SpdmResult<usize> {
     let len = data.len();
     if len < 5
@@ -245,16 +1423,125 @@ fn check_object_identifier(data: &[u8], oid: Option<&'static [u8]>) -> SpdmResul
     }
 }
 
-fn check_name(data: &[u8]) -> SpdmResult<usize> {
-    check_and_skip_common_sequence(data)
+// the leaf public key must be usable with the negotiated algorithm:
+// ECDSA needs an id-ecPublicKey on the matched curve,
+// RSA needs an rsaEncryption (or id-RSASSA-PSS for PSS) key with the matched modulus size.
+fn check_public_key_algorithm(
+    public_key_info: &X509SubjectPublicKeyInfo,
+    base_asym_algo: SpdmBaseAsymAlgo,
+) -> SpdmResult {
+    let algorithm_oid = public_key_info.algorithm.algorithm;
+
+    match base_asym_algo {
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256
+        | SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384 => {
+            let curve_oid = if base_asym_algo == SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256 {
+                OID_EC_NIST_P256
+            } else {
+                OID_EC_NIST_P384
+            };
+            if !object_identifiers_are_same(algorithm_oid, OID_EC_PUBLIC_KEY) {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+            let parameters = public_key_info.algorithm.parameters.unwrap_or(&[]);
+            if check_object_identifier(parameters, Some(curve_oid))? != parameters.len() {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+            Ok(())
+        }
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048
+        | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
+        | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096
+        | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048
+        | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072
+        | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096 => {
+            let is_pss = matches!(
+                base_asym_algo,
+                SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048
+                    | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072
+                    | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096
+            );
+            if !object_identifiers_are_same(algorithm_oid, OID_RSA_ENCRYPTION)
+                && !(is_pss && object_identifiers_are_same(algorithm_oid, OID_RSASSA_PSS))
+            {
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+
+            // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+            let (rsa_public_key, _) =
+                check_tag_and_get_value(public_key_info.subject_public_key, ASN1_TAG_SEQUENCE)?;
+            let (modulus, _) = check_tag_and_get_value(rsa_public_key, ASN1_TAG_NUMBER_INTEGER)?;
+            let modulus = match modulus.split_first() {
+                Some((0, modulus)) => modulus,
+                _ => modulus,
+            };
+            if modulus.len() == base_asym_algo.get_size() as usize {
+                Ok(())
+            } else {
+                Err(SPDM_STATUS_VERIF_FAIL)
+            }
+        }
+        _ => Err(SPDM_STATUS_VERIF_FAIL),
+    }
+}
+
+// IN bytes slice
+// OUT Ok (value, bytes consumed)
+// OUT Error the tag is not expected or mulformed certificate found
+fn check_tag_and_get_value(data: &[u8], tag: u8) -> SpdmResult<(&[u8], usize)> {
+    if data.first() != Some(&tag) {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+    let (length, bytes_consumed) = check_length(&data[1..])?;
+    let tlv_size = get_tlv_size(bytes_consumed, length)?;
+    let value = data
+        .get(1 + bytes_consumed..tlv_size)
+        .ok_or(SPDM_STATUS_VERIF_FAIL)?;
+    Ok((value, tlv_size))
+}
+
+// IN bytes slice
+// OUT Ok (tag, value, bytes consumed)
+fn get_tag_and_value(data: &[u8]) -> SpdmResult<(u8, &[u8], usize)> {
+    let tag = *data.first().ok_or(SPDM_STATUS_VERIF_FAIL)?;
+    let (value, bytes_consumed) = check_tag_and_get_value(data, tag)?;
+    Ok((tag, value, bytes_consumed))
+}
+
+// DER encodes TRUE as 0xFF
+fn get_boolean(value: &[u8]) -> SpdmResult<bool> {
+    match value {
+        [0x00] => Ok(false),
+        [0xFF] => Ok(true),
+        _ => Err(SPDM_STATUS_VERIF_FAIL),
+    }
 }
 
-fn check_validity(data: &[u8]) -> SpdmResult<usize> {
-    check_and_skip_common_sequence(data)
+// IN ASCII decimal digits
+fn get_decimal_digits(digits: &[u8]) -> SpdmResult<u64> {
+    digits.iter().try_fold(0u64, |value, digit| {
+        if digit.is_ascii_digit() {
+            Ok(value * 10 + (digit - b'0') as u64)
+        } else {
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+    })
 }
 
-fn check_public_key_info(data: &[u8]) -> SpdmResult<usize> {
-    check_and_skip_common_sequence(data)
+// IN the content of a non-negative INTEGER
+fn get_unsigned_integer(value: &[u8]) -> SpdmResult<u32> {
+    let value = match value {
+        [] => return Err(SPDM_STATUS_VERIF_FAIL),
+        [first, ..] if first & 0x80 != 0 => return Err(SPDM_STATUS_VERIF_FAIL),
+        [0x00, rest @ ..] if !rest.is_empty() => rest,
+        _ => value,
+    };
+    if value.len() > core::mem::size_of::<u32>() {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+    Ok(value
+        .iter()
+        .fold(0u32, |integer, byte| (integer << 8) | *byte as u32))
 }
 
 fn check_and_skip_common_sequence(data: &[u8]) -> SpdmResult<usize> {
@@ -263,10 +1550,11 @@ fn check_and_skip_common_sequence(data: &[u8]) -> SpdmResult<usize> {
         Err(SPDM_STATUS_VERIF_FAIL)
     } else {
         let (payload_length, bytes_consumed) = check_length(&data[1..])?;
-        if len < 1 + bytes_consumed + payload_length {
+        let tlv_size = get_tlv_size(bytes_consumed, payload_length)?;
+        if len < tlv_size {
             Err(SPDM_STATUS_VERIF_FAIL)
         } else {
-            Ok(1 + bytes_consumed + payload_length)
+            Ok(tlv_size)
         }
     }
 }
@@ -277,10 +1565,11 @@ fn check_and_skip_common_tag(data: &[u8]) -> SpdmResult<usize> {
         Err(SPDM_STATUS_VERIF_FAIL)
     } else {
         let (payload_length, bytes_consumed) = check_length(&data[1..])?;
-        if len < 1 + bytes_consumed + payload_length {
+        let tlv_size = get_tlv_size(bytes_consumed, payload_length)?;
+        if len < tlv_size {
             Err(SPDM_STATUS_VERIF_FAIL)
         } else {
-            Ok(1 + bytes_consumed + payload_length)
+            Ok(tlv_size)
         }
     }
 }
@@ -467,6 +1756,40 @@ mod tests {
         assert_eq!(check_length(&l3_wrong), Err(SPDM_STATUS_VERIF_FAIL));
     }
 
+    #[test]
+    fn test_case1_check_length() {
+        // more length octets than usize can hold
+        let mut l9 = [0xFFu8; 10];
+        l9[0] = 0x89;
+        assert_eq!(check_length(&l9), Err(SPDM_STATUS_VERIF_FAIL));
+        for length_count in 0x80..=0xFFu8 {
+            let mut data = [0xFFu8; 0x80];
+            data[0] = length_count;
+            let _ = check_length(&data);
+            let mut cert = [ASN1_TAG_SEQUENCE; 0x81];
+            cert[1] = length_count;
+            let _ = check_tag_and_get_value(&cert, ASN1_TAG_SEQUENCE);
+            let _ = check_and_skip_common_sequence(&cert);
+            let _ = check_and_skip_common_tag(&cert);
+            let _ = check_cert_format(&cert, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384);
+        }
+
+        // a length that overflows with the tag and the length octets
+        let length_count = core::mem::size_of::<usize>();
+        let mut tlv = [0xFFu8; 10];
+        tlv[0] = ASN1_TAG_SEQUENCE;
+        tlv[1] = ASN1_LENGTH_MULTI_OCTET_MASK | length_count as u8;
+        assert_eq!(check_length(&tlv[1..]), Ok((usize::MAX, length_count + 1)));
+        assert_eq!(
+            check_tag_and_get_value(&tlv, ASN1_TAG_SEQUENCE),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_and_skip_common_sequence(&tlv),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
     #[test]
     fn test_case0_check_tag_is_sequence() {
         let l1 = [0x30];
@@ -564,7 +1887,7 @@ mod tests {
                 SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
                 false
             ),
-            Ok(357)
+            Ok(381)
         );
         assert_eq!(
             check_tbs_certificate(
@@ -572,7 +1895,7 @@ mod tests {
                 SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
                 false
             ),
-            Ok(460)
+            Ok(484)
         );
         assert_eq!(
             check_tbs_certificate(
@@ -580,7 +1903,7 @@ mod tests {
                 SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
                 false
             ),
-            Ok(460)
+            Ok(484)
         );
         assert_eq!(
             check_tbs_certificate(
@@ -588,7 +1911,7 @@ mod tests {
                 SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
                 true
             ),
-            Ok(460)
+            Ok(484)
         );
         assert_eq!(
             check_tbs_certificate(
@@ -632,11 +1955,11 @@ mod tests {
         );
         assert_eq!(
             check_cert_format(&c2, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384),
-            Ok(478)
+            Ok(503)
         );
         assert_eq!(
             check_cert_format(&c3, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384),
-            Ok(582)
+            Ok(607)
         );
         assert_eq!(
             check_cert_format(&c3, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256),
@@ -692,4 +2015,535 @@ mod tests {
             Err(SPDM_STATUS_VERIF_FAIL)
         );
     }
+
+    #[test]
+    fn test_case0_check_name() {
+        // CN=test
+        let n1 = [
+            0x30, 0x0F, 0x31, 0x0D, 0x30, 0x0B, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0C, 0x04, 0x74,
+            0x65, 0x73, 0x74,
+        ];
+        let n2 = [0x30, 0x00];
+        let n1_wrong = [0x30, 0x02, 0x31, 0x00];
+        let n2_wrong = [
+            0x30, 0x0F, 0x30, 0x0D, 0x30, 0x0B, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0C, 0x04, 0x74,
+            0x65, 0x73, 0x74,
+        ];
+        let n3_wrong = [
+            0x30, 0x0F, 0x31, 0x0D, 0x30, 0x0B, 0x0C, 0x03, 0x55, 0x04, 0x03, 0x0C, 0x04, 0x74,
+            0x65, 0x73, 0x74,
+        ];
+        assert_eq!(X509Name::parse(&n1).map(|(_, n)| n), Ok(17));
+        assert_eq!(X509Name::parse(&n2).map(|(_, n)| n), Ok(2));
+        assert_eq!(
+            X509Name::parse(&n1_wrong).map(|(_, n)| n),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            X509Name::parse(&n2_wrong).map(|(_, n)| n),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            X509Name::parse(&n3_wrong).map(|(_, n)| n),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_check_validity() {
+        let mut v1 = [0u8; 32];
+        v1[..4].copy_from_slice(&[0x30, 0x1E, 0x17, 0x0D]);
+        v1[4..17].copy_from_slice(b"230101000000Z");
+        v1[17..19].copy_from_slice(&[0x17, 0x0D]);
+        v1[19..].copy_from_slice(b"330101000000Z");
+        let mut v2 = [0u8; 34];
+        v2[..4].copy_from_slice(&[0x30, 0x20, 0x17, 0x0D]);
+        v2[4..17].copy_from_slice(b"230101000000Z");
+        v2[17..19].copy_from_slice(&[0x18, 0x0F]);
+        v2[19..].copy_from_slice(b"20530101000000Z");
+        let mut v1_wrong = v1;
+        v1_wrong[17] = 0x04;
+        assert_eq!(X509Validity::parse(&v1).map(|(_, n)| n), Ok(32));
+        assert_eq!(X509Validity::parse(&v2).map(|(_, n)| n), Ok(34));
+        assert_eq!(
+            X509Validity::parse(&v1_wrong).map(|(_, n)| n),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            X509Validity::parse(&v1[..20]).map(|(_, n)| n),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    fn check_public_key_info(
+        data: &[u8],
+        base_asym_algo: SpdmBaseAsymAlgo,
+        is_leaf_cert: bool,
+    ) -> SpdmResult<usize> {
+        let (public_key_info, bytes_consumed) = X509SubjectPublicKeyInfo::parse(data)?;
+        if is_leaf_cert {
+            check_public_key_algorithm(&public_key_info, base_asym_algo)?;
+        }
+        Ok(bytes_consumed)
+    }
+
+    #[test]
+    fn test_case0_check_public_key_info() {
+        let ec = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let rsa = std::fs::read("../test_key/rsa2048/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let ec_spki = &ec[157..];
+        let rsa_spki = &rsa[154..];
+
+        assert_eq!(
+            check_public_key_info(ec_spki, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384, true),
+            Ok(120)
+        );
+        assert_eq!(
+            check_public_key_info(
+                ec_spki,
+                SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+                false
+            ),
+            Ok(120)
+        );
+        assert_eq!(
+            check_public_key_info(ec_spki, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_public_key_info(ec_spki, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_public_key_info(rsa_spki, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048, true),
+            Ok(294)
+        );
+        assert_eq!(
+            check_public_key_info(rsa_spki, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048, true),
+            Ok(294)
+        );
+        assert_eq!(
+            check_public_key_info(rsa_spki, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_public_key_info(
+                rsa_spki,
+                SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+                true
+            ),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_public_key_info(&ec_spki[..100], SpdmBaseAsymAlgo::empty(), false),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_check_basic_constraints_and_key_usage() {
+        assert_eq!(check_basic_constraints(&[0x30, 0x00]), Ok(()));
+        assert_eq!(
+            check_basic_constraints(&[0x30, 0x03, 0x01, 0x01, 0x00]),
+            Ok(())
+        );
+        assert_eq!(
+            check_basic_constraints(&[0x30, 0x03, 0x01, 0x01, 0xFF]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_basic_constraints(&[0x30, 0x03, 0x01, 0x01]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+
+        assert_eq!(check_key_usage(&[0x03, 0x02, 0x05, 0xE0]), Ok(()));
+        assert_eq!(check_key_usage(&[0x03, 0x02, 0x07, 0x80]), Ok(()));
+        assert_eq!(
+            check_key_usage(&[0x03, 0x02, 0x01, 0x06]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_key_usage(&[0x03, 0x01, 0x00]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_check_extended_key_usage() {
+        let responder_auth = [
+            0x30, 0x0C, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x03,
+        ];
+        let requester_auth = [
+            0x30, 0x0C, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x04,
+        ];
+        let both_auth = [
+            0x30, 0x18, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x03,
+            0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x04,
+        ];
+        let server_auth = [
+            0x30, 0x0A, 0x06, 0x08, 0x2B, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01,
+        ];
+        let eku_wrong = [0x30, 0x00];
+
+        assert_eq!(check_extended_key_usage(&responder_auth, false), Ok(()));
+        assert_eq!(
+            check_extended_key_usage(&responder_auth, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(check_extended_key_usage(&requester_auth, true), Ok(()));
+        assert_eq!(
+            check_extended_key_usage(&requester_auth, false),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(check_extended_key_usage(&both_auth, false), Ok(()));
+        assert_eq!(check_extended_key_usage(&both_auth, true), Ok(()));
+        assert_eq!(check_extended_key_usage(&server_auth, false), Ok(()));
+        assert_eq!(check_extended_key_usage(&server_auth, true), Ok(()));
+        assert_eq!(
+            check_extended_key_usage(&eku_wrong, false),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_parse_spdm_extension() {
+        // the bare OBJECT IDENTIFIER form
+        let bare = [
+            0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82, 0x12, 0x02,
+        ];
+        // SPDMExtension with id-DMTF-hardware-identity and no value
+        let sequence = [
+            0x30, 0x0E, 0x30, 0x0C, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82,
+            0x12, 0x02,
+        ];
+        // SPDMExtension with another id, whose value has the hardware identity OID bytes
+        let in_value = [
+            0x30, 0x1C, 0x30, 0x1A, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82,
+            0x12, 0x05, 0x04, 0x0C, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1C, 0x82,
+            0x12, 0x02,
+        ];
+
+        let spdm_extension = X509SpdmExtension::from_extn_value(&bare).unwrap();
+        assert!(spdm_extension.contains(OID_DMTF_HARDWARE_IDENTITY));
+        assert_eq!(spdm_extension.ids().count(), 1);
+
+        let spdm_extension = X509SpdmExtension::from_extn_value(&sequence).unwrap();
+        assert!(spdm_extension.contains(OID_DMTF_HARDWARE_IDENTITY));
+
+        let spdm_extension = X509SpdmExtension::from_extn_value(&in_value).unwrap();
+        assert!(!spdm_extension.contains(OID_DMTF_HARDWARE_IDENTITY));
+        assert_eq!(spdm_extension.ids().count(), 1);
+
+        assert_eq!(
+            X509SpdmExtension::from_extn_value(&sequence[..15]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            X509SpdmExtension::from_extn_value(&[0x30, 0x00]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        // a bare OBJECT IDENTIFIER outside of id-DMTF-spdm
+        assert_eq!(
+            X509SpdmExtension::from_extn_value(&[0x06, 0x03, 0x55, 0x1D, 0x0F]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_check_leaf_certificate() {
+        let c1 = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let c2 = std::fs::read("../test_key/rsa3072/end_requester.cert.der")
+            .expect("unable to read leaf cert!");
+        let c3 =
+            std::fs::read("../test_key/ecp384/inter.cert.der").expect("unable to read inter cert!");
+
+        assert_eq!(check_leaf_certificate(&c1, false, true), Ok(()));
+        assert_eq!(check_leaf_certificate(&c1, true, true), Ok(()));
+        assert_eq!(check_leaf_certificate(&c2, true, true), Ok(()));
+        // the leaf has the hardware identity
+        assert_eq!(
+            check_leaf_certificate(&c1, false, false),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        // the hardware identity is optional
+        let spdm_extension = c1
+            .windows(OID_DMTF_SPDM_EXTENSION.len())
+            .position(|w| w == OID_DMTF_SPDM_EXTENSION)
+            .unwrap();
+        let mut c1_no_spdm_extension = c1.clone();
+        c1_no_spdm_extension[spdm_extension + OID_DMTF_SPDM_EXTENSION.len() - 1] = 0x07;
+        assert_eq!(
+            check_leaf_certificate(&c1_no_spdm_extension, false, true),
+            Ok(())
+        );
+        assert_eq!(
+            check_leaf_certificate(&c1_no_spdm_extension, false, false),
+            Ok(())
+        );
+        // a CA cert is not a leaf
+        assert_eq!(
+            check_leaf_certificate(&c3, false, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            check_leaf_certificate(&c1[..300], false, true),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_parse_certificate() {
+        let c1 = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let c2 =
+            std::fs::read("../test_key/ecp384/inter.cert.der").expect("unable to read inter cert!");
+
+        let certificate = X509Certificate::from_der(&c1).unwrap();
+        assert_eq!(certificate.as_der(), &c1[..]);
+        assert_eq!(certificate.tbs_certificate.as_der().len(), 484);
+        assert!(object_identifiers_are_same(
+            certificate.signature_algorithm.algorithm,
+            OID_ECDSA_SHA384
+        ));
+        assert_eq!(
+            certificate.tbs_certificate.subject.common_name(),
+            Some(&b"DMTF libspdm ECP256 responder cert"[..])
+        );
+        assert_eq!(
+            certificate.tbs_certificate.issuer.common_name(),
+            Some(&b"DMTF libspdm ECP256 intermediate cert"[..])
+        );
+        assert!(matches!(
+            certificate.tbs_certificate.validity.not_before,
+            X509Time::UtcTime(_)
+        ));
+
+        let public_key_info = certificate.tbs_certificate.subject_public_key_info;
+        assert_eq!(public_key_info.as_der(), &c1[157..157 + 120]);
+        assert!(object_identifiers_are_same(
+            public_key_info.algorithm.algorithm,
+            OID_EC_PUBLIC_KEY
+        ));
+        // uncompressed point
+        assert_eq!(public_key_info.subject_public_key.len(), 97);
+        assert_eq!(public_key_info.subject_public_key[0], 0x04);
+
+        let extensions = certificate.tbs_certificate.extensions.unwrap();
+        assert_eq!(
+            extensions.basic_constraints(),
+            Ok(Some(X509BasicConstraints {
+                ca: false,
+                path_len_constraint: None
+            }))
+        );
+        assert_eq!(
+            extensions.key_usage(),
+            Ok(Some(
+                X509KeyUsage::DIGITAL_SIGNATURE
+                    | X509KeyUsage::NON_REPUDIATION
+                    | X509KeyUsage::KEY_ENCIPHERMENT
+            ))
+        );
+        let extended_key_usage = extensions.extended_key_usage().unwrap().unwrap();
+        assert_eq!(extended_key_usage.key_purposes().count(), 5);
+        assert!(extended_key_usage.contains(OID_DMTF_EKU_RESPONDER_AUTH));
+        assert!(extended_key_usage.contains(OID_DMTF_EKU_REQUESTER_AUTH));
+        assert!(extensions.get(OID_DMTF_SPDM_EXTENSION).is_some());
+
+        let certificate = X509Certificate::from_der(&c2).unwrap();
+        let extensions = certificate.tbs_certificate.extensions.unwrap();
+        assert!(extensions.basic_constraints().unwrap().unwrap().ca);
+        assert!(extensions
+            .key_usage()
+            .unwrap()
+            .unwrap()
+            .contains(X509KeyUsage::KEY_CERT_SIGN | X509KeyUsage::CRL_SIGN));
+        assert_eq!(extensions.get(OID_DMTF_SPDM_EXTENSION), None);
+
+        assert!(X509Certificate::from_der(&c1[..300]).is_err());
+        let mut c1_trailing = c1.clone();
+        c1_trailing.push(0);
+        assert!(X509Certificate::from_der(&c1_trailing).is_err());
+    }
+
+    #[test]
+    fn test_case0_x509_time() {
+        assert_eq!(
+            X509Time::UtcTime(b"230403120000Z").to_unix_time(),
+            Ok(1680523200)
+        );
+        assert_eq!(
+            X509Time::GeneralizedTime(b"20230403120000Z").to_unix_time(),
+            Ok(1680523200)
+        );
+        assert_eq!(
+            X509Time::GeneralizedTime(b"20500101000000Z").to_unix_time(),
+            Ok(2524608000)
+        );
+        // 29 February of a leap year
+        assert_eq!(
+            X509Time::UtcTime(b"240229000000Z").to_unix_time(),
+            Ok(1709164800)
+        );
+
+        let c1 = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let validity = X509Certificate::from_der(&c1)
+            .unwrap()
+            .tbs_certificate
+            .validity;
+        assert_eq!(validity.not_before.to_unix_time(), Ok(1680501354));
+
+        // 1950 is before the Unix epoch
+        assert!(X509Time::UtcTime(b"500101000000Z").to_unix_time().is_err());
+        // a local time or one without seconds is not allowed by RFC 5280
+        assert!(X509Time::UtcTime(b"230403120000+0100")
+            .to_unix_time()
+            .is_err());
+        assert!(X509Time::UtcTime(b"2304031200Z").to_unix_time().is_err());
+        assert!(X509Time::UtcTime(b"231303120000Z").to_unix_time().is_err());
+        assert!(X509Time::GeneralizedTime(b"2023040312000aZ")
+            .to_unix_time()
+            .is_err());
+    }
+
+    #[test]
+    fn test_case0_parse_crl() {
+        let crl = std::fs::read("../test_key/ecp384/inter.crl.der").expect("unable to read crl!");
+        let inter =
+            std::fs::read("../test_key/ecp384/inter.cert.der").expect("unable to read inter cert!");
+
+        let certificate_list = X509CertificateList::from_der(&crl).unwrap();
+        assert_eq!(certificate_list.as_der(), &crl[..]);
+        assert!(object_identifiers_are_same(
+            certificate_list.signature_algorithm.algorithm,
+            OID_ECDSA_SHA384
+        ));
+
+        let tbs_cert_list = certificate_list.tbs_cert_list;
+        let inter = X509Certificate::from_der(&inter).unwrap();
+        assert_eq!(
+            tbs_cert_list.issuer.as_der(),
+            inter.tbs_certificate.subject.as_der()
+        );
+        assert!(tbs_cert_list.next_update.is_some());
+        assert!(tbs_cert_list.crl_extensions.is_some());
+        assert_eq!(tbs_cert_list.revoked_certificates().count(), 1);
+        assert!(tbs_cert_list.is_revoked(&[0x02]));
+        assert!(!tbs_cert_list.is_revoked(&[0x03]));
+
+        assert!(X509CertificateList::from_der(&crl[..crl.len() - 1]).is_err());
+        let mut crl_trailing = crl.clone();
+        crl_trailing.push(0);
+        assert!(X509CertificateList::from_der(&crl_trailing).is_err());
+        // a certificate is not a CRL
+        assert!(X509CertificateList::from_der(inter.as_der()).is_err());
+    }
+
+    #[test]
+    fn test_case0_basic_constraints_and_key_usage() {
+        assert_eq!(
+            X509BasicConstraints::from_extn_value(&[
+                0x30, 0x06, 0x01, 0x01, 0xFF, 0x02, 0x01, 0x03
+            ]),
+            Ok(X509BasicConstraints {
+                ca: true,
+                path_len_constraint: Some(3)
+            })
+        );
+        assert_eq!(
+            X509BasicConstraints::from_extn_value(&[0x30, 0x03, 0x02, 0x01, 0x80]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            X509BasicConstraints::from_extn_value(&[0x30, 0x03, 0x01, 0x01, 0x01]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+
+        assert_eq!(
+            X509KeyUsage::from_extn_value(&[0x03, 0x02, 0x01, 0x06]),
+            Ok(X509KeyUsage::KEY_CERT_SIGN | X509KeyUsage::CRL_SIGN)
+        );
+        assert_eq!(
+            X509KeyUsage::from_extn_value(&[0x03, 0x03, 0x07, 0x80, 0x80]),
+            Ok(X509KeyUsage::DIGITAL_SIGNATURE | X509KeyUsage::DECIPHER_ONLY)
+        );
+        assert_eq!(
+            X509KeyUsage::from_extn_value(&[0x03, 0x00]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_parse_long_cert_chain() {
+        for (name, cert_count) in [
+            ("Shorter1024B", 2),
+            ("ShorterMAXINT16", 24),
+            ("ShorterMAXUINT16", 49),
+        ] {
+            for role in ["responder", "requester"] {
+                let cert_chain = std::fs::read(format!(
+                    "../test_key/long_chains/{}_bundle_{}.certchain.der",
+                    name, role
+                ))
+                .expect("unable to read cert chain!");
+
+                let certificates: Vec<_> = X509CertChain::new(&cert_chain)
+                    .collect::<SpdmResult<_>>()
+                    .unwrap();
+                assert_eq!(certificates.len(), cert_count);
+
+                let leaf = get_leaf_certificate(&cert_chain).unwrap();
+                assert_eq!(leaf, certificates[cert_count - 1]);
+                assert_eq!(
+                    get_leaf_public_key_info(&cert_chain),
+                    Ok(leaf.tbs_certificate.subject_public_key_info)
+                );
+                let end_cert = std::fs::read(format!(
+                    "../test_key/long_chains/{}_end_{}.cert.der",
+                    name, role
+                ))
+                .expect("unable to read leaf cert!");
+                assert_eq!(leaf.as_der(), &end_cert[..]);
+                assert_eq!(
+                    get_leaf_subject(&cert_chain),
+                    Ok(leaf.tbs_certificate.subject)
+                );
+
+                // every certificate is issued by the previous one
+                for pair in certificates.windows(2) {
+                    assert_eq!(
+                        pair[1].tbs_certificate.issuer.as_der(),
+                        pair[0].tbs_certificate.subject.as_der()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_case0_get_leaf_public_key_info() {
+        let cert_chain = std::fs::read("../test_key/rsa2048/bundle_responder.certchain.der")
+            .expect("unable to read cert chain!");
+        let leaf = std::fs::read("../test_key/rsa2048/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+
+        let public_key_info = get_leaf_public_key_info(&cert_chain).unwrap();
+        assert_eq!(public_key_info.as_der(), &leaf[154..154 + 294]);
+        assert!(object_identifiers_are_same(
+            public_key_info.algorithm.algorithm,
+            OID_RSA_ENCRYPTION
+        ));
+        assert_eq!(
+            public_key_info.algorithm.parameters,
+            Some(&[0x05u8, 0x00][..])
+        );
+        assert_eq!(public_key_info.subject_public_key[0], ASN1_TAG_SEQUENCE);
+
+        assert!(get_leaf_public_key_info(&[]).is_err());
+        assert!(get_leaf_public_key_info(&cert_chain[..cert_chain.len() - 1]).is_err());
+    }
 }
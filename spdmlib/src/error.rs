@@ -108,6 +108,9 @@ pub enum StatusCodeCrypto {
     VERIF_FAIL = 1,
     SEQUENCE_NUMBER_OVERFLOW = 2,
     VERIF_NO_AUTHORITY = 3,
+
+    // only in Rust-SPDM
+    CERT_REVOKED = 0xFE,
 }
 
 impl TryFrom<u16> for StatusCodeCrypto {
@@ -119,6 +122,7 @@ impl TryFrom<u16> for StatusCodeCrypto {
             1 => Ok(Self::VERIF_FAIL),
             2 => Ok(Self::SEQUENCE_NUMBER_OVERFLOW),
             3 => Ok(Self::VERIF_NO_AUTHORITY),
+            0xFE => Ok(Self::CERT_REVOKED),
             _ => Err(()),
         }
     }
@@ -547,6 +551,12 @@ pub const SPDM_STATUS_VERIF_NO_AUTHORITY: SpdmStatus = spdm_return_status!(
     StatusCode::CRYPTO(StatusCodeCrypto::VERIF_NO_AUTHORITY)
 );
 
+/*  Provided cert chain is valid but one of its certs is revoked by a CRL of its issuer. */
+pub const SPDM_STATUS_CERT_REVOKED: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CRYPTO(StatusCodeCrypto::CERT_REVOKED)
+);
+
 /* - Certificate Parsing Errors - */
 
 /*  Certificate is malformed or does not comply to x.509 standard. */
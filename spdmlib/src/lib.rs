@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 #![cfg_attr(not(feature = "std"), no_std)]
 #![feature(stmt_expr_attributes)]
 #![feature(try_trait_v2)]
@@ -20,6 +20,8 @@ pub mod protocol;
 pub mod error;
 pub mod common;
 pub mod crypto;
+#[cfg(feature = "loopback")]
+pub mod loopback;
 pub mod message;
 pub mod requester;
 pub mod responder;
@@ -0,0 +1,309 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+// an in-process connection between a RequesterContext and a ResponderContext.
+// the requester end steps the responder on every send, so a full flow runs in
+// one thread without sockets. faults can be injected into single messages.
+
+extern crate alloc;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::common::{SpdmDeviceIo, ST1};
+use crate::error::SpdmResult;
+use crate::responder::ResponderContext;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpdmLoopbackDirection {
+    RequesterToResponder = 0,
+    ResponderToRequester = 1,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpdmLoopbackFault {
+    // the message is lost.
+    Drop,
+    // xor the byte at offset, the offset wraps around the message.
+    Corrupt { offset: usize, mask: u8 },
+    // the message arrives twice.
+    Duplicate,
+    // the message arrives only after this many receives found nothing.
+    Delay(usize),
+}
+
+struct SpdmLoopbackMessage {
+    data: Vec<u8>,
+    delay: usize,
+}
+
+#[derive(Default)]
+struct SpdmLoopbackQueue {
+    messages: VecDeque<SpdmLoopbackMessage>,
+    sent: usize,
+}
+
+struct SpdmLoopbackFaultEntry {
+    direction: SpdmLoopbackDirection,
+    index: usize,
+    fault: SpdmLoopbackFault,
+}
+
+// the wire between the two ends, it keeps message boundaries.
+#[derive(Default)]
+pub struct SpdmLoopback {
+    queues: [RefCell<SpdmLoopbackQueue>; 2],
+    faults: RefCell<Vec<SpdmLoopbackFaultEntry>>,
+}
+
+impl SpdmLoopback {
+    pub fn new() -> Self {
+        SpdmLoopback::default()
+    }
+
+    // apply fault to the message sent in direction after skip more messages,
+    // 0 is the next one. several faults may hit the same message.
+    pub fn inject_fault(
+        &self,
+        direction: SpdmLoopbackDirection,
+        skip: usize,
+        fault: SpdmLoopbackFault,
+    ) {
+        let index = self.queues[direction as usize].borrow().sent + skip;
+        self.faults.borrow_mut().push(SpdmLoopbackFaultEntry {
+            direction,
+            index,
+            fault,
+        });
+    }
+
+    pub fn clear_faults(&self) {
+        self.faults.borrow_mut().clear();
+    }
+
+    // messages sent in direction so far, including lost ones.
+    pub fn sent(&self, direction: SpdmLoopbackDirection) -> usize {
+        self.queues[direction as usize].borrow().sent
+    }
+
+    // messages in flight in direction, including delayed ones.
+    pub fn pending(&self, direction: SpdmLoopbackDirection) -> usize {
+        self.queues[direction as usize].borrow().messages.len()
+    }
+
+    pub fn send(&self, direction: SpdmLoopbackDirection, buffer: &[u8]) {
+        let mut queue = self.queues[direction as usize].borrow_mut();
+        let index = queue.sent;
+        queue.sent += 1;
+
+        let mut message = SpdmLoopbackMessage {
+            data: buffer.to_vec(),
+            delay: 0,
+        };
+        let mut drop = false;
+        let mut duplicate = false;
+        self.faults.borrow_mut().retain(|entry| {
+            if entry.direction != direction || entry.index != index {
+                return true;
+            }
+            info!(
+                "loopback {:?} message {} - {:?}\n",
+                direction, index, entry.fault
+            );
+            match entry.fault {
+                SpdmLoopbackFault::Drop => drop = true,
+                SpdmLoopbackFault::Corrupt { offset, mask } if !message.data.is_empty() => {
+                    let offset = offset % message.data.len();
+                    message.data[offset] ^= mask;
+                }
+                SpdmLoopbackFault::Corrupt { .. } => {}
+                SpdmLoopbackFault::Duplicate => duplicate = true,
+                SpdmLoopbackFault::Delay(polls) => message.delay += polls,
+            }
+            false
+        });
+
+        if drop {
+            return;
+        }
+        if duplicate {
+            queue.messages.push_back(SpdmLoopbackMessage {
+                data: message.data.clone(),
+                delay: message.delay,
+            });
+        }
+        queue.messages.push_back(message);
+    }
+
+    pub fn receive(
+        &self,
+        direction: SpdmLoopbackDirection,
+        buffer: &mut [u8],
+    ) -> Result<usize, usize> {
+        let mut queue = self.queues[direction as usize].borrow_mut();
+        let message = queue.messages.front_mut().ok_or(0usize)?;
+        if message.delay != 0 {
+            message.delay -= 1;
+            return Err(0);
+        }
+        let message = queue.messages.pop_front().ok_or(0usize)?;
+        let size = message.data.len();
+        if buffer.len() < size {
+            return Err(0);
+        }
+        buffer[..size].copy_from_slice(&message.data);
+        Ok(size)
+    }
+
+    fn flush(&self, direction: SpdmLoopbackDirection) {
+        self.queues[direction as usize]
+            .borrow_mut()
+            .messages
+            .clear();
+    }
+}
+
+// the device io of the ResponderContext.
+pub struct SpdmLoopbackResponderIo<'a> {
+    loopback: &'a SpdmLoopback,
+}
+
+impl<'a> SpdmLoopbackResponderIo<'a> {
+    pub fn new(loopback: &'a SpdmLoopback) -> Self {
+        SpdmLoopbackResponderIo { loopback }
+    }
+}
+
+impl SpdmDeviceIo for SpdmLoopbackResponderIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.loopback
+            .send(SpdmLoopbackDirection::ResponderToRequester, buffer);
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        self.loopback
+            .receive(SpdmLoopbackDirection::RequesterToResponder, buffer)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.loopback
+            .flush(SpdmLoopbackDirection::RequesterToResponder);
+        Ok(())
+    }
+}
+
+// the device io of the RequesterContext, it runs the responder once for every
+// message in flight towards it, a delayed message takes that many sends.
+pub struct SpdmLoopbackRequesterIo<'a, 'b> {
+    loopback: &'a SpdmLoopback,
+    responder: &'a mut ResponderContext<'b>,
+}
+
+impl<'a, 'b> SpdmLoopbackRequesterIo<'a, 'b> {
+    pub fn new(loopback: &'a SpdmLoopback, responder: &'a mut ResponderContext<'b>) -> Self {
+        SpdmLoopbackRequesterIo {
+            loopback,
+            responder,
+        }
+    }
+}
+
+impl SpdmDeviceIo for SpdmLoopbackRequesterIo<'_, '_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.loopback
+            .send(SpdmLoopbackDirection::RequesterToResponder, buffer);
+        for _ in 0..self
+            .loopback
+            .pending(SpdmLoopbackDirection::RequesterToResponder)
+        {
+            // a message the responder cannot process gets no response, the
+            // requester finds out on receive.
+            let _ = self.responder.process_message(ST1, &[0]);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        self.loopback
+            .receive(SpdmLoopbackDirection::ResponderToRequester, buffer)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.loopback
+            .flush(SpdmLoopbackDirection::ResponderToRequester);
+        Ok(())
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+
+    const TO_RESPONDER: SpdmLoopbackDirection = SpdmLoopbackDirection::RequesterToResponder;
+    const TO_REQUESTER: SpdmLoopbackDirection = SpdmLoopbackDirection::ResponderToRequester;
+
+    #[test]
+    fn test_case0_loopback() {
+        let loopback = SpdmLoopback::new();
+        let buffer = &mut [0u8; 8];
+
+        loopback.send(TO_RESPONDER, &[1, 2, 3]);
+        loopback.send(TO_RESPONDER, &[4]);
+        assert_eq!(loopback.pending(TO_RESPONDER), 2);
+        assert_eq!(loopback.receive(TO_REQUESTER, buffer), Err(0));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(3));
+        assert_eq!(&buffer[..3], &[1, 2, 3]);
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(1));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Err(0));
+        assert_eq!(loopback.sent(TO_RESPONDER), 2);
+
+        // a message larger than the buffer is lost.
+        loopback.send(TO_REQUESTER, &[0u8; 9]);
+        assert_eq!(loopback.receive(TO_REQUESTER, buffer), Err(0));
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+    }
+
+    #[test]
+    fn test_case0_loopback_fault() {
+        let loopback = SpdmLoopback::new();
+        let buffer = &mut [0u8; 8];
+
+        loopback.inject_fault(TO_RESPONDER, 0, SpdmLoopbackFault::Drop);
+        loopback.inject_fault(
+            TO_RESPONDER,
+            1,
+            SpdmLoopbackFault::Corrupt {
+                offset: 5,
+                mask: 0x80,
+            },
+        );
+        loopback.inject_fault(TO_RESPONDER, 2, SpdmLoopbackFault::Duplicate);
+        loopback.inject_fault(TO_RESPONDER, 2, SpdmLoopbackFault::Delay(2));
+        // the other direction does not count.
+        loopback.send(TO_REQUESTER, &[9]);
+
+        loopback.send(TO_RESPONDER, &[1]);
+        assert_eq!(loopback.pending(TO_RESPONDER), 0);
+
+        loopback.send(TO_RESPONDER, &[1, 2, 3]);
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(3));
+        assert_eq!(&buffer[..3], &[1, 2, 0x83]);
+
+        loopback.send(TO_RESPONDER, &[4, 5]);
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Err(0));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Err(0));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(2));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Err(0));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Err(0));
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(2));
+        assert_eq!(&buffer[..2], &[4, 5]);
+
+        loopback.inject_fault(TO_RESPONDER, 0, SpdmLoopbackFault::Drop);
+        loopback.clear_faults();
+        loopback.send(TO_RESPONDER, &[6]);
+        assert_eq!(loopback.receive(TO_RESPONDER, buffer), Ok(1));
+        assert_eq!(loopback.receive(TO_REQUESTER, buffer), Ok(1));
+    }
+}
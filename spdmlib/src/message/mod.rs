@@ -1301,6 +1301,7 @@ mod tests {
             }),
         };
         create_spdm_context!(context);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
         let spdm_message = new_spdm_message(value, context);
         assert_eq!(
@@ -71,7 +71,7 @@ impl SpdmTransportEncap for TransportEncap {
         &mut self,
         _spdm_buffer: &[u8],
         _app_buffer: &mut [u8],
-        _is_app_messagesage: bool,
+        _app_message_type: Option<u8>,
     ) -> crate::error::SpdmResult<usize> {
         unimplemented!()
     }
@@ -80,7 +80,11 @@ impl SpdmTransportEncap for TransportEncap {
         &mut self,
         _app_buffer: &[u8],
         _spdm_buffer: &mut [u8],
-    ) -> crate::error::SpdmResult<(usize, bool)> {
+    ) -> crate::error::SpdmResult<(usize, Option<u8>)> {
+        unimplemented!()
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
         unimplemented!()
     }
 
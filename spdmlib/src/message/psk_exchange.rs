@@ -112,7 +112,10 @@ impl SpdmCodec for SpdmPskExchangeRequestPayload {
             return None;
         }
         psk_context.data_size = u16::read(r)?;
-        if psk_context.data_size > MAX_SPDM_PSK_CONTEXT_SIZE as u16 {
+        // the requester context is at least H bytes.
+        if psk_context.data_size > MAX_SPDM_PSK_CONTEXT_SIZE as u16
+            || psk_context.data_size < context.get_hash_size()
+        {
             return None;
         }
         opaque.data_size = u16::read(r)?;
@@ -234,7 +237,10 @@ impl SpdmCodec for SpdmPskExchangeResponsePayload {
         {
             return None;
         }
-        if psk_context.data_size > MAX_SPDM_PSK_CONTEXT_SIZE as u16 {
+        // the responder context is at least H bytes if there is one.
+        if psk_context.data_size > MAX_SPDM_PSK_CONTEXT_SIZE as u16
+            || (!psk_without_context && psk_context.data_size < context.get_hash_size())
+        {
             return None;
         }
 
@@ -310,6 +316,7 @@ mod tests {
         };
 
         create_spdm_context!(context);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_512;
 
         assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
         let mut reader = Reader::init(u8_slice);
@@ -370,30 +377,49 @@ mod tests {
         };
 
         create_spdm_context!(context);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
+        // a requester context is mandatory.
         assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
         let mut reader = Reader::init(u8_slice);
         assert_eq!(10, reader.left());
+        assert!(SpdmPskExchangeRequestPayload::spdm_read(&mut context, &mut reader).is_none());
+    }
+    #[test]
+    fn test_case2_spdm_psk_exchange_request_payload() {
+        let u8_slice = &mut [0u8; 10 + MAX_SPDM_PSK_CONTEXT_SIZE];
+        let mut value = SpdmPskExchangeRequestPayload {
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            req_session_id: 100u16,
+            psk_hint: SpdmPskHintStruct::default(),
+            psk_context: SpdmPskContextStruct {
+                data_size: SHA384_DIGEST_SIZE as u16 - 1,
+                data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+            },
+            opaque: SpdmOpaqueStruct::default(),
+        };
+
+        create_spdm_context!(context);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+        // a requester context shorter than H is rejected.
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(writer.used_slice());
+        assert!(SpdmPskExchangeRequestPayload::spdm_read(&mut context, &mut reader).is_none());
+
+        // exactly H bytes is accepted.
+        value.psk_context.data_size = SHA384_DIGEST_SIZE as u16;
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(writer.used_slice());
         let psk_exchange_request =
             SpdmPskExchangeRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
-
         assert_eq!(
-            psk_exchange_request.measurement_summary_hash_type,
-            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone
+            psk_exchange_request.psk_context.data_size,
+            SHA384_DIGEST_SIZE as u16
         );
-        assert_eq!(psk_exchange_request.psk_hint.data_size, 0);
-        assert_eq!(psk_exchange_request.psk_context.data_size, 0);
-        assert_eq!(psk_exchange_request.opaque.data_size, 0);
-        for i in 0..MAX_SPDM_PSK_HINT_SIZE {
-            assert_eq!(psk_exchange_request.psk_hint.data[i], 0);
-        }
-        for i in 0..MAX_SPDM_PSK_CONTEXT_SIZE {
-            assert_eq!(psk_exchange_request.psk_context.data[i], 0);
-        }
-        for i in 0..MAX_SPDM_OPAQUE_SIZE {
-            assert_eq!(psk_exchange_request.opaque.data[i], 0);
-        }
-        assert_eq!(0, reader.left());
     }
     #[test]
     fn test_case0_spdm_psk_exchange_response_payload() {
@@ -562,4 +588,60 @@ mod tests {
         }
         assert_eq!(0, reader.left());
     }
+    #[test]
+    fn test_case2_spdm_psk_exchange_response_payload() {
+        let u8_slice = &mut [0u8; 10 + MAX_SPDM_PSK_CONTEXT_SIZE + SPDM_MAX_HASH_SIZE];
+        let mut value = SpdmPskExchangeResponsePayload {
+            heartbeat_period: 0xaau8,
+            rsp_session_id: 0xaa55u16,
+            measurement_summary_hash: SpdmDigestStruct::default(),
+            psk_context: SpdmPskContextStruct {
+                data_size: SHA384_DIGEST_SIZE as u16 - 1,
+                data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+            },
+            opaque: SpdmOpaqueStruct::default(),
+            verify_data: SpdmDigestStruct {
+                data_size: SHA384_DIGEST_SIZE as u16,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE]),
+            },
+        };
+
+        create_spdm_context!(context);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        context.negotiate_info.rsp_capabilities_sel =
+            SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+        context.runtime_info.need_measurement_summary_hash = false;
+
+        // a responder context shorter than H is rejected.
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(writer.used_slice());
+        assert!(SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).is_none());
+
+        // so is a missing one.
+        value.psk_context.data_size = 0;
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(writer.used_slice());
+        assert!(SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).is_none());
+
+        // exactly H bytes is accepted.
+        value.psk_context.data_size = SHA384_DIGEST_SIZE as u16;
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let used = writer.used();
+        let mut reader = Reader::init(&u8_slice[..used]);
+        let psk_exchange_response =
+            SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(
+            psk_exchange_response.psk_context.data_size,
+            SHA384_DIGEST_SIZE as u16
+        );
+
+        // but not if the responder said it would not send a context.
+        context.negotiate_info.rsp_capabilities_sel =
+            SpdmResponseCapabilityFlags::PSK_CAP_WITHOUT_CONTEXT;
+        let mut reader = Reader::init(&u8_slice[..used]);
+        assert!(SpdmPskExchangeResponsePayload::spdm_read(&mut context, &mut reader).is_none());
+    }
 }
@@ -1208,6 +1208,7 @@ pub enum SpdmHkdfInputKeyingMaterial<'a> {
     SpdmDigest(&'a SpdmDigestStruct),
     SpdmMasterSecret(&'a SpdmMasterSecretStruct),
     SpdmDirectionDataSecret(&'a SpdmDirectionDataSecretStruct),
+    SpdmExportMasterSecret(&'a SpdmExportMasterSecretStruct),
 }
 
 impl AsRef<[u8]> for SpdmHkdfInputKeyingMaterial<'_> {
@@ -1221,6 +1222,7 @@ impl AsRef<[u8]> for SpdmHkdfInputKeyingMaterial<'_> {
             SpdmHkdfInputKeyingMaterial::SpdmMasterSecret(inner) => inner.as_ref(),
             SpdmHkdfInputKeyingMaterial::SpdmDirectionDataSecret(inner) => inner.as_ref(),
             SpdmHkdfInputKeyingMaterial::SpdmFinishedKey(inner) => inner.as_ref(),
+            SpdmHkdfInputKeyingMaterial::SpdmExportMasterSecret(inner) => inner.as_ref(),
         }
     }
 }
@@ -1236,6 +1238,7 @@ impl SpdmHkdfInputKeyingMaterial<'_> {
             SpdmHkdfInputKeyingMaterial::SpdmMasterSecret(inner) => inner.data_size,
             SpdmHkdfInputKeyingMaterial::SpdmDirectionDataSecret(inner) => inner.data_size,
             SpdmHkdfInputKeyingMaterial::SpdmFinishedKey(inner) => inner.data_size,
+            SpdmHkdfInputKeyingMaterial::SpdmExportMasterSecret(inner) => inner.data_size,
         }
     }
 }
@@ -1510,6 +1513,9 @@ impl SpdmHkdfPseudoRandomKey {
                 SpdmHkdfInputKeyingMaterial::SpdmDirectionDataSecret(inner) => prk.data
                     [..inner.data_size as usize]
                     .copy_from_slice(&inner.data[..inner.data_size as usize]),
+                SpdmHkdfInputKeyingMaterial::SpdmExportMasterSecret(inner) => prk.data
+                    [..inner.data_size as usize]
+                    .copy_from_slice(&inner.data[..inner.data_size as usize]),
             }
             Some(prk)
         }
@@ -13,36 +13,54 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_challenge(
         &mut self,
         slot_id: u8,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     ) -> SpdmResult {
-        info!("send spdm challenge\n");
+        block_on(self.send_receive_spdm_challenge_async(slot_id, measurement_summary_hash_type))
+    }
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+    pub fn send_receive_spdm_challenge_async<'b>(
+        &'b mut self,
+        slot_id: u8,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm challenge\n");
+
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
 
-        self.common
-            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestChallenge, None);
+            self.common
+                .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestChallenge, None);
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used =
-            self.encode_spdm_challenge(slot_id, measurement_summary_hash_type, &mut send_buffer)?;
-        self.send_message(&send_buffer[..send_used])?;
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let send_used = self.encode_spdm_challenge(
+                slot_id,
+                measurement_summary_hash_type,
+                &mut send_buffer,
+            )?;
+            self.send_message_async(&send_buffer[..send_used]).await?;
 
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_message(&mut receive_buffer, true)?;
-        self.handle_spdm_challenge_response(
-            0, // NULL
-            slot_id,
-            measurement_summary_hash_type,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+            // Receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self
+                .receive_message_async(&mut receive_buffer, true)
+                .await?;
+            self.handle_spdm_challenge_response(
+                0, // NULL
+                slot_id,
+                measurement_summary_hash_type,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )
+        })
     }
 
     pub fn encode_spdm_challenge(
@@ -2,12 +2,22 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use crate::common::block_on;
 use crate::common::ST1;
-use crate::common::{self, SpdmDeviceIo, SpdmTransportEncap};
+use crate::common::{
+    self, SpdmDeviceIo, SpdmDeviceIoHandle, SpdmFuture, SpdmTransportEncap,
+    SpdmTransportEncapHandle,
+};
 use crate::config;
-use crate::error::{SpdmResult, SPDM_STATUS_RECEIVE_FAIL, SPDM_STATUS_SEND_FAIL};
+use crate::error::{
+    SpdmResult, SPDM_STATUS_DECAP_APP_FAIL, SPDM_STATUS_RECEIVE_FAIL, SPDM_STATUS_SEND_FAIL,
+};
+use crate::message::SpdmRequestResponseCode;
 use crate::protocol::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 pub struct RequesterContext<'a> {
     pub common: common::SpdmContext<'a>,
 }
@@ -29,10 +39,34 @@ impl<'a> RequesterContext<'a> {
         }
     }
 
+    // a requester over an async device io or transport encap, to be driven by
+    // the *_async functions. the sync ones still work, they poll until done.
+    pub fn new_with_handle(
+        device_io: SpdmDeviceIoHandle<'a>,
+        transport_encap: SpdmTransportEncapHandle<'a>,
+        config_info: common::SpdmConfigInfo,
+        provision_info: common::SpdmProvisionInfo,
+    ) -> Self {
+        RequesterContext {
+            common: common::SpdmContext::new_with_handle(
+                device_io,
+                transport_encap,
+                config_info,
+                provision_info,
+            ),
+        }
+    }
+
     pub fn init_connection(&mut self) -> SpdmResult {
-        self.send_receive_spdm_version()?;
-        self.send_receive_spdm_capability()?;
-        self.send_receive_spdm_algorithm()
+        block_on(self.init_connection_async())
+    }
+
+    pub fn init_connection_async(&mut self) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move {
+            self.send_receive_spdm_version_async().await?;
+            self.send_receive_spdm_capability_async().await?;
+            self.send_receive_spdm_algorithm_async().await
+        })
     }
 
     pub fn start_session(
@@ -41,38 +75,95 @@ impl<'a> RequesterContext<'a> {
         slot_id: u8,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     ) -> SpdmResult<u32> {
-        if !use_psk {
-            let session_id =
-                self.send_receive_spdm_key_exchange(slot_id, measurement_summary_hash_type)?;
-            #[cfg(not(feature = "mut-auth"))]
-            let req_slot_id: Option<u8> = None;
-            #[cfg(feature = "mut-auth")]
-            self.session_based_mutual_authenticate(session_id)?;
-            #[cfg(feature = "mut-auth")]
-            let req_slot_id = Some(self.common.runtime_info.get_local_used_cert_chain_slot_id());
-            self.send_receive_spdm_finish(req_slot_id, session_id)?;
-            Ok(session_id)
-        } else {
-            let session_id =
-                self.send_receive_spdm_psk_exchange(measurement_summary_hash_type, None)?;
-            self.send_receive_spdm_psk_finish(session_id)?;
+        block_on(self.start_session_async(use_psk, slot_id, measurement_summary_hash_type))
+    }
+
+    pub fn start_session_async(
+        &mut self,
+        use_psk: bool,
+        slot_id: u8,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    ) -> SpdmFuture<'_, SpdmResult<u32>> {
+        Box::pin(async move {
+            if !use_psk {
+                let session_id = self
+                    .send_receive_spdm_key_exchange_async(slot_id, measurement_summary_hash_type)
+                    .await?;
+                #[cfg(not(feature = "mut-auth"))]
+                let req_slot_id: Option<u8> = None;
+                #[cfg(feature = "mut-auth")]
+                self.session_based_mutual_authenticate_async(session_id)
+                    .await?;
+                #[cfg(feature = "mut-auth")]
+                let req_slot_id =
+                    Some(self.common.runtime_info.get_local_used_cert_chain_slot_id());
+                self.send_receive_spdm_finish_async(req_slot_id, session_id)
+                    .await?;
+                Ok(session_id)
+            } else {
+                self.start_psk_session_async(None, measurement_summary_hash_type)
+                    .await
+            }
+        })
+    }
+
+    // start a PSK session with the PSK the responder provisioned for `psk_hint`,
+    // the empty hint is used if none is given.
+    pub fn start_psk_session(
+        &mut self,
+        psk_hint: Option<&SpdmPskHintStruct>,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    ) -> SpdmResult<u32> {
+        block_on(self.start_psk_session_async(psk_hint, measurement_summary_hash_type))
+    }
+
+    pub fn start_psk_session_async<'b>(
+        &'b mut self,
+        psk_hint: Option<&'b SpdmPskHintStruct>,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    ) -> SpdmFuture<'b, SpdmResult<u32>> {
+        Box::pin(async move {
+            let session_id = self
+                .send_receive_spdm_psk_exchange_async(measurement_summary_hash_type, psk_hint)
+                .await?;
+            self.send_receive_spdm_psk_finish_async(session_id).await?;
             Ok(session_id)
-        }
+        })
     }
 
     pub fn end_session(&mut self, session_id: u32) -> SpdmResult {
-        self.send_receive_spdm_end_session(session_id)
+        block_on(self.end_session_async(session_id))
+    }
+
+    pub fn end_session_async(&mut self, session_id: u32) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move { self.send_receive_spdm_end_session_async(session_id).await })
     }
 
     pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
-        if self.common.negotiate_info.rsp_data_transfer_size_sel != 0
-            && send_buffer.len() > self.common.negotiate_info.rsp_data_transfer_size_sel as usize
-        {
-            return Err(SPDM_STATUS_SEND_FAIL);
-        }
-        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
-        let used = self.common.encap(send_buffer, &mut transport_buffer)?;
-        self.common.device_io.send(&transport_buffer[..used])
+        block_on(self.send_message_async(send_buffer))
+    }
+
+    pub fn send_message_async<'b>(
+        &'b mut self,
+        send_buffer: &'b [u8],
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            if self.common.negotiate_info.rsp_data_transfer_size_sel != 0
+                && send_buffer.len()
+                    > self.common.negotiate_info.rsp_data_transfer_size_sel as usize
+            {
+                return Err(SPDM_STATUS_SEND_FAIL);
+            }
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = self
+                .common
+                .encap_async(send_buffer, &mut transport_buffer)
+                .await?;
+            self.common
+                .device_io
+                .send_async(&transport_buffer[..used])
+                .await
+        })
     }
 
     pub fn send_secured_message(
@@ -81,21 +172,96 @@ impl<'a> RequesterContext<'a> {
         send_buffer: &[u8],
         is_app_message: bool,
     ) -> SpdmResult {
-        if !is_app_message
-            && self.common.negotiate_info.rsp_data_transfer_size_sel != 0
-            && (send_buffer.len() > self.common.negotiate_info.rsp_data_transfer_size_sel as usize)
-        {
-            return Err(SPDM_STATUS_SEND_FAIL);
-        }
-        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
-        let used = self.common.encode_secured_message(
-            session_id,
-            send_buffer,
-            &mut transport_buffer,
-            true,
-            is_app_message,
-        )?;
-        self.common.device_io.send(&transport_buffer[..used])
+        block_on(self.send_secured_message_async(session_id, send_buffer, is_app_message))
+    }
+
+    pub fn send_secured_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        send_buffer: &'b [u8],
+        is_app_message: bool,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            if is_app_message {
+                let app_message_type = self.common.get_app_message_type(session_id)?;
+                return self
+                    .send_secured_app_message_async(session_id, send_buffer, app_message_type)
+                    .await;
+            }
+            if self.common.negotiate_info.rsp_data_transfer_size_sel != 0
+                && (send_buffer.len()
+                    > self.common.negotiate_info.rsp_data_transfer_size_sel as usize)
+            {
+                return Err(SPDM_STATUS_SEND_FAIL);
+            }
+            // a KEY_UPDATE request is itself part of updating the keys.
+            if send_buffer.get(1) != Some(&SpdmRequestResponseCode::SpdmRequestKeyUpdate.get_u8()) {
+                self.key_update_by_policy_async(session_id).await?;
+            }
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = self
+                .common
+                .encode_secured_message_async(
+                    session_id,
+                    send_buffer,
+                    &mut transport_buffer,
+                    true,
+                    None,
+                )
+                .await?;
+            self.common
+                .device_io
+                .send_async(&transport_buffer[..used])
+                .await
+        })
+    }
+
+    // send an application message of the given transport specific type in a session.
+    pub fn send_secured_app_message(
+        &mut self,
+        session_id: u32,
+        send_buffer: &[u8],
+        app_message_type: u8,
+    ) -> SpdmResult {
+        block_on(self.send_secured_app_message_async(session_id, send_buffer, app_message_type))
+    }
+
+    pub fn send_secured_app_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        send_buffer: &'b [u8],
+        app_message_type: u8,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            self.key_update_by_policy_async(session_id).await?;
+            let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+            let used = self
+                .common
+                .encode_secured_message_async(
+                    session_id,
+                    send_buffer,
+                    &mut transport_buffer,
+                    true,
+                    Some(app_message_type),
+                )
+                .await?;
+            self.common
+                .device_io
+                .send_async(&transport_buffer[..used])
+                .await
+        })
+    }
+
+    // derive a secret for a downstream protocol from the export master secret of a session.
+    pub fn export_secret(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> SpdmResult<SpdmHkdfOutputKeyingMaterial> {
+        self.common
+            .export_secret(session_id, label, context, length)
     }
 
     pub fn receive_message(
@@ -103,22 +269,35 @@ impl<'a> RequesterContext<'a> {
         receive_buffer: &mut [u8],
         crypto_request: bool,
     ) -> SpdmResult<usize> {
-        info!("receive_message!\n");
+        block_on(self.receive_message_async(receive_buffer, crypto_request))
+    }
 
-        let timeout: usize = if crypto_request {
-            2 << self.common.negotiate_info.rsp_ct_exponent_sel
-        } else {
-            ST1
-        };
+    pub fn receive_message_async<'b>(
+        &'b mut self,
+        receive_buffer: &'b mut [u8],
+        crypto_request: bool,
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            info!("receive_message!\n");
+
+            let timeout: usize = if crypto_request {
+                2 << self.common.negotiate_info.rsp_ct_exponent_sel
+            } else {
+                ST1
+            };
 
-        let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
-        let used = self
-            .common
-            .device_io
-            .receive(&mut transport_buffer, timeout)
-            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+            let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let used = self
+                .common
+                .device_io
+                .receive_async(&mut transport_buffer, timeout)
+                .await
+                .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
 
-        self.common.decap(&transport_buffer[..used], receive_buffer)
+            self.common
+                .decap_async(&transport_buffer[..used], receive_buffer)
+                .await
+        })
     }
 
     pub fn receive_secured_message(
@@ -127,23 +306,77 @@ impl<'a> RequesterContext<'a> {
         receive_buffer: &mut [u8],
         crypto_request: bool,
     ) -> SpdmResult<usize> {
-        info!("receive_secured_message!\n");
+        block_on(self.receive_secured_message_async(session_id, receive_buffer, crypto_request))
+    }
 
-        let timeout: usize = if crypto_request {
-            2 << self.common.negotiate_info.rsp_ct_exponent_sel
-        } else {
-            ST1
-        };
+    pub fn receive_secured_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        receive_buffer: &'b mut [u8],
+        crypto_request: bool,
+    ) -> SpdmFuture<'b, SpdmResult<usize>> {
+        Box::pin(async move {
+            info!("receive_secured_message!\n");
 
-        let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+            let timeout: usize = if crypto_request {
+                2 << self.common.negotiate_info.rsp_ct_exponent_sel
+            } else {
+                ST1
+            };
 
-        let used = self
-            .common
-            .device_io
-            .receive(&mut transport_buffer, timeout)
-            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+            let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
 
-        self.common
-            .decode_secured_message(session_id, &transport_buffer[..used], receive_buffer)
+            let used = self
+                .common
+                .device_io
+                .receive_async(&mut transport_buffer, timeout)
+                .await
+                .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+
+            self.common
+                .decode_secured_message_async(session_id, &transport_buffer[..used], receive_buffer)
+                .await
+        })
+    }
+
+    // receive an application message in a session, along with its transport specific type.
+    pub fn receive_secured_app_message(
+        &mut self,
+        session_id: u32,
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, u8)> {
+        block_on(self.receive_secured_app_message_async(session_id, receive_buffer))
+    }
+
+    pub fn receive_secured_app_message_async<'b>(
+        &'b mut self,
+        session_id: u32,
+        receive_buffer: &'b mut [u8],
+    ) -> SpdmFuture<'b, SpdmResult<(usize, u8)>> {
+        Box::pin(async move {
+            info!("receive_secured_app_message!\n");
+
+            let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+
+            let used = self
+                .common
+                .device_io
+                .receive_async(&mut transport_buffer, ST1)
+                .await
+                .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+
+            match self
+                .common
+                .decode_secured_app_message_async(
+                    session_id,
+                    &transport_buffer[..used],
+                    receive_buffer,
+                )
+                .await?
+            {
+                (used, Some(app_message_type)) => Ok((used, app_message_type)),
+                (_, None) => Err(SPDM_STATUS_DECAP_APP_FAIL),
+            }
+        })
     }
 }
@@ -9,22 +9,37 @@ use crate::error::{
 use crate::message::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_end_session(&mut self, session_id: u32) -> SpdmResult {
-        info!("send spdm end_session\n");
+        block_on(self.send_receive_spdm_end_session_async(session_id))
+    }
+
+    pub fn send_receive_spdm_end_session_async<'b>(
+        &'b mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm end_session\n");
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestEndSession,
-            Some(session_id),
-        );
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestEndSession,
+                Some(session_id),
+            );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.encode_spdm_end_session(&mut send_buffer)?;
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self.encode_spdm_end_session(&mut send_buffer)?;
+            self.send_secured_message_async(session_id, &send_buffer[..used], false)
+                .await?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
-        self.handle_spdm_end_session_response(session_id, &receive_buffer[..used])
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self
+                .receive_secured_message_async(session_id, &mut receive_buffer, false)
+                .await?;
+            self.handle_spdm_end_session_response(session_id, &receive_buffer[..used])
+        })
     }
 
     pub fn encode_spdm_end_session(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
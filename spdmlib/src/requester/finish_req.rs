@@ -16,91 +16,103 @@ impl<'a> RequesterContext<'a> {
         req_slot_id: Option<u8>,
         session_id: u32,
     ) -> SpdmResult {
-        info!("send spdm finish\n");
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
+        block_on(self.send_receive_spdm_finish_async(req_slot_id, session_id))
+    }
+
+    pub fn send_receive_spdm_finish_async<'b>(
+        &'b mut self,
+        req_slot_id: Option<u8>,
+        session_id: u32,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm finish\n");
+            let in_clear_text = self
                 .common
                 .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
-        info!("in_clear_text {:?}\n", in_clear_text);
+                .req_capabilities_sel
+                .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
+                && self
+                    .common
+                    .negotiate_info
+                    .rsp_capabilities_sel
+                    .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+            info!("in_clear_text {:?}\n", in_clear_text);
+
+            let req_slot_id = if let Some(req_slot_id) = req_slot_id {
+                if req_slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                    return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+                }
+                if self.common.provision_info.my_cert_chain[req_slot_id as usize].is_none() {
+                    return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+                }
+                req_slot_id
+            } else {
+                0
+            };
 
-        let req_slot_id = if let Some(req_slot_id) = req_slot_id {
-            if req_slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-                return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
-            }
-            if self.common.provision_info.my_cert_chain[req_slot_id as usize].is_none() {
-                return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+            if self.common.get_session_via_id(session_id).is_none() {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
             }
-            req_slot_id
-        } else {
-            0
-        };
-
-        if self.common.get_session_via_id(session_id).is_none() {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestFinish,
-            Some(session_id),
-        );
-
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let res = self.encode_spdm_finish(session_id, req_slot_id, &mut send_buffer);
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return Err(res.err().unwrap());
-        }
-        let send_used = res.unwrap();
-        let res = if in_clear_text {
-            self.send_message(&send_buffer[..send_used])
-        } else {
-            self.send_secured_message(session_id, &send_buffer[..send_used], false)
-        };
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return res;
-        }
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestFinish,
+                Some(session_id),
+            );
+
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let res = self.encode_spdm_finish(session_id, req_slot_id, &mut send_buffer);
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return Err(res.err().unwrap());
+            }
+            let send_used = res.unwrap();
+            let res = if in_clear_text {
+                self.send_message_async(&send_buffer[..send_used]).await
+            } else {
+                self.send_secured_message_async(session_id, &send_buffer[..send_used], false)
+                    .await
+            };
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return res;
+            }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let res = if in_clear_text {
-            self.receive_message(&mut receive_buffer, false)
-        } else {
-            self.receive_secured_message(session_id, &mut receive_buffer, false)
-        };
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return Err(res.err().unwrap());
-        }
-        let receive_used = res.unwrap();
-        let res = self.handle_spdm_finish_response(
-            session_id,
-            req_slot_id,
-            &receive_buffer[..receive_used],
-        );
-        if res.is_err() {
-            if let Some(session) = self.common.get_session_via_id(session_id) {
-                let _ = session.teardown(session_id);
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let res = if in_clear_text {
+                self.receive_message_async(&mut receive_buffer, false).await
+            } else {
+                self.receive_secured_message_async(session_id, &mut receive_buffer, false)
+                    .await
+            };
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return Err(res.err().unwrap());
             }
-        }
-        res
+            let receive_used = res.unwrap();
+            let res = self.handle_spdm_finish_response(
+                session_id,
+                req_slot_id,
+                &receive_buffer[..receive_used],
+            );
+            if res.is_err() {
+                if let Some(session) = self.common.get_session_via_id(session_id) {
+                    let _ = session.teardown(session_id);
+                }
+            }
+            res
+        })
     }
 
     pub fn encode_spdm_finish(
@@ -292,8 +304,6 @@ impl<'a> RequesterContext<'a> {
                             crate::common::session::SpdmSessionState::SpdmSessionEstablished,
                         );
 
-                        self.common.runtime_info.set_last_session_id(None);
-
                         Ok(())
                     } else {
                         error!("!!! finish : fail !!!\n");
@@ -4,53 +4,64 @@
 
 use crate::crypto;
 use crate::error::{
-    SpdmResult, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_CERT,
-    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL,
+    SpdmResult, SPDM_STATUS_CERT_REVOKED, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER,
+    SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_INVALID_STATE_LOCAL,
 };
 use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
-    fn send_receive_spdm_certificate_partial(
-        &mut self,
+    fn send_receive_spdm_certificate_partial_async<'b>(
+        &'b mut self,
         session_id: Option<u32>,
         slot_id: u8,
         total_size: u16,
         offset: u16,
         length: u16,
-    ) -> SpdmResult<(u16, u16)> {
-        info!("send spdm certificate\n");
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used =
-            self.encode_spdm_certificate_partial(slot_id, offset, length, &mut send_buffer)?;
+    ) -> SpdmFuture<'b, SpdmResult<(u16, u16)>> {
+        Box::pin(async move {
+            info!("send spdm certificate\n");
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let send_used =
+                self.encode_spdm_certificate_partial(slot_id, offset, length, &mut send_buffer)?;
 
-        match session_id {
-            Some(session_id) => {
-                self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
-            }
-            None => {
-                self.send_message(&send_buffer[..send_used])?;
+            match session_id {
+                Some(session_id) => {
+                    self.send_secured_message_async(session_id, &send_buffer[..send_used], false)
+                        .await?;
+                }
+                None => {
+                    self.send_message_async(&send_buffer[..send_used]).await?;
+                }
             }
-        }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = match session_id {
-            Some(session_id) => {
-                self.receive_secured_message(session_id, &mut receive_buffer, false)?
-            }
-            None => self.receive_message(&mut receive_buffer, false)?,
-        };
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = match session_id {
+                Some(session_id) => {
+                    self.receive_secured_message_async(session_id, &mut receive_buffer, false)
+                        .await?
+                }
+                None => {
+                    self.receive_message_async(&mut receive_buffer, false)
+                        .await?
+                }
+            };
 
-        self.handle_spdm_certificate_partial_response(
-            session_id,
-            slot_id,
-            total_size,
-            offset,
-            length,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+            self.handle_spdm_certificate_partial_response(
+                session_id,
+                slot_id,
+                total_size,
+                offset,
+                length,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )
+        })
     }
 
     pub fn encode_spdm_certificate_partial(
@@ -184,48 +195,63 @@ impl<'a> RequesterContext<'a> {
         session_id: Option<u32>,
         slot_id: u8,
     ) -> SpdmResult {
-        let mut offset = 0u16;
-        let mut length = MAX_SPDM_CERT_PORTION_LEN as u16;
-        let mut total_size = 0u16;
+        block_on(self.send_receive_spdm_certificate_async(session_id, slot_id))
+    }
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
-        }
+    pub fn send_receive_spdm_certificate_async(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+    ) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move {
+            let mut offset = 0u16;
+            let mut length = MAX_SPDM_CERT_PORTION_LEN as u16;
+            let mut total_size = 0u16;
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestGetCertificate,
-            session_id,
-        );
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+            }
 
-        self.common.peer_info.peer_cert_chain_temp = Some(SpdmCertChainBuffer::default());
-        while length != 0 {
-            let (portion_length, remainder_length) = self.send_receive_spdm_certificate_partial(
-                session_id, slot_id, total_size, offset, length,
-            )?;
-            if total_size == 0 {
-                total_size = portion_length + remainder_length;
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetCertificate,
+                session_id,
+            );
+
+            self.common.peer_info.peer_cert_chain_temp = Some(SpdmCertChainBuffer::default());
+            while length != 0 {
+                let (portion_length, remainder_length) = self
+                    .send_receive_spdm_certificate_partial_async(
+                        session_id, slot_id, total_size, offset, length,
+                    )
+                    .await?;
+                if total_size == 0 {
+                    total_size = portion_length + remainder_length;
+                }
+                offset += portion_length;
+                length = remainder_length;
+                if length > MAX_SPDM_CERT_PORTION_LEN as u16 {
+                    length = MAX_SPDM_CERT_PORTION_LEN as u16;
+                }
             }
-            offset += portion_length;
-            length = remainder_length;
-            if length > MAX_SPDM_CERT_PORTION_LEN as u16 {
-                length = MAX_SPDM_CERT_PORTION_LEN as u16;
+            if total_size == 0 {
+                self.common.peer_info.peer_cert_chain_temp = None;
+                return Err(SPDM_STATUS_INVALID_CERT);
             }
-        }
-        if total_size == 0 {
-            self.common.peer_info.peer_cert_chain_temp = None;
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
 
-        let result = self.verify_spdm_certificate_chain();
-        if result.is_ok() {
-            self.common.peer_info.peer_cert_chain[slot_id as usize] =
-                self.common.peer_info.peer_cert_chain_temp.clone();
-        }
-        self.common.peer_info.peer_cert_chain_temp = None;
-        result
+            let result = self
+                .verify_spdm_certificate_chain()
+                .map(|peer_root_cert_index| {
+                    self.common.peer_info.peer_cert_chain[slot_id as usize] =
+                        self.common.peer_info.peer_cert_chain_temp.clone();
+                    self.common.peer_info.peer_root_cert_index[slot_id as usize] =
+                        peer_root_cert_index;
+                });
+            self.common.peer_info.peer_cert_chain_temp = None;
+            result
+        })
     }
 
-    pub fn verify_spdm_certificate_chain(&mut self) -> SpdmResult {
+    pub fn verify_spdm_certificate_chain(&mut self) -> SpdmResult<Option<usize>> {
         //
         // 1. Verify the integrity of cert chain
         //
@@ -262,20 +288,7 @@ impl<'a> RequesterContext<'a> {
         info!("1. get runtime_peer_cert_chain_data!\n");
 
         //
-        // 1.1 verify the integrity of the chain
-        //
-        if crypto::cert_operation::verify_cert_chain(
-            &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
-        )
-        .is_err()
-        {
-            error!("cert_chain verification - fail! - TBD later\n");
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
-        info!("1.1. integrity of cert_chain is verified!\n");
-
-        //
-        // 1.2 verify the root cert hash
+        // 1.1 verify the root cert hash
         //
         let (root_cert_begin, root_cert_end) = crypto::cert_operation::get_cert_from_cert_chain(
             &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
@@ -296,29 +309,32 @@ impl<'a> RequesterContext<'a> {
             error!("root_hash - fail!\n");
             return Err(SPDM_STATUS_INVALID_CERT);
         }
-        info!("1.2. root cert hash is verified!\n");
+        info!("1.1. root cert hash is verified!\n");
 
         //
-        // 2. verify the authority of cert chain if provisioned
+        // 2. verify the SPDM certificate profile, the integrity and
+        //    the authority of cert chain against the provisioned root certs
         //
-        if let Some(peer_root_cert_data) = &self.common.provision_info.peer_root_cert_data {
-            if root_cert.len() != peer_root_cert_data.data_size as usize {
-                error!("root_cert size - fail!\n");
-                debug!(
-                    "provision root_cert data size - {:?}\n",
-                    peer_root_cert_data.data_size
-                );
-                debug!("runtime root_cert data size - {:?}\n", root_cert.len());
-                return Err(SPDM_STATUS_INVALID_CERT);
+        let peer_root_cert_index = match self.common.verify_peer_cert_chain(
+            &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
+            false,
+        ) {
+            Ok(index) => index,
+            Err(e) if e == SPDM_STATUS_CERT_REVOKED => {
+                error!("cert_chain verification - revoked!\n");
+                return Err(SPDM_STATUS_CERT_REVOKED);
             }
-            if root_cert[..] != peer_root_cert_data.data[..peer_root_cert_data.data_size as usize] {
-                error!("root_cert data - fail!\n");
+            Err(_) => {
+                error!("cert_chain verification - fail!\n");
                 return Err(SPDM_STATUS_INVALID_CERT);
             }
-            info!("2. root cert is verified!\n");
+        };
+        match peer_root_cert_index {
+            Some(index) => info!("2. cert_chain is anchored on root cert {:?}!\n", index),
+            None => info!("2. integrity of cert_chain is verified!\n"),
         }
 
         info!("cert_chain verification - pass!\n");
-        Ok(())
+        Ok(peer_root_cert_index)
     }
 }
@@ -6,39 +6,56 @@ use crate::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_F
 use crate::message::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_digest(&mut self, session_id: Option<u32>) -> SpdmResult {
-        info!("send spdm digest\n");
+        block_on(self.send_receive_spdm_digest_async(session_id))
+    }
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestGetDigests,
-            session_id,
-        );
+    pub fn send_receive_spdm_digest_async<'b>(
+        &'b mut self,
+        session_id: Option<u32>,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm digest\n");
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used = self.encode_spdm_digest(&mut send_buffer)?;
-        match session_id {
-            Some(session_id) => {
-                self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
-            }
-            None => {
-                self.send_message(&send_buffer[..send_used])?;
-            }
-        }
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetDigests,
+                session_id,
+            );
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = match session_id {
-            Some(session_id) => {
-                self.receive_secured_message(session_id, &mut receive_buffer, false)?
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let send_used = self.encode_spdm_digest(&mut send_buffer)?;
+            match session_id {
+                Some(session_id) => {
+                    self.send_secured_message_async(session_id, &send_buffer[..send_used], false)
+                        .await?;
+                }
+                None => {
+                    self.send_message_async(&send_buffer[..send_used]).await?;
+                }
             }
-            None => self.receive_message(&mut receive_buffer, false)?,
-        };
 
-        self.handle_spdm_digest_response(
-            session_id,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = match session_id {
+                Some(session_id) => {
+                    self.receive_secured_message_async(session_id, &mut receive_buffer, false)
+                        .await?
+                }
+                None => {
+                    self.receive_message_async(&mut receive_buffer, false)
+                        .await?
+                }
+            };
+
+            self.handle_spdm_digest_response(
+                session_id,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )
+        })
     }
 
     pub fn encode_spdm_digest(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
@@ -13,60 +13,70 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
-    fn send_receive_spdm_measurement_record(
-        &mut self,
+    fn send_receive_spdm_measurement_record_async<'b>(
+        &'b mut self,
         session_id: Option<u32>,
         measurement_attributes: SpdmMeasurementAttributes,
         measurement_operation: SpdmMeasurementOperation,
-        spdm_measurement_record_structure: &mut SpdmMeasurementRecordStructure,
+        spdm_measurement_record_structure: &'b mut SpdmMeasurementRecordStructure,
         slot_id: u8,
-    ) -> SpdmResult<u8> {
-        info!("send spdm measurement\n");
-
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+    ) -> SpdmFuture<'b, SpdmResult<u8>> {
+        Box::pin(async move {
+            info!("send spdm measurement\n");
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestGetMeasurements,
-            session_id,
-        );
-
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used = self.encode_spdm_measurement_record(
-            measurement_attributes,
-            measurement_operation,
-            slot_id,
-            &mut send_buffer,
-        )?;
-        match session_id {
-            Some(session_id) => {
-                self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
-            }
-            None => {
-                self.send_message(&send_buffer[..send_used])?;
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
             }
-        }
 
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = match session_id {
-            Some(session_id) => {
-                self.receive_secured_message(session_id, &mut receive_buffer, true)?
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetMeasurements,
+                session_id,
+            );
+
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let send_used = self.encode_spdm_measurement_record(
+                measurement_attributes,
+                measurement_operation,
+                slot_id,
+                &mut send_buffer,
+            )?;
+            match session_id {
+                Some(session_id) => {
+                    self.send_secured_message_async(session_id, &send_buffer[..send_used], false)
+                        .await?;
+                }
+                None => {
+                    self.send_message_async(&send_buffer[..send_used]).await?;
+                }
             }
-            None => self.receive_message(&mut receive_buffer, true)?,
-        };
 
-        self.handle_spdm_measurement_record_response(
-            session_id,
-            slot_id,
-            measurement_attributes,
-            measurement_operation,
-            spdm_measurement_record_structure,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+            // Receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = match session_id {
+                Some(session_id) => {
+                    self.receive_secured_message_async(session_id, &mut receive_buffer, true)
+                        .await?
+                }
+                None => {
+                    self.receive_message_async(&mut receive_buffer, true)
+                        .await?
+                }
+            };
+
+            self.handle_spdm_measurement_record_response(
+                session_id,
+                slot_id,
+                measurement_attributes,
+                measurement_operation,
+                spdm_measurement_record_structure,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )
+        })
     }
 
     pub fn encode_spdm_measurement_record(
@@ -218,14 +228,38 @@ impl<'a> RequesterContext<'a> {
         //      number of blocks got measured.
         spdm_measurement_record_structure: &mut SpdmMeasurementRecordStructure, // out
     ) -> SpdmResult {
-        *out_total_number = self.send_receive_spdm_measurement_record(
+        block_on(self.send_receive_spdm_measurement_async(
             session_id,
+            slot_id,
             spdm_measuremente_attributes,
             measurement_operation,
+            out_total_number,
             spdm_measurement_record_structure,
-            slot_id,
-        )?;
-        Ok(())
+        ))
+    }
+
+    pub fn send_receive_spdm_measurement_async<'b>(
+        &'b mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+        spdm_measuremente_attributes: SpdmMeasurementAttributes,
+        measurement_operation: SpdmMeasurementOperation,
+        out_total_number: &'b mut u8, // out, total number when measurement_operation = SpdmMeasurementQueryTotalNumber
+        //      number of blocks got measured.
+        spdm_measurement_record_structure: &'b mut SpdmMeasurementRecordStructure, // out
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            *out_total_number = self
+                .send_receive_spdm_measurement_record_async(
+                    session_id,
+                    spdm_measuremente_attributes,
+                    measurement_operation,
+                    spdm_measurement_record_structure,
+                    slot_id,
+                )
+                .await?;
+            Ok(())
+        })
     }
 
     #[cfg(feature = "hashed-transcript-data")]
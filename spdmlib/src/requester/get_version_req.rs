@@ -9,18 +9,31 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_version(&mut self) -> SpdmResult {
-        self.common
-            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestGetVersion, None);
+        block_on(self.send_receive_spdm_version_async())
+    }
+
+    pub fn send_receive_spdm_version_async<'b>(&'b mut self) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetVersion,
+                None,
+            );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used = self.encode_spdm_version(&mut send_buffer)?;
-        self.send_message(&send_buffer[..send_used])?;
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let send_used = self.encode_spdm_version(&mut send_buffer)?;
+            self.send_message_async(&send_buffer[..send_used]).await?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_version_response(0, &send_buffer[..send_used], &receive_buffer[..used])
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self
+                .receive_message_async(&mut receive_buffer, false)
+                .await?;
+            self.handle_spdm_version_response(0, &send_buffer[..send_used], &receive_buffer[..used])
+        })
     }
 
     pub fn encode_spdm_version(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
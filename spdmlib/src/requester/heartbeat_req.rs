@@ -2,27 +2,72 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use crate::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD};
+use crate::error::{
+    SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_INVALID_PARAMETER,
+};
 use crate::message::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_heartbeat(&mut self, session_id: u32) -> SpdmResult {
-        info!("send spdm heartbeat\n");
+        block_on(self.send_receive_spdm_heartbeat_async(session_id))
+    }
+
+    pub fn send_receive_spdm_heartbeat_async<'b>(
+        &'b mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm heartbeat\n");
+
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestHeartbeat,
+                Some(session_id),
+            );
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestHeartbeat,
-            Some(session_id),
-        );
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self.encode_spdm_heartbeat(&mut send_buffer)?;
+            self.send_secured_message_async(session_id, &send_buffer[..used], false)
+                .await?;
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.encode_spdm_heartbeat(&mut send_buffer)?;
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+            // Receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self
+                .receive_secured_message_async(session_id, &mut receive_buffer, false)
+                .await?;
+            self.handle_spdm_heartbeat_response(session_id, &receive_buffer[..used])
+        })
+    }
 
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
-        self.handle_spdm_heartbeat_response(session_id, &receive_buffer[..used])
+    // keep the session alive: send HEARTBEAT when nothing was sent or received
+    // in the session for a heartbeat period, as told by the monotonic clock.
+    // without a clock it never sends.
+    pub fn send_receive_spdm_heartbeat_if_idle(&mut self, session_id: u32) -> SpdmResult {
+        block_on(self.send_receive_spdm_heartbeat_if_idle_async(session_id))
+    }
+
+    pub fn send_receive_spdm_heartbeat_if_idle_async(
+        &mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move {
+            let session = self
+                .common
+                .get_immutable_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+            let is_idle = match crate::time::get_monotonic_time() {
+                Some(now) => session.heartbeat_period != 0 && session.is_heartbeat_idle(now),
+                None => false,
+            };
+            if !is_idle {
+                return Ok(());
+            }
+            self.send_receive_spdm_heartbeat_async(session_id).await
+        })
     }
 
     pub fn encode_spdm_heartbeat(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
@@ -31,37 +31,51 @@ impl<'a> RequesterContext<'a> {
         slot_id: u8,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     ) -> SpdmResult<u32> {
-        info!("send spdm key exchange\n");
+        block_on(self.send_receive_spdm_key_exchange_async(slot_id, measurement_summary_hash_type))
+    }
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+    pub fn send_receive_spdm_key_exchange_async<'b>(
+        &'b mut self,
+        slot_id: u8,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    ) -> SpdmFuture<'b, SpdmResult<u32>> {
+        Box::pin(async move {
+            info!("send spdm key exchange\n");
 
-        let req_session_id = self.common.get_next_half_session_id(true)?;
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
 
-        self.common
-            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestKeyExchange, None);
+            let req_session_id = self.common.get_next_half_session_id(true)?;
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let (key_exchange_context, send_used) = self.encode_spdm_key_exchange(
-            req_session_id,
-            &mut send_buffer,
-            slot_id,
-            measurement_summary_hash_type,
-        )?;
-        self.send_message(&send_buffer[..send_used])?;
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestKeyExchange,
+                None,
+            );
 
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let receive_used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_key_exhcange_response(
-            req_session_id,
-            slot_id,
-            &send_buffer[..send_used],
-            &receive_buffer[..receive_used],
-            measurement_summary_hash_type,
-            key_exchange_context,
-        )
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let (key_exchange_context, send_used) = self.encode_spdm_key_exchange(
+                req_session_id,
+                &mut send_buffer,
+                slot_id,
+                measurement_summary_hash_type,
+            )?;
+            self.send_message_async(&send_buffer[..send_used]).await?;
+
+            // Receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let receive_used = self
+                .receive_message_async(&mut receive_buffer, false)
+                .await?;
+            self.handle_spdm_key_exhcange_response(
+                req_session_id,
+                slot_id,
+                &send_buffer[..send_used],
+                &receive_buffer[..receive_used],
+                measurement_summary_hash_type,
+                key_exchange_context,
+            )
+        })
     }
 
     pub fn encode_spdm_key_exchange(
@@ -212,8 +226,10 @@ impl<'a> RequesterContext<'a> {
                                 secure_spdm_version_sel
                             );
 
-                            let session_id = ((key_exchange_rsp.rsp_session_id as u32) << 16)
-                                + req_session_id as u32;
+                            let session_id = self.common.get_new_session_id(
+                                req_session_id,
+                                key_exchange_rsp.rsp_session_id,
+                            )?;
                             let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
                             let message_a = self.common.runtime_info.message_a.clone();
                             let cert_chain_hash =
@@ -248,6 +264,8 @@ impl<'a> RequesterContext<'a> {
                                 );
                             }
 
+                            let key_update_policy = self.common.config_info.key_update_policy;
+                            let sequence_number_mode = self.common.config_info.sequence_number_mode;
                             let session = self
                                 .common
                                 .get_next_avaiable_session()
@@ -256,6 +274,7 @@ impl<'a> RequesterContext<'a> {
                             session.setup(session_id)?;
 
                             session.set_use_psk(false);
+                            session.set_key_update_policy(key_update_policy);
                             session.set_mut_auth_requested(key_exchange_rsp.mut_auth_req);
 
                             session.set_crypto_param(
@@ -265,6 +284,7 @@ impl<'a> RequesterContext<'a> {
                                 key_schedule_algo,
                             );
                             session.set_transport_param(sequence_number_count, max_random_count);
+                            session.set_sequence_number_mode(sequence_number_mode);
                             session.set_dhe_secret(spdm_version_sel, final_key)?;
                             session.runtime_info.message_a = message_a;
                             session.runtime_info.rsp_cert_hash = cert_chain_hash;
@@ -384,12 +404,6 @@ impl<'a> RequesterContext<'a> {
                                 crate::common::session::SpdmSessionState::SpdmSessionHandshaking,
                             );
 
-                            if in_clear_text {
-                                self.common
-                                    .runtime_info
-                                    .set_last_session_id(Some(session_id));
-                            }
-
                             Ok(session_id)
                         } else {
                             error!("!!! key_exchange : fail !!!\n");
@@ -7,46 +7,62 @@ use crate::error::{
     SPDM_STATUS_INVALID_PARAMETER,
 };
 use crate::message::*;
+use crate::protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags};
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
-    fn send_receive_spdm_key_update_op(
-        &mut self,
+    fn send_receive_spdm_key_update_op_async<'b>(
+        &'b mut self,
         session_id: u32,
         key_update_operation: SpdmKeyUpdateOperation,
         tag: u8,
-    ) -> SpdmResult {
-        info!("send spdm key_update\n");
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm key_update\n");
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestKeyUpdate,
-            Some(session_id),
-        );
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestKeyUpdate,
+                Some(session_id),
+            );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.encode_spdm_key_update_op(key_update_operation, tag, &mut send_buffer)?;
-        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used =
+                self.encode_spdm_key_update_op(key_update_operation, tag, &mut send_buffer)?;
+            self.send_secured_message_async(session_id, &send_buffer[..used], false)
+                .await?;
 
-        // update key
-        let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
-        let session = if let Some(s) = self.common.get_session_via_id(session_id) {
-            s
-        } else {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        };
-        let update_requester = key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateSingleKey
-            || key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
-        let update_responder = key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
-        session.create_data_secret_update(spdm_version_sel, update_requester, update_responder)?;
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
+            // update key
+            let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
+            let session = if let Some(s) = self.common.get_session_via_id(session_id) {
+                s
+            } else {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            };
+            let update_requester = key_update_operation
+                == SpdmKeyUpdateOperation::SpdmUpdateSingleKey
+                || key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
+            let update_responder =
+                key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
+            session.create_data_secret_update(
+                spdm_version_sel,
+                update_requester,
+                update_responder,
+            )?;
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self
+                .receive_secured_message_async(session_id, &mut receive_buffer, false)
+                .await?;
 
-        self.handle_spdm_key_update_op_response(
-            session_id,
-            update_requester,
-            update_responder,
-            &receive_buffer[..used],
-        )
+            self.handle_spdm_key_update_op_response(
+                session_id,
+                update_requester,
+                update_responder,
+                &receive_buffer[..used],
+            )
+        })
     }
 
     pub fn encode_spdm_key_update_op(
@@ -131,16 +147,68 @@ impl<'a> RequesterContext<'a> {
         session_id: u32,
         key_update_operation: SpdmKeyUpdateOperation,
     ) -> SpdmResult {
-        if key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateAllKeys
-            && key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateSingleKey
-        {
-            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
-        }
-        self.send_receive_spdm_key_update_op(session_id, key_update_operation, 1)?;
-        self.send_receive_spdm_key_update_op(
-            session_id,
-            SpdmKeyUpdateOperation::SpdmVerifyNewKey,
-            2,
-        )
+        block_on(self.send_receive_spdm_key_update_async(session_id, key_update_operation))
+    }
+
+    pub fn send_receive_spdm_key_update_async(
+        &mut self,
+        session_id: u32,
+        key_update_operation: SpdmKeyUpdateOperation,
+    ) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move {
+            if key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateAllKeys
+                && key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateSingleKey
+            {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            self.send_receive_spdm_key_update_op_async(session_id, key_update_operation, 1)
+                .await?;
+            self.send_receive_spdm_key_update_op_async(
+                session_id,
+                SpdmKeyUpdateOperation::SpdmVerifyNewKey,
+                2,
+            )
+            .await
+        })
+    }
+
+    // update all data keys of the session before sending once its key update policy
+    // says so. a peer without KEY_UPD_CAP leaves the keys in use until the sequence
+    // number reaches its limit. the future is boxed, the key update sends its own
+    // messages in the session, which come back here.
+    pub(crate) fn key_update_by_policy_async(
+        &mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move {
+            if !self
+                .common
+                .negotiate_info
+                .req_capabilities_sel
+                .contains(SpdmRequestCapabilityFlags::KEY_UPD_CAP)
+                || !self
+                    .common
+                    .negotiate_info
+                    .rsp_capabilities_sel
+                    .contains(SpdmResponseCapabilityFlags::KEY_UPD_CAP)
+            {
+                return Ok(());
+            }
+            let need_key_update = if let Some(s) = self.common.get_session_via_id(session_id) {
+                s.need_key_update()
+            } else {
+                false
+            };
+            if !need_key_update {
+                return Ok(());
+            }
+
+            info!("update session keys by policy\n");
+            self.send_receive_spdm_key_update_async(
+                session_id,
+                SpdmKeyUpdateOperation::SpdmUpdateAllKeys,
+            )
+            .await
+        })
     }
 }
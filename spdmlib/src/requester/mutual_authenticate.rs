@@ -2,30 +2,45 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use crate::common::block_on;
 use crate::{
+    common::SpdmFuture,
     error::{SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_STATE_LOCAL},
     message::SpdmKeyExchangeMutAuthAttributes,
 };
 
 use super::RequesterContext;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn session_based_mutual_authenticate(&mut self, session_id: u32) -> SpdmResult<()> {
-        self.common.construct_my_cert_chain()?;
+        block_on(self.session_based_mutual_authenticate_async(session_id))
+    }
+
+    pub fn session_based_mutual_authenticate_async(
+        &mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'_, SpdmResult<()>> {
+        Box::pin(async move {
+            self.common.construct_my_cert_chain()?;
 
-        let spdm_session = self
-            .common
-            .get_session_via_id(session_id)
-            .ok_or(SPDM_STATUS_INVALID_STATE_LOCAL)?;
+            let spdm_session = self
+                .common
+                .get_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_STATE_LOCAL)?;
 
-        let mut_auth_requested = spdm_session.get_mut_auth_requested();
-        match mut_auth_requested {
-            SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ => Ok(()),
-            SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_ENCAP_REQUEST
-            | SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_GET_DIGESTS => {
-                self.get_encapsulated_request_response(session_id, mut_auth_requested)
+            let mut_auth_requested = spdm_session.get_mut_auth_requested();
+            match mut_auth_requested {
+                SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ => Ok(()),
+                SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_ENCAP_REQUEST
+                | SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_GET_DIGESTS => {
+                    self.get_encapsulated_request_response_async(session_id, mut_auth_requested)
+                        .await
+                }
+                _ => Err(SPDM_STATUS_INVALID_MSG_FIELD),
             }
-            _ => Err(SPDM_STATUS_INVALID_MSG_FIELD),
-        }
+        })
     }
 }
@@ -16,6 +16,7 @@ use crate::protocol::SpdmMeasurementSummaryHashType;
 use crate::protocol::*;
 use crate::requester::*;
 extern crate alloc;
+use alloc::boxed::Box;
 
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_psk_exchange(
@@ -23,38 +24,52 @@ impl<'a> RequesterContext<'a> {
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
         psk_hint: Option<&SpdmPskHintStruct>,
     ) -> SpdmResult<u32> {
-        info!("send spdm psk exchange\n");
+        block_on(self.send_receive_spdm_psk_exchange_async(measurement_summary_hash_type, psk_hint))
+    }
 
-        let psk_hint = if let Some(hint) = psk_hint {
-            hint.clone()
-        } else {
-            SpdmPskHintStruct::default()
-        };
+    pub fn send_receive_spdm_psk_exchange_async<'b>(
+        &'b mut self,
+        measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+        psk_hint: Option<&'b SpdmPskHintStruct>,
+    ) -> SpdmFuture<'b, SpdmResult<u32>> {
+        Box::pin(async move {
+            info!("send spdm psk exchange\n");
+
+            let psk_hint = if let Some(hint) = psk_hint {
+                hint.clone()
+            } else {
+                SpdmPskHintStruct::default()
+            };
 
-        self.common
-            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestPskExchange, None);
-
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let half_session_id = self.common.get_next_half_session_id(true)?;
-        let send_used = self.encode_spdm_psk_exchange(
-            half_session_id,
-            measurement_summary_hash_type,
-            &psk_hint,
-            &mut send_buffer,
-        )?;
-
-        self.send_message(&send_buffer[..send_used])?;
-
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let receive_used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_psk_exchange_response(
-            half_session_id,
-            measurement_summary_hash_type,
-            &psk_hint,
-            &send_buffer[..send_used],
-            &receive_buffer[..receive_used],
-        )
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestPskExchange,
+                None,
+            );
+
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let half_session_id = self.common.get_next_half_session_id(true)?;
+            let send_used = self.encode_spdm_psk_exchange(
+                half_session_id,
+                measurement_summary_hash_type,
+                &psk_hint,
+                &mut send_buffer,
+            )?;
+
+            self.send_message_async(&send_buffer[..send_used]).await?;
+
+            // Receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let receive_used = self
+                .receive_message_async(&mut receive_buffer, false)
+                .await?;
+            self.handle_spdm_psk_exchange_response(
+                half_session_id,
+                measurement_summary_hash_type,
+                &psk_hint,
+                &send_buffer[..send_used],
+                &receive_buffer[..receive_used],
+            )
+        })
     }
 
     pub fn encode_spdm_psk_exchange(
@@ -66,8 +81,10 @@ impl<'a> RequesterContext<'a> {
     ) -> SpdmResult<usize> {
         let mut writer = Writer::init(buf);
 
+        // the requester context is H fresh random bytes.
+        let psk_context_size = self.common.get_hash_size();
         let mut psk_context = [0u8; MAX_SPDM_PSK_CONTEXT_SIZE];
-        crypto::rand::get_random(&mut psk_context)?;
+        crypto::rand::get_random(&mut psk_context[..psk_context_size as usize])?;
 
         let mut opaque;
         if self.common.negotiate_info.spdm_version_sel.get_u8()
@@ -108,7 +125,7 @@ impl<'a> RequesterContext<'a> {
                 req_session_id: half_session_id,
                 psk_hint: psk_hint.clone(),
                 psk_context: SpdmPskContextStruct {
-                    data_size: self.common.negotiate_info.base_hash_sel.get_size(),
+                    data_size: psk_context_size,
                     data: psk_context,
                 },
                 opaque,
@@ -171,10 +188,14 @@ impl<'a> RequesterContext<'a> {
                                 0
                             };
 
-                            let session_id = ((psk_exchange_rsp.rsp_session_id as u32) << 16)
-                                + half_session_id as u32;
+                            let session_id = self.common.get_new_session_id(
+                                half_session_id,
+                                psk_exchange_rsp.rsp_session_id,
+                            )?;
                             let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
                             let message_a = self.common.runtime_info.message_a.clone();
+                            let key_update_policy = self.common.config_info.key_update_policy;
+                            let sequence_number_mode = self.common.config_info.sequence_number_mode;
 
                             let session = self
                                 .common
@@ -184,6 +205,7 @@ impl<'a> RequesterContext<'a> {
                             session.setup(session_id)?;
 
                             session.set_use_psk(true);
+                            session.set_key_update_policy(key_update_policy);
 
                             session.set_crypto_param(
                                 base_hash_algo,
@@ -192,6 +214,7 @@ impl<'a> RequesterContext<'a> {
                                 key_schedule_algo,
                             );
                             session.set_transport_param(sequence_number_count, max_random_count);
+                            session.set_sequence_number_mode(sequence_number_mode);
 
                             session.runtime_info.psk_hint = Some(psk_hint.clone());
                             session.runtime_info.message_a = message_a;
@@ -14,56 +14,70 @@ use alloc::boxed::Box;
 
 impl<'a> RequesterContext<'a> {
     pub fn send_receive_spdm_psk_finish(&mut self, session_id: u32) -> SpdmResult {
-        info!("send spdm psk_finish\n");
+        block_on(self.send_receive_spdm_psk_finish_async(session_id))
+    }
 
-        if self.common.get_session_via_id(session_id).is_none() {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+    pub fn send_receive_spdm_psk_finish_async<'b>(
+        &'b mut self,
+        session_id: u32,
+    ) -> SpdmFuture<'b, SpdmResult> {
+        Box::pin(async move {
+            info!("send spdm psk_finish\n");
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestPskFinish,
-            Some(session_id),
-        );
-
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let res = self.encode_spdm_psk_finish(session_id, &mut send_buffer);
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return Err(res.err().unwrap());
-        }
-        let send_used = res.unwrap();
-        let res = self.send_secured_message(session_id, &send_buffer[..send_used], false);
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return res;
-        }
+            if self.common.get_session_via_id(session_id).is_none() {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let res = self.receive_secured_message(session_id, &mut receive_buffer, false);
-        if res.is_err() {
-            let _ = self
-                .common
-                .get_session_via_id(session_id)
-                .unwrap()
-                .teardown(session_id);
-            return Err(res.err().unwrap());
-        }
-        let receive_used = res.unwrap();
-        let res = self.handle_spdm_psk_finish_response(session_id, &receive_buffer[..receive_used]);
-        if res.is_err() {
-            if let Some(session) = self.common.get_session_via_id(session_id) {
-                let _ = session.teardown(session_id);
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestPskFinish,
+                Some(session_id),
+            );
+
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let res = self.encode_spdm_psk_finish(session_id, &mut send_buffer);
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return Err(res.err().unwrap());
             }
-        }
-        res
+            let send_used = res.unwrap();
+            let res = self
+                .send_secured_message_async(session_id, &send_buffer[..send_used], false)
+                .await;
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return res;
+            }
+
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let res = self
+                .receive_secured_message_async(session_id, &mut receive_buffer, false)
+                .await;
+            if res.is_err() {
+                let _ = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .unwrap()
+                    .teardown(session_id);
+                return Err(res.err().unwrap());
+            }
+            let receive_used = res.unwrap();
+            let res =
+                self.handle_spdm_psk_finish_response(session_id, &receive_buffer[..receive_used]);
+            if res.is_err() {
+                if let Some(session) = self.common.get_session_via_id(session_id) {
+                    let _ = session.teardown(session_id);
+                }
+            }
+            res
+        })
     }
 
     pub fn encode_spdm_psk_finish(&mut self, session_id: u32, buf: &mut [u8]) -> SpdmResult<usize> {
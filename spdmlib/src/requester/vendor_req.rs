@@ -6,6 +6,9 @@ use crate::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_F
 use crate::message::*;
 use crate::requester::*;
 
+extern crate alloc;
+use alloc::boxed::Box;
+
 impl<'a> RequesterContext<'a> {
     pub fn send_spdm_vendor_defined_request(
         &mut self,
@@ -14,52 +17,74 @@ impl<'a> RequesterContext<'a> {
         vendor_id_struct: VendorIDStruct,
         req_payload_struct: VendorDefinedReqPayloadStruct,
     ) -> SpdmResult<VendorDefinedRspPayloadStruct> {
-        info!("send vendor defined request\n");
-
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestVendorDefinedRequest,
+        block_on(self.send_spdm_vendor_defined_request_async(
             session_id,
-        );
+            standard_id,
+            vendor_id_struct,
+            req_payload_struct,
+        ))
+    }
+
+    pub fn send_spdm_vendor_defined_request_async<'b>(
+        &'b mut self,
+        session_id: Option<u32>,
+        standard_id: RegistryOrStandardsBodyID,
+        vendor_id_struct: VendorIDStruct,
+        req_payload_struct: VendorDefinedReqPayloadStruct,
+    ) -> SpdmFuture<'b, SpdmResult<VendorDefinedRspPayloadStruct>> {
+        Box::pin(async move {
+            info!("send vendor defined request\n");
 
-        // clear cache data
-        self.common.reset_context();
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestVendorDefinedRequest,
+                session_id,
+            );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let mut writer = Writer::init(&mut send_buffer);
-        let request = SpdmMessage {
-            header: SpdmMessageHeader {
-                version: self.common.negotiate_info.spdm_version_sel,
-                request_response_code: SpdmRequestResponseCode::SpdmRequestVendorDefinedRequest,
-            },
-            payload: SpdmMessagePayload::SpdmVendorDefinedRequest(
-                SpdmVendorDefinedRequestPayload {
-                    standard_id,
-                    vendor_id: vendor_id_struct,
-                    req_payload: req_payload_struct,
+            // clear cache data
+            self.common.reset_context();
+
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let mut writer = Writer::init(&mut send_buffer);
+            let request = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: self.common.negotiate_info.spdm_version_sel,
+                    request_response_code: SpdmRequestResponseCode::SpdmRequestVendorDefinedRequest,
                 },
-            ),
-        };
-        let used = request.spdm_encode(&mut self.common, &mut writer)?;
+                payload: SpdmMessagePayload::SpdmVendorDefinedRequest(
+                    SpdmVendorDefinedRequestPayload {
+                        standard_id,
+                        vendor_id: vendor_id_struct,
+                        req_payload: req_payload_struct,
+                    },
+                ),
+            };
+            let used = request.spdm_encode(&mut self.common, &mut writer)?;
 
-        match session_id {
-            Some(session_id) => {
-                self.send_secured_message(session_id, &send_buffer[..used], false)?;
-            }
-            None => {
-                self.send_message(&send_buffer[..used])?;
+            match session_id {
+                Some(session_id) => {
+                    self.send_secured_message_async(session_id, &send_buffer[..used], false)
+                        .await?;
+                }
+                None => {
+                    self.send_message_async(&send_buffer[..used]).await?;
+                }
             }
-        }
 
-        //receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let receive_used = match session_id {
-            Some(session_id) => {
-                self.receive_secured_message(session_id, &mut receive_buffer, false)?
-            }
-            None => self.receive_message(&mut receive_buffer, false)?,
-        };
+            //receive
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let receive_used = match session_id {
+                Some(session_id) => {
+                    self.receive_secured_message_async(session_id, &mut receive_buffer, false)
+                        .await?
+                }
+                None => {
+                    self.receive_message_async(&mut receive_buffer, false)
+                        .await?
+                }
+            };
 
-        self.handle_spdm_vendor_defined_respond(session_id, &receive_buffer[..receive_used])
+            self.handle_spdm_vendor_defined_respond(session_id, &receive_buffer[..receive_used])
+        })
     }
 
     pub fn handle_spdm_vendor_defined_respond(
@@ -10,14 +10,19 @@ use crate::responder::ResponderContext;
 
 type SpdmAppMessageCbRes = ([u8; MAX_SPDM_MSG_SIZE], usize);
 
+// app_message_type is the transport specific type of the application message,
+// e.g. the MCTP message type, and the response is sent back with the same type.
+type SpdmDispatchSecuredAppMessageCb = fn(
+    ctx: &mut ResponderContext,
+    session_id: u32,
+    app_message_type: u8,
+    app_buffer: &[u8],
+    auxiliary_app_data: &[u8],
+) -> SpdmResult<SpdmAppMessageCbRes>;
+
 #[derive(Clone)]
 pub struct SpdmAppMessageHandler {
-    pub dispatch_secured_app_message_cb: fn(
-        ctx: &mut ResponderContext,
-        session_id: u32,
-        app_buffer: &[u8],
-        auxiliary_app_data: &[u8],
-    ) -> SpdmResult<SpdmAppMessageCbRes>,
+    pub dispatch_secured_app_message_cb: SpdmDispatchSecuredAppMessageCb,
 }
 
 static SPDM_APP_MESSAGE_HANDLER: OnceCell<SpdmAppMessageHandler> = OnceCell::uninit();
@@ -25,6 +30,7 @@ static SPDM_APP_MESSAGE_HANDLER: OnceCell<SpdmAppMessageHandler> = OnceCell::uni
 static DEFAULT: SpdmAppMessageHandler = SpdmAppMessageHandler {
     dispatch_secured_app_message_cb: |_ctx: &mut ResponderContext,
                                       _session_id: u32,
+                                      _app_message_type: u8,
                                       _app_buffer: &[u8],
                                       _auxiliary_app_data: &[u8]|
      -> SpdmResult<SpdmAppMessageCbRes> { unimplemented!() },
@@ -35,14 +41,22 @@ pub fn register(context: SpdmAppMessageHandler) -> bool {
     SPDM_APP_MESSAGE_HANDLER.try_init_once(|| context).is_ok()
 }
 
+// reading does not lock in DEFAULT, a later register() still takes effect.
 pub fn dispatch_secured_app_message_cb(
     ctx: &mut ResponderContext,
     session_id: u32,
+    app_message_type: u8,
     app_buffer: &[u8],
     auxiliary_app_data: &[u8],
 ) -> SpdmResult<SpdmAppMessageCbRes> {
     (SPDM_APP_MESSAGE_HANDLER
-        .try_get_or_init(|| DEFAULT.clone())
+        .get()
         .unwrap_or(&DEFAULT)
-        .dispatch_secured_app_message_cb)(ctx, session_id, app_buffer, auxiliary_app_data)
+        .dispatch_secured_app_message_cb)(
+        ctx,
+        session_id,
+        app_message_type,
+        app_buffer,
+        auxiliary_app_data,
+    )
 }
@@ -3,13 +3,17 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use super::app_message_handler::dispatch_secured_app_message_cb;
-use crate::common::SpdmConnectionState;
+use super::session_policy::evict_session_cb;
 use crate::common::{session::SpdmSessionState, SpdmDeviceIo, SpdmTransportEncap};
+use crate::common::{SpdmConnectionState, INVALID_SESSION_ID};
 use crate::config;
 use crate::error::{SpdmResult, SPDM_STATUS_UNSUPPORTED_CAP};
 use crate::message::*;
-use crate::protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags};
+use crate::protocol::{
+    SpdmHkdfOutputKeyingMaterial, SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags,
+};
 use codec::{Codec, Reader, Writer};
+use zeroize::Zeroizing;
 
 pub struct ResponderContext<'a> {
     pub common: crate::common::SpdmContext<'a>,
@@ -78,15 +82,6 @@ impl<'a> ResponderContext<'a> {
                 self.common
                     .runtime_info
                     .set_connection_state(SpdmConnectionState::SpdmConnectionAuthenticated);
-            } else if opcode == SpdmRequestResponseCode::SpdmResponseFinishRsp.get_u8() {
-                let session = self
-                    .common
-                    .get_session_via_id(self.common.runtime_info.get_last_session_id().unwrap())
-                    .unwrap();
-                session.set_session_state(
-                    crate::common::session::SpdmSessionState::SpdmSessionEstablished,
-                );
-                self.common.runtime_info.set_last_session_id(None);
             }
         }
         result
@@ -98,14 +93,17 @@ impl<'a> ResponderContext<'a> {
         send_buffer: &[u8],
         is_app_message: bool,
     ) -> SpdmResult {
-        if !is_app_message
-            && self.common.negotiate_info.req_data_transfer_size_sel != 0
+        if is_app_message {
+            let app_message_type = self.common.get_app_message_type(session_id)?;
+            return self.send_secured_app_message(session_id, send_buffer, app_message_type);
+        }
+        if self.common.negotiate_info.req_data_transfer_size_sel != 0
             && send_buffer.len() > self.common.negotiate_info.req_data_transfer_size_sel as usize
         {
             let mut err_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
             let mut writer = Writer::init(&mut err_buffer);
             self.write_spdm_error(SpdmErrorCode::SpdmErrorResponseTooLarge, 0, &mut writer);
-            return self.send_secured_message(session_id, writer.used_slice(), is_app_message);
+            return self.send_secured_message(session_id, writer.used_slice(), false);
         }
 
         let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
@@ -114,7 +112,7 @@ impl<'a> ResponderContext<'a> {
             send_buffer,
             &mut transport_buffer,
             false,
-            is_app_message,
+            None,
         )?;
         let result = self.common.device_io.send(&transport_buffer[..used]);
         if result.is_ok() {
@@ -136,11 +134,70 @@ impl<'a> ResponderContext<'a> {
         result
     }
 
+    // send an application message of the given transport specific type in a session.
+    pub fn send_secured_app_message(
+        &mut self,
+        session_id: u32,
+        send_buffer: &[u8],
+        app_message_type: u8,
+    ) -> SpdmResult {
+        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+        let used = self.common.encode_secured_message(
+            session_id,
+            send_buffer,
+            &mut transport_buffer,
+            false,
+            Some(app_message_type),
+        )?;
+        self.common.device_io.send(&transport_buffer[..used])
+    }
+
+    // derive a secret for a downstream protocol from the export master secret of a session.
+    pub fn export_secret(
+        &self,
+        session_id: u32,
+        label: &[u8],
+        context: &[u8],
+        length: u16,
+    ) -> SpdmResult<SpdmHkdfOutputKeyingMaterial> {
+        self.common
+            .export_secret(session_id, label, context, length)
+    }
+
+    // make sure a slot is free for a new session, asking the session policy to
+    // evict a session when all of them are taken.
+    pub fn reserve_session_slot(&mut self) -> bool {
+        if self
+            .common
+            .get_immutable_session_via_id(INVALID_SESSION_ID)
+            .is_some()
+        {
+            return true;
+        }
+
+        let session_id = evict_session_cb(self);
+        match session_id {
+            Some(session_id) if session_id != INVALID_SESSION_ID => {
+                if let Some(session) = self.common.get_session_via_id(session_id) {
+                    info!("session {:08x} evicted\n", session_id);
+                    let _ = session.teardown(session_id);
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    }
+
     pub fn process_message(
         &mut self,
         timeout: usize,
         auxiliary_app_data: &[u8],
     ) -> Result<bool, (usize, [u8; config::RECEIVER_BUFFER_SIZE])> {
+        // sweep before the receive, a message which arrived in time must not find its
+        // session torn down while it waited in a blocking receive.
+        self.teardown_expired_sessions();
         let mut receive_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
         match self.receive_message(&mut receive_buffer[..], timeout) {
             Ok((used, secured_message)) => {
@@ -153,11 +210,12 @@ impl<'a> ResponderContext<'a> {
                         .get_session_via_id(session_id)
                         .ok_or((used, receive_buffer))?;
 
-                    let mut app_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+                    // decrypted plaintext, scrubbed when it goes out of scope.
+                    let mut app_buffer = Zeroizing::new([0u8; config::RECEIVER_BUFFER_SIZE]);
 
                     let decode_size = spdm_session.decode_spdm_secured_message(
                         &receive_buffer[..used],
-                        &mut app_buffer,
+                        &mut app_buffer[..],
                         true,
                     );
                     if decode_size.is_err() {
@@ -165,31 +223,24 @@ impl<'a> ResponderContext<'a> {
                     }
                     let decode_size = decode_size.unwrap();
 
-                    let mut spdm_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+                    let mut spdm_buffer = Zeroizing::new([0u8; config::MAX_SPDM_MSG_SIZE]);
                     let decap_result = self
                         .common
                         .transport_encap
-                        .decap_app(&app_buffer[0..decode_size], &mut spdm_buffer);
+                        .decap_app(&app_buffer[0..decode_size], &mut spdm_buffer[..]);
                     match decap_result {
                         Err(_) => Err((used, receive_buffer)),
-                        Ok((decode_size, is_app_message)) => {
-                            if !is_app_message {
-                                Ok(self
-                                    .dispatch_secured_message(
-                                        session_id,
-                                        &spdm_buffer[0..decode_size],
-                                    )
-                                    .is_ok())
-                            } else {
-                                Ok(self
-                                    .dispatch_secured_app_message(
-                                        session_id,
-                                        &spdm_buffer[..decode_size],
-                                        auxiliary_app_data,
-                                    )
-                                    .is_ok())
-                            }
-                        }
+                        Ok((decode_size, None)) => Ok(self
+                            .dispatch_secured_message(session_id, &spdm_buffer[0..decode_size])
+                            .is_ok()),
+                        Ok((decode_size, Some(app_message_type))) => Ok(self
+                            .dispatch_secured_app_message(
+                                session_id,
+                                app_message_type,
+                                &spdm_buffer[..decode_size],
+                                auxiliary_app_data,
+                            )
+                            .is_ok()),
                     }
                 } else {
                     Ok(self.dispatch_message(&receive_buffer[0..used]).is_ok())
@@ -199,6 +250,28 @@ impl<'a> ResponderContext<'a> {
         }
     }
 
+    // tear down every session that saw no traffic within twice its heartbeat period.
+    pub fn teardown_expired_sessions(&mut self) {
+        if !self.common.session.iter().any(|s| {
+            s.heartbeat_period != 0
+                && s.get_session_state() == SpdmSessionState::SpdmSessionEstablished
+        }) {
+            return;
+        }
+        let now = if let Some(now) = crate::time::get_monotonic_time() {
+            now
+        } else {
+            return;
+        };
+        for session in self.common.session.iter_mut() {
+            if session.is_heartbeat_expired(now) {
+                let session_id = session.get_session_id();
+                info!("session {:08x} heartbeat expired\n", session_id);
+                let _ = session.teardown(session_id);
+            }
+        }
+    }
+
     // Debug note: receive_buffer is used as return value, when receive got a command
     // whose value is not normal, will return Err to caller to handle the raw packet,
     // So can't swap transport_buffer and receive_buffer, even though it should be by
@@ -363,14 +436,21 @@ impl<'a> ResponderContext<'a> {
     fn dispatch_secured_app_message(
         &mut self,
         session_id: u32,
+        app_message_type: u8,
         bytes: &[u8],
         auxiliary_app_data: &[u8],
     ) -> SpdmResult {
-        debug!("dispatching secured app message\n");
+        debug!("dispatching secured app message {:02x}\n", app_message_type);
 
-        let (rsp_app_buffer, size) =
-            dispatch_secured_app_message_cb(self, session_id, bytes, auxiliary_app_data).unwrap();
-        self.send_secured_message(session_id, &rsp_app_buffer[..size], true)
+        let (rsp_app_buffer, size) = dispatch_secured_app_message_cb(
+            self,
+            session_id,
+            app_message_type,
+            bytes,
+            auxiliary_app_data,
+        )?;
+        // the response goes back as the same application protocol.
+        self.send_secured_app_message(session_id, &rsp_app_buffer[..size], app_message_type)
     }
     pub fn dispatch_message(&mut self, bytes: &[u8]) -> SpdmResult {
         let mut reader = Reader::init(bytes);
@@ -418,16 +498,14 @@ impl<'a> ResponderContext<'a> {
                             .rsp_capabilities_sel
                             .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
                     if in_clear_text {
-                        if let Some(session_id) = self.common.runtime_info.get_last_session_id() {
-                            if let Some(session) =
-                                self.common.get_immutable_session_via_id(session_id)
-                            {
-                                if session.get_session_state()
-                                    == SpdmSessionState::SpdmSessionHandshaking
-                                {
-                                    return self.handle_spdm_finish(session_id, bytes);
-                                }
-                            }
+                        // a FINISH in the clear carries no session id, it can only
+                        // complete the one session that is handshaking.
+                        let mut handshaking = self.common.session.iter().filter(|session| {
+                            session.get_session_state() == SpdmSessionState::SpdmSessionHandshaking
+                        });
+                        if let (Some(session), None) = (handshaking.next(), handshaking.next()) {
+                            let session_id = session.get_session_id();
+                            return self.handle_spdm_finish(session_id, bytes);
                         }
                     }
 
@@ -8,9 +8,9 @@ use crate::{
     common::SpdmCodec,
     config, crypto,
     error::{
-        SpdmResult, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_CERT,
-        SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_MSG_SIZE, SPDM_STATUS_INVALID_PARAMETER,
-        SPDM_STATUS_INVALID_STATE_LOCAL,
+        SpdmResult, SPDM_STATUS_CERT_REVOKED, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER,
+        SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_MSG_SIZE,
+        SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL,
     },
     message::{
         SpdmCertificateResponsePayload, SpdmGetCertificateRequestPayload, SpdmMessage,
@@ -149,20 +149,25 @@ impl<'a> ResponderContext<'a> {
             return Ok(true);
         }
 
-        let result = self.verify_spdm_certificate_chain().map(|_| {
-            self.common.peer_info.peer_cert_chain[self.common.encap_context.req_slot_id as usize] =
-                self.common.peer_info.peer_cert_chain_temp.clone();
-            self.common
-                .runtime_info
-                .set_peer_used_cert_chain_slot_id(self.common.encap_context.req_slot_id);
-            false
-        });
+        let result = self
+            .verify_spdm_certificate_chain()
+            .map(|peer_root_cert_index| {
+                self.common.peer_info.peer_cert_chain
+                    [self.common.encap_context.req_slot_id as usize] =
+                    self.common.peer_info.peer_cert_chain_temp.clone();
+                self.common.peer_info.peer_root_cert_index
+                    [self.common.encap_context.req_slot_id as usize] = peer_root_cert_index;
+                self.common
+                    .runtime_info
+                    .set_peer_used_cert_chain_slot_id(self.common.encap_context.req_slot_id);
+                false
+            });
 
         self.common.peer_info.peer_cert_chain_temp = None;
         result
     }
 
-    pub fn verify_spdm_certificate_chain(&mut self) -> SpdmResult {
+    pub fn verify_spdm_certificate_chain(&mut self) -> SpdmResult<Option<usize>> {
         //
         // 1. Verify the integrity of cert chain
         //
@@ -199,20 +204,7 @@ impl<'a> ResponderContext<'a> {
         info!("1. get runtime_peer_cert_chain_data!\n");
 
         //
-        // 1.1 verify the integrity of the chain
-        //
-        if crypto::cert_operation::verify_cert_chain(
-            &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
-        )
-        .is_err()
-        {
-            error!("cert_chain verification - fail! - TBD later\n");
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
-        info!("1.1. integrity of cert_chain is verified!\n");
-
-        //
-        // 1.2 verify the root cert hash
+        // 1.1 verify the root cert hash
         //
         let (root_cert_begin, root_cert_end) = crypto::cert_operation::get_cert_from_cert_chain(
             &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
@@ -233,29 +225,32 @@ impl<'a> ResponderContext<'a> {
             error!("root_hash - fail!\n");
             return Err(SPDM_STATUS_INVALID_CERT);
         }
-        info!("1.2. root cert hash is verified!\n");
+        info!("1.1. root cert hash is verified!\n");
 
         //
-        // 2. verify the authority of cert chain if provisioned
+        // 2. verify the SPDM certificate profile, the integrity and
+        //    the authority of cert chain against the provisioned root certs
         //
-        if let Some(peer_root_cert_data) = &self.common.provision_info.peer_root_cert_data {
-            if root_cert.len() != peer_root_cert_data.data_size as usize {
-                error!("root_cert size - fail!\n");
-                debug!(
-                    "provision root_cert data size - {:?}\n",
-                    peer_root_cert_data.data_size
-                );
-                debug!("runtime root_cert data size - {:?}\n", root_cert.len());
-                return Err(SPDM_STATUS_INVALID_CERT);
+        let peer_root_cert_index = match self.common.verify_peer_cert_chain(
+            &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
+            true,
+        ) {
+            Ok(index) => index,
+            Err(e) if e == SPDM_STATUS_CERT_REVOKED => {
+                error!("cert_chain verification - revoked!\n");
+                return Err(SPDM_STATUS_CERT_REVOKED);
             }
-            if root_cert[..] != peer_root_cert_data.data[..peer_root_cert_data.data_size as usize] {
-                error!("root_cert data - fail!\n");
+            Err(_) => {
+                error!("cert_chain verification - fail!\n");
                 return Err(SPDM_STATUS_INVALID_CERT);
             }
-            info!("2. root cert is verified!\n");
+        };
+        match peer_root_cert_index {
+            Some(index) => info!("2. cert_chain is anchored on root cert {:?}!\n", index),
+            None => info!("2. integrity of cert_chain is verified!\n"),
         }
 
         info!("cert_chain verification - pass!\n");
-        Ok(())
+        Ok(peer_root_cert_index)
     }
 }
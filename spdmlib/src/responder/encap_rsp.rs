@@ -85,21 +85,37 @@ impl<'a> ResponderContext<'a> {
             SpdmRequestResponseCode::SpdmRequestGetEncapsulatedRequest.get_u8(),
             &mut writer,
         );
-        self.write_encap_response_ack_response(bytes, &mut writer);
+        let used = if self
+            .write_encap_response_ack_response(bytes, &mut writer)
+            .is_ok()
+        {
+            writer.used()
+        } else {
+            // start over, a partial ack must not precede the error.
+            let mut writer = Writer::init(&mut encap_response_ack);
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidResponseCode, 0, &mut writer);
+            writer.used()
+        };
 
-        self.send_secured_message(session_id, writer.used_slice(), false)
+        self.send_secured_message(session_id, &encap_response_ack[..used], false)
     }
 
-    fn write_encap_response_ack_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+    // OUT Ok the ack or an error response is written
+    // OUT Error the encapsulated response failed, the partial ack must be dropped
+    fn write_encap_response_ack_response(
+        &mut self,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) -> SpdmResult {
         let mut reader = Reader::init(bytes);
         if let Some(request_header) = SpdmMessageHeader::read(&mut reader) {
             if request_header.version != self.common.negotiate_info.spdm_version_sel {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorVersionMismatch, 0, writer);
-                return;
+                return Ok(());
             }
         } else {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
-            return;
+            return Ok(());
         };
 
         let encap_response_payload = if let Some(encap_response_payload) =
@@ -108,15 +124,10 @@ impl<'a> ResponderContext<'a> {
             encap_response_payload
         } else {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
-            return;
+            return Ok(());
         };
 
-        if self
-            .process_encapsulated_response(&encap_response_payload, &bytes[reader.used()..], writer)
-            .is_err()
-        {
-            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidResponseCode, 0, writer);
-        }
+        self.process_encapsulated_response(&encap_response_payload, &bytes[reader.used()..], writer)
     }
 
     fn encap_check_version_cap_state(&mut self, request_response_code: u8, writer: &mut Writer) {
@@ -34,7 +34,18 @@ impl<'a> ResponderContext<'a> {
         let mut writer = Writer::init(&mut send_buffer);
         self.write_spdm_finish_response(session_id, bytes, &mut writer)?;
         if in_clear_text {
-            self.send_message(writer.used_slice())
+            self.send_message(writer.used_slice())?;
+            // change state after message is sent.
+            if writer.used_slice()[1] == SpdmRequestResponseCode::SpdmResponseFinishRsp.get_u8() {
+                let session = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .ok_or(SPDM_STATUS_INVALID_STATE_LOCAL)?;
+                session.set_session_state(
+                    crate::common::session::SpdmSessionState::SpdmSessionEstablished,
+                );
+            }
+            Ok(())
         } else {
             self.send_secured_message(session_id, writer.used_slice(), false)
         }
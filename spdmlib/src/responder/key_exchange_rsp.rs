@@ -8,10 +8,12 @@ use crate::common::ManagedBuffer12Sign;
 use crate::common::SpdmCodec;
 use crate::common::SpdmConnectionState;
 use crate::common::SpdmOpaqueSupport;
+use crate::common::INVALID_HALF_SESSION_ID;
 use crate::crypto;
 use crate::error::{
     SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_INVALID_MSG_FIELD,
     SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_INVALID_STATE_PEER,
+    SPDM_STATUS_SESSION_NUMBER_EXCEED,
 };
 use crate::protocol::*;
 use crate::responder::*;
@@ -199,12 +201,38 @@ impl<'a> ResponderContext<'a> {
         let final_key = final_key.unwrap();
         debug!("!!! final_key : {:02x?}\n", final_key.as_ref());
 
+        let cert_chain_hash = self.common.get_certchain_hash_local(false, slot_id);
+        if cert_chain_hash.is_none() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        if key_exchange_req.req_session_id == INVALID_HALF_SESSION_ID {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+
+        // only a valid request may evict a session for its slot.
+        if !self.reserve_session_slot() {
+            error!("!!! too many sessions : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
+            return Err(SPDM_STATUS_SESSION_NUMBER_EXCEED);
+        }
+
         let rsp_session_id = self.common.get_next_half_session_id(false);
         if rsp_session_id.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
             return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
         }
         let rsp_session_id = rsp_session_id.unwrap();
+        let session_id = if let Ok(session_id) = self
+            .common
+            .get_new_session_id(key_exchange_req.req_session_id, rsp_session_id)
+        {
+            session_id
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        };
 
         // create session structure
         let hash_algo = self.common.negotiate_info.base_hash_sel;
@@ -213,14 +241,10 @@ impl<'a> ResponderContext<'a> {
         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
         let sequence_number_count = self.common.transport_encap.get_sequence_number_count();
         let max_random_count = self.common.transport_encap.get_max_random_count();
+        let sequence_number_mode = self.common.config_info.sequence_number_mode;
 
         let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
         let message_a = self.common.runtime_info.message_a.clone();
-        let cert_chain_hash = self.common.get_certchain_hash_local(false, slot_id);
-        if cert_chain_hash.is_none() {
-            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
-            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
-        }
 
         let session = self.common.get_next_avaiable_session();
         if session.is_none() {
@@ -235,13 +259,13 @@ impl<'a> ResponderContext<'a> {
         let mut_auth_req = SpdmKeyExchangeMutAuthAttributes::empty();
 
         let session = session.unwrap();
-        let session_id = ((rsp_session_id as u32) << 16) + key_exchange_req.req_session_id as u32;
         session.setup(session_id).unwrap();
         session.set_use_psk(false);
         session.set_slot_id(slot_id as u8);
         session.set_crypto_param(hash_algo, dhe_algo, aead_algo, key_schedule_algo);
         session.set_mut_auth_requested(mut_auth_req);
         session.set_transport_param(sequence_number_count, max_random_count);
+        session.set_sequence_number_mode(sequence_number_mode);
         if session.set_dhe_secret(spdm_version_sel, final_key).is_err() {
             let _ = session.teardown(session_id);
             self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
@@ -427,12 +451,6 @@ impl<'a> ResponderContext<'a> {
 
         session.set_session_state(crate::common::session::SpdmSessionState::SpdmSessionHandshaking);
 
-        if in_clear_text {
-            self.common
-                .runtime_info
-                .set_last_session_id(Some(session_id));
-        }
-
         Ok(())
     }
 
@@ -29,6 +29,7 @@ mod error_rsp;
 mod vendor_rsp;
 
 pub mod app_message_handler;
+pub mod session_policy;
 
 pub use context::ResponderContext;
 
@@ -6,6 +6,7 @@ use crate::common::opaque::SpdmOpaqueStruct;
 use crate::common::SpdmCodec;
 use crate::common::SpdmConnectionState;
 use crate::common::SpdmOpaqueSupport;
+use crate::common::INVALID_HALF_SESSION_ID;
 use crate::common::INVALID_SLOT;
 use crate::crypto;
 use crate::error::SpdmResult;
@@ -13,6 +14,7 @@ use crate::error::SPDM_STATUS_CRYPTO_ERROR;
 use crate::error::SPDM_STATUS_INVALID_MSG_FIELD;
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
 use crate::error::SPDM_STATUS_INVALID_STATE_PEER;
+use crate::error::SPDM_STATUS_SESSION_NUMBER_EXCEED;
 use crate::message::*;
 use crate::protocol::*;
 use crate::responder::*;
@@ -98,6 +100,11 @@ impl<'a> ResponderContext<'a> {
             }
 
             psk_hint = psk_exchange_req.psk_hint.clone();
+            if !secret::psk::is_psk_provisioned(&psk_hint) {
+                error!("!!! psk_exchange req : unknown psk hint !!!\n");
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
 
             if let Some(secured_message_version_list) = psk_exchange_req
                 .opaque
@@ -164,26 +171,48 @@ impl<'a> ResponderContext<'a> {
             .negotiate_info
             .rsp_capabilities_sel
             .contains(SpdmResponseCapabilityFlags::PSK_CAP_WITHOUT_CONTEXT);
+        // a fresh H bytes context when PSK_CAP_WITH_CONTEXT, none otherwise.
         let psk_context_size = if psk_without_context {
             0u16
         } else {
-            MAX_SPDM_PSK_CONTEXT_SIZE as u16
+            self.common.get_hash_size()
         };
         let mut psk_context = [0u8; MAX_SPDM_PSK_CONTEXT_SIZE];
-        if psk_without_context {
-            let res = crypto::rand::get_random(&mut psk_context);
+        if !psk_without_context {
+            let res = crypto::rand::get_random(&mut psk_context[..psk_context_size as usize]);
             if res.is_err() {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             }
         }
 
+        if psk_exchange_req.as_ref().unwrap().req_session_id == INVALID_HALF_SESSION_ID {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+
+        // only a valid request may evict a session for its slot.
+        if !self.reserve_session_slot() {
+            error!("!!! too many sessions : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
+            return Err(SPDM_STATUS_SESSION_NUMBER_EXCEED);
+        }
+
         let rsp_session_id = self.common.get_next_half_session_id(false);
         if rsp_session_id.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
             return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
         }
         let rsp_session_id = rsp_session_id.unwrap();
+        let session_id = if let Ok(session_id) = self.common.get_new_session_id(
+            psk_exchange_req.as_ref().unwrap().req_session_id,
+            rsp_session_id,
+        ) {
+            session_id
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        };
 
         // create session structure
         let hash_algo = self.common.negotiate_info.base_hash_sel;
@@ -192,6 +221,7 @@ impl<'a> ResponderContext<'a> {
         let key_schedule_algo = self.common.negotiate_info.key_schedule_sel;
         let sequence_number_count = self.common.transport_encap.get_sequence_number_count();
         let max_random_count = self.common.transport_encap.get_max_random_count();
+        let sequence_number_mode = self.common.config_info.sequence_number_mode;
 
         let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
         let message_a = self.common.runtime_info.message_a.clone();
@@ -204,13 +234,12 @@ impl<'a> ResponderContext<'a> {
         }
 
         let session = session.unwrap();
-        let session_id =
-            ((rsp_session_id as u32) << 16) + psk_exchange_req.unwrap().req_session_id as u32;
         session.setup(session_id).unwrap();
         session.set_use_psk(true);
 
         session.set_crypto_param(hash_algo, dhe_algo, aead_algo, key_schedule_algo);
         session.set_transport_param(sequence_number_count, max_random_count);
+        session.set_sequence_number_mode(sequence_number_mode);
 
         session.runtime_info.psk_hint = Some(psk_hint);
         session.runtime_info.message_a = message_a;
@@ -0,0 +1,49 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use conquer_once::spin::OnceCell;
+
+use crate::common::session::SpdmSessionState;
+use crate::responder::ResponderContext;
+
+#[derive(Clone)]
+pub struct SpdmSessionPolicy {
+    // called when a new session needs a slot and all of them are taken.
+    // returns the session to tear down for it, or None to answer the request
+    // with ERROR(SessionLimitExceeded).
+    pub evict_session_cb: fn(ctx: &ResponderContext) -> Option<u32>,
+}
+
+static SPDM_SESSION_POLICY: OnceCell<SpdmSessionPolicy> = OnceCell::uninit();
+
+static DEFAULT: SpdmSessionPolicy = SpdmSessionPolicy {
+    evict_session_cb: |_ctx: &ResponderContext| -> Option<u32> { None },
+};
+
+pub fn register(context: SpdmSessionPolicy) -> bool {
+    SPDM_SESSION_POLICY.try_init_once(|| context).is_ok()
+}
+
+// reading does not lock in DEFAULT, a later register() still takes effect.
+pub fn evict_session_cb(ctx: &ResponderContext) -> Option<u32> {
+    (SPDM_SESSION_POLICY
+        .get()
+        .unwrap_or(&DEFAULT)
+        .evict_session_cb)(ctx)
+}
+
+// an evict_session_cb picking the established session that has been idle the
+// longest, once it was idle for at least one heartbeat period.
+pub fn evict_idle_session(ctx: &ResponderContext) -> Option<u32> {
+    let now = crate::time::get_monotonic_time()?;
+    ctx.common
+        .session
+        .iter()
+        .filter(|session| {
+            session.get_session_state() == SpdmSessionState::SpdmSessionEstablished
+                && session.is_heartbeat_idle(now)
+        })
+        .min_by_key(|session| session.get_heartbeat_timer())
+        .map(|session| session.get_session_id())
+}
@@ -110,6 +110,8 @@ pub mod psk {
          -> Option<SpdmHkdfOutputKeyingMaterial> {
             unimplemented!()
         },
+
+        is_psk_provisioned_cb: |_psk_hint: &SpdmPskHintStruct| -> bool { false },
     };
 
     pub fn handshake_secret_hkdf_expand(
@@ -135,6 +137,14 @@ pub mod psk {
             .ok()?
             .master_secret_hkdf_expand_cb)(spdm_version, base_hash_algo, psk_hint, info)
     }
+
+    pub fn is_psk_provisioned(psk_hint: &SpdmPskHintStruct) -> bool {
+        if let Ok(instance) = SECRET_PSK_INSTANCE.try_get_or_init(|| UNIMPLETEMTED.clone()) {
+            (instance.is_psk_provisioned_cb)(psk_hint)
+        } else {
+            false
+        }
+    }
 }
 
 pub mod asym_sign {
@@ -35,6 +35,7 @@ type SpdmPskMasterSecretHkdfExpandCbType = fn(
     psk_hint: &SpdmPskHintStruct,
     info: &[u8],
 ) -> Option<SpdmHkdfOutputKeyingMaterial>;
+type SpdmPskIsProvisionedCbType = fn(psk_hint: &SpdmPskHintStruct) -> bool;
 
 #[derive(Clone)]
 pub struct SpdmSecretMeasurement {
@@ -48,6 +49,8 @@ pub struct SpdmSecretPsk {
     pub handshake_secret_hkdf_expand_cb: SpdmPskHandshakeSecretHkdfExpandCbType,
 
     pub master_secret_hkdf_expand_cb: SpdmPskMasterSecretHkdfExpandCbType,
+
+    pub is_psk_provisioned_cb: SpdmPskIsProvisionedCbType,
 }
 
 #[derive(Clone)]
@@ -4,7 +4,7 @@
 
 mod time_callbacks;
 
-pub use time_callbacks::SpdmTime;
+pub use time_callbacks::{SpdmCertValidityTime, SpdmTime};
 
 use conquer_once::spin::OnceCell;
 
@@ -12,16 +12,67 @@ static TIME_INSTANCE: OnceCell<SpdmTime> = OnceCell::uninit();
 
 static DEFAULT: SpdmTime = SpdmTime {
     sleep_cb: |_: usize| unimplemented!(),
+    get_cert_validity_time_cb: get_platform_time,
+    get_monotonic_time_cb: get_platform_monotonic_time,
 };
 
 pub fn register(context: SpdmTime) -> bool {
     TIME_INSTANCE.try_init_once(|| context).is_ok()
 }
 
+// reading does not lock in DEFAULT, a later register() still takes effect.
 pub fn sleep(us: usize) {
+    (TIME_INSTANCE.get().unwrap_or(&DEFAULT).sleep_cb)(us)
+}
+
+pub fn get_cert_validity_time() -> SpdmCertValidityTime {
+    (TIME_INSTANCE
+        .get()
+        .unwrap_or(&DEFAULT)
+        .get_cert_validity_time_cb)()
+}
+
+pub fn get_monotonic_time() -> Option<u64> {
     (TIME_INSTANCE
-        .try_get_or_init(|| DEFAULT.clone())
-        .ok()
-        .unwrap()
-        .sleep_cb)(us)
+        .get()
+        .unwrap_or(&DEFAULT)
+        .get_monotonic_time_cb)()
+}
+
+#[cfg(all(any(target_os = "uefi", target_os = "none"), feature = "spdm-ring"))]
+fn get_platform_time() -> SpdmCertValidityTime {
+    SpdmCertValidityTime::Now(uefi_time::get_rtc_time() as u64)
+}
+
+#[cfg(all(
+    any(target_os = "uefi", target_os = "none"),
+    not(feature = "spdm-ring")
+))]
+fn get_platform_time() -> SpdmCertValidityTime {
+    // no trusted time without a registered SpdmTime.
+    SpdmCertValidityTime::Ignore
+}
+
+#[cfg(not(any(target_os = "uefi", target_os = "none")))]
+fn get_platform_time() -> SpdmCertValidityTime {
+    extern crate std;
+    // a clock before the Unix epoch makes every certificate not yet valid.
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|ds| ds.as_secs())
+        .unwrap_or(0);
+    SpdmCertValidityTime::Now(timestamp)
+}
+
+#[cfg(any(target_os = "uefi", target_os = "none"))]
+fn get_platform_monotonic_time() -> Option<u64> {
+    None
+}
+
+#[cfg(not(any(target_os = "uefi", target_os = "none")))]
+fn get_platform_monotonic_time() -> Option<u64> {
+    extern crate std;
+    static START: OnceCell<std::time::Instant> = OnceCell::uninit();
+    let start = START.try_get_or_init(std::time::Instant::now).ok()?;
+    Some(start.elapsed().as_millis() as u64)
 }
@@ -2,7 +2,21 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+/// The time the validity period of a certificate is checked against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpdmCertValidityTime {
+    /// seconds since the Unix epoch.
+    Now(u64),
+    /// do not check the validity period, for platforms without a trusted time.
+    Ignore,
+}
+
 #[derive(Clone)]
 pub struct SpdmTime {
     pub sleep_cb: fn(us: usize),
+    pub get_cert_validity_time_cb: fn() -> SpdmCertValidityTime,
+    /// milliseconds since an arbitrary fixed point, never going backwards.
+    /// None on a platform without such a clock, heartbeat timeouts, idle session
+    /// eviction and key lifetimes are not enforced then.
+    pub get_monotonic_time_cb: fn() -> Option<u64>,
 }
@@ -3,15 +3,15 @@
  *
  **/
 
-#include <mbedtls/x509.h>
-#include <mbedtls/x509_crt.h>
+#include <mbedtls/pk.h>
+#include <mbedtls/rsa.h>
 
 /**
  * Verifies RSASSA and Ecdsa signature.
  *
  * @param[in]  md_type      Hash algorithm used.
- * @param[in]  cert         Certificate which contains public key.
- * @param[in]  cert_size    Certificate size in bytes.
+ * @param[in]  public_key       DER encoded SubjectPublicKeyInfo.
+ * @param[in]  public_key_size  SubjectPublicKeyInfo size in bytes.
  * @param[in]  data         Pointer to octet data to be checked (hash).
  * @param[in]  data_size    Size of the data in bytes.
  * @param[in]  signature    Pointer to Ed-DSA signature to be verified.
@@ -26,23 +26,23 @@
  **/
 int spdm_pk_verify(
     const int md_type,
-    const uint8_t *cert, size_t cert_size,
+    const uint8_t *public_key, size_t public_key_size,
     const uint8_t *data, size_t data_size,
     const uint8_t *signature, size_t signature_size)
 {
-    mbedtls_x509_crt crt;
+    mbedtls_pk_context pk;
     int ret;
 
-    mbedtls_x509_crt_init(&crt);
+    mbedtls_pk_init(&pk);
 
-    ret = mbedtls_x509_crt_parse_der(&crt, cert, cert_size);
+    ret = mbedtls_pk_parse_public_key(&pk, public_key, public_key_size);
 
     if (ret == 0)
     {
-        ret = mbedtls_pk_verify(&crt.pk, md_type, data, data_size, signature, signature_size);
+        ret = mbedtls_pk_verify(&pk, md_type, data, data_size, signature, signature_size);
     }
 
-    mbedtls_x509_crt_free(&crt);
+    mbedtls_pk_free(&pk);
 
     return ret;
 }
@@ -51,8 +51,8 @@ int spdm_pk_verify(
  * Verifies the RSA-PSS signature.
  *
  * @param[in]  md_type      Hash algorithm used.
- * @param[in]  cert         Certificate which contains public key.
- * @param[in]  cert_size    Certificate size in bytes.
+ * @param[in]  public_key       DER encoded SubjectPublicKeyInfo.
+ * @param[in]  public_key_size  SubjectPublicKeyInfo size in bytes.
  * @param[in]  data         Pointer to octet data to be checked (hash).
  * @param[in]  data_size    Size of the data in bytes.
  * @param[in]  signature    Pointer to RSA-PSS signature to be verified.
@@ -67,23 +67,23 @@ int spdm_pk_verify(
  **/
 int spdm_rsa_pss_verify(
     const int md_type,
-    const uint8_t *cert, size_t cert_size,
+    const uint8_t *public_key, size_t public_key_size,
     const uint8_t *data, size_t data_size,
     const uint8_t *signature, size_t signature_size)
 {
-    mbedtls_x509_crt crt;
+    mbedtls_pk_context pk;
     mbedtls_rsa_context *rsa_context;
     int ret;
     // suppress "unused"
     (void)signature_size;
 
-    mbedtls_x509_crt_init(&crt);
+    mbedtls_pk_init(&pk);
 
-    ret = mbedtls_x509_crt_parse_der(&crt, cert, cert_size);
+    ret = mbedtls_pk_parse_public_key(&pk, public_key, public_key_size);
 
     if (ret == 0)
     {
-        rsa_context = mbedtls_pk_rsa(crt.pk);
+        rsa_context = mbedtls_pk_rsa(pk);
         if (rsa_context == NULL)
         {
             ret = MBEDTLS_ERR_PK_INVALID_PUBKEY;
@@ -98,7 +98,7 @@ int spdm_rsa_pss_verify(
             md_type, data_size, data, signature);
     }
 
-    mbedtls_x509_crt_free(&crt);
+    mbedtls_pk_free(&pk);
 
     return ret;
 }
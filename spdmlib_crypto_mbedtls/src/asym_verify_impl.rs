@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use spdmlib::crypto::SpdmAsymVerify;
+use spdmlib::crypto::{SpdmAsymVerify, X509SubjectPublicKeyInfo};
 use spdmlib::error::{SpdmResult, SPDM_STATUS_CRYPTO_ERROR};
 use spdmlib::protocol::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmSignatureStruct};
 
@@ -19,7 +19,7 @@ use super::ffi::{spdm_pk_verify, spdm_rsa_pss_verify};
 fn asym_verify(
     base_hash_algo: SpdmBaseHashAlgo,
     base_asym_algo: SpdmBaseAsymAlgo,
-    public_cert_der: &[u8],
+    public_key: &X509SubjectPublicKeyInfo,
     data: &[u8],
     signature: &SpdmSignatureStruct,
 ) -> SpdmResult {
@@ -55,9 +55,7 @@ fn asym_verify(
         }
     };
 
-    let (leaf_begin, leaf_end) =
-        (super::cert_operation_impl::DEFAULT.get_cert_from_cert_chain_cb)(public_cert_der, -1)?;
-    let leaf_cert_der = &public_cert_der[leaf_begin..leaf_end];
+    let public_key_der = public_key.as_der();
 
     let data_hash = (super::hash_impl::DEFAULT.hash_all_cb)(base_hash_algo, data).unwrap();
 
@@ -69,8 +67,8 @@ fn asym_verify(
         | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096 => unsafe {
             spdm_pk_verify(
                 mbedtls_hash_algo,
-                leaf_cert_der.as_ptr(),
-                leaf_cert_der.len(),
+                public_key_der.as_ptr(),
+                public_key_der.len(),
                 data_hash.data.as_ptr(),
                 data_hash.data_size as usize,
                 signature.as_ptr(),
@@ -82,8 +80,8 @@ fn asym_verify(
         | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096 => unsafe {
             spdm_rsa_pss_verify(
                 mbedtls_hash_algo,
-                leaf_cert_der.as_ptr(),
-                leaf_cert_der.len(),
+                public_key_der.as_ptr(),
+                public_key_der.len(),
                 data_hash.data.as_ptr(),
                 data_hash.data_size as usize,
                 signature.as_ptr(),
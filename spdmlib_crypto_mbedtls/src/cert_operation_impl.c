@@ -4,23 +4,128 @@
 
 #include <mbedtls/x509.h>
 #include <mbedtls/x509_crt.h>
+#include <mbedtls/x509_crl.h>
 #include <mbedtls/asn1.h>
+#include <mbedtls/md.h>
+#include <mbedtls/oid.h>
+#include <mbedtls/pk.h>
+#include <string.h>
+
+/* seconds since the Unix epoch of an X509 time. */
+static unsigned long long x509_time_to_epoch(const mbedtls_x509_time *t)
+{
+    long long y = t->year - (t->mon <= 2);
+    long long m = t->mon <= 2 ? t->mon + 9 : t->mon - 3;
+    long long era = (y >= 0 ? y : y - 399) / 400;
+    long long yoe = y - era * 400;
+    long long doy = (153 * m + 2) / 5 + t->day - 1;
+    long long doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    long long days = era * 146097 + doe - 719468;
+
+    if (days < 0)
+    {
+        return 0;
+    }
+    return (unsigned long long)days * 86400 + t->hour * 3600 + t->min * 60 + t->sec;
+}
+
+/* mbedtls is built without MBEDTLS_HAVE_TIME_DATE, check the validity period here. */
+static int spdm_x509_verify_validity(void *data, mbedtls_x509_crt *crt, int depth,
+                                     uint32_t *flags)
+{
+    unsigned long long now = *(unsigned long long *)data;
+    (void)depth;
+
+    *flags &= ~(MBEDTLS_X509_BADCERT_EXPIRED | MBEDTLS_X509_BADCERT_FUTURE);
+    if (now < x509_time_to_epoch(&crt->valid_from))
+    {
+        *flags |= MBEDTLS_X509_BADCERT_FUTURE;
+    }
+    if (now > x509_time_to_epoch(&crt->valid_to))
+    {
+        *flags |= MBEDTLS_X509_BADCERT_EXPIRED;
+    }
+    return 0;
+}
+
+static int spdm_x509_ignore_validity(void *data, mbedtls_x509_crt *crt, int depth,
+                                     uint32_t *flags)
+{
+    (void)data;
+    (void)crt;
+    (void)depth;
+
+    *flags &= ~(MBEDTLS_X509_BADCERT_EXPIRED | MBEDTLS_X509_BADCERT_FUTURE);
+    return 0;
+}
+
+/* id-DMTF-eku-responder-auth 1.3.6.1.4.1.412.274.3 */
+#define SPDM_OID_EKU_RESPONDER_AUTH "\x2b\x06\x01\x04\x01\x83\x1c\x82\x12\x03"
+/* id-DMTF-eku-requester-auth 1.3.6.1.4.1.412.274.4 */
+#define SPDM_OID_EKU_REQUESTER_AUTH "\x2b\x06\x01\x04\x01\x83\x1c\x82\x12\x04"
+
+/*
+ * Every cert below the root which has the extKeyUsage extension must allow the role,
+ * by the DMTF EKU of the role or the TLS EKU which is used by the SPDM chains,
+ * as the ring backend does.
+ */
+static int spdm_x509_check_extended_key_usage(const mbedtls_x509_crt *crt, int is_requester_cert)
+{
+    const char *spdm_oid = SPDM_OID_EKU_RESPONDER_AUTH;
+    size_t spdm_oid_len = MBEDTLS_OID_SIZE(SPDM_OID_EKU_RESPONDER_AUTH);
+    const char *tls_oid = MBEDTLS_OID_SERVER_AUTH;
+    size_t tls_oid_len = MBEDTLS_OID_SIZE(MBEDTLS_OID_SERVER_AUTH);
+
+    if (is_requester_cert)
+    {
+        spdm_oid = SPDM_OID_EKU_REQUESTER_AUTH;
+        spdm_oid_len = MBEDTLS_OID_SIZE(SPDM_OID_EKU_REQUESTER_AUTH);
+        tls_oid = MBEDTLS_OID_CLIENT_AUTH;
+        tls_oid_len = MBEDTLS_OID_SIZE(MBEDTLS_OID_CLIENT_AUTH);
+    }
+
+    for (; crt != NULL; crt = crt->next)
+    {
+        /* the root is not checked, just as the trust anchor in the ring backend. */
+        if (crt->issuer_raw.len == crt->subject_raw.len &&
+            memcmp(crt->issuer_raw.p, crt->subject_raw.p, crt->subject_raw.len) == 0)
+        {
+            continue;
+        }
+        /* 0 when the cert has no extKeyUsage extension or allows the usage. */
+        if (mbedtls_x509_crt_check_extended_key_usage(crt, spdm_oid, spdm_oid_len) != 0 &&
+            mbedtls_x509_crt_check_extended_key_usage(crt, tls_oid, tls_oid_len) != 0)
+        {
+            return 1;
+        }
+    }
+    return 0;
+}
 
 /**
  * Verify X509 certificate chains
  *
+ * @param[in]      root_cert            ASN.1 DER-encoded X.509 trust anchor certificate.
+ * @param[in]      root_cert_size       Size of the trust anchor certificate, in bytes.
  * @param[in]      cert_chain           One or more ASN.1 DER-encoded X.509 certificates
  *                                      where the first certificate CA certificate.
  *                                      Certificate or is the Root Cerificate itself. and
  *                                      subsequent cerificate is signed by the preceding
  *                                      cerificate.
  * @param[in]      cert_chain_length    Total length of the certificate chain, in bytes.
- * 
- * @retval  0       All cerificates was issued by the first certificate in X509Certchain.
+ * @param[in]      check_validity       Check the validity period of the certificates or not.
+ * @param[in]      now                  Current time in seconds since the Unix epoch,
+ *                                      used when check_validity is not 0.
+ * @param[in]      is_requester_cert    The chain authenticates a requester, not a responder.
+ *
+ * @retval  0       All cerificates was issued by the trust anchor certificate.
  * @retval  1       Invalid certificate or the certificate was not issued by the given
- *                  first CA cert.
+ *                  trust anchor certificate.
  **/
-int spdm_verify_cert_chain(char *certchain, size_t certchain_size)
+int spdm_verify_cert_chain(unsigned char *root_cert, size_t root_cert_size,
+                           char *certchain, size_t certchain_size,
+                           int check_validity, unsigned long long now,
+                           int is_requester_cert)
 {
     int ret;
     mbedtls_x509_crt crt, ca;
@@ -36,10 +141,10 @@ int spdm_verify_cert_chain(char *certchain, size_t certchain_size)
     pcert = p = (unsigned char *)certchain;
     end = (unsigned char *)certchain + certchain_size;
 
-    // Parse CA cert in certchain.
+    // Parse trust anchor cert.
     if (ret == 0)
     {
-        ret = mbedtls_x509_crt_parse_der_nocopy(&ca, pcert, end - pcert);
+        ret = mbedtls_x509_crt_parse_der_nocopy(&ca, root_cert, root_cert_size);
     }
     if (ret == 0)
     {
@@ -80,7 +185,13 @@ int spdm_verify_cert_chain(char *certchain, size_t certchain_size)
 
     /* Verify certificate chains.
      */
-    ret = mbedtls_x509_crt_verify(&crt, &ca, NULL, NULL, &flags, NULL, NULL);
+    ret = mbedtls_x509_crt_verify(&crt, &ca, NULL, NULL, &flags,
+                                  check_validity ? spdm_x509_verify_validity : spdm_x509_ignore_validity,
+                                  &now);
+    if (ret == 0)
+    {
+        ret = spdm_x509_check_extended_key_usage(&crt, is_requester_cert);
+    }
 
     mbedtls_x509_crt_free(&crt);
     mbedtls_x509_crt_free(&ca);
@@ -88,6 +199,112 @@ int spdm_verify_cert_chain(char *certchain, size_t certchain_size)
     return ret;
 }
 
+/**
+ * Check X509 certificate chains against a certificate revocation list
+ *
+ * @param[in]      crl                  ASN.1 DER-encoded X.509 certificate revocation list.
+ * @param[in]      crl_size             Size of the certificate revocation list, in bytes.
+ * @param[in]      cert_chain           One or more ASN.1 DER-encoded X.509 certificates
+ *                                      where the first certificate is the Root Cerificate
+ *                                      and subsequent cerificate is signed by the preceding
+ *                                      cerificate.
+ * @param[in]      cert_chain_length    Total length of the certificate chain, in bytes.
+ * @param[in]      check_validity       Check the validity period of the list or not.
+ * @param[in]      now                  Current time in seconds since the Unix epoch,
+ *                                      used when check_validity is not 0.
+ *
+ * @retval  0       No certificate is revoked by the list, or the list is not signed by
+ *                  the issuer of any certificate in the chain.
+ * @retval  1       A certificate is revoked by the list signed by its issuer.
+ * @retval  <0      Invalid certificate revocation list or certificate chain, or the
+ *                  list is outside its validity period.
+ **/
+int spdm_check_cert_revocation(unsigned char *crl, size_t crl_size,
+                               unsigned char *certchain, size_t certchain_size,
+                               int check_validity, unsigned long long now)
+{
+    int ret;
+    int revoked;
+    mbedtls_x509_crl x509_crl;
+    mbedtls_x509_crt crt;
+    mbedtls_x509_crt *issuer, *child;
+    const mbedtls_md_info_t *md_info;
+    unsigned char hash[MBEDTLS_MD_MAX_SIZE];
+    unsigned char *p, *end, *pcert;
+    size_t len;
+
+    revoked = 0;
+    mbedtls_x509_crl_init(&x509_crl);
+    mbedtls_x509_crt_init(&crt);
+
+    ret = mbedtls_x509_crl_parse_der(&x509_crl, crl, crl_size);
+
+    /* a stale list may miss recent revocations, one not yet issued cannot be trusted.
+     * nextUpdate is optional, it is all zero when absent.
+     */
+    if (ret == 0 && check_validity &&
+        (now < x509_time_to_epoch(&x509_crl.this_update) ||
+         (x509_crl.next_update.year != 0 && now > x509_time_to_epoch(&x509_crl.next_update))))
+    {
+        ret = MBEDTLS_ERR_X509_INVALID_DATE;
+    }
+
+    /* parse certs in certchain, the parsed list keeps the chain order.
+     * pcert point to next certificate.
+     */
+    p = pcert = certchain;
+    end = certchain + certchain_size;
+    len = 0;
+    while (ret == 0 && (pcert = p + len) < end)
+    {
+        ret = mbedtls_x509_crt_parse_der_nocopy(&crt, pcert, end - pcert);
+        if (ret == 0)
+        {
+            p = pcert;
+            ret = mbedtls_asn1_get_tag(&p, end, &len,
+                                       MBEDTLS_ASN1_CONSTRUCTED | MBEDTLS_ASN1_SEQUENCE);
+        }
+    }
+
+    for (issuer = &crt, child = crt.next; ret == 0 && child != NULL;
+         issuer = child, child = child->next)
+    {
+        if (x509_crl.issuer_raw.len != issuer->subject_raw.len ||
+            memcmp(x509_crl.issuer_raw.p, issuer->subject_raw.p, issuer->subject_raw.len) != 0 ||
+            x509_crl.issuer_raw.len != child->issuer_raw.len ||
+            memcmp(x509_crl.issuer_raw.p, child->issuer_raw.p, child->issuer_raw.len) != 0)
+        {
+            continue;
+        }
+
+        /* the list is only authoritative if it is signed by the issuer in the chain. */
+        md_info = mbedtls_md_info_from_type(x509_crl.sig_md);
+        if (md_info == NULL ||
+            mbedtls_md(md_info, x509_crl.tbs.p, x509_crl.tbs.len, hash) != 0 ||
+            mbedtls_pk_verify_ext(x509_crl.sig_pk, x509_crl.sig_opts, &issuer->pk,
+                                  x509_crl.sig_md, hash, mbedtls_md_get_size(md_info),
+                                  x509_crl.sig.p, x509_crl.sig.len) != 0)
+        {
+            continue;
+        }
+
+        if (mbedtls_x509_crt_is_revoked(child, &x509_crl))
+        {
+            revoked = 1;
+            break;
+        }
+    }
+
+    mbedtls_x509_crt_free(&crt);
+    mbedtls_x509_crl_free(&x509_crl);
+
+    if (ret != 0)
+    {
+        return ret;
+    }
+    return revoked;
+}
+
 /**
  * Certificate Check for SPDM leaf cert.
  *
@@ -131,7 +348,10 @@ int main(int argc, char **argv)
 
     if (certchain_buffer_size > 0)
     {
-        ret = spdm_verify_cert_chain(certchain_buffer, certchain_buffer_size);
+        ret = spdm_verify_cert_chain((unsigned char *)certchain_buffer,
+                                     ((unsigned char)certchain_buffer[2] << 8) +
+                                         (unsigned char)certchain_buffer[3] + 4,
+                                     certchain_buffer, certchain_buffer_size, 0, 0, 0);
         assert(ret == 0);
     }
 }
@@ -4,15 +4,18 @@
 
 extern crate alloc;
 
+use core::ffi::c_int;
 use spdmlib::crypto::SpdmCertOperation;
-use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_CERT};
+use spdmlib::error::{SpdmResult, SPDM_STATUS_CERT_REVOKED, SPDM_STATUS_INVALID_CERT};
+use spdmlib::time::SpdmCertValidityTime;
 
 pub static DEFAULT: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: get_cert_from_cert_chain,
     verify_cert_chain_cb: verify_cert_chain,
+    check_cert_chain_revocation_cb: check_cert_chain_revocation,
 };
 
-use crate::ffi::spdm_verify_cert_chain;
+use crate::ffi::{spdm_check_cert_revocation, spdm_verify_cert_chain};
 
 fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usize, usize)> {
     let mut offset = 0usize;
@@ -43,13 +46,69 @@ fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usiz
     }
 }
 
-fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
-    let ret = unsafe { spdm_verify_cert_chain(cert_chain.as_ptr(), cert_chain.len()) };
-    if ret == 0 {
-        Ok(())
-    } else {
-        Err(SPDM_STATUS_INVALID_CERT)
+fn verify_cert_chain(
+    cert_chain: &[u8],
+    trust_anchors: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+    is_requester_cert: bool,
+) -> SpdmResult<usize> {
+    let (check_validity, now) = match validity_time {
+        SpdmCertValidityTime::Now(now) => (1, now),
+        SpdmCertValidityTime::Ignore => (0, 0),
+    };
+    for (index, trust_anchor) in trust_anchors.iter().enumerate() {
+        let ret = unsafe {
+            spdm_verify_cert_chain(
+                trust_anchor.as_ptr(),
+                trust_anchor.len(),
+                cert_chain.as_ptr(),
+                cert_chain.len(),
+                check_validity,
+                now,
+                is_requester_cert as c_int,
+            )
+        };
+        if ret == 0 {
+            return Ok(index);
+        }
     }
+    Err(SPDM_STATUS_INVALID_CERT)
+}
+
+fn check_cert_chain_revocation(
+    cert_chain: &[u8],
+    trust_anchor: &[u8],
+    crls: &[&[u8]],
+    validity_time: SpdmCertValidityTime,
+) -> SpdmResult {
+    // the trust anchor heads the path, so that the first cert is checked against its CRLs.
+    let mut path = alloc::vec::Vec::new();
+    if !cert_chain.starts_with(trust_anchor) {
+        path.extend_from_slice(trust_anchor);
+    }
+    path.extend_from_slice(cert_chain);
+    let (check_validity, now) = match validity_time {
+        SpdmCertValidityTime::Now(now) => (1, now),
+        SpdmCertValidityTime::Ignore => (0, 0),
+    };
+    for crl in crls.iter() {
+        let ret = unsafe {
+            spdm_check_cert_revocation(
+                crl.as_ptr(),
+                crl.len(),
+                path.as_ptr(),
+                path.len(),
+                check_validity,
+                now,
+            )
+        };
+        match ret {
+            0 => continue,
+            1 => return Err(SPDM_STATUS_CERT_REVOKED),
+            _ => return Err(SPDM_STATUS_INVALID_CERT),
+        }
+    }
+    Ok(())
 }
 
 #[cfg(all(test,))]
@@ -95,7 +154,137 @@ mod tests {
         let status = get_cert_from_cert_chain(cert_chain, -1).is_ok();
         assert!(status);
 
-        let status = verify_cert_chain(cert_chain).is_ok();
+        let (root_cert_begin, root_cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let root_cert = &cert_chain[root_cert_begin..root_cert_end];
+        let status = verify_cert_chain(
+            cert_chain,
+            &[root_cert],
+            spdmlib::time::get_cert_validity_time(),
+            false,
+        )
+        .is_ok();
         assert!(status);
     }
+
+    #[test]
+    fn test_case7_verify_cert_chain_validity() {
+        let cert_chain =
+            &include_bytes!("../../test_key/rsa3072_Expiration/bundle_responder.certchain.der")[..];
+        let (root_cert_begin, root_cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let root_cert = &cert_chain[root_cert_begin..root_cert_end];
+
+        // expired on 2023-04-04
+        assert!(verify_cert_chain(
+            cert_chain,
+            &[root_cert],
+            spdmlib::time::get_cert_validity_time(),
+            false
+        )
+        .is_err());
+        assert!(verify_cert_chain(
+            cert_chain,
+            &[root_cert],
+            SpdmCertValidityTime::Ignore,
+            false
+        )
+        .is_ok());
+        // 2023-04-03T12:00:00Z
+        assert!(verify_cert_chain(
+            cert_chain,
+            &[root_cert],
+            SpdmCertValidityTime::Now(1680523200),
+            false
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_case9_verify_cert_chain_role() {
+        let cert_chain =
+            &include_bytes!("../../test_key/ecp384/bundle_requester.certchain.der")[..];
+        let (root_cert_begin, root_cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let root_cert = &cert_chain[root_cert_begin..root_cert_end];
+
+        // the certs have both the serverAuth and the clientAuth EKU
+        for is_requester_cert in [false, true] {
+            assert_eq!(
+                verify_cert_chain(
+                    cert_chain,
+                    &[root_cert],
+                    SpdmCertValidityTime::Ignore,
+                    is_requester_cert
+                ),
+                Ok(0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_case6_check_cert_chain_revocation() {
+        let ca_crl = &include_bytes!("../../test_key/ecp384/ca.crl.der")[..];
+        let inter_crl = &include_bytes!("../../test_key/ecp384/inter.crl.der")[..];
+        let root = &include_bytes!("../../test_key/ecp384/ca.cert.der")[..];
+        let ecp256_ca_crl = &include_bytes!("../../test_key/ecp256/ca.crl.der")[..];
+        let responder_certs_der =
+            &include_bytes!("../../test_key/ecp384/bundle_responder.certchain.der")[..];
+        let requester_certs_der =
+            &include_bytes!("../../test_key/ecp384/bundle_requester.certchain.der")[..];
+        let now = spdmlib::time::get_cert_validity_time();
+
+        assert!(check_cert_chain_revocation(responder_certs_der, root, &[], now).is_ok());
+        assert!(check_cert_chain_revocation(responder_certs_der, root, &[inter_crl], now).is_ok());
+        assert!(
+            check_cert_chain_revocation(responder_certs_der, root, &[ecp256_ca_crl], now).is_ok()
+        );
+        assert_eq!(
+            check_cert_chain_revocation(responder_certs_der, root, &[inter_crl, ca_crl], now),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+        assert_eq!(
+            check_cert_chain_revocation(requester_certs_der, root, &[inter_crl], now),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+
+        // the first intermediate cert is revoked by the trust anchor
+        let cert_chain = &responder_certs_der[root.len()..];
+        assert_eq!(
+            check_cert_chain_revocation(cert_chain, root, &[ca_crl], now),
+            Err(SPDM_STATUS_CERT_REVOKED)
+        );
+        assert!(check_cert_chain_revocation(cert_chain, root, &[inter_crl], now).is_ok());
+    }
+
+    #[test]
+    fn test_case8_check_cert_chain_revocation_validity() {
+        let inter_crl = &include_bytes!("../../test_key/ecp384/inter.crl.der")[..];
+        let root = &include_bytes!("../../test_key/ecp384/ca.cert.der")[..];
+        let responder_certs_der =
+            &include_bytes!("../../test_key/ecp384/bundle_responder.certchain.der")[..];
+
+        // thisUpdate 2026-10-19T09:16:25Z, nextUpdate 2036-10-16T09:16:25Z
+        for (timestamp, is_ok) in [
+            (1792401385, true),
+            (1792401384, false),
+            (2107761385, true),
+            (2107761386, false),
+        ] {
+            assert_eq!(
+                check_cert_chain_revocation(
+                    responder_certs_der,
+                    root,
+                    &[inter_crl],
+                    SpdmCertValidityTime::Now(timestamp)
+                )
+                .is_ok(),
+                is_ok
+            );
+        }
+        assert!(check_cert_chain_revocation(
+            responder_certs_der,
+            root,
+            &[inter_crl],
+            SpdmCertValidityTime::Ignore
+        )
+        .is_ok());
+    }
 }
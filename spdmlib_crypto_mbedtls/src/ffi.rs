@@ -67,8 +67,8 @@ extern "C" {
 
     pub fn spdm_pk_verify(
         md_type: c_int,
-        cert: *const c_uchar,
-        cert_size: usize,
+        public_key: *const c_uchar,
+        public_key_size: usize,
         data: *const c_uchar,
         data_size: usize,
         signature: *const c_uchar,
@@ -77,15 +77,32 @@ extern "C" {
 
     pub fn spdm_rsa_pss_verify(
         md_type: c_int,
-        cert: *const c_uchar,
-        cert_size: usize,
+        public_key: *const c_uchar,
+        public_key_size: usize,
         data: *const c_uchar,
         data_size: usize,
         signature: *const c_uchar,
         signature_size: usize,
     ) -> c_int;
 
-    pub fn spdm_verify_cert_chain(certchain: *const c_uchar, certchain_size: usize) -> c_int;
+    pub fn spdm_verify_cert_chain(
+        root_cert: *const c_uchar,
+        root_cert_size: usize,
+        certchain: *const c_uchar,
+        certchain_size: usize,
+        check_validity: c_int,
+        now: u64,
+        is_requester_cert: c_int,
+    ) -> c_int;
+
+    pub fn spdm_check_cert_revocation(
+        crl: *const c_uchar,
+        crl_size: usize,
+        certchain: *const c_uchar,
+        certchain_size: usize,
+        check_validity: c_int,
+        now: u64,
+    ) -> c_int;
 
     pub fn spdm_ecdh_compute_shared_p256(
         private_key: *const c_uchar,
@@ -78,10 +78,14 @@ pub fn req_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
     peer_root_cert_data.data_size = (ca_len) as u16;
     peer_root_cert_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
 
+    let mut peer_root_cert_data_list = gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT);
+    peer_root_cert_data_list[0] = Some(peer_root_cert_data);
+
     let provision_info = common::SpdmProvisionInfo {
         my_cert_chain_data: [None, None, None, None, None, None, None, None],
         my_cert_chain: [None, None, None, None, None, None, None, None],
-        peer_root_cert_data: Some(peer_root_cert_data),
+        peer_root_cert_data: peer_root_cert_data_list,
+        peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
     };
 
     (config_info, provision_info)
@@ -165,7 +169,8 @@ pub fn rsp_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
-        peer_root_cert_data: None,
+        peer_root_cert_data: gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT),
+        peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
     };
 
     (config_info, provision_info)
@@ -94,8 +94,13 @@ mod test_mbedtls {
 
         spdmlib::crypto::asym_verify::verify(hash_algo, asym_algo, cert_chain, data, &sig).unwrap();
 
+        let public_key = spdmlib::crypto::get_leaf_public_key_info(cert_chain).unwrap();
         (spdmlib_crypto_mbedtls::asym_verify_impl::DEFAULT.verify_cb)(
-            hash_algo, asym_algo, cert_chain, data, &sig,
+            hash_algo,
+            asym_algo,
+            &public_key,
+            data,
+            &sig,
         )
         .unwrap();
     }
@@ -0,0 +1,492 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::*;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::config;
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_SIZE,
+    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_NOT_READY_PEER, SPDM_STATUS_SEND_FAIL,
+};
+
+// the security commands of the drive, as issued by the host driver.
+pub trait StorageSecurityCommands {
+    // IF_SEND, NVMe Security Send or SCSI SECURITY PROTOCOL OUT.
+    fn security_send(
+        &mut self,
+        security_protocol: u8,
+        security_protocol_specific: u16,
+        data: &[u8],
+    ) -> SpdmResult;
+    // IF_RECV, NVMe Security Receive or SCSI SECURITY PROTOCOL IN, the
+    // allocation length is data.len(). returns the bytes the drive transferred.
+    fn security_receive(
+        &mut self,
+        security_protocol: u8,
+        security_protocol_specific: u16,
+        data: &mut [u8],
+    ) -> SpdmResult<usize>;
+}
+
+// issue the messages built by StorageTransportEncap as security commands.
+// the response is fetched with IF_RECV on the SPSP of the last IF_SEND, once
+// Pending Info reports it. the last IF_SEND stays pending until its response
+// is transferred, a receive which fails can be retried.
+pub struct StorageDeviceIo<'a> {
+    commands: &'a mut dyn StorageSecurityCommands,
+    pending: Option<StorageMessageHeader>,
+}
+
+impl<'a> StorageDeviceIo<'a> {
+    pub fn new(commands: &'a mut dyn StorageSecurityCommands) -> Self {
+        StorageDeviceIo {
+            commands,
+            pending: None,
+        }
+    }
+
+    fn pending_info(&mut self, header: &StorageMessageHeader) -> SpdmResult<StoragePendingInfo> {
+        let spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodePendingInfo,
+            connection_id: header.security_protocol_specific.connection_id,
+        };
+        let mut data = [0u8; STORAGE_PENDING_INFO_SIZE];
+        let size =
+            self.commands
+                .security_receive(header.security_protocol, spsp.to_u16(), &mut data)?;
+        let mut reader = Reader::init(&data[..size]);
+        StoragePendingInfo::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_SIZE)
+    }
+}
+
+impl SpdmDeviceIo for StorageDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        let mut reader = Reader::init(buffer);
+        let header = StorageMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_SEND_FAIL)?;
+        let data = &buffer[reader.used()..];
+        if header.length as usize != data.len() {
+            return Err(SPDM_STATUS_INVALID_MSG_SIZE);
+        }
+        self.commands.security_send(
+            header.security_protocol,
+            header.security_protocol_specific.to_u16(),
+            data,
+        )?;
+        self.pending = Some(header);
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        let mut header = self.pending.ok_or(0usize)?;
+        if buffer.len() < STORAGE_MESSAGE_HEADER_SIZE {
+            return Err(0);
+        }
+        // not ready yet, or larger than the allocation length.
+        let pending_info = self.pending_info(&header).map_err(|_| 0usize)?;
+        if !pending_info.is_pending()
+            || pending_info.response_length as usize > buffer.len() - STORAGE_MESSAGE_HEADER_SIZE
+        {
+            return Err(0);
+        }
+        let size = self
+            .commands
+            .security_receive(
+                header.security_protocol,
+                header.security_protocol_specific.to_u16(),
+                &mut buffer[STORAGE_MESSAGE_HEADER_SIZE..],
+            )
+            .map_err(|_| 0usize)?;
+        self.pending = None;
+        header.length = size as u32;
+        let mut writer = Writer::init(&mut buffer[..STORAGE_MESSAGE_HEADER_SIZE]);
+        header.encode(&mut writer).map_err(|_| 0usize)?;
+        Ok(STORAGE_MESSAGE_HEADER_SIZE + size)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.pending = None;
+        Ok(())
+    }
+}
+
+pub const STORAGE_EMULATED_DRIVE_BUFFER_SIZE: usize = config::RECEIVER_BUFFER_SIZE;
+
+// answers the data of an IF_SEND with the data of the next IF_RECV, and
+// returns its size. None leaves the drive without a response.
+pub type StorageDriveHandler<'a> =
+    &'a mut dyn FnMut(StorageSecurityProtocolSpecific, &[u8], &mut [u8]) -> Option<usize>;
+
+// an in-memory drive, the device side of the security commands.
+pub struct StorageEmulatedDrive<'a> {
+    handler: StorageDriveHandler<'a>,
+    response: [u8; STORAGE_EMULATED_DRIVE_BUFFER_SIZE],
+    response_size: usize,
+    response_spsp: Option<u16>,
+}
+
+impl<'a> StorageEmulatedDrive<'a> {
+    pub fn new(handler: StorageDriveHandler<'a>) -> Self {
+        StorageEmulatedDrive {
+            handler,
+            response: [0u8; STORAGE_EMULATED_DRIVE_BUFFER_SIZE],
+            response_size: 0,
+            response_spsp: None,
+        }
+    }
+
+    pub fn is_response_pending(&self) -> bool {
+        self.response_spsp.is_some()
+    }
+
+    // the state of the response, for an IF_RECV with the Pending Info operation code.
+    fn pending_info(
+        &self,
+        security_protocol: u8,
+        connection_id: u8,
+        data: &mut [u8],
+    ) -> SpdmResult<usize> {
+        if security_protocol != STORAGE_SECURITY_PROTOCOL_DMTF_SPDM
+            || connection_id > STORAGE_MAX_CONNECTION_ID
+        {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        let pending = matches!(
+            self.response_spsp.map(StorageSecurityProtocolSpecific::from_u16),
+            Some(spsp) if spsp.connection_id == connection_id
+        );
+        let pending_info = StoragePendingInfo {
+            data_length: STORAGE_PENDING_INFO_SIZE as u16,
+            storage_binding_version: STORAGE_BINDING_VERSION,
+            pending_info_flag: if pending {
+                STORAGE_PENDING_INFO_FLAG_PENDING
+            } else {
+                0
+            },
+            response_length: if pending {
+                self.response_size as u32
+            } else {
+                0
+            },
+        };
+        let mut writer = Writer::init(data);
+        pending_info
+            .encode(&mut writer)
+            .map_err(|_| SPDM_STATUS_INVALID_MSG_SIZE)
+    }
+
+    fn check_command(security_protocol: u8, security_protocol_specific: u16) -> SpdmResult {
+        let spsp = StorageSecurityProtocolSpecific::from_u16(security_protocol_specific);
+        match spsp.operation_code {
+            StorageOperationCode::StorageOperationCodeMessage
+            | StorageOperationCode::StorageOperationCodeSecuredMessage
+                if security_protocol == STORAGE_SECURITY_PROTOCOL_DMTF_SPDM
+                    && spsp.connection_id <= STORAGE_MAX_CONNECTION_ID =>
+            {
+                Ok(())
+            }
+            _ => Err(SPDM_STATUS_INVALID_PARAMETER),
+        }
+    }
+}
+
+impl StorageSecurityCommands for StorageEmulatedDrive<'_> {
+    fn security_send(
+        &mut self,
+        security_protocol: u8,
+        security_protocol_specific: u16,
+        data: &[u8],
+    ) -> SpdmResult {
+        Self::check_command(security_protocol, security_protocol_specific)?;
+        // a new request drops a response that was not read.
+        self.response_spsp = None;
+        let spsp = StorageSecurityProtocolSpecific::from_u16(security_protocol_specific);
+        if let Some(size) = (self.handler)(spsp, data, &mut self.response) {
+            self.response_size = size.min(STORAGE_EMULATED_DRIVE_BUFFER_SIZE);
+            self.response_spsp = Some(security_protocol_specific);
+        }
+        Ok(())
+    }
+
+    fn security_receive(
+        &mut self,
+        security_protocol: u8,
+        security_protocol_specific: u16,
+        data: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let spsp = StorageSecurityProtocolSpecific::from_u16(security_protocol_specific);
+        if spsp.operation_code == StorageOperationCode::StorageOperationCodePendingInfo {
+            return self.pending_info(security_protocol, spsp.connection_id, data);
+        }
+        Self::check_command(security_protocol, security_protocol_specific)?;
+        match self.response_spsp {
+            None => Err(SPDM_STATUS_NOT_READY_PEER),
+            Some(spsp) if spsp != security_protocol_specific => Err(SPDM_STATUS_ERROR_PEER),
+            // the response stays for another IF_RECV with a larger allocation length.
+            Some(_) if data.len() < self.response_size => Err(SPDM_STATUS_INVALID_MSG_SIZE),
+            Some(_) => {
+                self.response_spsp = None;
+                data[..self.response_size].copy_from_slice(&self.response[..self.response_size]);
+                Ok(self.response_size)
+            }
+        }
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use spdmlib::common::SpdmTransportEncap;
+
+    // echo the request back with the first byte incremented.
+    fn echo(
+        _spsp: StorageSecurityProtocolSpecific,
+        request: &[u8],
+        response: &mut [u8],
+    ) -> Option<usize> {
+        response[..request.len()].copy_from_slice(request);
+        response[0] = response[0].wrapping_add(1);
+        Some(request.len())
+    }
+
+    #[test]
+    fn test_case0_send_receive() {
+        let handler = &mut echo;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let mut device_io = StorageDeviceIo::new(&mut drive);
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 2 };
+
+        let mut transport_buffer = [0u8; 64];
+        let used = storage_transport_encap
+            .encap(&[0x10, 0x84, 0, 0], &mut transport_buffer, true)
+            .unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        let mut receive_buffer = [0u8; 64];
+        let received = device_io.receive(&mut receive_buffer, 0).unwrap();
+        assert_eq!(received, 12);
+        let mut spdm_buffer = [0u8; 64];
+        assert_eq!(
+            storage_transport_encap.decap(&receive_buffer[..received], &mut spdm_buffer),
+            Ok((4, true))
+        );
+        assert_eq!(spdm_buffer[..4], [0x11, 0x84, 0, 0]);
+
+        // one IF_RECV per IF_SEND.
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+    }
+
+    #[test]
+    fn test_case1_send_receive() {
+        let handler = &mut echo;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 0 };
+        let mut transport_buffer = [0u8; 64];
+        let used = storage_transport_encap
+            .encap(&[0x10, 0x84, 0, 0], &mut transport_buffer, false)
+            .unwrap();
+        {
+            let mut device_io = StorageDeviceIo::new(&mut drive);
+            // the header length must match the data.
+            assert!(device_io.send(&transport_buffer[..(used - 1)]).is_err());
+            device_io.send(&transport_buffer[..used]).unwrap();
+            // the allocation length is too small, the drive keeps the response.
+            assert!(device_io.receive(&mut [0u8; 11], 0).is_err());
+        }
+        assert!(drive.is_response_pending());
+
+        let spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: 0,
+        };
+        let other_spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: 1,
+        };
+        let mut data = [0u8; 16];
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                other_spsp.to_u16(),
+                &mut data
+            ),
+            Err(SPDM_STATUS_ERROR_PEER)
+        );
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp.to_u16(),
+                &mut data
+            ),
+            Ok(4)
+        );
+        assert_eq!(data[..4], [0x11, 0x84, 0, 0]);
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp.to_u16(),
+                &mut data
+            ),
+            Err(SPDM_STATUS_NOT_READY_PEER)
+        );
+    }
+
+    #[test]
+    fn test_case2_send_receive() {
+        let handler = &mut echo;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let mut device_io = StorageDeviceIo::new(&mut drive);
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 1 };
+
+        let mut transport_buffer = [0u8; 64];
+        let used = storage_transport_encap
+            .encap(&[0x10, 0x84, 0, 0], &mut transport_buffer, false)
+            .unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+
+        // the response is kept for a receive with a larger buffer.
+        assert!(device_io.receive(&mut [0u8; 11], 0).is_err());
+        let mut receive_buffer = [0u8; 64];
+        assert_eq!(device_io.receive(&mut receive_buffer, 0), Ok(12));
+        assert_eq!(receive_buffer[8..12], [0x11, 0x84, 0, 0]);
+        assert!(device_io.receive(&mut receive_buffer, 0).is_err());
+    }
+
+    #[test]
+    fn test_case0_pending_info() {
+        let handler = &mut echo;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: 2,
+        };
+        let pending_info_spsp = |connection_id| StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodePendingInfo,
+            connection_id,
+        };
+        let read_pending_info = |drive: &mut StorageEmulatedDrive, connection_id| {
+            let mut data = [0u8; STORAGE_PENDING_INFO_SIZE];
+            assert_eq!(
+                drive.security_receive(
+                    STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                    pending_info_spsp(connection_id).to_u16(),
+                    &mut data
+                ),
+                Ok(STORAGE_PENDING_INFO_SIZE)
+            );
+            StoragePendingInfo::read(&mut Reader::init(&data)).unwrap()
+        };
+
+        assert!(!read_pending_info(&mut drive, 2).is_pending());
+
+        drive
+            .security_send(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp.to_u16(),
+                &[0x10, 0x84, 0, 0],
+            )
+            .unwrap();
+        let pending_info = read_pending_info(&mut drive, 2);
+        assert!(pending_info.is_pending());
+        assert_eq!(
+            pending_info.storage_binding_version,
+            STORAGE_BINDING_VERSION
+        );
+        assert_eq!(pending_info.response_length, 4);
+        // the response belongs to another connection.
+        assert!(!read_pending_info(&mut drive, 1).is_pending());
+
+        // Pending Info leaves the response to the message IF_RECV.
+        let mut data = [0u8; 16];
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                spsp.to_u16(),
+                &mut data
+            ),
+            Ok(4)
+        );
+        assert!(!read_pending_info(&mut drive, 2).is_pending());
+
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                pending_info_spsp(2).to_u16(),
+                &mut [0u8; STORAGE_PENDING_INFO_SIZE - 1]
+            ),
+            Err(SPDM_STATUS_INVALID_MSG_SIZE)
+        );
+        assert_eq!(
+            drive.security_receive(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                pending_info_spsp(STORAGE_MAX_CONNECTION_ID + 1).to_u16(),
+                &mut data
+            ),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        // Pending Info is not a request.
+        assert_eq!(
+            drive.security_send(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                pending_info_spsp(2).to_u16(),
+                &[0x10, 0x84, 0, 0]
+            ),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+    }
+
+    #[test]
+    fn test_case0_invalid_command() {
+        let handler = &mut echo;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: 0,
+        };
+        let discovery = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeDiscovery,
+            connection_id: 0,
+        };
+        let connection = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: STORAGE_MAX_CONNECTION_ID + 1,
+        };
+
+        assert_eq!(
+            drive.security_send(0xEF, spsp.to_u16(), &[0x10, 0x84, 0, 0]),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert_eq!(
+            drive.security_send(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                discovery.to_u16(),
+                &[0x10, 0x84, 0, 0]
+            ),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert_eq!(
+            drive.security_send(
+                STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+                connection.to_u16(),
+                &[0x10, 0x84, 0, 0]
+            ),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert!(!drive.is_response_pending());
+    }
+
+    #[test]
+    fn test_case0_no_response() {
+        let handler = &mut |_spsp, _request: &[u8], _response: &mut [u8]| None;
+        let mut drive = StorageEmulatedDrive::new(handler);
+        let mut device_io = StorageDeviceIo::new(&mut drive);
+
+        let mut transport_buffer = [0u8; 64];
+        let used = StorageTransportEncap::default()
+            .encap(&[0x10, 0x84, 0, 0], &mut transport_buffer, false)
+            .unwrap();
+        device_io.send(&transport_buffer[..used]).unwrap();
+        assert!(device_io.receive(&mut [0u8; 64], 0).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+// SPDM over storage, DSP0286. the messages ride in the data of the security
+// commands, IF_SEND and IF_RECV: NVMe Security Send/Receive or SCSI
+// SECURITY PROTOCOL OUT/IN.
+
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmTransportEncap;
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_DECAP_APP_FAIL, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_APP_FAIL,
+    SPDM_STATUS_ENCAP_FAIL,
+};
+
+pub const STORAGE_SECURITY_PROTOCOL_DMTF_SPDM: u8 = 0xE8;
+pub const STORAGE_MAX_CONNECTION_ID: u8 = 0x03;
+pub const STORAGE_MESSAGE_HEADER_SIZE: usize = 8;
+pub const STORAGE_BINDING_VERSION: u16 = 0x1000;
+pub const STORAGE_PENDING_INFO_SIZE: usize = 10;
+pub const STORAGE_PENDING_INFO_FLAG_PENDING: u8 = 0x01;
+
+enum_builder! {
+    @U8
+    EnumName: StorageOperationCode;
+    EnumVal{
+        StorageOperationCodeDiscovery => 0x01,
+        StorageOperationCodePendingInfo => 0x02,
+        StorageOperationCodeMessage => 0x05,
+        StorageOperationCodeSecuredMessage => 0x06
+    }
+}
+impl Default for StorageOperationCode {
+    fn default() -> StorageOperationCode {
+        StorageOperationCode::Unknown(0)
+    }
+}
+
+// the security protocol specific field of the commands, SPSP0 holds the
+// operation code and SPSP1 the connection ID.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct StorageSecurityProtocolSpecific {
+    pub operation_code: StorageOperationCode,
+    pub connection_id: u8,
+}
+
+impl StorageSecurityProtocolSpecific {
+    pub fn to_u16(&self) -> u16 {
+        self.operation_code.get_u8() as u16 | (self.connection_id as u16) << 8
+    }
+
+    pub fn from_u16(security_protocol_specific: u16) -> StorageSecurityProtocolSpecific {
+        let bytes = security_protocol_specific.to_le_bytes();
+        let mut reader = Reader::init(&bytes);
+        StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::read(&mut reader).unwrap_or_default(),
+            connection_id: bytes[1],
+        }
+    }
+}
+
+impl Codec for StorageSecurityProtocolSpecific {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.to_u16().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageSecurityProtocolSpecific> {
+        let security_protocol_specific = u16::read(r)?;
+        Some(StorageSecurityProtocolSpecific::from_u16(
+            security_protocol_specific,
+        ))
+    }
+}
+
+// the parameters of the security command that carries the message. it is
+// not on the wire, StorageDeviceIo takes it apart into the command fields.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageMessageHeader {
+    pub security_protocol: u8,
+    pub security_protocol_specific: StorageSecurityProtocolSpecific,
+    pub length: u32, // transfer length in bytes
+}
+
+impl Codec for StorageMessageHeader {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.security_protocol.encode(bytes)?;
+        cnt += self.security_protocol_specific.encode(bytes)?;
+        cnt += 0u8.encode(bytes)?;
+        cnt += self.length.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<StorageMessageHeader> {
+        let security_protocol = u8::read(r)?;
+        let security_protocol_specific = StorageSecurityProtocolSpecific::read(r)?;
+        u8::read(r)?;
+        let length = u32::read(r)?;
+        Some(StorageMessageHeader {
+            security_protocol,
+            security_protocol_specific,
+            length,
+        })
+    }
+}
+
+// the data of an IF_RECV with the Pending Info operation code, the state of
+// the response to the last IF_SEND of the connection.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct StoragePendingInfo {
+    pub data_length: u16,
+    pub storage_binding_version: u16,
+    pub pending_info_flag: u8,
+    pub response_length: u32,
+}
+
+impl StoragePendingInfo {
+    pub fn is_pending(&self) -> bool {
+        self.pending_info_flag & STORAGE_PENDING_INFO_FLAG_PENDING != 0
+    }
+}
+
+impl Codec for StoragePendingInfo {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.data_length.encode(bytes)?;
+        cnt += self.storage_binding_version.encode(bytes)?;
+        cnt += self.pending_info_flag.encode(bytes)?;
+        cnt += 0u8.encode(bytes)?;
+        cnt += self.response_length.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<StoragePendingInfo> {
+        let data_length = u16::read(r)?;
+        let storage_binding_version = u16::read(r)?;
+        let pending_info_flag = u8::read(r)?;
+        u8::read(r)?;
+        let response_length = u32::read(r)?;
+        Some(StoragePendingInfo {
+            data_length,
+            storage_binding_version,
+            pending_info_flag,
+            response_length,
+        })
+    }
+}
+
+// one SPDM connection to the drive, selected by connection_id.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StorageTransportEncap {
+    pub connection_id: u8,
+}
+
+impl SpdmTransportEncap for StorageTransportEncap {
+    fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        if self.connection_id > STORAGE_MAX_CONNECTION_ID {
+            return Err(SPDM_STATUS_ENCAP_FAIL);
+        }
+        let payload_len = spdm_buffer.len();
+        let mut writer = Writer::init(&mut *transport_buffer);
+        let storage_header = StorageMessageHeader {
+            security_protocol: STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+            security_protocol_specific: StorageSecurityProtocolSpecific {
+                operation_code: if secured_message {
+                    StorageOperationCode::StorageOperationCodeSecuredMessage
+                } else {
+                    StorageOperationCode::StorageOperationCodeMessage
+                },
+                connection_id: self.connection_id,
+            },
+            length: payload_len as u32,
+        };
+        storage_header
+            .encode(&mut writer)
+            .map_err(|_| SPDM_STATUS_ENCAP_FAIL)?;
+        let header_size = writer.used();
+        if transport_buffer.len() < header_size + payload_len {
+            return Err(SPDM_STATUS_ENCAP_FAIL);
+        }
+        transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(spdm_buffer);
+        Ok(header_size + payload_len)
+    }
+
+    fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        let mut reader = Reader::init(transport_buffer);
+        let storage_header =
+            StorageMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_DECAP_FAIL)?;
+        if storage_header.security_protocol != STORAGE_SECURITY_PROTOCOL_DMTF_SPDM
+            || storage_header.security_protocol_specific.connection_id != self.connection_id
+        {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        let secured_message = match storage_header.security_protocol_specific.operation_code {
+            StorageOperationCode::StorageOperationCodeMessage => false,
+            StorageOperationCode::StorageOperationCodeSecuredMessage => true,
+            _ => return Err(SPDM_STATUS_DECAP_FAIL),
+        };
+        let header_size = reader.used();
+        let payload_size = storage_header.length as usize;
+        if transport_buffer.len() < header_size + payload_size {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        if spdm_buffer.len() < payload_size {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        let payload = &transport_buffer[header_size..(header_size + payload_size)];
+        spdm_buffer[..payload_size].copy_from_slice(payload);
+        Ok((payload_size, secured_message))
+    }
+
+    // DSP0286 carries SPDM only, a secured message has no application
+    // message type in front.
+    fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult<usize> {
+        if app_message_type.is_some() || app_buffer.len() < spdm_buffer.len() {
+            return Err(SPDM_STATUS_ENCAP_APP_FAIL);
+        }
+        app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
+        Ok(spdm_buffer.len())
+    }
+
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        if spdm_buffer.len() < app_buffer.len() {
+            return Err(SPDM_STATUS_DECAP_APP_FAIL);
+        }
+        spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        0
+    }
+
+    fn get_sequence_number_count(&mut self) -> u8 {
+        0
+    }
+    fn get_max_random_count(&mut self) -> u16 {
+        0
+    }
+}
+
+#[cfg(all(test,))]
+mod tests_header {
+    use super::*;
+
+    #[test]
+    fn test_case0_storagemessageheader() {
+        let u8_slice = &mut [0u8; 8];
+        let mut writer = Writer::init(u8_slice);
+        let value = StorageMessageHeader {
+            security_protocol: STORAGE_SECURITY_PROTOCOL_DMTF_SPDM,
+            security_protocol_specific: StorageSecurityProtocolSpecific {
+                operation_code: StorageOperationCode::StorageOperationCodeSecuredMessage,
+                connection_id: 2,
+            },
+            length: 0x1234,
+        };
+        assert!(value.encode(&mut writer).is_ok());
+        assert_eq!(0, writer.left());
+        assert_eq!(u8_slice, &[0xE8, 0x06, 0x02, 0x00, 0x34, 0x12, 0x00, 0x00]);
+
+        let mut reader = Reader::init(u8_slice);
+        let storagemessageheader = StorageMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(
+            storagemessageheader.security_protocol,
+            STORAGE_SECURITY_PROTOCOL_DMTF_SPDM
+        );
+        assert_eq!(
+            storagemessageheader.security_protocol_specific,
+            value.security_protocol_specific
+        );
+        assert_eq!(storagemessageheader.length, 0x1234);
+
+        let mut reader = Reader::init(&u8_slice[..7]);
+        assert!(StorageMessageHeader::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_storagependinginfo() {
+        let u8_slice = &mut [0u8; STORAGE_PENDING_INFO_SIZE];
+        let mut writer = Writer::init(u8_slice);
+        let value = StoragePendingInfo {
+            data_length: STORAGE_PENDING_INFO_SIZE as u16,
+            storage_binding_version: STORAGE_BINDING_VERSION,
+            pending_info_flag: STORAGE_PENDING_INFO_FLAG_PENDING,
+            response_length: 0x1234,
+        };
+        assert!(value.encode(&mut writer).is_ok());
+        assert_eq!(0, writer.left());
+        assert_eq!(
+            u8_slice,
+            &[0x0A, 0x00, 0x00, 0x10, 0x01, 0x00, 0x34, 0x12, 0x00, 0x00]
+        );
+
+        let mut reader = Reader::init(u8_slice);
+        let pending_info = StoragePendingInfo::read(&mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(pending_info, value);
+        assert!(pending_info.is_pending());
+
+        let mut reader = Reader::init(&u8_slice[..9]);
+        assert!(StoragePendingInfo::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_security_protocol_specific() {
+        let spsp = StorageSecurityProtocolSpecific {
+            operation_code: StorageOperationCode::StorageOperationCodeMessage,
+            connection_id: 3,
+        };
+        assert_eq!(spsp.to_u16(), 0x0305);
+        assert_eq!(StorageSecurityProtocolSpecific::from_u16(0x0305), spsp);
+        assert_eq!(
+            StorageSecurityProtocolSpecific::from_u16(0x0077).operation_code,
+            StorageOperationCode::Unknown(0x77)
+        );
+    }
+    #[test]
+    fn test_case0_encap_decap() {
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 1 };
+        let spdm_buffer = [0x12u8, 0x84, 0x00, 0x00];
+        let transport_buffer = &mut [0u8; 16];
+
+        let used = storage_transport_encap
+            .encap(&spdm_buffer, transport_buffer, false)
+            .unwrap();
+        assert_eq!(used, 12);
+        assert_eq!(&transport_buffer[..4], &[0xE8, 0x05, 0x01, 0x00]);
+        let spdm_out = &mut [0u8; 16];
+        assert_eq!(
+            storage_transport_encap.decap(&transport_buffer[..used], spdm_out),
+            Ok((4, false))
+        );
+        assert_eq!(&spdm_out[..4], &spdm_buffer);
+
+        let used = storage_transport_encap
+            .encap(&spdm_buffer, transport_buffer, true)
+            .unwrap();
+        assert_eq!(transport_buffer[1], 0x06);
+        assert_eq!(
+            storage_transport_encap.decap(&transport_buffer[..used], spdm_out),
+            Ok((4, true))
+        );
+
+        // another connection does not take the message.
+        assert!(StorageTransportEncap { connection_id: 0 }
+            .decap(&transport_buffer[..used], spdm_out)
+            .is_err());
+        assert!(storage_transport_encap
+            .decap(&transport_buffer[..(used - 1)], spdm_out)
+            .is_err());
+        assert!(storage_transport_encap
+            .encap(&spdm_buffer, &mut [0u8; 11], false)
+            .is_err());
+        assert!(StorageTransportEncap { connection_id: 4 }
+            .encap(&spdm_buffer, transport_buffer, false)
+            .is_err());
+
+        transport_buffer[0] = 0xEF;
+        assert!(storage_transport_encap
+            .decap(&transport_buffer[..used], spdm_out)
+            .is_err());
+        transport_buffer[0] = STORAGE_SECURITY_PROTOCOL_DMTF_SPDM;
+        transport_buffer[1] = StorageOperationCode::StorageOperationCodeDiscovery.get_u8();
+        assert!(storage_transport_encap
+            .decap(&transport_buffer[..used], spdm_out)
+            .is_err());
+    }
+    #[test]
+    fn test_case0_encap_decap_app() {
+        let mut storage_transport_encap = StorageTransportEncap { connection_id: 1 };
+        let app_buffer = &mut [0u8; 8];
+        assert_eq!(
+            storage_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, None),
+            Ok(2)
+        );
+        let spdm_buffer = &mut [0u8; 8];
+        assert_eq!(
+            storage_transport_encap.decap_app(&app_buffer[..2], spdm_buffer),
+            Ok((2, None))
+        );
+        assert_eq!(&spdm_buffer[..2], &[0x12, 0xE8]);
+        assert_eq!(
+            storage_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, Some(0x01)),
+            Err(SPDM_STATUS_ENCAP_APP_FAIL)
+        );
+        assert_eq!(
+            storage_transport_encap.encap_app(&[0u8; 9], app_buffer, None),
+            Err(SPDM_STATUS_ENCAP_APP_FAIL)
+        );
+        assert_eq!(
+            storage_transport_encap.decap_app(&[0u8; 9], spdm_buffer),
+            Err(SPDM_STATUS_DECAP_APP_FAIL)
+        );
+    }
+}
@@ -0,0 +1,14 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+#![forbid(unsafe_code)]
+#![no_std]
+
+mod header;
+pub use header::*;
+
+mod device_io;
+pub use device_io::*;
+
+extern crate codec;
@@ -0,0 +1,378 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::enum_builder;
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmTransportEncap;
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_DECAP_APP_FAIL, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_APP_FAIL,
+    SPDM_STATUS_ENCAP_FAIL,
+};
+
+// SPDM over TCP, DSP0287.
+pub const TCP_BINDING_VERSION: u8 = 0x01;
+pub const TCP_MESSAGE_HEADER_SIZE: usize = 4;
+// the payload length counts the binding version and message type as well.
+pub const TCP_MAX_MESSAGE_SIZE: usize = 2 + u16::MAX as usize;
+// IANA assigned port for SPDM over TCP.
+pub const TCP_SPDM_PORT: u16 = 4194;
+
+enum_builder! {
+    @U8
+    EnumName: TcpMessageType;
+    EnumVal{
+        TcpMessageTypeOutOfSession => 0x05,
+        TcpMessageTypeInSession => 0x06,
+        TcpMessageTypeRoleInquiry => 0xBF,
+        TcpMessageTypeErrorTooLarge => 0xC0,
+        TcpMessageTypeErrorNotSupported => 0xC1,
+        TcpMessageTypeErrorCannotOperateAsRequester => 0xC2,
+        TcpMessageTypeErrorCannotOperateAsResponder => 0xC3
+    }
+}
+impl Default for TcpMessageType {
+    fn default() -> TcpMessageType {
+        TcpMessageType::Unknown(0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpMessageHeader {
+    pub payload_length: u16,
+    pub binding_version: u8,
+    pub message_type: TcpMessageType,
+}
+
+impl TcpMessageHeader {
+    pub fn new(message_type: TcpMessageType, message_size: usize) -> Option<TcpMessageHeader> {
+        if message_size > TCP_MAX_MESSAGE_SIZE - TCP_MESSAGE_HEADER_SIZE {
+            return None;
+        }
+        Some(TcpMessageHeader {
+            payload_length: (message_size + 2) as u16,
+            binding_version: TCP_BINDING_VERSION,
+            message_type,
+        })
+    }
+
+    // size of the message that follows the header.
+    pub fn message_size(&self) -> usize {
+        (self.payload_length as usize).saturating_sub(2)
+    }
+}
+
+impl Codec for TcpMessageHeader {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.payload_length.encode(bytes)?;
+        cnt += self.binding_version.encode(bytes)?;
+        cnt += self.message_type.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<TcpMessageHeader> {
+        let payload_length = u16::read(r)?;
+        if payload_length < 2 {
+            return None;
+        }
+        let binding_version = u8::read(r)?;
+        let message_type = TcpMessageType::read(r)?;
+        Some(TcpMessageHeader {
+            payload_length,
+            binding_version,
+            message_type,
+        })
+    }
+}
+
+// role inquiry and the error messages are a header only.
+pub fn encode_tcp_binding_message(
+    message_type: TcpMessageType,
+    transport_buffer: &mut [u8],
+) -> SpdmResult<usize> {
+    match message_type {
+        TcpMessageType::TcpMessageTypeOutOfSession | TcpMessageType::TcpMessageTypeInSession => {
+            return Err(SPDM_STATUS_ENCAP_FAIL)
+        }
+        _ => {}
+    }
+    let header = TcpMessageHeader::new(message_type, 0).ok_or(SPDM_STATUS_ENCAP_FAIL)?;
+    let mut writer = Writer::init(transport_buffer);
+    header
+        .encode(&mut writer)
+        .map_err(|_| SPDM_STATUS_ENCAP_FAIL)
+}
+
+// returns the type of a binding message, None for an SPDM message or
+// something that is not a DSP0287 message at all.
+pub fn decode_tcp_binding_message(transport_buffer: &[u8]) -> Option<TcpMessageType> {
+    let mut reader = Reader::init(transport_buffer);
+    let header = TcpMessageHeader::read(&mut reader)?;
+    if header.binding_version != TCP_BINDING_VERSION || header.message_size() != 0 {
+        return None;
+    }
+    match header.message_type {
+        TcpMessageType::TcpMessageTypeOutOfSession | TcpMessageType::TcpMessageTypeInSession => {
+            None
+        }
+        message_type => Some(message_type),
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpTransportEncap {}
+
+impl SpdmTransportEncap for TcpTransportEncap {
+    fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        let payload_len = spdm_buffer.len();
+        let tcp_header = TcpMessageHeader::new(
+            if secured_message {
+                TcpMessageType::TcpMessageTypeInSession
+            } else {
+                TcpMessageType::TcpMessageTypeOutOfSession
+            },
+            payload_len,
+        )
+        .ok_or(SPDM_STATUS_ENCAP_FAIL)?;
+        let mut writer = Writer::init(&mut *transport_buffer);
+        tcp_header
+            .encode(&mut writer)
+            .map_err(|_| SPDM_STATUS_ENCAP_FAIL)?;
+        let header_size = writer.used();
+        if transport_buffer.len() < header_size + payload_len {
+            return Err(SPDM_STATUS_ENCAP_FAIL);
+        }
+        transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(spdm_buffer);
+        Ok(header_size + payload_len)
+    }
+
+    fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        let mut reader = Reader::init(transport_buffer);
+        let tcp_header = TcpMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_DECAP_FAIL)?;
+        if tcp_header.binding_version != TCP_BINDING_VERSION {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        let secured_message = match tcp_header.message_type {
+            TcpMessageType::TcpMessageTypeOutOfSession => false,
+            TcpMessageType::TcpMessageTypeInSession => true,
+            _ => return Err(SPDM_STATUS_DECAP_FAIL),
+        };
+        let header_size = reader.used();
+        let payload_size = tcp_header.message_size();
+        if transport_buffer.len() < header_size + payload_size {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        if spdm_buffer.len() < payload_size {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+        let payload = &transport_buffer[header_size..(header_size + payload_size)];
+        spdm_buffer[..payload_size].copy_from_slice(payload);
+        Ok((payload_size, secured_message))
+    }
+
+    // DSP0287 carries SPDM only, a secured message has no application
+    // message type in front.
+    fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmResult<usize> {
+        if app_message_type.is_some() || app_buffer.len() < spdm_buffer.len() {
+            return Err(SPDM_STATUS_ENCAP_APP_FAIL);
+        }
+        app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
+        Ok(spdm_buffer.len())
+    }
+
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, Option<u8>)> {
+        if spdm_buffer.len() < app_buffer.len() {
+            return Err(SPDM_STATUS_DECAP_APP_FAIL);
+        }
+        spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        0
+    }
+
+    // TCP is reliable and in order, the sequence number stays implicit.
+    fn get_sequence_number_count(&mut self) -> u8 {
+        0
+    }
+    fn get_max_random_count(&mut self) -> u16 {
+        0
+    }
+}
+
+#[cfg(all(test,))]
+mod tests_header {
+    use super::*;
+
+    #[test]
+    fn test_case0_tcpmessageheader() {
+        let u8_slice = &mut [0u8; 4];
+        let mut writer = Writer::init(u8_slice);
+        let value =
+            TcpMessageHeader::new(TcpMessageType::TcpMessageTypeOutOfSession, 0x100).unwrap();
+        assert!(value.encode(&mut writer).is_ok());
+        assert_eq!(0, writer.left());
+        assert_eq!(u8_slice, &[0x02, 0x01, 0x01, 0x05]);
+
+        let mut reader = Reader::init(u8_slice);
+        let tcpmessageheader = TcpMessageHeader::read(&mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(tcpmessageheader.binding_version, TCP_BINDING_VERSION);
+        assert_eq!(
+            tcpmessageheader.message_type,
+            TcpMessageType::TcpMessageTypeOutOfSession
+        );
+        assert_eq!(tcpmessageheader.message_size(), 0x100);
+    }
+    #[test]
+    fn test_case1_tcpmessageheader() {
+        assert!(TcpMessageHeader::new(
+            TcpMessageType::TcpMessageTypeInSession,
+            TCP_MAX_MESSAGE_SIZE - TCP_MESSAGE_HEADER_SIZE
+        )
+        .is_some());
+        assert!(TcpMessageHeader::new(
+            TcpMessageType::TcpMessageTypeInSession,
+            TCP_MAX_MESSAGE_SIZE - TCP_MESSAGE_HEADER_SIZE + 1
+        )
+        .is_none());
+
+        // a payload length must at least cover the version and type.
+        let mut reader = Reader::init(&[0x01, 0x00, 0x01, 0x05]);
+        assert!(TcpMessageHeader::read(&mut reader).is_none());
+        let mut reader = Reader::init(&[0x02, 0x00, 0x01]);
+        assert!(TcpMessageHeader::read(&mut reader).is_none());
+    }
+    #[test]
+    fn test_case0_encap_decap() {
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let spdm_buffer = [0x12u8, 0x84, 0x00, 0x00];
+        let transport_buffer = &mut [0u8; 16];
+
+        let used = tcp_transport_encap
+            .encap(&spdm_buffer, transport_buffer, false)
+            .unwrap();
+        assert_eq!(used, 8);
+        assert_eq!(&transport_buffer[..4], &[0x06, 0x00, 0x01, 0x05]);
+        let spdm_out = &mut [0u8; 16];
+        assert_eq!(
+            tcp_transport_encap.decap(&transport_buffer[..used], spdm_out),
+            Ok((4, false))
+        );
+        assert_eq!(&spdm_out[..4], &spdm_buffer);
+
+        let used = tcp_transport_encap
+            .encap(&spdm_buffer, transport_buffer, true)
+            .unwrap();
+        assert_eq!(transport_buffer[3], 0x06);
+        assert_eq!(
+            tcp_transport_encap.decap(&transport_buffer[..used], spdm_out),
+            Ok((4, true))
+        );
+
+        assert!(tcp_transport_encap
+            .encap(&spdm_buffer, &mut [0u8; 7], false)
+            .is_err());
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..(used - 1)], spdm_out)
+            .is_err());
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..used], &mut [0u8; 3])
+            .is_err());
+
+        transport_buffer[2] = 0x02;
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..used], spdm_out)
+            .is_err());
+    }
+    #[test]
+    fn test_case0_binding_message() {
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let transport_buffer = &mut [0u8; 8];
+
+        let used =
+            encode_tcp_binding_message(TcpMessageType::TcpMessageTypeRoleInquiry, transport_buffer)
+                .unwrap();
+        assert_eq!(&transport_buffer[..used], &[0x02, 0x00, 0x01, 0xBF]);
+        assert_eq!(
+            decode_tcp_binding_message(&transport_buffer[..used]),
+            Some(TcpMessageType::TcpMessageTypeRoleInquiry)
+        );
+        // not an SPDM message.
+        assert!(tcp_transport_encap
+            .decap(&transport_buffer[..used], &mut [0u8; 8])
+            .is_err());
+
+        let used = encode_tcp_binding_message(
+            TcpMessageType::TcpMessageTypeErrorCannotOperateAsRequester,
+            transport_buffer,
+        )
+        .unwrap();
+        assert_eq!(
+            decode_tcp_binding_message(&transport_buffer[..used]),
+            Some(TcpMessageType::TcpMessageTypeErrorCannotOperateAsRequester)
+        );
+
+        assert!(encode_tcp_binding_message(
+            TcpMessageType::TcpMessageTypeOutOfSession,
+            transport_buffer
+        )
+        .is_err());
+        assert!(encode_tcp_binding_message(
+            TcpMessageType::TcpMessageTypeRoleInquiry,
+            &mut [0u8; 3]
+        )
+        .is_err());
+
+        let used = tcp_transport_encap
+            .encap(&[0x12, 0x84], transport_buffer, false)
+            .unwrap();
+        assert_eq!(decode_tcp_binding_message(&transport_buffer[..used]), None);
+        assert_eq!(decode_tcp_binding_message(&[0x02, 0x00, 0x02, 0xBF]), None);
+    }
+    #[test]
+    fn test_case0_encap_decap_app() {
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let app_buffer = &mut [0u8; 8];
+        assert_eq!(
+            tcp_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, None),
+            Ok(2)
+        );
+        assert_eq!(&app_buffer[..2], &[0x12, 0xE8]);
+        let spdm_buffer = &mut [0u8; 8];
+        assert_eq!(
+            tcp_transport_encap.decap_app(&app_buffer[..2], spdm_buffer),
+            Ok((2, None))
+        );
+        assert!(tcp_transport_encap
+            .encap_app(&[0u8; 9], app_buffer, None)
+            .is_err());
+        assert_eq!(
+            tcp_transport_encap.encap_app(&[0x12, 0xE8], app_buffer, Some(0x01)),
+            Err(SPDM_STATUS_ENCAP_APP_FAIL)
+        );
+        assert!(tcp_transport_encap
+            .decap_app(&[0u8; 9], spdm_buffer)
+            .is_err());
+    }
+}
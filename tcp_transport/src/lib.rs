@@ -0,0 +1,19 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+#![forbid(unsafe_code)]
+#![no_std]
+
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+mod header;
+pub use header::*;
+
+#[cfg(any(feature = "std", test))]
+mod stream;
+#[cfg(any(feature = "std", test))]
+pub use stream::*;
+
+extern crate codec;
@@ -0,0 +1,212 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::header::*;
+use codec::{Codec, Reader};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::{SpdmResult, SPDM_STATUS_SEND_FAIL};
+use std::io::{ErrorKind, Read, Write};
+
+const TCP_DISCARD_SIZE: usize = 256;
+
+// one DSP0287 message at a time over a byte stream, normally a
+// std::net::TcpStream. a read timeout, if any, is the one configured on the stream.
+pub struct TcpDeviceIo<S: Read + Write> {
+    stream: S,
+}
+
+impl<S: Read + Write> TcpDeviceIo<S> {
+    pub fn new(stream: S) -> Self {
+        TcpDeviceIo { stream }
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), usize> {
+        let mut used = 0usize;
+        while used < buffer.len() {
+            match self.stream.read(&mut buffer[used..]) {
+                Ok(0) => return Err(used),
+                Ok(size) => used += size,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return Err(used),
+            }
+        }
+        Ok(())
+    }
+
+    // drops the rest of a message that does not fit, so the next receive
+    // starts at a message boundary again.
+    fn discard(&mut self, size: usize) -> Result<(), usize> {
+        let mut left = size;
+        let mut scratch = [0u8; TCP_DISCARD_SIZE];
+        while left > 0 {
+            let size = left.min(TCP_DISCARD_SIZE);
+            self.read_exact(&mut scratch[..size])?;
+            left -= size;
+        }
+        Ok(())
+    }
+}
+
+impl<S: Read + Write> SpdmDeviceIo for TcpDeviceIo<S> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.stream
+            .write_all(buffer)
+            .map_err(|_| SPDM_STATUS_SEND_FAIL)?;
+        self.stream.flush().map_err(|_| SPDM_STATUS_SEND_FAIL)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        let mut header = [0u8; TCP_MESSAGE_HEADER_SIZE];
+        self.read_exact(&mut header)?;
+        let mut reader = Reader::init(&header);
+        let tcp_header = TcpMessageHeader::read(&mut reader).ok_or(0usize)?;
+        let size = TCP_MESSAGE_HEADER_SIZE + tcp_header.message_size();
+        if buffer.len() < size {
+            self.discard(tcp_header.message_size())?;
+            // the message is lost either way, the peer learns why if the
+            // reply goes through.
+            let mut error = [0u8; TCP_MESSAGE_HEADER_SIZE];
+            if let Ok(used) =
+                encode_tcp_binding_message(TcpMessageType::TcpMessageTypeErrorTooLarge, &mut error)
+            {
+                let _ = self.send(&error[..used]);
+            }
+            return Err(0);
+        }
+        buffer[..TCP_MESSAGE_HEADER_SIZE].copy_from_slice(&header);
+        self.read_exact(&mut buffer[TCP_MESSAGE_HEADER_SIZE..size])?;
+        Ok(size)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    extern crate alloc;
+    use alloc::collections::VecDeque;
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use spdmlib::common::SpdmTransportEncap;
+
+    use super::*;
+
+    // one direction of a connection, handed out a few bytes per read like a
+    // real socket may do.
+    #[derive(Clone, Default)]
+    struct TcpLine(Rc<RefCell<VecDeque<u8>>>);
+
+    struct TcpEnd {
+        tx: TcpLine,
+        rx: TcpLine,
+    }
+
+    impl Read for TcpEnd {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let mut rx = self.rx.0.borrow_mut();
+            let size = buf.len().min(rx.len()).min(3);
+            for (d, s) in buf.iter_mut().zip(rx.drain(..size)) {
+                *d = s;
+            }
+            Ok(size)
+        }
+    }
+
+    impl Write for TcpEnd {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.tx.0.borrow_mut().extend(buf.iter());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn connection() -> (TcpDeviceIo<TcpEnd>, TcpDeviceIo<TcpEnd>) {
+        let to_responder = TcpLine::default();
+        let to_requester = TcpLine::default();
+        (
+            TcpDeviceIo::new(TcpEnd {
+                tx: to_responder.clone(),
+                rx: to_requester.clone(),
+            }),
+            TcpDeviceIo::new(TcpEnd {
+                tx: to_requester,
+                rx: to_responder,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_case0_tcp_send_receive() {
+        let (mut requester, mut responder) = connection();
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let transport_buffer = &mut [0u8; 1024];
+
+        let request: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let used = tcp_transport_encap
+            .encap(&request, transport_buffer, false)
+            .unwrap();
+        assert!(requester.send(&transport_buffer[..used]).is_ok());
+        let role_inquiry =
+            encode_tcp_binding_message(TcpMessageType::TcpMessageTypeRoleInquiry, transport_buffer)
+                .unwrap();
+        assert!(requester.send(&transport_buffer[..role_inquiry]).is_ok());
+
+        let buffer = &mut [0u8; 1024];
+        assert_eq!(responder.receive(buffer, 0), Ok(used));
+        let spdm_buffer = &mut [0u8; 1024];
+        assert_eq!(
+            tcp_transport_encap.decap(&buffer[..used], spdm_buffer),
+            Ok((300, false))
+        );
+        assert_eq!(&spdm_buffer[..300], &request[..]);
+        assert_eq!(responder.receive(buffer, 0), Ok(role_inquiry));
+        assert_eq!(
+            decode_tcp_binding_message(&buffer[..role_inquiry]),
+            Some(TcpMessageType::TcpMessageTypeRoleInquiry)
+        );
+
+        // nothing left on the connection.
+        assert!(requester.receive(buffer, 0).is_err());
+    }
+
+    #[test]
+    fn test_case1_tcp_send_receive() {
+        let (mut requester, mut responder) = connection();
+        let mut tcp_transport_encap = TcpTransportEncap {};
+        let transport_buffer = &mut [0u8; 1024];
+
+        // a message too large for the receiver is dropped as a whole, and
+        // answered with ErrorTooLarge.
+        let used = tcp_transport_encap
+            .encap(&[0x5Au8; 600], transport_buffer, true)
+            .unwrap();
+        assert!(requester.send(&transport_buffer[..used]).is_ok());
+        let used = tcp_transport_encap
+            .encap(&[0x12, 0x84, 0x00, 0x00], transport_buffer, false)
+            .unwrap();
+        assert!(requester.send(&transport_buffer[..used]).is_ok());
+
+        let buffer = &mut [0u8; 64];
+        assert!(responder.receive(buffer, 0).is_err());
+        assert_eq!(responder.receive(buffer, 0), Ok(8));
+        assert_eq!(&buffer[..8], &transport_buffer[..8]);
+
+        assert_eq!(requester.receive(buffer, 0), Ok(TCP_MESSAGE_HEADER_SIZE));
+        assert_eq!(
+            decode_tcp_binding_message(&buffer[..TCP_MESSAGE_HEADER_SIZE]),
+            Some(TcpMessageType::TcpMessageTypeErrorTooLarge)
+        );
+        assert!(requester.receive(buffer, 0).is_err());
+    }
+}
@@ -27,6 +27,7 @@ pub static SECRET_MEASUREMENT_IMPL_INSTANCE: SpdmSecretMeasurement = SpdmSecretM
 pub static SECRET_PSK_IMPL_INSTANCE: SpdmSecretPsk = SpdmSecretPsk {
     handshake_secret_hkdf_expand_cb: handshake_secret_hkdf_expand_impl,
     master_secret_hkdf_expand_cb: master_secret_hkdf_expand_impl,
+    is_psk_provisioned_cb: is_psk_provisioned_impl,
 };
 
 #[allow(clippy::field_reassign_with_default)]
@@ -215,17 +216,37 @@ const SALT_0: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
 const ZERO_FILLED: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
 const BIN_STR0_LABEL: &[u8] = b"derived";
 
+// provisioned (psk_hint, psk) pairs, the empty hint selects the default PSK.
+// the PSK is only read in this file, the library just sees the derived secrets.
+const PSK_STORE: &[(&[u8], &[u8])] = &[
+    (b"", b"TestPskData\0"),
+    (b"TestPskHint\0", b"TestPskData\0"),
+    (b"TestPskHint2\0", b"TestPskData2\0"),
+];
+
+fn get_psk(psk_hint: &SpdmPskHintStruct) -> Option<SpdmDheFinalKeyStruct> {
+    let (_, psk) = PSK_STORE
+        .iter()
+        .find(|(hint, _)| *hint == psk_hint.as_ref())?;
+    let mut psk_key = SpdmDheFinalKeyStruct {
+        data_size: psk.len() as u16,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    psk_key.data[..psk.len()].copy_from_slice(psk);
+    Some(psk_key)
+}
+
+fn is_psk_provisioned_impl(psk_hint: &SpdmPskHintStruct) -> bool {
+    PSK_STORE.iter().any(|(hint, _)| *hint == psk_hint.as_ref())
+}
+
 fn handshake_secret_hkdf_expand_impl(
     spdm_version: SpdmVersion,
     base_hash_algo: SpdmBaseHashAlgo,
     psk_hint: &SpdmPskHintStruct,
     info: &[u8],
 ) -> Option<SpdmHkdfOutputKeyingMaterial> {
-    let mut psk_key: SpdmDheFinalKeyStruct = SpdmDheFinalKeyStruct {
-        data_size: b"TestPskData\0".len() as u16,
-        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
-    };
-    psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskData\0");
+    let psk_key = get_psk(psk_hint)?;
 
     let hs_sec = crypto::hkdf::hkdf_extract(
         base_hash_algo,
@@ -241,11 +262,7 @@ fn master_secret_hkdf_expand_impl(
     psk_hint: &SpdmPskHintStruct,
     info: &[u8],
 ) -> Option<SpdmHkdfOutputKeyingMaterial> {
-    let mut psk_key: SpdmDheFinalKeyStruct = SpdmDheFinalKeyStruct {
-        data_size: b"TestPskData\0".len() as u16,
-        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
-    };
-    psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskData\0");
+    let psk_key = get_psk(psk_hint)?;
 
     let buffer = &mut [0; MAX_BIN_CONCAT_BUF_SIZE];
     let bin_str0 = SpdmKeySchedule::binconcat(
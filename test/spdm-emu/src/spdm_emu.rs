@@ -20,6 +20,35 @@ pub const SOCKET_SPDM_COMMAND_STOP: u32 = 0xFFFE;
 pub const SOCKET_SPDM_COMMAND_UNKOWN: u32 = 0xFFFF;
 pub const SOCKET_SPDM_COMMAND_TEST: u32 = 0xDEAD;
 
+// set SPDM_EMU_SERIAL to a serial device, e.g. each end of a pseudo-terminal pair
+// from `socat pty,raw,echo=0,link=/tmp/spdm-req pty,raw,echo=0,link=/tmp/spdm-rsp`,
+// to run MCTP over serial instead of the socket.
+pub const SPDM_EMU_SERIAL_ENV: &str = "SPDM_EMU_SERIAL";
+pub const MCTP_REQUESTER_EID: u8 = 0x08;
+pub const MCTP_RESPONDER_EID: u8 = 0x10;
+
+pub fn open_serial_device() -> Option<std::fs::File> {
+    let path = std::env::var(SPDM_EMU_SERIAL_ENV).ok()?;
+    let serial = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .expect("unable to open serial device!");
+    println!("mctp over serial - {:?}", path);
+    Some(serial)
+}
+
+// set SPDM_EMU_TCP to an address, e.g. 127.0.0.1:4194, to run the DSP0287 SPDM
+// over TCP binding instead of the socket. the responder listens on it and the
+// requester connects to it.
+pub const SPDM_EMU_TCP_ENV: &str = "SPDM_EMU_TCP";
+
+pub fn get_tcp_binding_address() -> Option<String> {
+    let address = std::env::var(SPDM_EMU_TCP_ENV).ok()?;
+    println!("spdm over tcp - {:?}", address);
+    Some(address)
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SpdmSocketHeader {
     pub command: u32,
@@ -4,7 +4,7 @@
 
 #![forbid(unsafe_code)]
 
-use common::SpdmTransportEncap;
+use common::{SpdmDeviceIo, SpdmTransportEncap};
 
 use log::LevelFilter;
 use log::*;
@@ -20,11 +20,14 @@ use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester;
 
-use mctp_transport::MctpTransportEncap;
-use pcidoe_transport::PciDoeTransportEncap;
+use mctp_transport::{MctpPacketDeviceIo, MctpSerialDeviceIo, MctpTransportEncap};
+use pcidoe_transport::{
+    pci_doe_discovery, PciDoeProtocol, PciDoeTransportEncap, PCI_DOE_SPDM_PROTOCOLS,
+};
 use spdm_emu::socket_io_transport::SocketIoTransport;
 use spdm_emu::spdm_emu::*;
 use std::net::TcpStream;
+use tcp_transport::{TcpDeviceIo, TcpTransportEncap};
 
 fn send_receive_hello(
     stream: &mut TcpStream,
@@ -72,7 +75,7 @@ fn send_receive_stop(
 }
 
 fn test_spdm(
-    socket_io_transport: &mut SocketIoTransport,
+    socket_io_transport: &mut dyn SpdmDeviceIo,
     transport_encap: &mut dyn SpdmTransportEncap,
 ) {
     let req_capabilities = SpdmRequestCapabilityFlags::CERT_CAP
@@ -157,6 +160,9 @@ fn test_spdm(
     peer_root_cert_data.data_size = (ca_len) as u16;
     peer_root_cert_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
 
+    let mut peer_root_cert_data_list = gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT);
+    peer_root_cert_data_list[0] = Some(peer_root_cert_data);
+
     let provision_info = if cfg!(feature = "mut-auth") {
         spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
         let mut my_cert_chain_data = SpdmCertChainData {
@@ -181,13 +187,15 @@ fn test_spdm(
                 None,
             ],
             my_cert_chain: [None, None, None, None, None, None, None, None],
-            peer_root_cert_data: Some(peer_root_cert_data),
+            peer_root_cert_data: peer_root_cert_data_list,
+            peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
         }
     } else {
         common::SpdmProvisionInfo {
             my_cert_chain_data: [None, None, None, None, None, None, None, None],
             my_cert_chain: [None, None, None, None, None, None, None, None],
-            peer_root_cert_data: Some(peer_root_cert_data),
+            peer_root_cert_data: peer_root_cert_data_list,
+            peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
         }
     };
 
@@ -307,9 +315,13 @@ fn test_spdm(
         panic!("\nSession session_id not got\n");
     }
 
-    let result = context.start_session(
-        true,
-        0,
+    let mut psk_hint = SpdmPskHintStruct {
+        data_size: b"TestPskHint\0".len() as u16,
+        ..Default::default()
+    };
+    psk_hint.data[..psk_hint.data_size as usize].copy_from_slice(b"TestPskHint\0");
+    let result = context.start_psk_session(
+        Some(&psk_hint),
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
     );
     if let Ok(session_id) = result {
@@ -351,12 +363,26 @@ fn main() {
         .expect("Time went backwards");
     println!("current unit time epoch - {:?}", since_the_epoch.as_secs());
 
-    let mut socket =
-        TcpStream::connect("127.0.0.1:2323").expect("Couldn't connect to the server...");
-
     let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
     let mctp_transport_encap = &mut MctpTransportEncap {};
 
+    if let Some(serial) = open_serial_device() {
+        let serial_io = &mut MctpSerialDeviceIo::new(serial);
+        let packet_io =
+            &mut MctpPacketDeviceIo::new(serial_io, MCTP_REQUESTER_EID, MCTP_RESPONDER_EID);
+        test_spdm(packet_io, mctp_transport_encap);
+        return;
+    }
+
+    if let Some(address) = get_tcp_binding_address() {
+        let stream = TcpStream::connect(address).expect("Couldn't connect to the server...");
+        test_spdm(&mut TcpDeviceIo::new(stream), &mut TcpTransportEncap {});
+        return;
+    }
+
+    let mut socket =
+        TcpStream::connect("127.0.0.1:2323").expect("Couldn't connect to the server...");
+
     let transport_encap: &mut dyn SpdmTransportEncap = if USE_PCIDOE {
         pcidoe_transport_encap
     } else {
@@ -372,6 +398,19 @@ fn main() {
     send_receive_hello(&mut socket, transport_encap, transport_type);
 
     let socket_io_transport = &mut SocketIoTransport::new(&mut socket);
+    if USE_PCIDOE {
+        let mut protocols = [PciDoeProtocol::default(); 8];
+        let count =
+            pci_doe_discovery(socket_io_transport, &mut protocols).expect("DOE discovery failed!");
+        println!("DOE protocols - {:?}", &protocols[..count]);
+        for protocol in &PCI_DOE_SPDM_PROTOCOLS[1..] {
+            assert!(
+                protocols[..count].contains(protocol),
+                "DOE mailbox does not support {:?}",
+                protocol.data_object_type
+            );
+        }
+    }
     test_spdm(socket_io_transport, transport_encap);
 
     send_receive_stop(&mut socket, transport_encap, transport_type);
@@ -12,18 +12,23 @@ use spdmlib::common::{DMTF_SECURE_SPDM_VERSION_10, DMTF_SECURE_SPDM_VERSION_11};
 use std::net::{TcpListener, TcpStream};
 use std::u32;
 
-use codec::{Codec, Reader, Writer};
-use common::SpdmTransportEncap;
+use codec::{Codec, Reader};
 use common::ST1;
-use mctp_transport::MctpTransportEncap;
+use common::{SpdmDeviceIo, SpdmTransportEncap};
+use mctp_transport::{MctpPacketDeviceIo, MctpSerialDeviceIo, MctpTransportEncap};
 use pcidoe_transport::{
-    PciDoeDataObjectType, PciDoeMessageHeader, PciDoeTransportEncap, PciDoeVendorId,
+    pci_doe_discovery_response, PciDoeTransportEncap, PCI_DOE_DISCOVERY_OBJECT_SIZE,
+    PCI_DOE_SPDM_PROTOCOLS,
 };
 use spdm_emu::crypto_callback::SECRET_ASYM_IMPL_INSTANCE;
 use spdm_emu::secret_impl_sample::*;
 use spdm_emu::socket_io_transport::SocketIoTransport;
 use spdm_emu::spdm_emu::*;
 use spdmlib::{common, config, protocol::*, responder};
+use tcp_transport::{
+    decode_tcp_binding_message, encode_tcp_binding_message, TcpDeviceIo, TcpMessageType,
+    TcpTransportEncap, TCP_MESSAGE_HEADER_SIZE,
+};
 
 fn process_socket_message(
     stream: &mut TcpStream,
@@ -53,12 +58,8 @@ fn process_socket_message(
         }
         SOCKET_SPDM_COMMAND_NORMAL => true,
         _ => {
-            if USE_PCIDOE {
-                send_pci_discovery(stream, transport_encap, res.0, buffer)
-            } else {
-                send_unknown(stream, transport_encap, res.0);
-                false
-            }
+            send_unknown(stream, transport_encap, res.0);
+            false
         }
     }
 }
@@ -89,19 +90,45 @@ fn main() {
     spdmlib::secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
     spdmlib::secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
 
-    let listener = TcpListener::bind("127.0.0.1:2323").expect("Couldn't bind to the server");
-    println!("server start!");
-
     let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
     let mctp_transport_encap = &mut MctpTransportEncap {};
 
+    if let Some(serial) = open_serial_device() {
+        let serial_io = &mut MctpSerialDeviceIo::new(serial);
+        let packet_io =
+            &mut MctpPacketDeviceIo::new(serial_io, MCTP_RESPONDER_EID, MCTP_REQUESTER_EID);
+        // no socket commands on a serial line, serve until the line goes away.
+        while handle_message(packet_io, mctp_transport_encap).is_ok() {}
+        return;
+    }
+
+    if let Some(address) = get_tcp_binding_address() {
+        let listener = TcpListener::bind(address).expect("Couldn't bind to the server");
+        println!("server start!");
+        let (stream, _) = listener.accept().expect("Read stream error!");
+        println!("new connection!");
+        let tcp_io = &mut TcpDeviceIo::new(stream);
+        let tcp_transport_encap = &mut TcpTransportEncap {};
+        // no socket commands either, serve until the requester disconnects.
+        loop {
+            match handle_message(tcp_io, tcp_transport_encap) {
+                Ok(_spdm_result) => {}
+                Err((used, buffer)) if send_tcp_binding_response(tcp_io, &buffer[0..used]) => {}
+                Err(_) => return,
+            }
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:2323").expect("Couldn't bind to the server");
+    println!("server start!");
+
     for stream in listener.incoming() {
         let mut stream = stream.expect("Read stream error!");
         println!("new connection!");
         let mut need_continue;
         loop {
             let res = handle_message(
-                &mut stream,
+                &mut SocketIoTransport::new(&mut stream),
                 if USE_PCIDOE {
                     pcidoe_transport_encap
                 } else {
@@ -113,6 +140,11 @@ fn main() {
                 Ok(_spdm_result) => {
                     need_continue = true;
                 }
+                Err((used, buffer))
+                    if USE_PCIDOE && send_pci_discovery(&mut stream, &buffer[0..used]) =>
+                {
+                    need_continue = true;
+                }
                 Err((used, buffer)) => {
                     need_continue = process_socket_message(
                         &mut stream,
@@ -134,11 +166,10 @@ fn main() {
 }
 
 fn handle_message(
-    stream: &mut TcpStream,
+    device_io: &mut dyn SpdmDeviceIo,
     transport_encap: &mut dyn SpdmTransportEncap,
 ) -> Result<bool, (usize, [u8; config::RECEIVER_BUFFER_SIZE])> {
     println!("handle_message!");
-    let mut socket_io_transport = SocketIoTransport::new(stream);
     let rsp_capabilities = SpdmResponseCapabilityFlags::CERT_CAP
         | SpdmResponseCapabilityFlags::CHAL_CAP
         | SpdmResponseCapabilityFlags::MEAS_CAP_SIG
@@ -241,16 +272,13 @@ fn handle_message(
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
-        peer_root_cert_data: None,
+        peer_root_cert_data: gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT),
+        peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
     };
 
     spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
-    let mut context = responder::ResponderContext::new(
-        &mut socket_io_transport,
-        transport_encap,
-        config_info,
-        provision_info,
-    );
+    let mut context =
+        responder::ResponderContext::new(device_io, transport_encap, config_info, provision_info);
     loop {
         // if failed, receieved message can't be processed. then the message will need caller to deal.
         // now caller need to deal with message in context.
@@ -271,6 +299,25 @@ fn handle_message(
     }
 }
 
+// the emulator never acts as requester, which is the answer to a role inquiry.
+fn send_tcp_binding_response(device_io: &mut dyn SpdmDeviceIo, buffer: &[u8]) -> bool {
+    let response_type = match decode_tcp_binding_message(buffer) {
+        Some(TcpMessageType::TcpMessageTypeRoleInquiry) => {
+            TcpMessageType::TcpMessageTypeErrorCannotOperateAsRequester
+        }
+        Some(TcpMessageType::Unknown(_)) => TcpMessageType::TcpMessageTypeErrorNotSupported,
+        // an error from the peer needs no answer.
+        Some(_) => return true,
+        None => return false,
+    };
+    println!("tcp binding message - {:?}", response_type);
+
+    let mut response = [0u8; TCP_MESSAGE_HEADER_SIZE];
+    encode_tcp_binding_message(response_type, &mut response)
+        .and_then(|used| device_io.send(&response[..used]))
+        .is_ok()
+}
+
 pub fn send_hello(
     stream: &mut TcpStream,
     transport_encap: &mut dyn SpdmTransportEncap,
@@ -326,70 +373,19 @@ pub fn send_stop(
     );
 }
 
-pub fn send_pci_discovery(
-    stream: &mut TcpStream,
-    transport_encap: &mut dyn SpdmTransportEncap,
-    transport_type: u32,
-    buffer: &[u8],
-) -> bool {
-    let mut reader = Reader::init(buffer);
-    let mut unknown_message = false;
-    match PciDoeMessageHeader::read(&mut reader) {
-        Some(pcidoe_header) => {
-            match pcidoe_header.vendor_id {
-                PciDoeVendorId::PciDoeVendorIdPciSig => {}
-                _ => unknown_message = true,
-            }
-            match pcidoe_header.data_object_type {
-                PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery => {}
-                _ => unknown_message = true,
-            }
+// answer a DOE discovery request, the responder context only handles SPDM data objects.
+pub fn send_pci_discovery(stream: &mut TcpStream, buffer: &[u8]) -> bool {
+    let mut response = [0u8; PCI_DOE_DISCOVERY_OBJECT_SIZE];
+    match pci_doe_discovery_response(buffer, &PCI_DOE_SPDM_PROTOCOLS, &mut response) {
+        Ok(used) => {
+            let _buffer_size = spdm_emu::spdm_emu::send_message(
+                stream,
+                SOCKET_TRANSPORT_TYPE_PCI_DOE,
+                spdm_emu::spdm_emu::SOCKET_SPDM_COMMAND_NORMAL,
+                &response[..used],
+            );
+            true
         }
-        None => unknown_message = true,
-    }
-
-    let payload = &mut [1u8, 0u8, 0u8, 0u8];
-
-    match u8::read(&mut reader) {
-        None => unknown_message = true,
-        Some(discovery_index) => match discovery_index {
-            0 => {
-                payload[2] = 0;
-                payload[3] = 1;
-            }
-            1 => {
-                payload[2] = 1;
-                payload[3] = 2;
-            }
-            2 => {
-                payload[2] = 2;
-                payload[3] = 0;
-            }
-            _ => unknown_message = true,
-        },
-    }
-    if unknown_message {
-        send_unknown(stream, transport_encap, transport_type);
-        return false;
+        Err(_) => false,
     }
-
-    let payload_len = 4;
-    let mut transport_buffer = [0u8; 1024];
-    let mut writer = Writer::init(&mut transport_buffer);
-    let pcidoe_header = PciDoeMessageHeader {
-        vendor_id: PciDoeVendorId::PciDoeVendorIdPciSig,
-        data_object_type: PciDoeDataObjectType::PciDoeDataObjectTypeDoeDiscovery,
-        payload_length: 4,
-    };
-    assert!(pcidoe_header.encode(&mut writer).is_ok());
-    let header_size = writer.used();
-    transport_buffer[header_size..(header_size + payload_len)].copy_from_slice(payload);
-    let _buffer_size = spdm_emu::spdm_emu::send_message(
-        stream,
-        SOCKET_TRANSPORT_TYPE_PCI_DOE,
-        spdm_emu::spdm_emu::SOCKET_SPDM_COMMAND_NORMAL,
-        &transport_buffer[..(header_size + payload_len)],
-    );
-    //need continue
-    true
 }
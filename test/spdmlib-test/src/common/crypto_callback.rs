@@ -4,9 +4,10 @@
 
 use spdmlib::crypto::SpdmCertOperation;
 use spdmlib::crypto::SpdmCryptoRandom;
-use spdmlib::crypto::{SpdmAead, SpdmAsymVerify, SpdmHkdf, SpdmHmac};
+use spdmlib::crypto::{SpdmAead, SpdmAsymVerify, SpdmHkdf, SpdmHmac, X509SubjectPublicKeyInfo};
 use spdmlib::error::{SpdmResult, SPDM_STATUS_VERIF_FAIL};
 use spdmlib::protocol::*;
+use spdmlib::time::SpdmCertValidityTime;
 
 pub static FAKE_HMAC: SpdmHmac = SpdmHmac {
     hmac_cb: fake_hmac,
@@ -34,6 +35,7 @@ pub static FAKE_HKDF: SpdmHkdf = SpdmHkdf {
 pub static FAKE_CERT_OPERATION: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: fake_get_cert_from_cert_chain,
     verify_cert_chain_cb: fake_verify_cert_chain,
+    check_cert_chain_revocation_cb: fake_check_cert_chain_revocation,
 };
 
 fn fake_hmac(
@@ -109,7 +111,7 @@ fn get_random(data: &mut [u8]) -> SpdmResult<usize> {
 fn fake_asym_verify(
     _base_hash_algo: SpdmBaseHashAlgo,
     _base_asym_algo: SpdmBaseAsymAlgo,
-    _public_cert_der: &[u8],
+    _public_key: &X509SubjectPublicKeyInfo,
     _data: &[u8],
     _signature: &SpdmSignatureStruct,
 ) -> SpdmResult {
@@ -165,7 +167,21 @@ fn fake_get_cert_from_cert_chain(cert_chain: &[u8], _index: isize) -> SpdmResult
     return Ok((0, cert_chain.len()));
 }
 
-fn fake_verify_cert_chain(_cert_chain: &[u8]) -> SpdmResult {
+fn fake_verify_cert_chain(
+    _cert_chain: &[u8],
+    _trust_anchors: &[&[u8]],
+    _validity_time: SpdmCertValidityTime,
+    _is_requester_cert: bool,
+) -> SpdmResult<usize> {
+    Ok(0)
+}
+
+fn fake_check_cert_chain_revocation(
+    _cert_chain: &[u8],
+    _trust_anchor: &[u8],
+    _crls: &[&[u8]],
+    _validity_time: SpdmCertValidityTime,
+) -> SpdmResult {
     Ok(())
 }
 
@@ -0,0 +1,226 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::secret_callback::register_secrets;
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::{req_create_info, rsp_create_info};
+use spdmlib::common::{
+    AsyncSpdmDeviceIo, AsyncSpdmTransportEncap, SpdmDeviceIo, SpdmDeviceIoHandle, SpdmFuture,
+    SpdmTransportEncap, SpdmTransportEncapHandle,
+};
+use spdmlib::error::SpdmResult;
+use spdmlib::loopback::{
+    SpdmLoopback, SpdmLoopbackDirection, SpdmLoopbackRequesterIo, SpdmLoopbackResponderIo,
+};
+use spdmlib::{requester, responder};
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+pub const TO_RESPONDER: SpdmLoopbackDirection = SpdmLoopbackDirection::RequesterToResponder;
+pub const TO_REQUESTER: SpdmLoopbackDirection = SpdmLoopbackDirection::ResponderToRequester;
+
+// runs test with a responder on the responder end of a loopback.
+fn with_loopback_responder(test: impl FnOnce(&SpdmLoopback, &mut responder::ResponderContext)) {
+    register_secrets();
+
+    let loopback = SpdmLoopback::new();
+    let device_io_responder = &mut SpdmLoopbackResponderIo::new(&loopback);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    test(&loopback, &mut responder_context);
+}
+
+// runs test with a requester connected to a responder over loopback.
+pub fn with_loopback(test: impl FnOnce(&SpdmLoopback, &mut requester::RequesterContext)) {
+    with_loopback_responder(|loopback, responder_context| {
+        let device_io_requester = &mut SpdmLoopbackRequesterIo::new(loopback, responder_context);
+        let transport_encap_requester = &mut PciDoeTransportEncap {};
+        let (config_info, provision_info) = req_create_info();
+        let mut requester_context = requester::RequesterContext::new(
+            device_io_requester,
+            transport_encap_requester,
+            config_info,
+            provision_info,
+        );
+
+        test(loopback, &mut requester_context);
+    });
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+// runs a future of the async API to completion. the loopback never waits, a
+// pending future can be polled again at once.
+pub fn poll_to_completion<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+// pending once before it completes, as an io waiting for its interrupt.
+struct YieldOnce<'a> {
+    yielded: bool,
+    pending: &'a Cell<usize>,
+}
+
+impl<'a> YieldOnce<'a> {
+    fn new(pending: &'a Cell<usize>) -> Self {
+        YieldOnce {
+            yielded: false,
+            pending,
+        }
+    }
+}
+
+impl Future for YieldOnce<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        self.pending.set(self.pending.get() + 1);
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+struct AsyncLoopbackIo<'a, 'b> {
+    device_io: SpdmLoopbackRequesterIo<'a, 'b>,
+    pending: &'a Cell<usize>,
+}
+
+impl AsyncSpdmDeviceIo for AsyncLoopbackIo<'_, '_> {
+    fn send<'a>(&'a mut self, buffer: &'a [u8]) -> SpdmFuture<'a, SpdmResult> {
+        Box::pin(async move {
+            YieldOnce::new(self.pending).await;
+            self.device_io.send(buffer)
+        })
+    }
+
+    fn receive<'a>(
+        &'a mut self,
+        buffer: &'a mut [u8],
+        timeout: usize,
+    ) -> SpdmFuture<'a, Result<usize, usize>> {
+        Box::pin(async move {
+            YieldOnce::new(self.pending).await;
+            self.device_io.receive(buffer, timeout)
+        })
+    }
+
+    fn flush_all(&mut self) -> SpdmFuture<'_, SpdmResult> {
+        Box::pin(async move { self.device_io.flush_all() })
+    }
+}
+
+struct AsyncPciDoeTransportEncap<'a> {
+    transport_encap: PciDoeTransportEncap,
+    pending: &'a Cell<usize>,
+}
+
+impl AsyncSpdmTransportEncap for AsyncPciDoeTransportEncap<'_> {
+    fn encap<'a>(
+        &'a mut self,
+        spdm_buffer: &'a [u8],
+        transport_buffer: &'a mut [u8],
+        secured_message: bool,
+    ) -> SpdmFuture<'a, SpdmResult<usize>> {
+        Box::pin(async move {
+            YieldOnce::new(self.pending).await;
+            self.transport_encap
+                .encap(spdm_buffer, transport_buffer, secured_message)
+        })
+    }
+
+    fn decap<'a>(
+        &'a mut self,
+        transport_buffer: &'a [u8],
+        spdm_buffer: &'a mut [u8],
+    ) -> SpdmFuture<'a, SpdmResult<(usize, bool)>> {
+        Box::pin(async move {
+            YieldOnce::new(self.pending).await;
+            self.transport_encap.decap(transport_buffer, spdm_buffer)
+        })
+    }
+
+    fn encap_app<'a>(
+        &'a mut self,
+        spdm_buffer: &'a [u8],
+        app_buffer: &'a mut [u8],
+        app_message_type: Option<u8>,
+    ) -> SpdmFuture<'a, SpdmResult<usize>> {
+        Box::pin(async move {
+            self.transport_encap
+                .encap_app(spdm_buffer, app_buffer, app_message_type)
+        })
+    }
+
+    fn decap_app<'a>(
+        &'a mut self,
+        app_buffer: &'a [u8],
+        spdm_buffer: &'a mut [u8],
+    ) -> SpdmFuture<'a, SpdmResult<(usize, Option<u8>)>> {
+        Box::pin(async move { self.transport_encap.decap_app(app_buffer, spdm_buffer) })
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        self.transport_encap.get_default_app_message_type()
+    }
+
+    fn get_sequence_number_count(&mut self) -> u8 {
+        self.transport_encap.get_sequence_number_count()
+    }
+
+    fn get_max_random_count(&mut self) -> u16 {
+        self.transport_encap.get_max_random_count()
+    }
+}
+
+// runs test with a requester on an async device io and transport encap,
+// connected to a responder over loopback. pending counts the yields.
+pub fn with_async_loopback(
+    test: impl FnOnce(&SpdmLoopback, &Cell<usize>, &mut requester::RequesterContext),
+) {
+    with_loopback_responder(|loopback, responder_context| {
+        let pending = Cell::new(0usize);
+        let device_io_requester = &mut AsyncLoopbackIo {
+            device_io: SpdmLoopbackRequesterIo::new(loopback, responder_context),
+            pending: &pending,
+        };
+        let transport_encap_requester = &mut AsyncPciDoeTransportEncap {
+            transport_encap: PciDoeTransportEncap {},
+            pending: &pending,
+        };
+        let (config_info, provision_info) = req_create_info();
+        let mut requester_context = requester::RequesterContext::new_with_handle(
+            SpdmDeviceIoHandle::Async(device_io_requester),
+            SpdmTransportEncapHandle::Async(transport_encap_requester),
+            config_info,
+            provision_info,
+        );
+
+        test(loopback, &pending, &mut requester_context);
+    });
+}
@@ -10,7 +10,10 @@ pub const USE_ECDSA: bool = true;
 pub mod util;
 
 pub mod device_io;
+pub mod loopback;
 pub mod transport;
 
 pub mod crypto_callback;
 pub mod secret_callback;
+
+pub mod time;
@@ -6,27 +6,29 @@
 #![allow(unused_variables)]
 use crate::common::util::get_test_key_directory;
 use codec::{u24, Codec, Writer};
-use spdmlib::common::key_schedule::SpdmKeySchedule;
 use spdmlib::config;
-use spdmlib::crypto;
 use spdmlib::crypto::hash;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
-use spdmlib::secret::{SpdmSecretAsymSign, SpdmSecretMeasurement, SpdmSecretPsk};
+use spdmlib::secret;
+use spdmlib::secret::{SpdmSecretAsymSign, SpdmSecretMeasurement};
+
+pub use spdm_emu::secret_impl_sample::SECRET_PSK_IMPL_INSTANCE;
 
 pub static SECRET_MEASUREMENT_IMPL_INSTANCE: SpdmSecretMeasurement = SpdmSecretMeasurement {
     measurement_collection_cb: measurement_collection_impl,
     generate_measurement_summary_hash_cb: generate_measurement_summary_hash_impl,
 };
 
-pub static SECRET_PSK_IMPL_INSTANCE: SpdmSecretPsk = SpdmSecretPsk {
-    handshake_secret_hkdf_expand_cb: handshake_secret_hkdf_expand_impl,
-    master_secret_hkdf_expand_cb: master_secret_hkdf_expand_impl,
-};
-
 pub static SECRET_ASYM_IMPL_INSTANCE: SpdmSecretAsymSign =
     SpdmSecretAsymSign { sign_cb: asym_sign };
 
+pub fn register_secrets() {
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+}
+
 #[allow(clippy::field_reassign_with_default)]
 fn measurement_collection_impl(
     spdm_version: SpdmVersion,
@@ -208,72 +210,6 @@ fn generate_measurement_summary_hash_impl(
     }
 }
 
-const MAX_BIN_CONCAT_BUF_SIZE: usize = 2 + 8 + 12 + SPDM_MAX_HASH_SIZE;
-const SALT_0: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
-const ZERO_FILLED: [u8; SPDM_MAX_HASH_SIZE] = [0u8; SPDM_MAX_HASH_SIZE];
-const BIN_STR0_LABEL: &[u8] = b"derived";
-
-fn handshake_secret_hkdf_expand_impl(
-    spdm_version: SpdmVersion,
-    base_hash_algo: SpdmBaseHashAlgo,
-    psk_hint: &SpdmPskHintStruct,
-    info: &[u8],
-) -> Option<SpdmHkdfOutputKeyingMaterial> {
-    let mut psk_key: SpdmDheFinalKeyStruct = SpdmDheFinalKeyStruct {
-        data_size: b"TestPskData\0".len() as u16,
-        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
-    };
-    psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskData\0");
-
-    let hs_sec = crypto::hkdf::hkdf_extract(
-        base_hash_algo,
-        &SALT_0[0..base_hash_algo.get_size() as usize],
-        &SpdmHkdfInputKeyingMaterial::SpdmDheFinalKey(&psk_key),
-    )?;
-    crypto::hkdf::hkdf_expand(base_hash_algo, &hs_sec, info, base_hash_algo.get_size())
-}
-
-fn master_secret_hkdf_expand_impl(
-    spdm_version: SpdmVersion,
-    base_hash_algo: SpdmBaseHashAlgo,
-    psk_hint: &SpdmPskHintStruct,
-    info: &[u8],
-) -> Option<SpdmHkdfOutputKeyingMaterial> {
-    let mut psk_key: SpdmDheFinalKeyStruct = SpdmDheFinalKeyStruct {
-        data_size: b"TestPskData\0".len() as u16,
-        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
-    };
-    psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskData\0");
-
-    let buffer = &mut [0; MAX_BIN_CONCAT_BUF_SIZE];
-    let bin_str0 = SpdmKeySchedule::binconcat(
-        &SpdmKeySchedule::default(),
-        base_hash_algo.get_size(),
-        spdm_version,
-        BIN_STR0_LABEL,
-        None,
-        buffer,
-    )?;
-
-    let hs_sec = crypto::hkdf::hkdf_extract(
-        base_hash_algo,
-        &SALT_0[0..base_hash_algo.get_size() as usize],
-        &SpdmHkdfInputKeyingMaterial::SpdmDheFinalKey(&psk_key),
-    )?;
-    let salt_1 =
-        crypto::hkdf::hkdf_expand(base_hash_algo, &hs_sec, bin_str0, base_hash_algo.get_size())?;
-
-    let mst_sec = crypto::hkdf::hkdf_extract(
-        base_hash_algo,
-        salt_1.as_ref(),
-        &SpdmHkdfInputKeyingMaterial::SpdmZeroFilled(&SpdmZeroFilledStruct {
-            data_size: base_hash_algo.get_size(),
-            data: Box::new([0u8; SPDM_MAX_HASH_SIZE]),
-        }),
-    )?;
-    crypto::hkdf::hkdf_expand(base_hash_algo, &mst_sec, info, base_hash_algo.get_size())
-}
-
 fn asym_sign(
     base_hash_algo: SpdmBaseHashAlgo,
     base_asym_algo: SpdmBaseAsymAlgo,
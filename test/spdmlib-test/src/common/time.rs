@@ -0,0 +1,44 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use spdmlib::time::{SpdmCertValidityTime, SpdmTime};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+// a monotonic clock that only moves when a test advances it.
+// spdmlib keeps the first registered SpdmTime, once a test registered the
+// fake clock, it is the clock of every test in the binary.
+static FAKE_NOW: AtomicU64 = AtomicU64::new(0);
+static FAKE_CLOCK_LOCK: Mutex<()> = Mutex::new(());
+
+pub static FAKE_TIME_IMPL: SpdmTime = SpdmTime {
+    sleep_cb: |us: usize| {
+        FAKE_NOW.fetch_add(us as u64 / 1000, Ordering::SeqCst);
+    },
+    get_cert_validity_time_cb: || {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|ds| ds.as_secs())
+            .unwrap_or(0);
+        SpdmCertValidityTime::Now(timestamp)
+    },
+    get_monotonic_time_cb: || Some(FAKE_NOW.load(Ordering::SeqCst)),
+};
+
+// tests driving the fake clock hold the guard so they do not advance it under each other.
+pub fn lock_fake_clock() -> MutexGuard<'static, ()> {
+    let guard = FAKE_CLOCK_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    spdmlib::time::register(FAKE_TIME_IMPL.clone());
+    guard
+}
+
+pub fn advance_fake_clock(ms: u64) {
+    FAKE_NOW.fetch_add(ms, Ordering::SeqCst);
+}
+
+pub fn fake_now() -> u64 {
+    FAKE_NOW.load(Ordering::SeqCst)
+}
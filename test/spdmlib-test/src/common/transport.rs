@@ -145,7 +145,7 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         spdm_buffer: &[u8],
         app_buffer: &mut [u8],
-        _is_app_message: bool,
+        _app_message_type: Option<u8>,
     ) -> SpdmResult<usize> {
         app_buffer[0..spdm_buffer.len()].copy_from_slice(spdm_buffer);
         Ok(spdm_buffer.len())
@@ -155,9 +155,13 @@ impl SpdmTransportEncap for PciDoeTransportEncap {
         &mut self,
         app_buffer: &[u8],
         spdm_buffer: &mut [u8],
-    ) -> SpdmResult<(usize, bool)> {
+    ) -> SpdmResult<(usize, Option<u8>)> {
         spdm_buffer[0..app_buffer.len()].copy_from_slice(app_buffer);
-        Ok((app_buffer.len(), false))
+        Ok((app_buffer.len(), None))
+    }
+
+    fn get_default_app_message_type(&mut self) -> u8 {
+        0
     }
 
     fn get_sequence_number_count(&mut self) -> u8 {
@@ -7,8 +7,10 @@
 use super::USE_ECDSA;
 use crate::common::device_io::MySpdmDeviceIo;
 use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
+use crate::common::time::fake_now;
 use crate::common::transport::PciDoeTransportEncap;
 use codec::{Reader, Writer};
+use spdmlib::common::session::{SpdmSession, SpdmSessionState};
 use spdmlib::common::{
     SpdmCodec, SpdmConfigInfo, SpdmContext, SpdmOpaqueSupport, SpdmProvisionInfo,
     DMTF_SECURE_SPDM_VERSION_10, DMTF_SECURE_SPDM_VERSION_11,
@@ -20,6 +22,18 @@ use spdmlib::protocol::*;
 use std::path::PathBuf;
 
 pub fn create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
+    create_info_with_req_asym_algo(SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048)
+}
+
+// create_info for a mutual authentication, which negotiates the algorithm of the
+// requester cert chain, the ECDSA P384 chain of the fixtures.
+pub fn create_mut_auth_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
+    create_info_with_req_asym_algo(SpdmReqAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384)
+}
+
+fn create_info_with_req_asym_algo(
+    req_asym_algo: SpdmReqAsymAlgo,
+) -> (SpdmConfigInfo, SpdmProvisionInfo) {
     let config_info = SpdmConfigInfo {
         spdm_version: [
             SpdmVersion::SpdmVersion10,
@@ -57,7 +71,7 @@ pub fn create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
         dhe_algo: SpdmDheAlgo::SECP_384_R1,
 
         aead_algo: SpdmAeadAlgo::AES_256_GCM,
-        req_asym_algo: SpdmReqAsymAlgo::TPM_ALG_RSAPSS_2048,
+        req_asym_algo,
         key_schedule_algo: SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
         opaque_support: SpdmOpaqueSupport::OPAQUE_DATA_FMT1,
         data_transfer_size: 0x1200,
@@ -93,6 +107,9 @@ pub fn create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
     peer_root_cert_data.data_size = (ca_len) as u16;
     peer_root_cert_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
 
+    let mut peer_root_cert_data_list = gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT);
+    peer_root_cert_data_list[0] = Some(peer_root_cert_data);
+
     let provision_info = SpdmProvisionInfo {
         my_cert_chain_data: [
             Some(my_cert_chain_data.clone()),
@@ -105,12 +122,52 @@ pub fn create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
-        peer_root_cert_data: Some(peer_root_cert_data),
+        peer_root_cert_data: peer_root_cert_data_list,
+        peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
     };
 
     (config_info, provision_info)
 }
 
+// an established session whose secrets derive from fixed DHE and transcript
+// inputs, so the sessions of both sides set up this way share the keys.
+pub fn setup_established_session(session: &mut SpdmSession, session_id: u32) {
+    session.setup(session_id).unwrap();
+    session.set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    session
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 48,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE]),
+            },
+        )
+        .unwrap();
+    let th = SpdmDigestStruct {
+        data_size: 48,
+        data: Box::new([100u8; SPDM_MAX_HASH_SIZE]),
+    };
+    session
+        .generate_handshake_secret(SpdmVersion::SpdmVersion12, &th)
+        .unwrap();
+    session
+        .generate_data_secret(SpdmVersion::SpdmVersion12, &th)
+        .unwrap();
+    session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+}
+
+// an established session whose heartbeat timer starts at the fake clock.
+pub fn setup_heartbeat_session(session: &mut SpdmSession, session_id: u32, heartbeat_period: u8) {
+    setup_established_session(session, session_id);
+    session.heartbeat_period = heartbeat_period;
+    session.reset_heartbeat_timer(fake_now());
+}
+
 pub fn new_context<'a>(
     my_spdm_device_io: &'a mut MySpdmDeviceIo,
     pcidoe_transport_encap: &'a mut PciDoeTransportEncap,
@@ -218,6 +275,9 @@ pub fn req_create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
     peer_root_cert_data.data_size = (ca_len) as u16;
     peer_root_cert_data.data[0..ca_len].copy_from_slice(ca_cert.as_ref());
 
+    let mut peer_root_cert_data_list = gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT);
+    peer_root_cert_data_list[0] = Some(peer_root_cert_data);
+
     let provision_info = if cfg!(feature = "mut-auth") {
         spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
         let mut my_cert_chain_data = SpdmCertChainData {
@@ -242,13 +302,15 @@ pub fn req_create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
                 None,
             ],
             my_cert_chain: [None, None, None, None, None, None, None, None],
-            peer_root_cert_data: Some(peer_root_cert_data),
+            peer_root_cert_data: peer_root_cert_data_list,
+            peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
         }
     } else {
         SpdmProvisionInfo {
             my_cert_chain_data: [None, None, None, None, None, None, None, None],
             my_cert_chain: [None, None, None, None, None, None, None, None],
-            peer_root_cert_data: Some(peer_root_cert_data),
+            peer_root_cert_data: peer_root_cert_data_list,
+            peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
         }
     };
 
@@ -359,7 +421,8 @@ pub fn rsp_create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
-        peer_root_cert_data: None,
+        peer_root_cert_data: gen_array_clone(None, config::MAX_ROOT_CERT_SUPPORT),
+        peer_crl_data: gen_array_clone(None, config::MAX_CRL_SUPPORT),
     };
 
     (config_info, provision_info)
@@ -10,6 +10,8 @@ pub mod common;
 mod test_client_server;
 #[cfg(test)]
 mod test_library;
+#[cfg(test)]
+mod test_session;
 
 #[cfg(test)]
 mod requester_tests;
@@ -2,22 +2,38 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use crate::common::crypto_callback::FAKE_RAND;
 use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::loopback::{
+    poll_to_completion, with_async_loopback, TO_REQUESTER, TO_RESPONDER,
+};
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
-use crate::common::util::create_info;
+use crate::common::util::{create_info, create_mut_auth_info, setup_established_session};
 use codec::Writer;
+use mctp_transport::{MctpMessageType, MctpTransportEncap};
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
 use spdmlib::common::SpdmCodec;
+use spdmlib::config::MAX_SPDM_MSG_SIZE;
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_STATE_LOCAL,
+    SPDM_STATUS_SESSION_NUMBER_EXCEED,
+};
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
-use spdmlib::{config, protocol, responder, secret};
+use spdmlib::responder::app_message_handler::{self, SpdmAppMessageHandler};
+use spdmlib::responder::ResponderContext;
+use spdmlib::{config, crypto, protocol, responder, secret};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
 
 #[test]
 fn test_case0_start_session() {
-    let (rsp_config_info, rsp_provision_info) = create_info();
-    let (req_config_info, req_provision_info) = create_info();
+    let (rsp_config_info, rsp_provision_info) = create_mut_auth_info();
+    let (req_config_info, req_provision_info) = create_mut_auth_info();
 
     let shared_buffer = SharedBuffer::new();
     let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
@@ -82,8 +98,8 @@ fn test_case0_start_session() {
 
 #[test]
 fn test_case0_get_next_half_session() {
-    let (rsp_config_info, rsp_provision_info) = create_info();
-    let (req_config_info, req_provision_info) = create_info();
+    let (rsp_config_info, rsp_provision_info) = create_mut_auth_info();
+    let (req_config_info, req_provision_info) = create_mut_auth_info();
 
     let shared_buffer = SharedBuffer::new();
     let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
@@ -92,6 +108,8 @@ fn test_case0_get_next_half_session() {
     secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
     secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
     secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+    // FAKE_RAND always returns 0xff, so each half starts at 0xffff and moves down on a clash.
+    crypto::rand::register(FAKE_RAND.clone());
 
     let mut responder = responder::ResponderContext::new(
         &mut device_io_responder,
@@ -129,30 +147,30 @@ fn test_case0_get_next_half_session() {
         0,
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
     );
-    assert_eq!(result.unwrap(), 0xfffdfffd);
+    assert_eq!(result.unwrap(), 0xffffffff);
 
     let result = requester.start_session(
         false,
         0,
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
     );
-    assert_eq!(result.unwrap(), 0xfffcfffc);
+    assert_eq!(result.unwrap(), 0xfffefffe);
 
     let result = requester.start_session(
         false,
         0,
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
     );
-    assert_eq!(result.unwrap(), 0xfffbfffb);
+    assert_eq!(result.unwrap(), 0xfffdfffd);
 
     let result = requester.start_session(
         true,
         0,
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
     );
-    assert_eq!(result.unwrap(), 0xfffafffa);
+    assert_eq!(result.unwrap(), 0xfffcfffc);
 
-    let result = requester.end_session(0xfffbfffb);
+    let result = requester.end_session(0xfffdfffd);
     assert!(result.is_ok());
 
     let result = requester.start_session(
@@ -160,7 +178,7 @@ fn test_case0_get_next_half_session() {
         0,
         SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
     );
-    assert_eq!(result.unwrap(), 0xfffbfffb);
+    assert_eq!(result.unwrap(), 0xfffdfffd);
 
     let result = requester.start_session(
         false,
@@ -170,6 +188,55 @@ fn test_case0_get_next_half_session() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_case1_get_next_half_session() {
+    let (config_info, provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    crypto::rand::register(FAKE_RAND.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    assert_eq!(responder.common.get_next_half_session_id(false), Ok(0xffff));
+
+    // the peer half does not matter, only our half of every session is avoided.
+    responder.common.session[0].setup(0xffff0001).unwrap();
+    responder.common.session[1].setup(0x0001fffe).unwrap();
+    assert_eq!(responder.common.get_next_half_session_id(false), Ok(0xfffe));
+    responder.common.session[2].setup(0xfffe0002).unwrap();
+    assert_eq!(responder.common.get_next_half_session_id(false), Ok(0xfffd));
+    assert_eq!(responder.common.get_next_half_session_id(true), Ok(0xffff));
+
+    assert_eq!(
+        responder.common.get_new_session_id(0x0001, 0xfffd),
+        Ok(0xfffd0001)
+    );
+    assert_eq!(
+        responder.common.get_new_session_id(0x0001, 0xffff),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+    assert_eq!(
+        responder.common.get_new_session_id(0x0000, 0xfffd),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+
+    for (i, session) in responder.common.session.iter_mut().enumerate().skip(3) {
+        session.setup(0x10001 * (i as u32 + 1)).unwrap();
+    }
+    assert_eq!(
+        responder.common.get_next_half_session_id(false),
+        Err(SPDM_STATUS_SESSION_NUMBER_EXCEED)
+    );
+}
+
 #[test]
 fn test_case0_receive_secured_message() {
     let (rsp_config_info, rsp_provision_info) = create_info();
@@ -191,7 +258,7 @@ fn test_case0_receive_secured_message() {
     responder.common.negotiate_info.base_hash_sel = protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(session_id).unwrap();
     responder.common.session[0].set_crypto_param(
         protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -242,7 +309,7 @@ fn test_case0_receive_secured_message() {
     requester.common.negotiate_info.base_hash_sel = protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(session_id).unwrap();
     requester.common.session[0].set_crypto_param(
         protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -308,3 +375,349 @@ fn test_case0_receive_secured_message() {
         .is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case0_export_secret() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.base_hash_sel = protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    let rsp_session_id = 0xffu16;
+    let session_id = (0xffu32 << 16) + rsp_session_id as u32;
+    responder.common.session = gen_array(4);
+    responder.common.session[0].setup(session_id).unwrap();
+    responder.common.session[0].set_crypto_param(
+        protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        protocol::SpdmDheAlgo::SECP_384_R1,
+        protocol::SpdmAeadAlgo::AES_256_GCM,
+        protocol::SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(responder.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    responder.common.session[0]
+        .set_session_state(spdmlib::common::session::SpdmSessionState::SpdmSessionEstablished);
+
+    let rsp_secret = responder
+        .export_secret(session_id, b"ide key", &[0, 1, 2, 3], 32)
+        .unwrap();
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.base_hash_sel = protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    let rsp_session_id = 0xffu16;
+    let session_id = (0xffu32 << 16) + rsp_session_id as u32;
+    requester.common.session = gen_array(4);
+    requester.common.session[0].setup(session_id).unwrap();
+    requester.common.session[0].set_crypto_param(
+        protocol::SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        protocol::SpdmDheAlgo::SECP_384_R1,
+        protocol::SpdmAeadAlgo::AES_256_GCM,
+        protocol::SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(requester.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    requester.common.session[0]
+        .set_session_state(spdmlib::common::session::SpdmSessionState::SpdmSessionEstablished);
+
+    let req_secret = requester
+        .export_secret(session_id, b"ide key", &[0, 1, 2, 3], 32)
+        .unwrap();
+    assert_eq!(req_secret.data_size, 32);
+    assert_eq!(req_secret.as_ref(), rsp_secret.as_ref());
+
+    assert!(requester
+        .export_secret(session_id + 1, b"ide key", &[0, 1, 2, 3], 32)
+        .is_err());
+}
+
+// reply with the application message type followed by the request.
+fn echo_app_message(
+    _ctx: &mut ResponderContext,
+    _session_id: u32,
+    app_message_type: u8,
+    app_buffer: &[u8],
+    _auxiliary_app_data: &[u8],
+) -> SpdmResult<([u8; MAX_SPDM_MSG_SIZE], usize)> {
+    let mut rsp_app_buffer = [0u8; MAX_SPDM_MSG_SIZE];
+    rsp_app_buffer[0] = app_message_type;
+    rsp_app_buffer[1..1 + app_buffer.len()].copy_from_slice(app_buffer);
+    Ok((rsp_app_buffer, 1 + app_buffer.len()))
+}
+
+#[test]
+fn test_case0_send_receive_secured_app_message() {
+    app_message_handler::register(SpdmAppMessageHandler {
+        dispatch_secured_app_message_cb: echo_app_message,
+    });
+
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let mctp_transport_encap = &mut MctpTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        mctp_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array(4);
+    setup_established_session(&mut responder.common.session[0], session_id);
+
+    let mctp_transport_encap2 = &mut MctpTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        mctp_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.session = gen_array(4);
+    setup_established_session(&mut requester.common.session[0], session_id);
+
+    let app_message = [0x11u8, 0x22, 0x33];
+    let mut receive_buffer = [0u8; MAX_SPDM_MSG_SIZE];
+
+    // without a session setting, the transport default applies.
+    let pldm = MctpMessageType::MctpMessageTypePldm.get_u8();
+    requester
+        .send_secured_message(session_id, &app_message, true)
+        .unwrap();
+    let (used, app_message_type) = requester
+        .receive_secured_app_message(session_id, &mut receive_buffer)
+        .unwrap();
+    assert_eq!(app_message_type, pldm);
+    assert_eq!(receive_buffer[..used], [pldm, 0x11, 0x22, 0x33]);
+
+    // per session.
+    let nvme = MctpMessageType::MctpMessageTypeNvme.get_u8();
+    requester.common.session[0].set_app_message_type(Some(nvme));
+    requester
+        .send_secured_message(session_id, &app_message, true)
+        .unwrap();
+    let (used, app_message_type) = requester
+        .receive_secured_app_message(session_id, &mut receive_buffer)
+        .unwrap();
+    assert_eq!(app_message_type, nvme);
+    assert_eq!(receive_buffer[..used], [nvme, 0x11, 0x22, 0x33]);
+
+    // per call.
+    let vendor_defined_pci = MctpMessageType::MctpMessageTypeVendorDefinedPci.get_u8();
+    requester
+        .send_secured_app_message(session_id, &app_message, vendor_defined_pci)
+        .unwrap();
+    let (used, app_message_type) = requester
+        .receive_secured_app_message(session_id, &mut receive_buffer)
+        .unwrap();
+    assert_eq!(app_message_type, vendor_defined_pci);
+    assert_eq!(
+        receive_buffer[..used],
+        [vendor_defined_pci, 0x11, 0x22, 0x33]
+    );
+
+    // SPDM message types cannot carry an application message.
+    let spdm = MctpMessageType::MctpMessageTypeSpdm.get_u8();
+    assert!(requester
+        .send_secured_app_message(session_id, &app_message, spdm)
+        .is_err());
+}
+
+#[test]
+fn test_case0_async_full_flow() {
+    with_async_loopback(|loopback, pending, requester_context| {
+        let mut total_number = 0u8;
+        let mut record = SpdmMeasurementRecordStructure::default();
+        let result = poll_to_completion(async {
+            requester_context.init_connection_async().await?;
+            requester_context
+                .send_receive_spdm_digest_async(None)
+                .await?;
+            requester_context
+                .send_receive_spdm_certificate_async(None, 0)
+                .await?;
+            requester_context
+                .send_receive_spdm_measurement_async(
+                    None,
+                    0,
+                    SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                    SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                    &mut total_number,
+                    &mut record,
+                )
+                .await?;
+
+            let session_id = requester_context
+                .start_session_async(
+                    false,
+                    0,
+                    SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+                )
+                .await?;
+            requester_context
+                .send_receive_spdm_measurement_async(
+                    Some(session_id),
+                    0,
+                    SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                    SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                    &mut total_number,
+                    &mut record,
+                )
+                .await?;
+            requester_context.end_session_async(session_id).await
+        });
+        assert!(result.is_ok());
+
+        // every send, receive, encap and decap yielded once.
+        assert_eq!(loopback.sent(TO_RESPONDER), loopback.sent(TO_REQUESTER));
+        assert_eq!(pending.get(), 4 * loopback.sent(TO_RESPONDER));
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+    });
+}
+
+#[test]
+fn test_case0_async_psk_session() {
+    with_async_loopback(|_loopback, _pending, requester_context| {
+        let result = poll_to_completion(async {
+            requester_context.init_connection_async().await?;
+            let session_id = requester_context
+                .start_session_async(
+                    true,
+                    0,
+                    SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+                )
+                .await?;
+            requester_context
+                .send_receive_spdm_heartbeat_async(session_id)
+                .await?;
+            requester_context.end_session_async(session_id).await
+        });
+        assert!(result.is_ok());
+    });
+}
+
+#[test]
+fn test_case0_async_sync_api() {
+    // the sync API does not wait for an async device io that is pending.
+    with_async_loopback(|loopback, pending, requester_context| {
+        assert_eq!(
+            requester_context.init_connection(),
+            Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+        );
+        assert_eq!(pending.get(), 1);
+        assert_eq!(loopback.sent(TO_RESPONDER), 0);
+    });
+}
+
+// counts the wakes, a pending io wakes the executor once it may go on.
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn test_case0_async_poll() {
+    // a pending device io hands back to the executor, the flow does not spin.
+    with_async_loopback(|loopback, pending, requester_context| {
+        let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut context = Context::from_waker(&waker);
+        let mut future = Box::pin(requester_context.init_connection_async());
+        let mut polls = 0usize;
+        let result = loop {
+            polls += 1;
+            if let Poll::Ready(result) = future.as_mut().poll(&mut context) {
+                break result;
+            }
+        };
+        assert!(result.is_ok());
+        assert_eq!(polls, pending.get() + 1);
+        assert_eq!(wakes.0.load(Ordering::Relaxed), pending.get());
+        assert_eq!(loopback.sent(TO_RESPONDER), 3);
+    });
+}
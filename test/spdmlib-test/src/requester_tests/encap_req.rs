@@ -97,7 +97,7 @@ fn test_receive_encapsulated_request() {
     let send = &mut [0u8; config::SENDER_BUFFER_SIZE];
     let size = context
         .common
-        .encode_secured_message(SESSION_ID, writer.used_slice(), send, true, false)
+        .encode_secured_message(SESSION_ID, writer.used_slice(), send, true, None)
         .unwrap();
     assert!(context.common.device_io.send(&send[..size]).is_ok());
 
@@ -184,7 +184,7 @@ fn test_receive_encapsulated_response_ack() {
     let send = &mut [0u8; config::SENDER_BUFFER_SIZE];
     let size = context
         .common
-        .encode_secured_message(SESSION_ID, writer.used_slice(), send, true, false)
+        .encode_secured_message(SESSION_ID, writer.used_slice(), send, true, None)
         .unwrap();
     assert!(context.common.device_io.send(&send[..size]).is_ok());
 
@@ -249,7 +249,7 @@ fn setup_test_context_and_session<'a>(
         .runtime_info
         .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCertificate);
 
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(SESSION_ID).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -32,7 +32,7 @@ fn test_case0_send_receive_spdm_end_session() {
     responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(session_id).unwrap();
     responder.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -83,7 +83,7 @@ fn test_case0_send_receive_spdm_end_session() {
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(session_id).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
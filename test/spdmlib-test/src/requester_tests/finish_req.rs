@@ -55,7 +55,7 @@ fn test_case0_send_receive_spdm_finish() {
 
     responder.common.reset_runtime_info();
 
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(4294901758).unwrap();
     responder.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -64,10 +64,6 @@ fn test_case0_send_receive_spdm_finish() {
         SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
     );
     responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
-    responder
-        .common
-        .runtime_info
-        .set_last_session_id(Some(4294901758));
     responder.common.session[0].runtime_info.digest_context_th =
         Some(crypto::hash::hash_ctx_init(responder.common.negotiate_info.base_hash_sel).unwrap());
 
@@ -116,7 +112,7 @@ fn test_case0_send_receive_spdm_finish() {
 
     requester.common.reset_runtime_info();
 
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(4294901758).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
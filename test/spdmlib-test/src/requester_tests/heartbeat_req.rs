@@ -4,8 +4,9 @@
 
 use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::*;
+use crate::common::time::{advance_fake_clock, lock_fake_clock};
 use crate::common::transport::PciDoeTransportEncap;
-use crate::common::util::create_info;
+use crate::common::util::{create_info, setup_heartbeat_session};
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
@@ -32,7 +33,7 @@ fn test_case0_send_receive_spdm_heartbeat() {
     let rsp_session_id = 0x11u16;
     let session_id = (0x11u32 << 16) + rsp_session_id as u32;
     responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(session_id).unwrap();
     responder.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -82,7 +83,7 @@ fn test_case0_send_receive_spdm_heartbeat() {
     let rsp_session_id = 0x11u16;
     let session_id = (0x11u32 << 16) + rsp_session_id as u32;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(session_id).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -122,3 +123,74 @@ fn test_case0_send_receive_spdm_heartbeat() {
     let status = requester.send_receive_spdm_heartbeat(session_id).is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case0_send_receive_spdm_heartbeat_if_idle() {
+    let _clock = lock_fake_clock();
+
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array(4);
+    setup_heartbeat_session(&mut responder.common.session[0], session_id, 1);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.session = gen_array(4);
+    setup_heartbeat_session(&mut requester.common.session[0], session_id, 1);
+
+    advance_fake_clock(999);
+    assert!(requester
+        .send_receive_spdm_heartbeat_if_idle(session_id)
+        .is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        0
+    );
+
+    advance_fake_clock(1);
+    assert!(requester
+        .send_receive_spdm_heartbeat_if_idle(session_id)
+        .is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        1
+    );
+
+    // the HEARTBEAT exchange restarted the timer.
+    assert!(requester
+        .send_receive_spdm_heartbeat_if_idle(session_id)
+        .is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        1
+    );
+
+    assert!(requester
+        .send_receive_spdm_heartbeat_if_idle(session_id + 1)
+        .is_err());
+}
@@ -6,7 +6,7 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
-use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::session::{SpdmKeyUpdatePolicy, SpdmSession, SpdmSessionState};
 use spdmlib::message::key_update::SpdmKeyUpdateOperation;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
@@ -33,7 +33,7 @@ fn test_case0_send_receive_spdm_key_update() {
     let rsp_session_id = 0xFFFEu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(session_id).unwrap();
     responder.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -74,7 +74,7 @@ fn test_case0_send_receive_spdm_key_update() {
     let rsp_session_id = 0xFFFEu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(session_id).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -114,3 +114,124 @@ fn test_case0_send_receive_spdm_key_update() {
         .is_err();
     assert!(status);
 }
+
+#[test]
+fn test_case1_key_update_by_policy() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let rsp_session_id = 0xFFFEu16;
+    let session_id = (0xffu32 << 16) + rsp_session_id as u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array(4);
+    responder.common.session[0].setup(session_id).unwrap();
+    responder.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = responder.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = responder.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = responder.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    let rsp_session_id = 0xFFFEu16;
+    let session_id = (0xffu32 << 16) + rsp_session_id as u32;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.session = gen_array(4);
+    requester.common.session[0].setup(session_id).unwrap();
+    requester.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = requester.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = requester.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = requester.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::KEY_UPD_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::KEY_UPD_CAP;
+    requester.common.session[0].set_key_update_policy(SpdmKeyUpdatePolicy {
+        max_messages: Some(2),
+        ..Default::default()
+    });
+
+    // the first heartbeat goes out with the current keys.
+    assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        1
+    );
+    assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        2
+    );
+
+    // the third one is preceded by KEY_UPDATE and VerifyNewKey with new keys.
+    assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    assert_eq!(
+        requester.common.session[0].get_request_direction_sequence_number(),
+        2
+    );
+    assert_eq!(
+        requester.common.session[0].get_response_direction_sequence_number(),
+        3
+    );
+}
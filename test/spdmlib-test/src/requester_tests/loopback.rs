@@ -0,0 +1,129 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::loopback::{with_loopback, TO_REQUESTER, TO_RESPONDER};
+use spdmlib::loopback::SpdmLoopbackFault;
+use spdmlib::message::{SpdmMeasurementAttributes, SpdmMeasurementOperation};
+use spdmlib::protocol::{SpdmMeasurementRecordStructure, SpdmMeasurementSummaryHashType};
+
+// the request code of a PCI DOE framed SPDM message.
+const REQUEST_CODE_OFFSET: usize = 9;
+
+#[test]
+fn test_case0_loopback_full_flow() {
+    with_loopback(|loopback, requester_context| {
+        assert!(requester_context.init_connection().is_ok());
+        assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+        assert!(requester_context
+            .send_receive_spdm_certificate(None, 0)
+            .is_ok());
+
+        let mut total_number = 0u8;
+        let mut record = SpdmMeasurementRecordStructure::default();
+        assert!(requester_context
+            .send_receive_spdm_measurement(
+                None,
+                0,
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                &mut total_number,
+                &mut record,
+            )
+            .is_ok());
+
+        let session_id = requester_context
+            .start_session(
+                false,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester_context
+            .send_receive_spdm_measurement(
+                Some(session_id),
+                0,
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                &mut total_number,
+                &mut record,
+            )
+            .is_ok());
+        assert!(requester_context.end_session(session_id).is_ok());
+
+        // every request got exactly one response.
+        assert_eq!(loopback.sent(TO_RESPONDER), loopback.sent(TO_REQUESTER));
+        assert_eq!(loopback.pending(TO_RESPONDER), 0);
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+    });
+}
+
+#[test]
+fn test_case0_loopback_drop() {
+    with_loopback(|loopback, requester_context| {
+        // VERSION is lost.
+        loopback.inject_fault(TO_REQUESTER, 0, SpdmLoopbackFault::Drop);
+        assert!(requester_context.init_connection().is_err());
+        assert!(requester_context.init_connection().is_ok());
+
+        // GET_DIGESTS is lost, the responder never answers.
+        loopback.inject_fault(TO_RESPONDER, 0, SpdmLoopbackFault::Drop);
+        assert!(requester_context.send_receive_spdm_digest(None).is_err());
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+    });
+}
+
+#[test]
+fn test_case0_loopback_corrupt() {
+    with_loopback(|loopback, requester_context| {
+        // GET_VERSION turns into an unknown request the responder rejects.
+        loopback.inject_fault(
+            TO_RESPONDER,
+            0,
+            SpdmLoopbackFault::Corrupt {
+                offset: REQUEST_CODE_OFFSET,
+                mask: 0xFF,
+            },
+        );
+        assert!(requester_context.init_connection().is_err());
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+        assert!(requester_context.init_connection().is_ok());
+    });
+}
+
+#[test]
+fn test_case0_loopback_duplicate() {
+    with_loopback(|loopback, requester_context| {
+        // the second VERSION is taken as the response to GET_CAPABILITIES.
+        loopback.inject_fault(TO_REQUESTER, 0, SpdmLoopbackFault::Duplicate);
+        assert!(requester_context.init_connection().is_err());
+
+        assert!(requester_context.common.device_io.flush_all().is_ok());
+        assert_eq!(loopback.pending(TO_REQUESTER), 0);
+        assert!(requester_context.init_connection().is_ok());
+    });
+}
+
+#[test]
+fn test_case0_loopback_delay() {
+    with_loopback(|loopback, requester_context| {
+        // VERSION arrives after the requester gave up on it.
+        loopback.inject_fault(TO_REQUESTER, 0, SpdmLoopbackFault::Delay(1));
+        assert!(requester_context.init_connection().is_err());
+        assert_eq!(loopback.pending(TO_REQUESTER), 1);
+
+        assert!(requester_context.common.device_io.flush_all().is_ok());
+        assert!(requester_context.init_connection().is_ok());
+
+        // a delayed request is processed on the next send, the requester
+        // finds nothing in between and then one response too many.
+        loopback.inject_fault(TO_RESPONDER, 0, SpdmLoopbackFault::Delay(1));
+        assert!(requester_context.send_receive_spdm_digest(None).is_err());
+        assert_eq!(loopback.pending(TO_RESPONDER), 1);
+        let _ = requester_context.send_receive_spdm_digest(None);
+        assert_eq!(loopback.pending(TO_RESPONDER), 0);
+        assert_eq!(loopback.pending(TO_REQUESTER), 1);
+        assert!(requester_context.common.device_io.flush_all().is_ok());
+        assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    });
+}
@@ -40,6 +40,8 @@ mod key_exchange_req;
 
 mod key_update_req;
 
+mod loopback;
+
 mod negotiate_algorithms_req;
 
 mod psk_exchange_req;
@@ -8,7 +8,6 @@ use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
 use spdmlib::common::session::{self, SpdmSession};
-use spdmlib::config::MAX_SPDM_PSK_HINT_SIZE;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{crypto, responder, secret};
@@ -41,7 +40,7 @@ fn test_case0_send_receive_spdm_psk_finish() {
 
     // let rsp_session_id = 0x11u16;
     // let session_id = (0x11u32 << 16) + rsp_session_id as u32;
-    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session = gen_array(4);
     responder.common.session[0].setup(4294901758).unwrap();
     responder.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -50,10 +49,7 @@ fn test_case0_send_receive_spdm_psk_finish() {
         SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
     );
     responder.common.session[0].set_use_psk(true);
-    responder.common.session[0].runtime_info.psk_hint = Some(SpdmPskHintStruct {
-        data_size: 5,
-        data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
-    });
+    responder.common.session[0].runtime_info.psk_hint = Some(SpdmPskHintStruct::default());
     responder.common.session[0]
         .set_session_state(session::SpdmSessionState::SpdmSessionHandshaking);
     responder.common.session[0].runtime_info.digest_context_th =
@@ -83,7 +79,7 @@ fn test_case0_send_receive_spdm_psk_finish() {
 
     // let rsp_session_id = 0x11u16;
     // let session_id = (0x11u32 << 16) + rsp_session_id as u32;
-    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session = gen_array(4);
     requester.common.session[0].setup(4294901758).unwrap();
     requester.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -92,10 +88,7 @@ fn test_case0_send_receive_spdm_psk_finish() {
         SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
     );
     requester.common.session[0].set_use_psk(true);
-    requester.common.session[0].runtime_info.psk_hint = Some(SpdmPskHintStruct {
-        data_size: 5,
-        data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
-    });
+    requester.common.session[0].runtime_info.psk_hint = Some(SpdmPskHintStruct::default());
     requester.common.session[0]
         .set_session_state(session::SpdmSessionState::SpdmSessionHandshaking);
     requester.common.session[0].runtime_info.digest_context_th =
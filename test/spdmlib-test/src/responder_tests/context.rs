@@ -31,7 +31,7 @@ fn test_case0_send_secured_message() {
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -148,7 +148,7 @@ fn test_case0_process_message() {
     let rsp_session_id = 0xFFFEu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -187,7 +187,7 @@ fn test_case0_dispatch_secured_message() {
         context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
         context.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
 
-        context.session = gen_array_clone(SpdmSession::new(), 4);
+        context.session = gen_array(4);
         context.session[0].setup(session_id).unwrap();
         context.session[0].set_crypto_param(
             SpdmBaseHashAlgo::TPM_ALG_SHA_384,
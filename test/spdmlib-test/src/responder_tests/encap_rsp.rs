@@ -238,7 +238,7 @@ fn setup_test_context_and_session<'a>(
         .runtime_info
         .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCertificate);
 
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(SESSION_ID).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
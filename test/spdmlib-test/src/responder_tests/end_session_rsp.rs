@@ -45,7 +45,7 @@ fn test_case0_handle_spdm_end_session() {
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -33,7 +33,7 @@ fn test_case0_handle_spdm_finish() {
 
     context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(4294901758).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -108,7 +108,7 @@ fn test_case1_handle_spdm_finish() {
     context.common.negotiate_info.rsp_capabilities_sel =
         SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
 
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(4294901758).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
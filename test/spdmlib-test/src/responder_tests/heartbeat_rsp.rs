@@ -4,10 +4,13 @@
 
 use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::*;
+use crate::common::time::{advance_fake_clock, lock_fake_clock};
 use crate::common::transport::PciDoeTransportEncap;
-use crate::common::util::create_info;
+use crate::common::util::{create_info, setup_heartbeat_session};
 use codec::{Codec, Writer};
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::SpdmDeviceIo;
+use spdmlib::error::SpdmResult;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::{responder, secret};
@@ -29,7 +32,7 @@ fn test_case0_handle_spdm_heartbeat() {
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -76,3 +79,103 @@ fn test_case0_handle_spdm_heartbeat() {
 
     assert!(context.handle_spdm_heartbeat(session_id, bytes).is_ok());
 }
+
+#[test]
+fn test_case0_teardown_expired_sessions() {
+    let _clock = lock_fake_clock();
+
+    let (rsp_config_info, rsp_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array(4);
+    setup_heartbeat_session(&mut responder.common.session[0], session_id, 1);
+    // no heartbeat negotiated, the session never expires.
+    setup_heartbeat_session(&mut responder.common.session[1], session_id + 1, 0);
+
+    advance_fake_clock(1999);
+    responder.teardown_expired_sessions();
+    assert_eq!(
+        responder.common.session[0].get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+
+    advance_fake_clock(1);
+    responder.teardown_expired_sessions();
+    assert_eq!(
+        responder.common.session[0].get_session_state(),
+        SpdmSessionState::SpdmSessionNotStarted
+    );
+    assert_eq!(
+        responder.common.session[0].get_session_id(),
+        spdmlib::common::INVALID_SESSION_ID
+    );
+    assert_eq!(
+        responder.common.session[1].get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+}
+
+// a receive which blocks for two heartbeat periods of one second before it gives up.
+struct SlowSpdmDeviceIo;
+
+impl SpdmDeviceIo for SlowSpdmDeviceIo {
+    fn send(&mut self, _buffer: &[u8]) -> SpdmResult {
+        Ok(())
+    }
+
+    fn receive(&mut self, _buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        advance_fake_clock(2000);
+        Err(0)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_case1_teardown_expired_sessions() {
+    let _clock = lock_fake_clock();
+
+    let (rsp_config_info, rsp_provision_info) = create_info();
+
+    let mut device_io_responder = SlowSpdmDeviceIo;
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array(4);
+    setup_heartbeat_session(&mut responder.common.session[0], session_id, 1);
+
+    // the session expires during the receive, the sweep ran before it.
+    assert!(responder.process_message(0, &[]).is_err());
+    assert_eq!(
+        responder.common.session[0].get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+
+    assert!(responder.process_message(0, &[]).is_err());
+    assert_eq!(
+        responder.common.session[0].get_session_state(),
+        SpdmSessionState::SpdmSessionNotStarted
+    );
+}
@@ -11,7 +11,8 @@ use bytes::BytesMut;
 use codec::{Codec, Writer};
 use spdmlib::common::opaque;
 use spdmlib::common::opaque::*;
-use spdmlib::common::SpdmCodec;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
+use spdmlib::error::SPDM_STATUS_SESSION_NUMBER_EXCEED;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::{crypto, responder, secret};
@@ -83,3 +84,96 @@ fn test_case0_handle_spdm_key_exchange() {
 
     context.handle_spdm_key_exchange(bytes);
 }
+
+#[test]
+fn test_case1_handle_spdm_key_exchange() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.common.negotiate_info.dhe_sel = SpdmDheAlgo::SECP_256_R1;
+    context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    assert!(context.common.construct_my_cert_chain().is_ok());
+    // every session slot is taken.
+    for (i, session) in context.common.session.iter_mut().enumerate() {
+        let half_session_id = i as u32 + 1;
+        session
+            .setup((half_session_id << 16) + half_session_id)
+            .unwrap();
+    }
+
+    // a truncated request is rejected before the session slots are looked at.
+    let bytes = &mut [0u8; 1024];
+    let mut writer = Writer::init(bytes);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion12,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestKeyExchange,
+    };
+    let _ = value.encode(&mut writer);
+    let used = writer.used();
+
+    let response = &mut [0u8; 1024];
+    let mut writer = Writer::init(response);
+    let status = context.write_spdm_key_exchange_response(&bytes[..used], &mut writer);
+    assert_ne!(status, Err(SPDM_STATUS_SESSION_NUMBER_EXCEED));
+
+    let rng = ring::rand::SystemRandom::new();
+    let private_key =
+        ring::agreement::EphemeralPrivateKey::generate(&ring::agreement::ECDH_P256, &rng)
+            .ok()
+            .unwrap();
+    let public_key_old = private_key.compute_public_key().ok().unwrap();
+    let public_key = BytesMut::from(&public_key_old.as_ref()[1..]);
+
+    let bytes = &mut [0u8; 1024];
+    let mut writer = Writer::init(bytes);
+    let _ = value.encode(&mut writer);
+    let mut value = SpdmKeyExchangeRequestPayload {
+        measurement_summary_hash_type:
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        slot_id: 0u8,
+        req_session_id: 0xffu16,
+        session_policy: 1,
+        random: SpdmRandomStruct {
+            data: [100u8; SPDM_RANDOM_SIZE],
+        },
+        exchange: SpdmDheExchangeStruct::from(public_key),
+        opaque: SpdmOpaqueStruct {
+            data_size: opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1.len() as u16,
+            data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+        },
+    };
+    value.opaque.data[0..value.opaque.data_size as usize]
+        .copy_from_slice(&opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1);
+    let _ = value.spdm_encode(&mut context.common, &mut writer);
+    let used = writer.used();
+
+    let response = &mut [0u8; 1024];
+    let mut writer = Writer::init(response);
+    let status = context.write_spdm_key_exchange_response(&bytes[..used], &mut writer);
+    assert_eq!(status, Err(SPDM_STATUS_SESSION_NUMBER_EXCEED));
+    assert_eq!(
+        writer.used_slice()[1],
+        SpdmRequestResponseCode::SpdmResponseError.get_u8()
+    );
+    assert_eq!(
+        writer.used_slice()[2],
+        SpdmErrorCode::SpdmErrorSessionLimitExceeded.get_u8()
+    );
+}
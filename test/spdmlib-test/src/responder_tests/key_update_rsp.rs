@@ -31,7 +31,7 @@ fn test_case0_handle_spdm_key_update() {
     let rsp_session_id = 0xFFFEu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -100,7 +100,7 @@ fn test_case1_handle_spdm_key_update() {
     let rsp_session_id = 0xFFFEu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
     context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
-    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session = gen_array(4);
     context.common.session[0].setup(session_id).unwrap();
     context.common.session[0].set_crypto_param(
         SpdmBaseHashAlgo::TPM_ALG_SHA_384,
@@ -46,6 +46,8 @@ mod psk_exchange_rsp;
 
 mod psk_finish_rsp;
 
+mod session_policy;
+
 mod vendor_rsp;
 
 mod version_rsp;
@@ -9,8 +9,9 @@ use crate::common::util::create_info;
 use codec::{Codec, Writer};
 use spdmlib::common::opaque;
 use spdmlib::common::opaque::*;
-use spdmlib::common::SpdmCodec;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
 use spdmlib::config::{MAX_SPDM_PSK_CONTEXT_SIZE, MAX_SPDM_PSK_HINT_SIZE};
+use spdmlib::error::SPDM_STATUS_INVALID_MSG_FIELD;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::{responder, secret};
@@ -49,8 +50,8 @@ fn test_case0_handle_spdm_psk_exchange() {
             SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
         req_session_id: 100u16,
         psk_hint: SpdmPskHintStruct {
-            data_size: 32,
-            data: [100u8; MAX_SPDM_PSK_HINT_SIZE],
+            data_size: b"TestPskHint\0".len() as u16,
+            data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
         },
         psk_context: SpdmPskContextStruct {
             data_size: 64,
@@ -61,6 +62,7 @@ fn test_case0_handle_spdm_psk_exchange() {
             data: [0u8; MAX_SPDM_OPAQUE_SIZE],
         },
     };
+    value.psk_hint.data[0..value.psk_hint.data_size as usize].copy_from_slice(b"TestPskHint\0");
     value.opaque.data[0..value.opaque.data_size as usize]
         .copy_from_slice(&opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1);
     assert!(value.spdm_encode(&mut context.common, &mut writer).is_ok());
@@ -70,3 +72,146 @@ fn test_case0_handle_spdm_psk_exchange() {
     bytes[2..].copy_from_slice(&challenge[0..1022]);
     context.handle_spdm_psk_exchange(bytes);
 }
+
+#[test]
+fn test_case1_handle_spdm_psk_exchange() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // no PSK is provisioned for this hint.
+    let bytes = &mut [0u8; 1024];
+    let mut writer = Writer::init(bytes);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion12,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestPskExchange,
+    };
+    assert!(value.encode(&mut writer).is_ok());
+    let mut value = SpdmPskExchangeRequestPayload {
+        measurement_summary_hash_type:
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        req_session_id: 100u16,
+        psk_hint: SpdmPskHintStruct {
+            data_size: b"UnknownPskHint\0".len() as u16,
+            data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
+        },
+        psk_context: SpdmPskContextStruct {
+            data_size: 64,
+            data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+        },
+        opaque: SpdmOpaqueStruct::default(),
+    };
+    value.psk_hint.data[0..value.psk_hint.data_size as usize].copy_from_slice(b"UnknownPskHint\0");
+    assert!(value.spdm_encode(&mut context.common, &mut writer).is_ok());
+    let used = writer.used();
+
+    let response = &mut [0u8; 1024];
+    let mut writer = Writer::init(response);
+    let status = context.write_spdm_psk_exchange_response(&bytes[..used], &mut writer);
+    assert_eq!(status, Err(SPDM_STATUS_INVALID_MSG_FIELD));
+    assert_eq!(
+        writer.used_slice()[1],
+        SpdmRequestResponseCode::SpdmResponseError.get_u8()
+    );
+    assert_eq!(
+        writer.used_slice()[2],
+        SpdmErrorCode::SpdmErrorInvalidRequest.get_u8()
+    );
+    assert!(context
+        .common
+        .session
+        .iter()
+        .all(|session| session.get_session_id() == 0));
+}
+
+// returns the ResponderContextLength of the PSK_EXCHANGE_RSP answering a
+// valid request under the given responder capabilities.
+fn psk_exchange_rsp_context_size(rsp_capabilities: SpdmResponseCapabilityFlags) -> u16 {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+    context.common.negotiate_info.rsp_capabilities_sel = rsp_capabilities;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let bytes = &mut [0u8; 1024];
+    let mut writer = Writer::init(bytes);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion12,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestPskExchange,
+    };
+    assert!(value.encode(&mut writer).is_ok());
+    let value = SpdmPskExchangeRequestPayload {
+        measurement_summary_hash_type:
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        req_session_id: 100u16,
+        psk_hint: SpdmPskHintStruct::default(),
+        psk_context: SpdmPskContextStruct {
+            data_size: SHA384_DIGEST_SIZE as u16,
+            data: [100u8; MAX_SPDM_PSK_CONTEXT_SIZE],
+        },
+        opaque: SpdmOpaqueStruct::default(),
+    };
+    assert!(value.spdm_encode(&mut context.common, &mut writer).is_ok());
+    let used = writer.used();
+
+    let response = &mut [0u8; 1024];
+    let mut writer = Writer::init(response);
+    assert!(context
+        .write_spdm_psk_exchange_response(&bytes[..used], &mut writer)
+        .is_ok());
+    let response = writer.used_slice();
+    assert_eq!(
+        response[1],
+        SpdmRequestResponseCode::SpdmResponsePskExchangeRsp.get_u8()
+    );
+    u16::from_le_bytes([response[8], response[9]])
+}
+
+#[test]
+fn test_case2_handle_spdm_psk_exchange() {
+    // a fresh H bytes context with PSK_CAP_WITH_CONTEXT.
+    assert_eq!(
+        psk_exchange_rsp_context_size(SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT),
+        SHA384_DIGEST_SIZE as u16
+    );
+    // no context with PSK_CAP_WITHOUT_CONTEXT.
+    assert_eq!(
+        psk_exchange_rsp_context_size(SpdmResponseCapabilityFlags::PSK_CAP_WITHOUT_CONTEXT),
+        0
+    );
+}
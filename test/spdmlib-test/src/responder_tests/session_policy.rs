@@ -0,0 +1,68 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::time::{advance_fake_clock, fake_now, lock_fake_clock};
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::create_info;
+use spdmlib::common::session::SpdmSessionState;
+use spdmlib::protocol::*;
+use spdmlib::responder;
+use spdmlib::responder::session_policy::{evict_idle_session, SpdmSessionPolicy};
+
+#[test]
+fn test_case0_evict_idle_session() {
+    let _clock = lock_fake_clock();
+    responder::session_policy::register(SpdmSessionPolicy {
+        evict_session_cb: evict_idle_session,
+    });
+
+    let (rsp_config_info, rsp_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    // every slot holds an established session, each one 100ms younger than the previous.
+    responder.common.session = gen_array(4);
+    for (i, session) in responder.common.session.iter_mut().enumerate() {
+        let half_session_id = i as u32 + 1;
+        session
+            .setup((half_session_id << 16) + half_session_id)
+            .unwrap();
+        session.set_session_state(SpdmSessionState::SpdmSessionEstablished);
+        session.heartbeat_period = 1;
+        session.reset_heartbeat_timer(fake_now());
+        advance_fake_clock(100);
+    }
+
+    // no session has been idle for a heartbeat period yet.
+    assert!(!responder.reserve_session_slot());
+
+    // the first two sessions are idle now, the longest idle one goes.
+    advance_fake_clock(750);
+    assert!(responder.reserve_session_slot());
+    assert_eq!(
+        responder.common.session[0].get_session_state(),
+        SpdmSessionState::SpdmSessionNotStarted
+    );
+    assert_eq!(
+        responder.common.session[1].get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+
+    // a free slot is used without evicting anything else.
+    assert!(responder.reserve_session_slot());
+    assert_eq!(
+        responder.common.session[1].get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+}
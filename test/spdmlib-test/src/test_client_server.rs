@@ -3,15 +3,20 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
-use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
+use crate::common::loopback::with_loopback;
+use crate::common::secret_callback::{
+    SECRET_ASYM_IMPL_INSTANCE, SECRET_MEASUREMENT_IMPL_INSTANCE, SECRET_PSK_IMPL_INSTANCE,
+};
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{get_rsp_cert_chain_buff, req_create_info, rsp_create_info};
+use spdmlib::message::{SpdmMeasurementAttributes, SpdmMeasurementOperation};
 use spdmlib::protocol::{
-    SpdmMeasurementSummaryHashType, SpdmReqAsymAlgo, SpdmRequestCapabilityFlags,
-    SpdmResponseCapabilityFlags,
+    SpdmMeasurementRecordStructure, SpdmMeasurementSummaryHashType, SpdmPskHintStruct,
+    SpdmReqAsymAlgo, SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags,
 };
 use spdmlib::requester;
 use spdmlib::responder;
+use spdmlib::{config, secret};
 
 #[test]
 fn intergration_client_server() {
@@ -114,3 +119,189 @@ fn intergration_client_server() {
         log::info!("\nSession session_id not got ????? \n");
     }
 }
+
+#[test]
+fn integration_client_server_concurrent_sessions() {
+    with_loopback(|_loopback, requester_context| {
+        assert!(requester_context.init_connection().is_ok());
+        assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+        assert!(requester_context
+            .send_receive_spdm_certificate(None, 0)
+            .is_ok());
+
+        // fill every session slot, alternating KEY_EXCHANGE and PSK_EXCHANGE.
+        let mut session_ids = [0u32; config::MAX_SPDM_SESSION_COUNT];
+        for (i, session_id) in session_ids.iter_mut().enumerate() {
+            *session_id = requester_context
+                .start_session(
+                    i % 2 == 1,
+                    0,
+                    SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+                )
+                .unwrap();
+        }
+
+        // unsigned GET_MEASUREMENTS interleaved across the sessions grow the L1/L2
+        // transcript of each session, the signed one at the end only verifies if
+        // both sides kept the transcripts apart.
+        let mut total_number = 0u8;
+        let mut record = SpdmMeasurementRecordStructure::default();
+        for round in 0..3 {
+            for (i, session_id) in session_ids.iter().enumerate() {
+                if (round + i) % 3 == 0 {
+                    continue;
+                }
+                assert!(requester_context
+                    .send_receive_spdm_measurement(
+                        Some(*session_id),
+                        0,
+                        SpdmMeasurementAttributes::empty(),
+                        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                        &mut total_number,
+                        &mut record,
+                    )
+                    .is_ok());
+            }
+        }
+        for session_id in session_ids.iter().rev() {
+            assert!(requester_context
+                .send_receive_spdm_measurement(
+                    Some(*session_id),
+                    0,
+                    SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                    SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                    &mut total_number,
+                    &mut record,
+                )
+                .is_ok());
+        }
+
+        for session_id in session_ids {
+            assert!(requester_context
+                .send_receive_spdm_heartbeat(session_id)
+                .is_ok());
+        }
+        for session_id in session_ids.iter().rev() {
+            assert!(requester_context.end_session(*session_id).is_ok());
+        }
+    });
+}
+
+#[test]
+fn integration_client_server_interleaved_handshakes() {
+    with_loopback(|_loopback, requester_context| {
+        assert!(requester_context.init_connection().is_ok());
+        assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+        assert!(requester_context
+            .send_receive_spdm_certificate(None, 0)
+            .is_ok());
+
+        // both handshakes are in flight before either FINISH.
+        let mut session_ids = [0u32; 2];
+        for session_id in session_ids.iter_mut() {
+            *session_id = requester_context
+                .send_receive_spdm_key_exchange(
+                    0,
+                    SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+                )
+                .unwrap();
+        }
+        for session_id in session_ids {
+            #[cfg(not(feature = "mut-auth"))]
+            let req_slot_id: Option<u8> = None;
+            #[cfg(feature = "mut-auth")]
+            assert!(requester_context
+                .session_based_mutual_authenticate(session_id)
+                .is_ok());
+            #[cfg(feature = "mut-auth")]
+            let req_slot_id = Some(
+                requester_context
+                    .common
+                    .runtime_info
+                    .get_local_used_cert_chain_slot_id(),
+            );
+            assert!(requester_context
+                .send_receive_spdm_finish(req_slot_id, session_id)
+                .is_ok());
+        }
+
+        for session_id in session_ids {
+            assert!(requester_context
+                .send_receive_spdm_heartbeat(session_id)
+                .is_ok());
+        }
+        for session_id in session_ids {
+            assert!(requester_context.end_session(session_id).is_ok());
+        }
+    });
+}
+
+fn psk_hint(hint: &[u8]) -> SpdmPskHintStruct {
+    let mut psk_hint = SpdmPskHintStruct {
+        data_size: hint.len() as u16,
+        ..Default::default()
+    };
+    psk_hint.data[..hint.len()].copy_from_slice(hint);
+    psk_hint
+}
+
+#[test]
+fn intergration_client_server_psk_hint() {
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+
+    // each provisioned hint selects its own PSK on both sides.
+    let mut session_ids = [0u32; 2];
+    for (session_id, hint) in session_ids
+        .iter_mut()
+        .zip([&b"TestPskHint\0"[..], &b"TestPskHint2\0"[..]])
+    {
+        *session_id = requester_context
+            .start_psk_session(
+                Some(&psk_hint(hint)),
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester_context
+            .send_receive_spdm_heartbeat(*session_id)
+            .is_ok());
+    }
+
+    // the responder refuses a hint it has no PSK for.
+    assert!(requester_context
+        .start_psk_session(
+            Some(&psk_hint(b"UnknownPskHint\0")),
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .is_err());
+
+    for session_id in session_ids {
+        assert!(requester_context.end_session(session_id).is_ok());
+    }
+}
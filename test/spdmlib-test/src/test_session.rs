@@ -0,0 +1,135 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::util::setup_established_session;
+use spdmlib::common::session::{SpdmSequenceNumberMode, SpdmSession, SPDM_REPLAY_WINDOW_SIZE};
+use spdmlib::error::SpdmResult;
+
+fn create_established_session(
+    sequence_number_count: u8,
+    sequence_number_mode: SpdmSequenceNumberMode,
+) -> SpdmSession {
+    let mut session = SpdmSession::default();
+    setup_established_session(&mut session, 4294901758u32);
+    session.set_transport_param(sequence_number_count, 0);
+    session.set_sequence_number_mode(sequence_number_mode);
+    session
+}
+
+// encode count messages starting at sequence number start, then rewind the
+// request direction so the same session can decode them.
+fn encode_messages(session: &mut SpdmSession, start: u64, count: usize) -> Vec<Vec<u8>> {
+    session.set_request_direction_sequence_number(start);
+    let mut messages = Vec::new();
+    for i in 0..count {
+        let mut secured_buffer = [0u8; 64];
+        let size = session
+            .encode_spdm_secured_message(&[i as u8; 8], &mut secured_buffer, true)
+            .unwrap();
+        messages.push(secured_buffer[..size].to_vec());
+    }
+    session.set_request_direction_sequence_number(start);
+    messages
+}
+
+// a length beyond the end of the message fails the decode once the sequence
+// number is taken. the tag is not touched, this lib registers a fake AEAD.
+fn corrupt_message(message: &[u8], sequence_number_count: u8) -> Vec<u8> {
+    let mut corrupted = message.to_vec();
+    let length = 4 + sequence_number_count as usize;
+    corrupted[length..length + 2].copy_from_slice(&0xFFFFu16.to_le_bytes());
+    corrupted
+}
+
+fn decode_message(session: &mut SpdmSession, message: &[u8]) -> SpdmResult<u8> {
+    let mut app_buffer = [0u8; 64];
+    let size = session.decode_spdm_secured_message(message, &mut app_buffer, true)?;
+    assert_eq!(size, 8);
+    Ok(app_buffer[0])
+}
+
+#[test]
+fn test_case0_decode_sequence_number_implicit() {
+    let mut session = create_established_session(2, SpdmSequenceNumberMode::Implicit);
+    let messages = encode_messages(&mut session, 0, 2);
+    let corrupted = corrupt_message(&messages[0], 2);
+
+    assert!(decode_message(&mut session, &corrupted).is_err());
+    assert_eq!(session.get_request_direction_sequence_number(), 1);
+    // the session is out of sync with the sender now.
+    assert!(decode_message(&mut session, &messages[0]).is_err());
+}
+
+#[test]
+fn test_case0_decode_sequence_number_strict() {
+    let mut session = create_established_session(2, SpdmSequenceNumberMode::Strict);
+    let messages = encode_messages(&mut session, 0, 2);
+    let corrupted = corrupt_message(&messages[0], 2);
+
+    assert!(decode_message(&mut session, &corrupted).is_err());
+    assert_eq!(session.get_request_direction_sequence_number(), 0);
+    assert_eq!(decode_message(&mut session, &messages[0]), Ok(0));
+    assert_eq!(decode_message(&mut session, &messages[1]), Ok(1));
+    assert_eq!(session.get_request_direction_sequence_number(), 2);
+}
+
+#[test]
+fn test_case0_decode_sequence_number_window() {
+    let mut session = create_established_session(2, SpdmSequenceNumberMode::Window);
+    let messages = encode_messages(&mut session, 0, 5);
+    let corrupted = corrupt_message(&messages[4], 2);
+
+    assert_eq!(decode_message(&mut session, &messages[1]), Ok(1));
+    assert_eq!(decode_message(&mut session, &messages[0]), Ok(0));
+    assert!(decode_message(&mut session, &messages[0]).is_err());
+    assert!(decode_message(&mut session, &messages[1]).is_err());
+    assert_eq!(decode_message(&mut session, &messages[3]), Ok(3));
+    assert!(decode_message(&mut session, &corrupted).is_err());
+    assert_eq!(decode_message(&mut session, &messages[2]), Ok(2));
+    assert_eq!(session.get_request_direction_sequence_number(), 4);
+    assert_eq!(decode_message(&mut session, &messages[4]), Ok(4));
+    assert_eq!(session.get_request_direction_sequence_number(), 5);
+}
+
+#[test]
+fn test_case1_decode_sequence_number_window() {
+    let mut session = create_established_session(2, SpdmSequenceNumberMode::Window);
+    let messages = encode_messages(&mut session, 0, SPDM_REPLAY_WINDOW_SIZE as usize + 2);
+
+    let last = messages.len() - 1;
+    assert_eq!(
+        decode_message(&mut session, &messages[last]),
+        Ok(last as u8)
+    );
+    // behind the window, even if it was never received.
+    assert!(decode_message(&mut session, &messages[0]).is_err());
+    assert_eq!(decode_message(&mut session, &messages[2]), Ok(2));
+    assert!(decode_message(&mut session, &messages[2]).is_err());
+}
+
+#[test]
+fn test_case2_decode_sequence_number_window() {
+    // the two sequence number bytes in the header wrap around.
+    let mut session = create_established_session(2, SpdmSequenceNumberMode::Window);
+    let messages = encode_messages(&mut session, 0xFFFE, 3);
+
+    assert_eq!(decode_message(&mut session, &messages[2]), Ok(2));
+    assert_eq!(session.get_request_direction_sequence_number(), 0x10001);
+    assert_eq!(decode_message(&mut session, &messages[0]), Ok(0));
+    assert_eq!(decode_message(&mut session, &messages[1]), Ok(1));
+    assert!(decode_message(&mut session, &messages[1]).is_err());
+}
+
+#[test]
+fn test_case3_decode_sequence_number_window() {
+    // no sequence number in the header, falls back to strict.
+    let mut session = create_established_session(0, SpdmSequenceNumberMode::Window);
+    let messages = encode_messages(&mut session, 0, 2);
+    let corrupted = corrupt_message(&messages[0], 0);
+
+    assert!(decode_message(&mut session, &corrupted).is_err());
+    assert_eq!(session.get_request_direction_sequence_number(), 0);
+    assert_eq!(decode_message(&mut session, &messages[0]), Ok(0));
+    assert_eq!(decode_message(&mut session, &messages[1]), Ok(1));
+}